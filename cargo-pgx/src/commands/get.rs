@@ -1,14 +1,21 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+use colored::Colorize;
+use pgx_utils::control_file::{ControlMetadata, CONTROL_FILE_HEADER};
 use pgx_utils::{exit_with_error, handle_result};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub fn get_property(name: &str) -> Option<String> {
-    let (control_file, extname) = find_control_file();
+    get_property_in(Path::new("."), name)
+}
+
+/// Like [`get_property`], for the extension crate in `crate_dir`.
+pub(crate) fn get_property_in(crate_dir: &Path, name: &str) -> Option<String> {
+    let (control_file, extname) = find_control_file_in(crate_dir);
 
     if name == "extname" {
         return Some(extname);
@@ -16,7 +23,7 @@ pub fn get_property(name: &str) -> Option<String> {
         return determine_git_hash();
     }
 
-    let control_file = File::open(control_file).unwrap();
+    let control_file = File::open(crate_dir.join(control_file)).unwrap();
     let reader = BufReader::new(control_file);
 
     for line in reader.lines() {
@@ -39,10 +46,63 @@ pub fn get_property(name: &str) -> Option<String> {
     None
 }
 
+/// Write the extension's control file from the `[package.metadata.pgx]` table of its
+/// `Cargo.toml`, if it has one.  Otherwise the control file is written by hand, and one written
+/// by hand is never overwritten.
+///
+/// With `check`, the control file is only checked to be up to date with `Cargo.toml`.
+pub(crate) fn generate_control_file(crate_dir: &Path, check: bool) -> Result<(), std::io::Error> {
+    let cargo_toml = match std::fs::read_to_string(crate_dir.join("Cargo.toml")) {
+        Ok(cargo_toml) => cargo_toml,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let metadata = match handle_result!(
+        ControlMetadata::from_cargo_toml(&cargo_toml),
+        "could not parse Cargo.toml"
+    ) {
+        Some(metadata) => metadata,
+        None => return Ok(()),
+    };
+
+    let path = crate_dir.join(format!("{}.control", metadata.extname));
+    let contents = metadata.to_control_file();
+    let existing = std::fs::read_to_string(&path).ok();
+    if let Some(existing) = &existing {
+        if !existing.starts_with(CONTROL_FILE_HEADER) {
+            exit_with_error!(
+                "`{}` was not generated by pgx, so it won't be overwritten from `[package.metadata.pgx]`.  \
+                Delete it to have it generated, or remove `[package.metadata.pgx]` from `Cargo.toml`",
+                path.display()
+            );
+        }
+    }
+    if check && existing.as_deref() != Some(contents.as_str()) {
+        exit_with_error!(
+            "`{}` is out of date with `[package.metadata.pgx]`, regenerate it with `cargo pgx schema`",
+            path.display()
+        );
+    } else if existing.as_deref() != Some(contents.as_str()) {
+        println!(
+            "{} `{}` from `[package.metadata.pgx]`",
+            "     Writing".bold().green(),
+            path.display().to_string().bold().cyan()
+        );
+        std::fs::write(&path, contents)?;
+    }
+    Ok(())
+}
+
 pub(crate) fn find_control_file() -> (PathBuf, String) {
+    find_control_file_in(Path::new("."))
+}
+
+/// The file name of the control file of the extension crate in `crate_dir`, and the extension's
+/// name.
+pub(crate) fn find_control_file_in(crate_dir: &Path) -> (PathBuf, String) {
     for f in handle_result!(
-        std::fs::read_dir("."),
-        "cannot open current directory for reading"
+        std::fs::read_dir(crate_dir),
+        format!("cannot open `{}` for reading", crate_dir.display())
     ) {
         if f.is_ok() {
             if let Ok(f) = f {
@@ -57,7 +117,7 @@ pub(crate) fn find_control_file() -> (PathBuf, String) {
         }
     }
 
-    exit_with_error!("control file not found in current directory")
+    exit_with_error!("control file not found in `{}`", crate_dir.display())
 }
 
 fn determine_git_hash() -> Option<String> {
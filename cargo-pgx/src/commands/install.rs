@@ -1,11 +1,11 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
-use crate::commands::get::{find_control_file, get_property};
+use crate::commands::get::{find_control_file, get_property, get_property_in};
 use colored::Colorize;
 use pgx_utils::pg_config::PgConfig;
 use pgx_utils::{exit_with_error, get_target_dir, handle_result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 pub(crate) fn install_extension(
@@ -149,7 +149,7 @@ fn get_target_sql_file(extdir: &PathBuf, base_directory: &PathBuf) -> PathBuf {
     dest.push(extdir);
 
     let (_, extname) = crate::commands::get::find_control_file();
-    let version = get_version();
+    let version = get_version(Path::new("."));
     dest.push(format!("{}--{}.sql", extname, version));
 
     dest
@@ -166,14 +166,20 @@ fn copy_sql_files(
     let (_, extname) = crate::commands::get::find_control_file();
 
     crate::schema::generate_schema(
+        Path::new("."),
+        &get_target_dir(),
         pg_config,
         is_release,
         &*additional_features,
         &dest,
         Option::<String>::None,
+        Option::<String>::None,
         None,
         false,
         true,
+        None,
+        None,
+        false,
     )?;
     copy_file(&dest, &dest, "extension schema file", true);
 
@@ -229,8 +235,8 @@ pub(crate) fn find_library_file(extname: &str, is_release: bool) -> PathBuf {
     }
 }
 
-pub(crate) fn get_version() -> String {
-    match get_property("default_version") {
+pub(crate) fn get_version(crate_dir: &Path) -> String {
+    match get_property_in(crate_dir, "default_version") {
         Some(v) => v,
         None => exit_with_error!("cannot determine extension version number.  Is the `default_version` property declared in the control file?"),
     }
@@ -273,7 +279,7 @@ fn filter_contents(mut input: String) -> String {
         input = input.replace("@GIT_HASH@", &get_git_hash());
     }
 
-    input = input.replace("@DEFAULT_VERSION@", &get_version());
+    input = input.replace("@DEFAULT_VERSION@", &get_version(Path::new(".")));
 
     input
 }
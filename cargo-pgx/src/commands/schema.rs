@@ -1,6 +1,6 @@
-use crate::commands::get::find_control_file;
-use crate::commands::get::get_property;
+use crate::commands::get::{find_control_file_in, generate_control_file, get_property_in};
 use colored::Colorize;
+use pgx_utils::control_file::ControlMetadata;
 use pgx_utils::pg_config::PgConfig;
 use pgx_utils::{exit_with_error, handle_result};
 use std::collections::HashSet;
@@ -8,7 +8,7 @@ use std::fs::File;
 use std::os::unix::prelude::PermissionsExt;
 use std::{
     io::{Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 use symbolic::{
@@ -16,25 +16,47 @@ use symbolic::{
     debuginfo::{Archive, SymbolIterator},
 };
 
+/// Generate the schema of the extension crate in `crate_dir`, building it in `target_dir`.
+/// Relative paths are relative to `crate_dir`.
 pub(crate) fn generate_schema(
+    crate_dir: &Path,
+    target_dir: &Path,
     pg_config: &PgConfig,
     is_release: bool,
     additional_features: &[&str],
     path: impl AsRef<std::path::Path>,
     dot: Option<impl AsRef<std::path::Path>>,
+    json: Option<impl AsRef<std::path::Path>>,
     log_level: Option<String>,
     force_default: bool,
     manual: bool,
+    upgrade_from: Option<&str>,
+    snapshot: Option<&Path>,
+    check: bool,
 ) -> Result<(), std::io::Error> {
-    let (control_file, _extname) = find_control_file();
+    let (control_file, extname) = find_control_file_in(crate_dir);
     let major_version = pg_config.major_version()?;
 
+    // The earlier version's snapshot is generated first, as it's built from git
+    let upgrade = match upgrade_from {
+        Some(from) => Some(upgrade_from_snapshot(
+            crate_dir,
+            target_dir,
+            pg_config,
+            is_release,
+            additional_features,
+            log_level.clone(),
+            from,
+        )?),
+        None => None,
+    };
+
     // If not manual, we should ensure a few files exist and are what is expected.
     if !manual {
         let cargo_toml = {
             let mut buf = String::default();
-            let mut cargo_file =
-                std::fs::File::open("Cargo.toml").expect(&format!("Could not open Cargo.toml"));
+            let mut cargo_file = std::fs::File::open(crate_dir.join("Cargo.toml"))
+                .expect(&format!("Could not open Cargo.toml"));
             cargo_file
                 .read_to_string(&mut buf)
                 .expect(&format!("Could not read Cargo.toml"));
@@ -56,6 +78,7 @@ pub(crate) fn generate_schema(
             crate_name
         );
         check_templated_file(
+            crate_dir,
             "src/bin/sql-generator.rs",
             expected_bin_source_content,
             force_default,
@@ -63,28 +86,33 @@ pub(crate) fn generate_schema(
 
         let expected_linker_script = include_str!("../templates/pgx-linker-script.sh");
         check_templated_file(
+            crate_dir,
             ".cargo/pgx-linker-script.sh",
             expected_linker_script.to_string(),
             force_default,
         )?;
         std::fs::set_permissions(
-            ".cargo/pgx-linker-script.sh",
+            crate_dir.join(".cargo/pgx-linker-script.sh"),
             std::fs::Permissions::from_mode(0o755),
         )
         .unwrap();
         let expected_cargo_config = include_str!("../templates/cargo_config");
         check_templated_file(
+            crate_dir,
             ".cargo/config",
             expected_cargo_config.to_string(),
             force_default,
         )?;
     }
 
-    if get_property("relocatable") != Some("false".into()) {
-        exit_with_error!(
-            "{}:  The `relocatable` property MUST be `false`.  Please update your .control file.",
-            control_file.display()
-        )
+    if get_property_in(crate_dir, "relocatable") != Some("false".into()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "{}:  The `relocatable` property MUST be `false`.  Please update your .control file.",
+                control_file.display()
+            ),
+        ));
     }
 
     let mut features =
@@ -99,6 +127,8 @@ pub(crate) fn generate_schema(
 
     // First, build the SQL generator so we can get a look at the symbol table
     let mut command = Command::new("cargo");
+    command.current_dir(crate_dir);
+    command.env("CARGO_TARGET_DIR", target_dir);
     command.args(&["build", "--bin", "sql-generator"]);
     if is_release {
         command.arg("--release");
@@ -131,11 +161,14 @@ pub(crate) fn generate_schema(
         format!("failed to spawn cargo: {}", command_str)
     );
     if !status.success() {
-        exit_with_error!("failed to build SQL generator");
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "failed to build SQL generator",
+        ));
     }
 
     // Inspect the symbol table for a list of `__pgx_internals` we should have the generator call
-    let mut sql_gen_path = pgx_utils::get_target_dir();
+    let mut sql_gen_path = target_dir.to_path_buf();
     sql_gen_path.push(if is_release { "release" } else { "debug" });
     sql_gen_path.push("sql-generator");
     println!("{} SQL entities", " Discovering".bold().green(),);
@@ -181,6 +214,7 @@ pub(crate) fn generate_schema(
     let mut num_sqls = 0;
     let mut num_ords = 0;
     let mut num_hashes = 0;
+    let mut num_aggregates = 0;
     for func in &fns_to_call {
         if func.starts_with("__pgx_internals_schema_") {
             let schema = func
@@ -201,11 +235,13 @@ pub(crate) fn generate_schema(
             num_ords += 1;
         } else if func.starts_with("__pgx_internals_hash_") {
             num_hashes += 1;
+        } else if func.starts_with("__pgx_internals_aggregate_") {
+            num_aggregates += 1;
         }
     }
 
     println!(
-        "{} {} SQL entities: {} schemas ({} unique), {} functions, {} types, {} enums, {} sqls, {} ords, {} hashes",
+        "{} {} SQL entities: {} schemas ({} unique), {} functions, {} types, {} enums, {} sqls, {} ords, {} hashes, {} aggregates",
         "  Discovered".bold().green(),
         fns_to_call.len().to_string().bold().cyan(),
         seen_schemas.iter().count().to_string().bold().cyan(),
@@ -216,10 +252,13 @@ pub(crate) fn generate_schema(
         num_sqls.to_string().bold().cyan(),
         num_ords.to_string().bold().cyan(),
         num_hashes.to_string().bold().cyan(),
+        num_aggregates.to_string().bold().cyan(),
     );
 
     // Now run the generator with the correct symbol table
     let mut command = Command::new("cargo");
+    command.current_dir(crate_dir);
+    command.env("CARGO_TARGET_DIR", target_dir);
     command.args(&["run", "--bin", "sql-generator"]);
     if is_release {
         command.arg("--release");
@@ -240,13 +279,56 @@ pub(crate) fn generate_schema(
     }
 
     let path = path.as_ref();
-    let _ = path.parent().map(|p| std::fs::create_dir_all(&p).unwrap());
+    if check && !crate_dir.join(path).exists() {
+        exit_with_error!(
+            "`{}` does not exist, generate it with `cargo pgx schema`",
+            path.display()
+        );
+    }
+    let _ = crate_dir
+        .join(path)
+        .parent()
+        .map(|p| std::fs::create_dir_all(&p).unwrap());
     command.arg("--");
     command.arg("--sql");
     command.arg(path);
-    if let Some(dot) = dot {
-        command.arg("--dot");
-        command.arg(dot.as_ref());
+    if check {
+        println!(
+            "{} `{}` against the extension's SQL",
+            "    Checking".bold().green(),
+            path.display().to_string().bold().cyan()
+        );
+        command.arg("--check");
+    } else {
+        if let Some(dot) = dot {
+            command.arg("--dot");
+            command.arg(dot.as_ref());
+        }
+        if let Some(json) = json {
+            command.arg("--json");
+            command.arg(json.as_ref());
+        }
+        if let Some(snapshot) = snapshot {
+            command.arg("--snapshot");
+            command.arg(snapshot);
+        }
+    }
+    if let Some((from_version, from_path)) = &upgrade {
+        let upgrade_path = format!(
+            "sql/{}--{}--{}.sql",
+            extname,
+            from_version,
+            crate::commands::install::get_version(crate_dir)
+        );
+        println!(
+            "{} upgrade script `{}`",
+            "  Generating".bold().green(),
+            upgrade_path.bold().cyan()
+        );
+        command.arg("--upgrade-from");
+        command.arg(from_path);
+        command.arg("--upgrade");
+        command.arg(upgrade_path);
     }
     command.env(
         "PGX_SQL_ENTITY_SYMBOLS",
@@ -268,19 +350,180 @@ pub(crate) fn generate_schema(
         format!("failed to spawn cargo: {}", command_str)
     );
     if !status.success() {
-        exit_with_error!("failed to run SQL generator");
+        // The generator exits with 2 only when the schema is out of date
+        if check && status.code() == Some(2) {
+            exit_with_error!(
+                "`{}` is out of date, regenerate it with `cargo pgx schema`",
+                path.display()
+            );
+        }
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "failed to run SQL generator",
+        ));
     }
     Ok(())
 }
 
+/// The version and the snapshot of the SQL objects of the earlier version of the extension the git
+/// ref `from` names, for `--upgrade-from`.  The snapshot is generated from `from`, checked out in
+/// a temporary worktree.
+fn upgrade_from_snapshot(
+    crate_dir: &Path,
+    target_dir: &Path,
+    pg_config: &PgConfig,
+    is_release: bool,
+    additional_features: &[&str],
+    log_level: Option<String>,
+    from: &str,
+) -> Result<(String, PathBuf), std::io::Error> {
+    // The control file might be generated from `Cargo.toml`, in which case `from` likely doesn't
+    // have it
+    let (control_file, extname) = find_control_file_in(crate_dir);
+    let from_version = git_show(crate_dir, from, &control_file)
+        .and_then(|control| {
+            control
+                .lines()
+                .filter_map(|line| {
+                    let mut parts = line.splitn(2, '=');
+                    match (parts.next(), parts.next()) {
+                        (Some(k), Some(v)) if k.trim() == "default_version" => {
+                            Some(v.trim().trim_matches('\'').trim().to_string())
+                        }
+                        _ => None,
+                    }
+                })
+                .next()
+        })
+        .or_else(|| {
+            let cargo_toml = git_show(crate_dir, from, Path::new("Cargo.toml"))?;
+            let metadata = ControlMetadata::from_cargo_toml(&cargo_toml).ok()??;
+            Some(metadata.default_version)
+        })
+        .unwrap_or_else(|| {
+            exit_with_error!(
+                "cannot determine the extension version of `{}`.  Is the `default_version` property declared in its control file, or its `Cargo.toml` `[package.metadata.pgx]`?",
+                from
+            )
+        });
+
+    // Outside of the crate, so cargo doesn't consider the worktree part of its workspace
+    let path = std::env::temp_dir().join(format!(
+        "pgx-upgrade-from-{}-{}",
+        extname,
+        from.replace(|c: char| !c.is_alphanumeric(), "_")
+    ));
+    let path_str = path.display().to_string();
+    if path.exists() {
+        // Left over from an earlier run which didn't finish
+        std::fs::remove_dir_all(&path)?;
+        git(crate_dir, &["worktree", "prune"]);
+    }
+    println!(
+        "{} `{}` into `{}`",
+        " Checking out".bold().green(),
+        from.bold().cyan(),
+        path_str
+    );
+    git(crate_dir, &["worktree", "add", "--detach", &path_str, from]);
+    let worktree = Worktree {
+        repo: crate_dir.to_path_buf(),
+        path,
+    };
+
+    // Generate the schema and its snapshot in the worktree as if `cargo pgx schema` ran there,
+    // sharing our target directory so dependencies aren't built twice
+    let from_sql_path = target_dir.join(format!("{}-{}.sql", extname, from_version));
+    let from_path = target_dir.join(format!("{}-{}.snapshot.json", extname, from_version));
+    let from_crate_dir = worktree
+        .path
+        .join(git(crate_dir, &["rev-parse", "--show-prefix"]).trim());
+    generate_control_file(&from_crate_dir, false)?;
+    generate_schema(
+        &from_crate_dir,
+        target_dir,
+        pg_config,
+        is_release,
+        additional_features,
+        &from_sql_path,
+        Option::<String>::None,
+        Option::<String>::None,
+        log_level,
+        false,
+        false,
+        None,
+        Some(&from_path),
+        false,
+    )?;
+
+    Ok((from_version, from_path))
+}
+
+/// A `git worktree`, removed when dropped, so that it's removed should generating the earlier
+/// version's schema fail
+struct Worktree {
+    /// The repository the worktree was added to
+    repo: PathBuf,
+    path: PathBuf,
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let removed = Command::new("git")
+            .current_dir(&self.repo)
+            .args(&["worktree", "remove", "--force"])
+            .arg(&self.path)
+            .output();
+        if !matches!(removed, Ok(output) if output.status.success()) {
+            eprintln!(
+                "{} could not remove the worktree `{}`",
+                "     Warning".bold().yellow(),
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Run `git` with `args` in `dir`, returning its output.
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = handle_result!(
+        Command::new("git").current_dir(dir).args(args).output(),
+        format!("problem running `git {}`", args.join(" "))
+    );
+    if !output.status.success() {
+        exit_with_error!(
+            "problem running `git {}`: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// The contents of `path`, relative to `dir`, at the git ref `from`, if it has that file.
+fn git_show(dir: &Path, from: &str, path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .arg("show")
+        .arg(format!("{}:./{}", from, path.display()))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
 /// Returns Ok(true) if something was created.
 fn check_templated_file(
+    crate_dir: &Path,
     path: impl AsRef<Path>,
     expected_content: String,
     overwrite: bool,
 ) -> Result<bool, std::io::Error> {
     let path = path.as_ref();
-    let existing_contents = match File::open(&path) {
+    let full_path = crate_dir.join(path);
+    let existing_contents = match File::open(&full_path) {
         Ok(mut file) => Some({
             let mut buf = String::default();
             file.read_to_string(&mut buf)?;
@@ -304,10 +547,10 @@ fn check_templated_file(
                     " Overwriting".bold().yellow(),
                     path.display().to_string().bold().cyan()
                 );
-                if let Some(parent) = path.parent() {
+                if let Some(parent) = full_path.parent() {
                     std::fs::create_dir_all(parent)?;
                 };
-                let mut fd = File::create(path)?;
+                let mut fd = File::create(&full_path)?;
                 fd.write_all(expected_content.as_bytes())?;
                 Ok(true)
             } else {
@@ -327,10 +570,10 @@ fn check_templated_file(
                 "    Creating".bold().green(),
                 path.display().to_string().bold().cyan()
             );
-            if let Some(parent) = path.parent() {
+            if let Some(parent) = full_path.parent() {
                 std::fs::create_dir_all(parent)?;
             };
-            let mut fd = File::create(path)?;
+            let mut fd = File::create(&full_path)?;
             fd.write_all(expected_content.as_bytes())?;
             Ok(true)
         }
@@ -7,7 +7,7 @@ extern crate clap;
 mod commands;
 
 use crate::commands::connect::connect_psql;
-use crate::commands::get::get_property;
+use crate::commands::get::{generate_control_file, get_property};
 use crate::commands::init::init_pgx;
 use crate::commands::install::install_extension;
 use crate::commands::new::create_crate_template;
@@ -24,7 +24,7 @@ use pgx_utils::handle_result;
 use pgx_utils::pg_config::{PgConfig, PgConfigSelector, Pgx};
 use pgx_utils::{exit, exit_with_error};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 const SUPPORTED_MAJOR_VERSIONS: &[u16] = &[10, 11, 12, 13, 14];
@@ -44,6 +44,13 @@ fn do_it() -> std::result::Result<(), std::io::Error> {
         .get_matches();
 
     if let Some(extension) = matches.subcommand_matches("pgx") {
+        // `schema` writes it itself, as `schema --check` only checks it
+        if let Some("install") | Some("package") | Some("run") | Some("test") =
+            extension.subcommand_name()
+        {
+            generate_control_file(Path::new("."), false)?;
+        }
+
         let result = match extension.subcommand() {
             ("init", Some(init)) => {
                 let mut versions = HashMap::new();
@@ -233,7 +240,7 @@ fn do_it() -> std::result::Result<(), std::io::Error> {
                         format!(
                             "sql/{}-{}.sql",
                             extname,
-                            crate::commands::install::get_version()
+                            crate::commands::install::get_version(Path::new("."))
                         )
                     });
                 let dot = if schema.occurrences_of("dot") == 1 {
@@ -241,6 +248,11 @@ fn do_it() -> std::result::Result<(), std::io::Error> {
                 } else {
                     None
                 };
+                let json = if schema.occurrences_of("json") == 1 {
+                    schema.value_of("json").map(|x| x.to_string())
+                } else {
+                    None
+                };
                 let is_release = schema.is_present("release");
 
                 let log_level = if let Ok(log_level) = std::env::var("RUST_LOG") {
@@ -280,9 +292,25 @@ fn do_it() -> std::result::Result<(), std::io::Error> {
 
                 let default = schema.is_present("force-default");
                 let manual = schema.is_present("manual");
+                let upgrade_from = schema.value_of("upgrade-from");
+                let check = schema.is_present("check");
 
+                generate_control_file(Path::new("."), check)?;
                 schema::generate_schema(
-                    &pg_config, is_release, &features, &out, dot, log_level, default, manual,
+                    Path::new("."),
+                    &pgx_utils::get_target_dir(),
+                    &pg_config,
+                    is_release,
+                    &features,
+                    &out,
+                    dot,
+                    json,
+                    log_level,
+                    default,
+                    manual,
+                    upgrade_from,
+                    None,
+                    check,
                 )
             }
             ("get", Some(get)) => {
@@ -54,32 +54,23 @@ impl IntegerAvgState {
     }
 }
 
-#[pg_extern]
-fn integer_avg_state_func(
-    internal_state: PgVarlena<IntegerAvgState>,
-    next_data_value: i32,
-) -> PgVarlena<IntegerAvgState> {
-    internal_state.acc(next_data_value)
-}
+#[pg_aggregate]
+impl Aggregate for IntegerAvgState {
+    type State = PgVarlena<Self>;
+    type Args = i32;
+    type Finalize = i32;
+    const NAME: &'static str = "DEMOAVG";
+    const INITIAL_CONDITION: Option<&'static str> = Some("0,0");
+
+    fn state(current: Self::State, arg: Self::Args) -> Self::State {
+        current.acc(arg)
+    }
 
-#[pg_extern]
-fn integer_avg_final_func(internal_state: PgVarlena<IntegerAvgState>) -> i32 {
-    internal_state.finalize()
+    fn finalize(current: Self::State, _direct_args: Self::OrderedSetArgs) -> Self::Finalize {
+        current.finalize()
+    }
 }
 
-extension_sql!(
-    r#"
-    CREATE AGGREGATE DEMOAVG (integer)
-    (
-        sfunc = integer_avg_state_func,
-        stype = IntegerAvgState,
-        finalfunc = integer_avg_final_func,
-        initcond = '0,0'
-    );
-    "#,
-    name = "create_demoavg_aggregate",
-);
-
 #[cfg(any(test, feature = "pg_test"))]
 #[pg_schema]
 mod tests {
@@ -40,6 +40,7 @@ pub use home::dogs::Dog;
 //    where `$ident` is a string identifier or a path to a SQL entity (such as a type which derives
 //    `PostgresType`)
 //  * `creates = [Enum($ident), Type($ident), Function($ident)]` tells the dependency graph that this block creates a given entity.
+//    A Rust type or enum of that name gets a `SqlTranslatable` implementation, so functions can use it.
 //  * `name` is an optional string identifier for the item, in case you need to refer to it in
 //    other positioning.
 extension_sql!(
@@ -21,7 +21,7 @@ INSERT INTO spi_example (title) VALUES ('I like pudding');
 
 #[pg_extern]
 fn spi_return_query(
-) -> impl std::iter::Iterator<Item = (name!(oid, Option<pg_sys::Oid>), name!(name, Option<String>))>
+) -> impl std::iter::Iterator<Item = (name!(oid, Option<PgOid>), name!(name, Option<String>))>
 {
     #[cfg(feature = "pg10")]
     let query = "SELECT oid, relname::text || '-pg10' FROM pg_class";
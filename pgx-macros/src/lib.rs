@@ -33,7 +33,10 @@ pub fn pg_guard(_attr: TokenStream, item: TokenStream) -> TokenStream {
         // process top-level functions
         // these functions get wrapped as public extern "C" functions with #[no_mangle] so they
         // can also be called from C code
-        Item::Fn(func) => rewriter.item_fn(func, None, false, false, false).0.into(),
+        Item::Fn(func) => rewriter
+            .item_fn(func, None, false, RewriteFlags::default())
+            .0
+            .into(),
         _ => {
             panic!("#[pg_guard] can only be applied to extern \"C\" blocks and top-level functions")
         }
@@ -181,11 +184,277 @@ mod dsl {
 
 File modules (like `mod name;`) aren't able to be supported due to [`rust/#54725`](https://github.com/rust-lang/rust/issues/54725).
 
+The module's doc comment becomes the `COMMENT ON SCHEMA`, unless `#[pg_schema(no_comment)]` is used.
+
+*/
+#[proc_macro_attribute]
+pub fn pg_schema(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match sql_entity_graph::Schema::new(attr.into(), item.into()) {
+        Ok(pgx_schema) => pgx_schema.to_token_stream().into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/**
+Declare an `impl Aggregate for ...` block to be a Postgres aggregate.
+
+Generates a `#[pg_extern]` support function for each of the trait's functions the block provides,
+and the `CREATE AGGREGATE` statement which uses them.
+
+```rust,ignore
+use pgx::*;
+
+pub struct DemoSum;
+
+#[pg_aggregate]
+impl Aggregate for DemoSum {
+    type State = i32;
+    type Args = i32;
+    const NAME: &'static str = "demo_sum";
+    const INITIAL_CONDITION: Option<&'static str> = Some("0");
+
+    fn state(current: Self::State, arg: Self::Args) -> Self::State {
+        current + arg
+    }
+}
+```
+
+See the `pgx::aggregate` module for details.
+*/
+#[proc_macro_attribute]
+pub fn pg_aggregate(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let pg_aggregate = parse_macro_input!(item as sql_entity_graph::PgAggregate);
+    pg_aggregate.to_token_stream().into()
+}
+
+/**
+Declare an `impl GistOpClass for ...` block to be the GiST operator class of a type.
+
+Generates a `#[pg_extern]` support function for each of the trait's functions the block provides,
+and the `CREATE OPERATOR CLASS ... USING gist` statement which uses them.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_gist_opclass]
+impl GistOpClass for Span {
+    const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+
+    fn consistent(key: &Span, query: &Span, _strategy: u16, _is_leaf: bool, _recheck: &mut bool) -> bool {
+        key.overlaps(query)
+    }
+    fn union(keys: Vec<Span>) -> Span { Span::cover(keys) }
+    fn penalty(original: &Span, new: &Span) -> f32 { original.growth(new) }
+    fn picksplit(keys: &[Span]) -> (Vec<usize>, Vec<usize>) { Span::split(keys) }
+    fn same(a: &Span, b: &Span) -> bool { a == b }
+}
+```
+
+See the `pgx::gist` module for details.
+*/
+#[proc_macro_attribute]
+pub fn pg_gist_opclass(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let pg_gist_opclass = parse_macro_input!(item as sql_entity_graph::PgGistOpClass);
+    pg_gist_opclass.to_token_stream().into()
+}
+
+/**
+Declare an `impl GinOpClass for ...` block to be the GIN operator class of a type.
+
+Generates the `compare` support function from the `Ord` of the `Key`, a `#[pg_extern]` support
+function for each of the trait's functions the block provides, and the
+`CREATE OPERATOR CLASS ... USING gin` statement which uses them.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_gin_opclass]
+impl GinOpClass for Tags {
+    type Key = String;
+    const OPERATORS: &'static [(u16, &'static str)] = &[(2, "@>")];
+
+    fn extract_value(value: Tags) -> Vec<String> { value.tags }
+    fn extract_query(query: &Tags, _strategy: u16) -> Vec<String> { query.tags.clone() }
+    fn consistent(check: &[bool], _strategy: u16, _query: &Tags, _recheck: &mut bool) -> bool {
+        check.iter().all(|&present| present)
+    }
+}
+```
+
+See the `pgx::gin` module for details.
+*/
+#[proc_macro_attribute]
+pub fn pg_gin_opclass(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let pg_gin_opclass = parse_macro_input!(item as sql_entity_graph::PgGinOpClass);
+    pg_gin_opclass.to_token_stream().into()
+}
+
+/**
+Declare an `impl BrinOpClass for ...` block to be the BRIN operator class of a type.
+
+Generates the `opcInfo`, `addValue`, `consistent` and `union` support functions, and the
+`CREATE OPERATOR CLASS ... USING brin` statement which uses them.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_brin_opclass]
+impl BrinOpClass for Reading {
+    type Summary = Period;
+    type Query = Period;
+    const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+
+    fn add_value(summary: Option<&Period>, value: Reading) -> Option<Period> { Period::extend(summary, value.at) }
+    fn consistent(summary: &Period, query: &Period, _strategy: u16) -> bool { summary.overlaps(query) }
+    fn union(a: &Period, b: &Period) -> Period { a.cover(b) }
+}
+```
+
+See the `pgx::brin` module for details.
+*/
+#[proc_macro_attribute]
+pub fn pg_brin_opclass(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let pg_brin_opclass = parse_macro_input!(item as sql_entity_graph::PgBrinOpClass);
+    pg_brin_opclass.to_token_stream().into()
+}
+
+/**
+Declare an `impl SpGistOpClass for ...` block to be the SP-GiST operator class of a type.
+
+Generates the `config`, `choose`, `picksplit`, `inner_consistent` and `leaf_consistent` support
+functions, and the `CREATE OPERATOR CLASS ... USING spgist` statement which uses them.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_spgist_opclass]
+impl SpGistOpClass for Spot {
+    type Prefix = Spot;
+    type Query = Area;
+    const OPERATORS: &'static [(u16, &'static str)] = &[(8, "<@")];
+
+    fn choose(value: &Spot, inner: &SpGistInner<Spot, ()>) -> SpGistChoice<Spot, ()> { Spot::quadrant(value, inner) }
+    fn picksplit(values: &[Spot], _level: u32) -> SpGistSplit<Spot, ()> { Spot::split(values) }
+    fn inner_consistent(queries: &[(u16, Area)], inner: &SpGistInner<Spot, ()>) -> Vec<usize> { Area::quadrants(queries, inner) }
+    fn leaf_consistent(value: &Spot, queries: &[(u16, Area)], _recheck: &mut bool) -> bool { Area::contain(queries, value) }
+}
+```
+
+See the `pgx::spgist` module for details.
+*/
+#[proc_macro_attribute]
+pub fn pg_spgist_opclass(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let pg_spgist_opclass = parse_macro_input!(item as sql_entity_graph::PgSpGistOpClass);
+    pg_spgist_opclass.to_token_stream().into()
+}
+
+/**
+Declare a function to be a Postgres trigger function.
+
+The function takes the `&PgTrigger` describing the event which fired it, and returns the row
+Postgres should use in place of the one it was given, `None` to skip the operation, or an error
+which is raised as a Postgres `ERROR`.
+
+```rust,ignore
+use pgx::*;
+
+#[pg_trigger]
+fn audit(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, PgHeapTupleError> {
+    let mut new = trigger.new().expect("not fired by a row-level INSERT or UPDATE");
+    new.set_by_name("updated_by", Some(trigger.name()))?;
+    Ok(Some(new))
+}
+
+extension_sql!(
+    r#"
+CREATE TRIGGER audit BEFORE INSERT OR UPDATE ON things FOR EACH ROW EXECUTE PROCEDURE audit();
+"#,
+    name = "create_audit_trigger",
+    requires = [audit]
+);
+```
+
+The function's doc comment becomes the `COMMENT ON FUNCTION`, unless `#[pg_trigger(no_comment)]`
+is used.
+
+See the `pgx::trigger_support` module for details.
+*/
+#[proc_macro_attribute]
+pub fn pg_trigger(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match sql_entity_graph::PgTrigger::with_attr(attr.into(), item.into()) {
+        Ok(pg_trigger) => pg_trigger.to_token_stream().into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/**
+Declare a function to be a Postgres event trigger function.
+
+The function takes the `&PgEventTrigger` describing the command which fired it.  Returning an error
+raises it as a Postgres `ERROR`, which aborts the command.
+
+Optionally accepts the `event` to fire for (one of `ddl_command_start`, `ddl_command_end`, `sql_drop`
+or `table_rewrite`) and the command `tags` to limit it to, in which case the `CREATE EVENT TRIGGER`
+statement is generated along with the function:
+
+```rust,ignore
+use pgx::*;
+
+#[pg_event_trigger(event = "ddl_command_start", tags = ["DROP TABLE"])]
+fn no_drop_table(trigger: &PgEventTrigger) -> Result<(), String> {
+    Err(format!("{} is not allowed", trigger.tag()))
+}
+```
+
+The function's doc comment becomes the `COMMENT ON FUNCTION`, unless `no_comment` is given.
+
+See the `pgx::event_trigger` module for details.
 */
 #[proc_macro_attribute]
-pub fn pg_schema(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let pgx_schema = parse_macro_input!(item as sql_entity_graph::Schema);
-    pgx_schema.to_token_stream().into()
+pub fn pg_event_trigger(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match sql_entity_graph::PgEventTrigger::new(attr.into(), item.into()) {
+        Ok(pg_event_trigger) => pg_event_trigger.to_token_stream().into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/**
+Declare a `static` `GucSetting` to be a GUC, a setting of the extension.
+
+```rust,ignore
+use pgx::*;
+
+/// How long to wait before giving up
+///
+/// Longer explanations go in the following paragraphs.
+#[pg_guc(name = "myext.threshold", context = Suset, min = 0, max = 100, units = "ms")]
+static THRESHOLD: GucSetting<i32> = GucSetting::new(10);
+
+#[pg_guard]
+pub extern "C" fn _PG_init() {}
+```
+
+The GUCs are defined, in order of their names, when `_PG_init()` is called, which a `#[pg_guard]`
+`_PG_init()` does before its own body.  Their prefixes (here `myext`) are reserved, so Postgres warns
+about unknown settings using them.
+
+Only the `name` is required, which must be prefixed by the extension's name:
+
+* `context`: a `GucContext`, `Userset` by default.
+* `min` and `max`: the range of an `i32` or `f64` GUC, the full range of the type by default.
+* `units`: one of `B` (Postgres 11 and later), `kB`, `MB`, `8kB`, `ms`, `s` or `min`.
+
+The first paragraph of the doc comment is the short description of the GUC, and the rest its long
+description.
+
+See the `pgx::guc` module for details.
+*/
+#[proc_macro_attribute]
+pub fn pg_guc(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match sql_entity_graph::PgGuc::new(attr.into(), item.into()) {
+        Ok(pg_guc) => pg_guc.to_token_stream().into(),
+        Err(e) => e.to_compile_error().into(),
+    }
 }
 
 /**
@@ -196,7 +465,7 @@ Accepts a String literal, a `name` attribute, and optionally others:
 * `name = "item"`: Set the unique identifer to `"item"` for use in `requires` declarations.
 * `requires = [item, item_two]`: References to other `name`s or Rust items which this SQL should be present after.
 * `creates = [ Type(submod::Cust), Enum(Pre), Function(defined)]`: Communicates that this SQL block creates certain entities.
-  Please note it **does not** create matching Rust types.
+  Please note it **does not** create matching Rust types, it only implements `pgx::datum::SqlTranslatable` for the `Type`s and `Enum`s.
 * `bootstrap` (**Unique**): Communicates that this is SQL intended to go before all other generated SQL.
 * `finalize` (**Unique**): Communicates that this is SQL intended to go after all other generated SQL.
 
@@ -383,6 +652,42 @@ pub fn search_path(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/**
+Associated macro for `#[pg_extern]`.  Declares the function to be a cast from the type of its first
+argument to its return type, with [`CREATE CAST`](https://www.postgresql.org/docs/current/sql-createcast.html).
+
+Optionally accepts the context the cast is applied in:
+
+* `#[pg_cast]`: Only when explicitly asked for, as in `value::type`.
+* `#[pg_cast(assignment)]`: Corresponds to `AS ASSIGNMENT`.
+* `#[pg_cast(implicit)]`: Corresponds to `AS IMPLICIT`.
+
+The function may also take the type modifier as an `i32` and whether the cast is explicit as a
+`bool`, as its second and third arguments.
+
+```rust,ignore
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PostgresType)]
+struct Celsius {
+    degrees: f64,
+}
+
+#[pg_extern(immutable)]
+#[pg_cast(implicit)]
+fn float8_to_celsius(degrees: f64) -> Celsius {
+    Celsius { degrees }
+}
+```
+
+`#[pg_cast]` must come after `#[pg_extern]`, which reads it.
+*/
+#[proc_macro_attribute]
+pub fn pg_cast(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /**
 Declare a function as `#[pg_extern]` to indicate that it can be used by Postgres as a UDF.
 
@@ -398,6 +703,24 @@ Optionally accepts the following attributes:
 * `parallel_unsafe`: Corresponds to [`PARALLEL UNSAFE`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `parallel_restricted`: Corresponds to [`PARALLEL RESTRICTED`](https://www.postgresql.org/docs/current/sql-createfunction.html).
 * `no_guard`: Do not use `#[pg_guard]` with the function.
+* `window`: Corresponds to [`WINDOW`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + Arguments are read from the current row through the function's `pgx::WindowObject`.
+* `materialize`: For functions returning an `Iterator`, collect all of its rows into a `pgx::Tuplestore`
+  which is handed to Postgres at once, rather than returning one row per call.
+* `called_on_null_input`: Corresponds to [`CALLED ON NULL INPUT`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + Disables the automatic `strict`, so a `NULL` passed to a non-`Option<T>` argument raises an error.
+* `security_definer`: Corresponds to [`SECURITY DEFINER`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+* `leakproof`: Corresponds to [`LEAKPROOF`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+* `cost = 1000`: Corresponds to [`COST`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+* `rows = 10`: Corresponds to [`ROWS`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + Only set-returning functions accept it.
+* `set(search_path = "pg_catalog", work_mem = "64MB")`: Corresponds to [`SET`](https://www.postgresql.org/docs/current/sql-createfunction.html).
+  + Comma separated values, such as `"pg_catalog, public"`, are set as a list.
+* `support = my_support_fn`: Corresponds to [`SUPPORT`](https://www.postgresql.org/docs/current/sql-createfunction.html) (Postgres 12+).
+  + A path refers to another `#[pg_extern]`, which is created first, while a string (`support = "pg_catalog.textlike_support"`)
+    is used as the SQL name of the function as-is.
+  + See `pgx::PgSupportRequest` for writing support functions in Rust.
+* `no_comment`: Do not turn the function's doc comment into a [`COMMENT ON FUNCTION`](https://www.postgresql.org/docs/current/sql-comment.html).
 
 Functions can accept and return any type which `pgx` supports. `pgx` supports many PostgreSQL types by default.
 New types can be defined via [`macro@PostgresType`] or [`macro@PostgresEnum`].
@@ -471,8 +794,6 @@ fn boop() -> i32 { todo!() }
 fn doop() -> Option<i32> { todo!() }
 #[pg_extern]
 fn swoop() -> Option<Vec<Option<i32>>> { todo!() }
-#[pg_extern]
-fn floop() -> (i32, i32) { todo!() }
 ```
 
 Like in PostgreSQL, it's possible to return tables using iterators and the `name!()` macro:
@@ -484,34 +805,57 @@ fn floop() -> impl Iterator<Item = (name!(a, i32), name!(b, i32))> {
     None.into_iter() // Help type inference...
 }
 
+```
+
+A single row is returned as a tuple of `name!()`s, which become `OUT` parameters of a function
+returning `record`, so that callers can `SELECT (singular_floop()).*`.  An `Option` of such a tuple
+returns a `NULL` row when `None`:
+
+```rust,ignore
+use pgx::*;
 #[pg_extern]
-fn singlular_floop() -> (name!(a, i32), name!(b, i32)) {
+fn singular_floop() -> (name!(a, i32), name!(b, i32)) {
     todo!()
 }
 ```
 
-The `name!()` macro may only be used in return position inside the `Item` of an `impl Iterator`.
+`inout!()` arguments are returned as the leading columns of the tuple, in argument order, followed
+by the `OUT` parameters (a function with a single `inout!()` argument may return its type directly):
+
+```rust,ignore
+use pgx::*;
+#[pg_extern]
+fn bump(counter: inout!(i32), step: i32) -> (name!(counter, i32), name!(previous, i32)) {
+    (counter + step, counter)
+}
+```
+
+The `name!()` macro may only be used in return position, inside a tuple.
 
 It accepts 2 arguments:
 
 * A name, such as `example`
 * A type
 
-# Special Cases
+# SQL Types
+
+The SQL type of each argument and of the return type comes from its implementation of
+`pgx::datum::SqlTranslatable`, so a type without one is a compile error.  Type aliases resolve
+like the type they alias, however they're written.
 
-`pg_sys::Oid` is a special cased type alias, in order to use it as an argument or return it must be
-passed with it's full module path (`pg_sys::Oid`) in order to be resolved.
+`pg_sys::Oid` is an alias of `u32`, which has no SQL type, so `oid`s are taken and returned as
+`pgx::PgOid`:
 
 ```rust,ignore
 use pgx::*;
 
 #[pg_extern]
-fn example_arg(animals: pg_sys::Oid) {
+fn example_arg(animals: PgOid) {
     todo!()
 }
 
 #[pg_extern]
-fn example_return() -> pg_sys::Oid {
+fn example_return() -> pgx::pg_sys::PgOid {
     todo!()
 }
 ```
@@ -522,7 +866,10 @@ pub fn pg_extern(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_extern_attributes(proc_macro2::TokenStream::from(attr.clone()));
 
     let sql_graph_entity_item =
-        sql_entity_graph::PgExtern::new(attr.clone().into(), item.clone().into()).unwrap();
+        match sql_entity_graph::PgExtern::new(attr.clone().into(), item.clone().into()) {
+            Ok(item) => item,
+            Err(e) => return e.to_compile_error().into(),
+        };
 
     let ast = parse_macro_input!(item as syn::Item);
     match ast {
@@ -531,13 +878,96 @@ pub fn pg_extern(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/**
+Declare a Postgres function written in SQL (or any other language Postgres supports) by its Rust
+signature, which has no body.
+
+The signature maps the arguments and return type to SQL exactly like `#[pg_extern]`'s, so they are
+checked against the Rust types, and the function is created in order with the types it uses.
+Anything else the body depends on can be named with `requires`.
+
+Requires `language` and `body`, and otherwise accepts the `#[pg_extern]` attributes which don't
+describe the Rust function (so not `raw`, `no_guard`, `window`, `materialize` or `error`):
+
+```rust,ignore
+use pgx::*;
+
+#[pg_sql_function(immutable, parallel_safe, language = "sql", body = "SELECT $1::bigint * 2")]
+fn double_it(value: i32) -> i64;
+```
+*/
+#[proc_macro_attribute]
+pub fn pg_sql_function(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match sql_entity_graph::PgSqlFunction::new(attr.into(), item.into()) {
+        Ok(pg_sql_function) => pg_sql_function.to_token_stream().into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/**
+Declare a function as `#[pg_procedure]` to expose it to Postgres as a procedure.
+`cargo pgx schema` will automatically generate the underlying `CREATE PROCEDURE` SQL.  Requires
+Postgres 11 or later.
+
+It accepts the `#[pg_extern]` attributes which make sense for a procedure (`name`, `schema`,
+`requires`, `raw`, `no_guard`, `no_comment`, `security_definer`, `set(...)`, and `#[search_path(...)]`).
+
+Arguments may be marked `INOUT` with the `inout!()` macro.  A procedure with an `INOUT` argument
+returns that argument's new value, which `CALL` hands back to its caller:
+
+```rust,ignore
+use pgx::*;
+
+#[pg_procedure]
+fn double_it(value: inout!(i32)) -> i32 {
+    value * 2
+}
+```
+
+When `CALL`ed outside of a transaction block (and not from within a function), a procedure may
+end the current transaction with `Spi::commit()` or `Spi::rollback()`:
+
+```rust,ignore
+use pgx::*;
+
+#[pg_procedure]
+fn load_in_batches(batches: i32) {
+    for batch in 0..batches {
+        Spi::run(&format!("INSERT INTO loaded VALUES ({})", batch));
+        Spi::commit();
+    }
+}
+```
+*/
+#[proc_macro_attribute]
+pub fn pg_procedure(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_extern_attributes(proc_macro2::TokenStream::from(attr.clone()));
+
+    let sql_graph_entity_item =
+        match sql_entity_graph::PgExtern::new_procedure(attr.clone().into(), item.clone().into()) {
+            Ok(item) => item,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+    let ast = parse_macro_input!(item as syn::Item);
+    match ast {
+        Item::Fn(func) => rewrite_item_fn(func, args, &sql_graph_entity_item).into(),
+        _ => panic!("#[pg_procedure] can only be applied to top-level functions"),
+    }
+}
+
 fn rewrite_item_fn(
     mut func: ItemFn,
     extern_args: HashSet<ExternArgs>,
     sql_graph_entity_submission: &sql_entity_graph::PgExtern,
 ) -> proc_macro2::TokenStream {
-    let is_raw = extern_args.contains(&ExternArgs::Raw);
-    let no_guard = extern_args.contains(&ExternArgs::NoGuard);
+    let flags = RewriteFlags {
+        is_raw: extern_args.contains(&ExternArgs::Raw),
+        is_window: extern_args.contains(&ExternArgs::Window),
+        is_procedure: sql_graph_entity_submission.procedure(),
+        is_materialize: extern_args.contains(&ExternArgs::Materialize),
+        no_guard: extern_args.contains(&ExternArgs::NoGuard),
+    };
 
     let finfo_name = syn::Ident::new(
         &format!("pg_finfo_{}_wrapper", func.sig.ident),
@@ -552,13 +982,8 @@ fn rewrite_item_fn(
     // make the function 'extern "C"' because this is for the #[pg_extern[ macro
     func.sig.abi = Some(syn::parse_str("extern \"C\"").unwrap());
     let func_span = func.span();
-    let (rewritten_func, need_wrapper) = rewriter.item_fn(
-        func,
-        Some(sql_graph_entity_submission),
-        true,
-        is_raw,
-        no_guard,
-    );
+    let (rewritten_func, need_wrapper) =
+        rewriter.item_fn(func, Some(sql_graph_entity_submission), true, flags);
 
     if need_wrapper {
         quote_spanned! {func_span=>
@@ -592,8 +1017,10 @@ enum DogNames {
 }
 ```
 
+The enum's doc comment becomes the `COMMENT ON TYPE`, unless it's marked `#[no_comment]`.
+
 */
-#[proc_macro_derive(PostgresEnum, attributes(requires))]
+#[proc_macro_derive(PostgresEnum, attributes(requires, no_comment))]
 pub fn postgres_enum(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
 
@@ -652,6 +1079,12 @@ fn impl_postgres_enum(ast: DeriveInput) -> proc_macro2::TokenStream {
             }
 
         }
+
+        impl pgx::datum::SqlTranslatable for #enum_ident {
+            fn sql_type() -> String {
+                String::from(#enum_name)
+            }
+        }
     });
 
     let sql_graph_entity_item =
@@ -679,9 +1112,13 @@ Optionally accepts the following attributes:
 
 * `inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the type.
 * `pgvarlena_inoutfuncs(some_in_fn, some_out_fn)`: Define custom in/out functions for the `PgVarlena` of this type.
+* `no_comment`: Do not turn the type's doc comment into a [`COMMENT ON TYPE`](https://www.postgresql.org/docs/current/sql-comment.html).
 
 */
-#[proc_macro_derive(PostgresType, attributes(inoutfuncs, pgvarlena_inoutfuncs, requires))]
+#[proc_macro_derive(
+    PostgresType,
+    attributes(inoutfuncs, pgvarlena_inoutfuncs, requires, no_comment)
+)]
 pub fn postgres_type(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as syn::DeriveInput);
 
@@ -716,6 +1153,12 @@ fn impl_postgres_type(ast: DeriveInput) -> proc_macro2::TokenStream {
     // all #[derive(PostgresType)] need to implement that trait
     stream.extend(quote! {
         impl #generics pgx::PostgresType for #name #generics { }
+
+        impl #generics pgx::datum::SqlTranslatable for #name #generics {
+            fn sql_type() -> String {
+                String::from(stringify!(#name))
+            }
+        }
     });
 
     // and if we don't have custom inout/funcs, we use the JsonInOutFuncs trait
@@ -16,6 +16,32 @@ use syn::{
 
 pub struct PgGuardRewriter();
 
+/// How [`PgGuardRewriter::item_fn`] wraps a function, from the attributes it was declared with
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RewriteFlags {
+    /// `raw`: arguments are taken as-is, rather than converted from their `Datum`s
+    pub is_raw: bool,
+    /// `window`: arguments are taken through the function's `WindowObject`
+    pub is_window: bool,
+    /// `#[pg_procedure]`: the function may control transactions
+    pub is_procedure: bool,
+    /// `materialize`: a set-returning function returns all its rows at once
+    pub is_materialize: bool,
+    /// `no_guard`: the function is not wrapped in `pg_sys::guard::guard()`
+    pub no_guard: bool,
+}
+
+/// The parts each kind of `extern "C"` wrapper around a rewritten function is built from
+struct WrapperFn<'a> {
+    func_span: Span,
+    prolog: proc_macro2::TokenStream,
+    vis: Visibility,
+    func_name_wrapper: Ident,
+    generics: &'a Generics,
+    func_call: proc_macro2::TokenStream,
+    sql_graph_entity_submission: Option<&'a pgx_utils::sql_entity_graph::PgExtern>,
+}
+
 impl PgGuardRewriter {
     pub fn new() -> Self {
         PgGuardRewriter()
@@ -36,14 +62,13 @@ impl PgGuardRewriter {
         func: ItemFn,
         entity_submission: Option<&pgx_utils::sql_entity_graph::PgExtern>,
         rewrite_args: bool,
-        is_raw: bool,
-        no_guard: bool,
+        flags: RewriteFlags,
     ) -> (proc_macro2::TokenStream, bool) {
         if rewrite_args {
-            self.item_fn_with_rewrite(func, entity_submission, is_raw, no_guard)
+            self.item_fn_with_rewrite(func, entity_submission, flags)
         } else {
             (
-                self.item_fn_without_rewrite(func, entity_submission, no_guard),
+                self.item_fn_without_rewrite(func, entity_submission, flags.no_guard),
                 true,
             )
         }
@@ -53,9 +78,16 @@ impl PgGuardRewriter {
         &self,
         mut func: ItemFn,
         entity_submission: Option<&pgx_utils::sql_entity_graph::PgExtern>,
-        is_raw: bool,
-        no_guard: bool,
+        flags: RewriteFlags,
     ) -> (proc_macro2::TokenStream, bool) {
+        let RewriteFlags {
+            is_raw,
+            is_window,
+            is_procedure,
+            is_materialize,
+            no_guard,
+        } = flags;
+
         // remember the original visibility and signature classifications as we want
         // to use those for the outer function
         let vis = func.vis.clone();
@@ -67,7 +99,7 @@ impl PgGuardRewriter {
         let arg_list = PgGuardRewriter::build_arg_list(&func.sig, true);
         let func_name = &func.sig.ident;
         let func_span = func.span();
-        let rewritten_args = self.rewrite_args(func.clone(), is_raw);
+        let rewritten_args = self.rewrite_args(func.clone(), is_raw, is_window);
         let rewritten_return_type = self.rewrite_return_type(func.clone());
         let generics = &func.sig.generics;
         let func_name_wrapper = Ident::new(
@@ -84,12 +116,24 @@ impl PgGuardRewriter {
             Ident::new("result", Span::call_site())
         };
 
-        let func_call = quote! {
-            let #result_var_name = {
-                #rewritten_args
+        let func_call = if is_procedure {
+            // procedures that may control transactions need a non-atomic SPI connection, and do not
+            // exist before Postgres 11, where `__pgx_call_procedure!` is a compile error
+            quote! {
+                let #result_var_name = {
+                    #rewritten_args
+
+                    pgx::__pgx_call_procedure!(fcinfo, || #func_name(#arg_list))
+                };
+            }
+        } else {
+            quote! {
+                let #result_var_name = {
+                    #rewritten_args
 
-                #func_name(#arg_list)
-            };
+                    #func_name(#arg_list)
+                };
+            }
         };
 
         let prolog = quote! {
@@ -97,100 +141,82 @@ impl PgGuardRewriter {
 
             #[allow(unused_variables)]
         };
+        let wrapper = WrapperFn {
+            func_span,
+            prolog,
+            vis,
+            func_name_wrapper,
+            generics,
+            func_call,
+            sql_graph_entity_submission: entity_submission,
+        };
         match categorize_return_type(&func) {
+            CategorizedType::Default if is_materialize => (
+                quote_spanned! {func_span=>
+                    compile_error!("`materialize` can only be used by functions which return an `Iterator`");
+                },
+                false,
+            ),
+
             CategorizedType::Default => (
-                PgGuardRewriter::impl_standard_udf(
-                    func_span,
-                    prolog,
-                    vis,
-                    func_name_wrapper,
-                    generics,
-                    func_call,
-                    rewritten_return_type,
-                    entity_submission,
-                    no_guard,
-                ),
+                PgGuardRewriter::impl_standard_udf(wrapper, rewritten_return_type, no_guard),
                 true,
             ),
 
-            CategorizedType::Tuple(_types) => (
-                PgGuardRewriter::impl_tuple_udf(func, entity_submission.clone()),
+            CategorizedType::Tuple(_types) if is_materialize => (
+                quote_spanned! {func_span=>
+                    compile_error!("`materialize` can only be used by functions which return an `Iterator`");
+                },
                 false,
             ),
 
+            CategorizedType::Tuple(types) => {
+                let optional = match &func.sig.output {
+                    ReturnType::Type(_, ty) => type_matches(ty, "Option"),
+                    ReturnType::Default => false,
+                };
+                (
+                    PgGuardRewriter::impl_record_udf(types, wrapper, optional, no_guard),
+                    true,
+                )
+            }
+
             CategorizedType::Iterator(types) if types.len() == 1 => (
-                PgGuardRewriter::impl_setof_srf(
-                    types,
-                    func_span,
-                    prolog,
-                    vis,
-                    func_name_wrapper,
-                    generics,
-                    func_call,
-                    entity_submission,
-                    false,
-                ),
+                PgGuardRewriter::impl_setof_srf(types, wrapper, false, is_materialize),
                 true,
             ),
 
             CategorizedType::OptionalIterator(types) if types.len() == 1 => (
-                PgGuardRewriter::impl_setof_srf(
-                    types,
-                    func_span,
-                    prolog,
-                    vis,
-                    func_name_wrapper,
-                    generics,
-                    func_call,
-                    entity_submission,
-                    true,
-                ),
+                PgGuardRewriter::impl_setof_srf(types, wrapper, true, is_materialize),
                 true,
             ),
 
             CategorizedType::Iterator(types) => (
-                PgGuardRewriter::impl_table_srf(
-                    types,
-                    func_span,
-                    prolog,
-                    vis,
-                    func_name_wrapper,
-                    generics,
-                    func_call,
-                    entity_submission,
-                    false,
-                ),
+                PgGuardRewriter::impl_table_srf(types, wrapper, false, is_materialize),
                 true,
             ),
 
             CategorizedType::OptionalIterator(types) => (
-                PgGuardRewriter::impl_table_srf(
-                    types,
-                    func_span,
-                    prolog,
-                    vis,
-                    func_name_wrapper,
-                    generics,
-                    func_call,
-                    entity_submission,
-                    true,
-                ),
+                PgGuardRewriter::impl_table_srf(types, wrapper, true, is_materialize),
                 true,
             ),
         }
     }
 
     fn impl_standard_udf(
-        func_span: Span,
-        prolog: proc_macro2::TokenStream,
-        vis: Visibility,
-        func_name_wrapper: Ident,
-        generics: &Generics,
-        func_call: proc_macro2::TokenStream,
+        wrapper: WrapperFn,
         rewritten_return_type: proc_macro2::TokenStream,
-        sql_graph_entity_submission: Option<&pgx_utils::sql_entity_graph::PgExtern>,
         no_guard: bool,
     ) -> proc_macro2::TokenStream {
+        let WrapperFn {
+            func_span,
+            prolog,
+            vis,
+            func_name_wrapper,
+            generics,
+            func_call,
+            sql_graph_entity_submission,
+        } = wrapper;
         let guard = if no_guard {
             quote! {#[no_mangle]}
         } else {
@@ -214,46 +240,101 @@ impl PgGuardRewriter {
         }
     }
 
-    fn impl_tuple_udf(
-        mut func: ItemFn,
-        entity_submission: Option<&pgx_utils::sql_entity_graph::PgExtern>,
+    /// A function returning a single row, whose columns are the `OUT` (and `INOUT`) parameters
+    /// named by the `name!()`s of its tuple
+    fn impl_record_udf(
+        types: Vec<String>,
+        wrapper: WrapperFn,
+        optional: bool,
+        no_guard: bool,
     ) -> proc_macro2::TokenStream {
-        let func_span = func.span();
-        let return_type = func.sig.output;
-        let return_type = format!("{}", quote! {#return_type});
-        let return_type =
-            proc_macro2::TokenStream::from_str(return_type.trim_start_matches("->")).unwrap();
-        let return_type = quote! {impl std::iter::Iterator<Item = #return_type>};
-        let attrs = entity_submission.unwrap().extern_attr_tokens();
-
-        func.sig.output = ReturnType::Default;
-        let sig = func.sig;
-        let body = func.block;
-
-        // We do **not** put an entity submission here as there still exists a `pg_extern` attribute.
-        //
-        // This is because we quietely rewrite the function signature to `Iterator<Item = T>` and
-        // rely on #[pg_extern] being called again during compilation.  It is important that we
-        // include the original #[pg_extern(<attributes>)] in the generated code.
+        let WrapperFn {
+            func_span,
+            prolog,
+            vis,
+            func_name_wrapper,
+            generics,
+            func_call,
+            sql_graph_entity_submission,
+        } = wrapper;
+        let guard = if no_guard {
+            quote! {#[no_mangle]}
+        } else {
+            quote! {#[pg_guard]}
+        };
+        let numtypes = types.len();
+        let i = (0..numtypes).map(syn::Index::from);
+        let result_handler = if optional {
+            quote! {
+                let result = match result {
+                    Some(result) => result,
+                    None => return pgx::pg_return_null(fcinfo),
+                };
+            }
+        } else {
+            quote! {}
+        };
+        let sql_graph_entity_submission = sql_graph_entity_submission.cloned().into_iter();
+
         quote_spanned! {func_span=>
-            #[pg_extern(#attrs)]
-            #sig -> #return_type {
-                Some(#body).into_iter()
+            #prolog
+
+            #[allow(clippy::missing_safety_doc)]
+            #guard
+            #vis unsafe extern "C" fn #func_name_wrapper #generics(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+
+                #func_call
+
+                #result_handler
+
+                let mut tupdesc: *mut pgx::pg_sys::TupleDescData = std::ptr::null_mut();
+
+                /* Build a tuple descriptor for our result type */
+                if pgx::pg_sys::get_call_result_type(fcinfo, std::ptr::null_mut(), &mut tupdesc) != pgx::pg_sys::TypeFuncClass_TYPEFUNC_COMPOSITE {
+                    pgx::error!("return type must be a row type");
+                }
+                let tupdesc = pgx::pg_sys::BlessTupleDesc(tupdesc);
+
+                let mut datums: [pgx::pg_sys::Datum; #numtypes] = [0; #numtypes];
+                let mut nulls: [bool; #numtypes] = [false; #numtypes];
+                #(
+                    match result.#i.into_datum() {
+                        Some(datum) => { datums[#i] = datum; },
+                        None => { nulls[#i] = true; }
+                    }
+                )*
+
+                let heap_tuple = pgx::pg_sys::heap_form_tuple(tupdesc, datums.as_mut_ptr(), nulls.as_mut_ptr());
+                pgx::heap_tuple_get_datum(heap_tuple)
             }
+
+            #(#sql_graph_entity_submission)*
         }
     }
 
     fn impl_setof_srf(
         types: Vec<String>,
-        func_span: Span,
-        prolog: proc_macro2::TokenStream,
-        vis: Visibility,
-        func_name_wrapper: Ident,
-        generics: &Generics,
-        func_call: proc_macro2::TokenStream,
-        sql_graph_entity_submission: Option<&pgx_utils::sql_entity_graph::PgExtern>,
+        wrapper: WrapperFn,
         optional: bool,
+        materialize: bool,
     ) -> proc_macro2::TokenStream {
+        if materialize {
+            return PgGuardRewriter::impl_materialize_srf(
+                wrapper,
+                optional,
+                quote! { &[result.into_datum()] },
+            );
+        }
+        let WrapperFn {
+            func_span,
+            prolog,
+            vis,
+            func_name_wrapper,
+            generics,
+            func_call,
+            sql_graph_entity_submission,
+        } = wrapper;
+
         let generic_type = proc_macro2::TokenStream::from_str(types.first().unwrap()).unwrap();
         let mut generic_type = syn::parse2::<syn::Type>(generic_type).unwrap();
         pgx_utils::anonymonize_lifetimes(&mut generic_type);
@@ -332,16 +413,29 @@ impl PgGuardRewriter {
 
     fn impl_table_srf(
         types: Vec<String>,
-        func_span: Span,
-        prolog: proc_macro2::TokenStream,
-        vis: Visibility,
-        func_name_wrapper: Ident,
-        generics: &Generics,
-        func_call: proc_macro2::TokenStream,
-        entity_submission: Option<&pgx_utils::sql_entity_graph::PgExtern>,
+        wrapper: WrapperFn,
         optional: bool,
+        materialize: bool,
     ) -> proc_macro2::TokenStream {
         let numtypes = types.len();
+        if materialize {
+            let i = (0..numtypes).map(syn::Index::from);
+            return PgGuardRewriter::impl_materialize_srf(
+                wrapper,
+                optional,
+                quote! { &[#(result.#i.into_datum()),*] },
+            );
+        }
+        let WrapperFn {
+            func_span,
+            prolog,
+            vis,
+            func_name_wrapper,
+            generics,
+            func_call,
+            sql_graph_entity_submission,
+        } = wrapper;
+
         let i = (0..numtypes).map(syn::Index::from);
         let create_heap_tuple = quote! {
             let mut datums: [usize; #numtypes] = [0; #numtypes];
@@ -380,7 +474,7 @@ impl PgGuardRewriter {
                 let result = pgx::PgMemoryContexts::For(funcctx.multi_call_memory_ctx).switch_to(|_| { #func_call result });
             }
         };
-        let sql_graph_entity_submission = entity_submission.cloned().into_iter();
+        let sql_graph_entity_submission = sql_graph_entity_submission.cloned().into_iter();
 
         quote_spanned! {func_span=>
             #prolog
@@ -445,6 +539,48 @@ impl PgGuardRewriter {
         }
     }
 
+    /// A set-returning function which drains its iterator into a `pgx::Tuplestore` and hands it to
+    /// Postgres in one go (`SFRM_Materialize`).  `values` converts each `result` into the
+    /// `&[Option<pg_sys::Datum>]` of a row.
+    fn impl_materialize_srf(
+        wrapper: WrapperFn,
+        optional: bool,
+        values: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let WrapperFn {
+            func_span,
+            prolog,
+            vis,
+            func_name_wrapper,
+            generics,
+            func_call,
+            sql_graph_entity_submission,
+        } = wrapper;
+        let results = if optional {
+            quote! { result.into_iter().flatten() }
+        } else {
+            quote! { result }
+        };
+        let sql_graph_entity_submission = sql_graph_entity_submission.cloned().into_iter();
+
+        quote_spanned! {func_span=>
+            #prolog
+            #[pg_guard]
+            #vis unsafe extern "C" fn #func_name_wrapper #generics(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+                let mut tuplestore = pgx::Tuplestore::for_materialize(fcinfo);
+
+                #func_call
+
+                for result in #results {
+                    tuplestore.put_values(#values);
+                }
+                tuplestore.return_materialized(fcinfo)
+            }
+
+            #(#sql_graph_entity_submission)*
+        }
+    }
+
     fn item_fn_without_rewrite(
         &self,
         mut func: ItemFn,
@@ -472,10 +608,17 @@ impl PgGuardRewriter {
         let arg_list = PgGuardRewriter::build_arg_list(&sig, false);
         let func_name = PgGuardRewriter::build_func_name(&func.sig);
 
-        let func_call = if no_guard {
+        let func_call = if input_func_name == "_PG_init" {
+            // the GUCs declared with `#[pg_guc]` are defined before the extension initializes itself
+            quote! { { pgx::guc::define_declared_gucs(); #func_name(#arg_list) } }
+        } else {
             quote! { #func_name(#arg_list) }
+        };
+
+        let func_call = if no_guard {
+            func_call
         } else {
-            quote! { pg_sys::guard::guard( || #func_name(#arg_list) ) }
+            quote! { pg_sys::guard::guard( || #func_call ) }
         };
 
         let prolog = if input_func_name == "__pgx_private_shmem_hook" {
@@ -640,9 +783,14 @@ impl PgGuardRewriter {
         sig.output.clone()
     }
 
-    pub fn rewrite_args(&self, func: ItemFn, is_raw: bool) -> proc_macro2::TokenStream {
+    pub fn rewrite_args(
+        &self,
+        func: ItemFn,
+        is_raw: bool,
+        is_window: bool,
+    ) -> proc_macro2::TokenStream {
         let fsr = FunctionSignatureRewriter::new(func);
-        let args = fsr.args(is_raw);
+        let args = fsr.args(is_raw, is_window);
 
         quote! {
             #args
@@ -705,7 +853,7 @@ impl FunctionSignatureRewriter {
         stream
     }
 
-    fn args(&self, is_raw: bool) -> proc_macro2::TokenStream {
+    fn args(&self, is_raw: bool, is_window: bool) -> proc_macro2::TokenStream {
         if self.func.sig.inputs.len() == 1 && self.return_type_is_datum() {
             if let FnArg::Typed(ty) = self.func.sig.inputs.first().unwrap() {
                 if type_matches(&ty.ty, "pg_sys :: FunctionCallInfo") {
@@ -724,6 +872,12 @@ impl FunctionSignatureRewriter {
                     Pat::Ident(ident) => {
                         let name = Ident::new(&format!("{}_", ident.ident), ident.span());
                         let mut type_ = ty.ty.clone();
+                        if let Type::Macro(type_macro) = type_.deref() {
+                            // `inout!(T)` only matters to the generated SQL
+                            if type_macro.mac.path.segments.last().unwrap().ident == "inout" {
+                                *type_ = type_macro.mac.parse_body().unwrap();
+                            }
+                        }
                         let is_option = type_matches(&type_, "Option");
 
                         if have_fcinfo {
@@ -735,8 +889,15 @@ impl FunctionSignatureRewriter {
                             let mut option_type = syn::parse2::<syn::Type>(option_type).unwrap();
                             pgx_utils::anonymonize_lifetimes(&mut option_type);
 
-                            quote_spanned! {ident.span()=>
-                                let #name = pgx::pg_getarg::<#option_type>(fcinfo, #i);
+                            if is_window {
+                                // window functions are handed their arguments through their WindowObject
+                                quote_spanned! {ident.span()=>
+                                    let #name = unsafe { pgx::WindowObject::from_fcinfo(fcinfo) }.arg_current::<#option_type>(#i);
+                                }
+                            } else {
+                                quote_spanned! {ident.span()=>
+                                    let #name = pgx::pg_getarg::<#option_type>(fcinfo, #i);
+                                }
                             }
                         } else if type_matches(&type_, "pg_sys :: FunctionCallInfo") {
                             have_fcinfo = true;
@@ -747,6 +908,11 @@ impl FunctionSignatureRewriter {
                             quote_spanned! {ident.span()=>
                                 let #name = pgx::pg_getarg_datum_raw(fcinfo, #i) as #type_;
                             }
+                        } else if is_window {
+                            pgx_utils::anonymonize_lifetimes(&mut type_);
+                            quote_spanned! {ident.span()=>
+                                let #name = unsafe { pgx::WindowObject::from_fcinfo(fcinfo) }.arg_current::<#type_>(#i).unwrap_or_else(|| panic!("{} is null", stringify!{#ident}));
+                            }
                         } else {
                             pgx_utils::anonymonize_lifetimes(&mut type_);
                             quote_spanned! {ident.span()=>
@@ -6,8 +6,11 @@
 #include "funcapi.h"
 #include "miscadmin.h"
 #include "pgstat.h"
+#include "windowapi.h"
 
 #include "access/amapi.h"
+#include "access/brin_internal.h"
+#include "access/brin_tuple.h"
 #include "access/genam.h"
 #include "access/generic_xlog.h"
 #include "access/gin.h"
@@ -18,6 +21,7 @@
 #include "access/reloptions.h"
 #include "access/relscan.h"
 #include "access/skey.h"
+#include "access/spgist.h"
 #include "access/sysattr.h"
 #include "access/xact.h"
 #include "catalog/dependency.h"
@@ -25,6 +29,7 @@
 #include "catalog/namespace.h"
 #include "catalog/objectaddress.h"
 #include "catalog/pg_class.h"
+#include "catalog/pg_collation.h"
 #include "catalog/pg_enum.h"
 #include "catalog/pg_proc.h"
 #include "catalog/pg_trigger.h"
@@ -64,6 +69,7 @@
 #include "optimizer/planner.h"
 #include "optimizer/restrictinfo.h"
 #include "optimizer/tlist.h"
+#include "parser/parse_coerce.h"
 #include "parser/parse_func.h"
 #include "parser/parse_type.h"
 #include "parser/parser.h"
@@ -82,6 +88,7 @@
 #include "utils/builtins.h"
 #include "utils/date.h"
 #include "utils/datetime.h"
+#include "utils/formatting.h"
 
 #define double float8
 #include "utils/geo_decls.h"
@@ -100,3 +107,4 @@
 #include "utils/snapmgr.h"
 #include "utils/syscache.h"
 #include "utils/typcache.h"
+#include "utils/varlena.h"
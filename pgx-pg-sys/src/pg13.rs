@@ -3072,6 +3072,10 @@ pub const Anum_pg_enum_enumtypid: u32 = 2;
 pub const Anum_pg_enum_enumsortorder: u32 = 3;
 pub const Anum_pg_enum_enumlabel: u32 = 4;
 pub const Natts_pg_enum: u32 = 4;
+pub const CollationRelationId: u32 = 3456;
+pub const DEFAULT_COLLATION_OID: u32 = 100;
+pub const C_COLLATION_OID: u32 = 950;
+pub const POSIX_COLLATION_OID: u32 = 951;
 pub const ProcedureRelationId: u32 = 1255;
 pub const ProcedureRelation_Rowtype_Id: u32 = 81;
 pub const Anum_pg_proc_oid: u32 = 1;
@@ -59140,6 +59144,366 @@ extern "C" {
     pub fn get_collation_name(colloid: Oid) -> *mut ::std::os::raw::c_char;
 }
 #[pg_guard]
+extern "C" {
+    pub fn str_tolower(
+        buff: *const ::std::os::raw::c_char,
+        nbytes: usize,
+        collid: Oid,
+    ) -> *mut ::std::os::raw::c_char;
+}
+#[pg_guard]
+extern "C" {
+    pub fn str_toupper(
+        buff: *const ::std::os::raw::c_char,
+        nbytes: usize,
+        collid: Oid,
+    ) -> *mut ::std::os::raw::c_char;
+}
+#[pg_guard]
+extern "C" {
+    pub fn varstr_cmp(
+        arg1: *const ::std::os::raw::c_char,
+        len1: ::std::os::raw::c_int,
+        arg2: *const ::std::os::raw::c_char,
+        len2: ::std::os::raw::c_int,
+        collid: Oid,
+    ) -> ::std::os::raw::c_int;
+}
+pub const WINDOW_SEEK_CURRENT: u32 = 0;
+pub const WINDOW_SEEK_HEAD: u32 = 1;
+pub const WINDOW_SEEK_TAIL: u32 = 2;
+pub type WindowObject = *mut WindowObjectData;
+#[pg_guard]
+extern "C" {
+    pub fn WinGetPartitionLocalMemory(winobj: WindowObject, sz: Size) -> *mut ::std::os::raw::c_void;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetCurrentPosition(winobj: WindowObject) -> int64;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetPartitionRowCount(winobj: WindowObject) -> int64;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinSetMarkPosition(winobj: WindowObject, markpos: int64);
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinRowsArePeers(winobj: WindowObject, pos1: int64, pos2: int64) -> bool;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetFuncArgInPartition(
+        winobj: WindowObject,
+        argno: ::std::os::raw::c_int,
+        relpos: ::std::os::raw::c_int,
+        seektype: ::std::os::raw::c_int,
+        set_mark: bool,
+        isnull: *mut bool,
+        isout: *mut bool,
+    ) -> Datum;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetFuncArgInFrame(
+        winobj: WindowObject,
+        argno: ::std::os::raw::c_int,
+        relpos: ::std::os::raw::c_int,
+        seektype: ::std::os::raw::c_int,
+        set_mark: bool,
+        isnull: *mut bool,
+        isout: *mut bool,
+    ) -> Datum;
+}
+#[pg_guard]
+extern "C" {
+    pub fn WinGetFuncArgCurrent(
+        winobj: WindowObject,
+        argno: ::std::os::raw::c_int,
+        isnull: *mut bool,
+    ) -> Datum;
+}
+#[repr(C)]
+#[derive(Debug)]
+pub struct BrinOpcInfo {
+    pub oi_nstored: uint16,
+    pub oi_opaque: *mut ::std::os::raw::c_void,
+    pub oi_typcache: __IncompleteArrayField<*mut TypeCacheEntry>,
+}
+impl Default for BrinOpcInfo {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug)]
+pub struct BrinDesc {
+    pub bd_context: MemoryContext,
+    pub bd_index: Relation,
+    pub bd_tupdesc: TupleDesc,
+    pub bd_disktdesc: TupleDesc,
+    pub bd_totalstored: ::std::os::raw::c_int,
+    pub bd_info: __IncompleteArrayField<*mut BrinOpcInfo>,
+}
+impl Default for BrinDesc {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct BrinValues {
+    pub bv_attno: AttrNumber,
+    pub bv_hasnulls: bool,
+    pub bv_allnulls: bool,
+    pub bv_values: *mut Datum,
+}
+impl Default for BrinValues {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct spgConfigIn {
+    pub attType: Oid,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct spgConfigOut {
+    pub prefixType: Oid,
+    pub labelType: Oid,
+    pub canReturnData: bool,
+    pub longValuesOK: bool,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgChooseIn {
+    pub datum: Datum,
+    pub leafDatum: Datum,
+    pub level: ::std::os::raw::c_int,
+    pub allTheSame: bool,
+    pub hasPrefix: bool,
+    pub prefixDatum: Datum,
+    pub nNodes: ::std::os::raw::c_int,
+    pub nodeLabels: *mut Datum,
+}
+impl Default for spgChooseIn {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+pub const spgChooseResultType_spgMatchNode: spgChooseResultType = 1;
+pub const spgChooseResultType_spgAddNode: spgChooseResultType = 2;
+pub const spgChooseResultType_spgSplitTuple: spgChooseResultType = 3;
+pub type spgChooseResultType = ::std::os::raw::c_uint;
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct spgChooseOut {
+    pub resultType: spgChooseResultType,
+    pub result: spgChooseOut__bindgen_ty_1,
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union spgChooseOut__bindgen_ty_1 {
+    pub matchNode: spgChooseOut__bindgen_ty_1__bindgen_ty_1,
+    pub addNode: spgChooseOut__bindgen_ty_1__bindgen_ty_2,
+    pub splitTuple: spgChooseOut__bindgen_ty_1__bindgen_ty_3,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct spgChooseOut__bindgen_ty_1__bindgen_ty_1 {
+    pub nodeN: ::std::os::raw::c_int,
+    pub levelAdd: ::std::os::raw::c_int,
+    pub restDatum: Datum,
+}
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct spgChooseOut__bindgen_ty_1__bindgen_ty_2 {
+    pub nodeLabel: Datum,
+    pub nodeN: ::std::os::raw::c_int,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgChooseOut__bindgen_ty_1__bindgen_ty_3 {
+    pub prefixHasPrefix: bool,
+    pub prefixPrefixDatum: Datum,
+    pub prefixNNodes: ::std::os::raw::c_int,
+    pub prefixNodeLabels: *mut Datum,
+    pub childNodeN: ::std::os::raw::c_int,
+    pub postfixHasPrefix: bool,
+    pub postfixPrefixDatum: Datum,
+}
+impl Default for spgChooseOut__bindgen_ty_1__bindgen_ty_3 {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+impl Default for spgChooseOut__bindgen_ty_1 {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+impl Default for spgChooseOut {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgPickSplitIn {
+    pub nTuples: ::std::os::raw::c_int,
+    pub datums: *mut Datum,
+    pub level: ::std::os::raw::c_int,
+}
+impl Default for spgPickSplitIn {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgPickSplitOut {
+    pub hasPrefix: bool,
+    pub prefixDatum: Datum,
+    pub nNodes: ::std::os::raw::c_int,
+    pub nodeLabels: *mut Datum,
+    pub mapTuplesToNodes: *mut ::std::os::raw::c_int,
+    pub leafTupleDatums: *mut Datum,
+}
+impl Default for spgPickSplitOut {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgInnerConsistentIn {
+    pub scankeys: ScanKey,
+    pub orderbys: ScanKey,
+    pub nkeys: ::std::os::raw::c_int,
+    pub norderbys: ::std::os::raw::c_int,
+    pub reconstructedValue: Datum,
+    pub traversalValue: *mut ::std::os::raw::c_void,
+    pub traversalMemoryContext: MemoryContext,
+    pub level: ::std::os::raw::c_int,
+    pub returnData: bool,
+    pub allTheSame: bool,
+    pub hasPrefix: bool,
+    pub prefixDatum: Datum,
+    pub nNodes: ::std::os::raw::c_int,
+    pub nodeLabels: *mut Datum,
+}
+impl Default for spgInnerConsistentIn {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgInnerConsistentOut {
+    pub nNodes: ::std::os::raw::c_int,
+    pub nodeNumbers: *mut ::std::os::raw::c_int,
+    pub levelAdds: *mut ::std::os::raw::c_int,
+    pub reconstructedValues: *mut Datum,
+    pub traversalValues: *mut *mut ::std::os::raw::c_void,
+    pub distances: *mut *mut f64,
+}
+impl Default for spgInnerConsistentOut {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgLeafConsistentIn {
+    pub scankeys: ScanKey,
+    pub orderbys: ScanKey,
+    pub nkeys: ::std::os::raw::c_int,
+    pub norderbys: ::std::os::raw::c_int,
+    pub reconstructedValue: Datum,
+    pub traversalValue: *mut ::std::os::raw::c_void,
+    pub level: ::std::os::raw::c_int,
+    pub returnData: bool,
+    pub leafDatum: Datum,
+}
+impl Default for spgLeafConsistentIn {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct spgLeafConsistentOut {
+    pub leafValue: Datum,
+    pub recheck: bool,
+    pub recheckDistances: bool,
+    pub distances: *mut f64,
+}
+impl Default for spgLeafConsistentOut {
+    fn default() -> Self {
+        let mut s = ::std::mem::MaybeUninit::<Self>::uninit();
+        unsafe {
+            ::std::ptr::write_bytes(s.as_mut_ptr(), 0, 1);
+            s.assume_init()
+        }
+    }
+}
+#[pg_guard]
+extern "C" {
+    pub fn IsBinaryCoercible(srctype: Oid, targettype: Oid) -> bool;
+}
+#[pg_guard]
 extern "C" {
     pub fn get_collation_isdeterministic(colloid: Oid) -> bool;
 }
@@ -186,6 +186,16 @@ fn get_pg_config() -> PgConfig {
         .clone()
 }
 
+/// Connect a client to the test database, setting it up first if necessary.
+///
+/// Unlike the body of a `#[pg_test]`, which is called as a function within a transaction that is
+/// rolled back, statements run through this client are committed.  This allows testing things
+/// such as procedures that control transactions.
+pub fn connect_test_client(postgresql_conf: Vec<&'static str>) -> postgres::Client {
+    initialize_test_framework(postgresql_conf);
+    client().0
+}
+
 pub fn client() -> (postgres::Client, String) {
     fn determine_session_id(client: &mut Client) -> String {
         let result = client.query("SELECT to_hex(trunc(EXTRACT(EPOCH FROM backend_start))::integer) || '.' || to_hex(pid) AS sid FROM pg_stat_activity WHERE pid = pg_backend_pid();", &[]).expect("failed to determine session id");
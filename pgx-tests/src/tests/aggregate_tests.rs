@@ -0,0 +1,160 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+pub struct DemoSum;
+
+#[pg_aggregate]
+impl Aggregate for DemoSum {
+    type State = i32;
+    type Args = i32;
+    const NAME: &'static str = "demo_sum";
+    const INITIAL_CONDITION: Option<&'static str> = Some("0");
+    const PARALLEL: Option<ParallelOption> = Some(ParallelOption::Safe);
+
+    fn state(current: Self::State, arg: Self::Args) -> Self::State {
+        current + arg
+    }
+
+    fn combine(current: Self::State, other: Self::State) -> Self::State {
+        current + other
+    }
+}
+
+pub struct DemoMax;
+
+#[pg_aggregate]
+impl Aggregate for DemoMax {
+    type State = Option<i32>;
+    type Args = Option<i32>;
+    const NAME: &'static str = "demo_max";
+    const SORT_OPERATOR: Option<&'static str> = Some(">");
+
+    fn state(current: Self::State, arg: Self::Args) -> Self::State {
+        match (current, arg) {
+            (Some(current), Some(arg)) => Some(current.max(arg)),
+            (current, arg) => current.or(arg),
+        }
+    }
+}
+
+pub struct DemoMovingSum;
+
+#[pg_aggregate]
+impl Aggregate for DemoMovingSum {
+    type State = i32;
+    type Args = i32;
+    const NAME: &'static str = "demo_moving_sum";
+    const INITIAL_CONDITION: Option<&'static str> = Some("0");
+    const MOVING_INITIAL_CONDITION: Option<&'static str> = Some("0");
+
+    fn state(current: Self::State, arg: Self::Args) -> Self::State {
+        current + arg
+    }
+
+    fn moving_state(current: Self::MovingState, arg: Self::Args) -> Self::MovingState {
+        current + arg
+    }
+
+    fn moving_state_inverse(current: Self::MovingState, arg: Self::Args) -> Self::MovingState {
+        current - arg
+    }
+}
+
+pub struct DemoNth;
+
+#[pg_aggregate]
+impl Aggregate for DemoNth {
+    type State = Vec<i32>;
+    type Args = i32;
+    type OrderedSetArgs = i32;
+    type Finalize = Option<i32>;
+    const NAME: &'static str = "demo_nth";
+    const INITIAL_CONDITION: Option<&'static str> = Some("{}");
+    const ORDERED_SET: bool = true;
+
+    fn state(mut current: Self::State, arg: Self::Args) -> Self::State {
+        current.push(arg);
+        current
+    }
+
+    fn finalize(mut current: Self::State, n: Self::OrderedSetArgs) -> Self::Finalize {
+        current.sort();
+        current.get((n - 1) as usize).cloned()
+    }
+}
+
+pub struct DemoRank;
+
+#[pg_aggregate]
+impl Aggregate for DemoRank {
+    type State = Vec<i32>;
+    type Args = i32;
+    type OrderedSetArgs = i32;
+    type Finalize = i64;
+    const NAME: &'static str = "demo_rank";
+    const INITIAL_CONDITION: Option<&'static str> = Some("{}");
+    const HYPOTHETICAL: bool = true;
+
+    fn state(mut current: Self::State, arg: Self::Args) -> Self::State {
+        current.push(arg);
+        current
+    }
+
+    fn finalize(current: Self::State, hypothetical: Self::OrderedSetArgs) -> Self::Finalize {
+        current
+            .iter()
+            .filter(|value| **value < hypothetical)
+            .count() as i64
+            + 1
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_aggregate_sum() {
+        let result =
+            Spi::get_one::<i32>("SELECT demo_sum(value) FROM generate_series(1, 10) value;");
+        assert_eq!(result, Some(55));
+    }
+
+    #[pg_test]
+    fn test_aggregate_max_nulls() {
+        let result = Spi::get_one::<i32>(
+            "SELECT demo_max(value) FROM (VALUES (NULL::integer), (3), (NULL), (7), (5)) t(value);",
+        );
+        assert_eq!(result, Some(7));
+    }
+
+    #[pg_test]
+    fn test_aggregate_moving_sum() {
+        let result = Spi::get_one::<Vec<i32>>(
+            "SELECT array_agg(s ORDER BY value) FROM (SELECT value, demo_moving_sum(value) OVER (ORDER BY value ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) s FROM generate_series(1, 4) value) t;",
+        );
+        assert_eq!(result, Some(vec![1, 3, 5, 7]));
+    }
+
+    #[pg_test]
+    fn test_aggregate_ordered_set() {
+        let result = Spi::get_one::<i32>(
+            "SELECT demo_nth(2) WITHIN GROUP (ORDER BY value) FROM generate_series(10, 1, -1) value;",
+        );
+        assert_eq!(result, Some(2));
+    }
+
+    #[pg_test]
+    fn test_aggregate_hypothetical_set() {
+        let result = Spi::get_one::<i64>(
+            "SELECT demo_rank(5) WITHIN GROUP (ORDER BY value) FROM generate_series(1, 10) value;",
+        );
+        assert_eq!(result, Some(5));
+    }
+}
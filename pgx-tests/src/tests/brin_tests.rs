@@ -0,0 +1,103 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PostgresType)]
+pub struct Reading {
+    at: i64,
+    value: f64,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PostgresType)]
+pub struct Period {
+    start: i64,
+    end: i64,
+}
+
+impl Period {
+    fn contains(&self, at: i64) -> bool {
+        self.start <= at && at <= self.end
+    }
+}
+
+#[pg_extern(immutable)]
+fn reading(at: i64, value: f64) -> Reading {
+    Reading { at, value }
+}
+
+#[pg_extern(immutable)]
+fn period(start: i64, end: i64) -> Period {
+    Period { start, end }
+}
+
+#[pg_operator(immutable)]
+#[opname(&&)]
+fn reading_during(left: Reading, right: Period) -> bool {
+    right.contains(left.at)
+}
+
+#[pg_brin_opclass]
+impl BrinOpClass for Reading {
+    type Summary = Period;
+    type Query = Period;
+    const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+
+    fn add_value(summary: Option<&Period>, value: Reading) -> Option<Period> {
+        match summary {
+            Some(period) if period.contains(value.at) => None,
+            Some(period) => Some(Period {
+                start: period.start.min(value.at),
+                end: period.end.max(value.at),
+            }),
+            None => Some(Period {
+                start: value.at,
+                end: value.at,
+            }),
+        }
+    }
+
+    fn consistent(summary: &Period, query: &Period, _strategy: u16) -> bool {
+        summary.start <= query.end && query.start <= summary.end
+    }
+
+    fn union(a: &Period, b: &Period) -> Period {
+        Period {
+            start: a.start.min(b.start),
+            end: a.end.max(b.end),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_brin_opclass() {
+        let storage = Spi::get_one::<String>(
+            "SELECT opckeytype::regtype::text FROM pg_opclass JOIN pg_am ON opcmethod = pg_am.oid WHERE opcname = 'reading_brin_ops' AND opcdefault AND amname = 'brin'",
+        );
+        assert!(storage.unwrap().ends_with("period"));
+    }
+
+    #[pg_test]
+    fn test_brin_index_scan() {
+        Spi::run(
+            "CREATE TABLE brin_readings AS SELECT reading(x, random()) AS r FROM generate_series(1, 5000) x",
+        );
+        Spi::run("INSERT INTO brin_readings SELECT NULL FROM generate_series(1, 10)");
+        Spi::run("CREATE INDEX brin_readings_idx ON brin_readings USING brin (r) WITH (pages_per_range = 1)");
+        Spi::run("SET LOCAL enable_seqscan TO off");
+        let count =
+            Spi::get_one::<i64>("SELECT count(*) FROM brin_readings WHERE r && period(100, 200)");
+        assert_eq!(count, Some(101));
+        let nulls = Spi::get_one::<i64>("SELECT count(*) FROM brin_readings WHERE r IS NULL");
+        assert_eq!(nulls, Some(10));
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct Celsius {
+    degrees: f64,
+}
+
+#[pg_extern(immutable)]
+#[pg_cast]
+fn int4_to_celsius(degrees: i32) -> Celsius {
+    Celsius {
+        degrees: degrees as f64,
+    }
+}
+
+#[pg_extern(immutable)]
+#[pg_cast(assignment)]
+fn celsius_to_int4(celsius: Celsius) -> i32 {
+    celsius.degrees.round() as i32
+}
+
+#[pg_extern(immutable)]
+#[pg_cast(implicit)]
+fn celsius_to_float8(celsius: Celsius) -> f64 {
+    celsius.degrees
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_explicit_cast() {
+        let result =
+            Spi::get_one::<String>("SELECT (42::Celsius)::text").expect("failed to get SPI result");
+        assert_eq!(result, r#"{"degrees":42.0}"#);
+
+        let context = Spi::get_one::<String>(
+            "SELECT castcontext::text FROM pg_cast WHERE castsource = 'integer'::regtype AND casttarget = 'Celsius'::regtype",
+        );
+        assert_eq!(context.as_deref(), Some("e"));
+    }
+
+    #[pg_test]
+    fn test_assignment_cast() {
+        let context = Spi::get_one::<String>(
+            "SELECT castcontext::text FROM pg_cast WHERE castsource = 'Celsius'::regtype AND casttarget = 'integer'::regtype",
+        );
+        assert_eq!(context.as_deref(), Some("a"));
+    }
+
+    #[pg_test]
+    fn test_implicit_cast() {
+        let result = Spi::get_one::<f64>("SELECT '{\"degrees\": 21.5}'::Celsius + 0.5")
+            .expect("failed to get SPI result");
+        assert_eq!(result, 22.0);
+    }
+
+    #[pg_test(error = "cannot cast type bigint to celsius")]
+    fn test_missing_cast() {
+        Spi::get_one::<f64>("SELECT 42::bigint::Celsius").expect("failed to get SPI result");
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern(collatable)]
+fn collation_oid(_input: &str, fcinfo: pg_sys::FunctionCallInfo) -> i64 {
+    unsafe { pg_collation(fcinfo) }.oid() as i64
+}
+
+#[pg_extern(collatable)]
+fn collation_cmp(a: &str, b: &str, fcinfo: pg_sys::FunctionCallInfo) -> i32 {
+    unsafe { pg_collation(fcinfo) }.cmp_str(a, b) as i32
+}
+
+#[pg_extern(collatable)]
+fn collation_lower(input: &str, fcinfo: pg_sys::FunctionCallInfo) -> String {
+    unsafe { pg_collation(fcinfo) }.to_lowercase(input)
+}
+
+#[pg_extern(collatable)]
+fn collation_upper(input: &str, fcinfo: pg_sys::FunctionCallInfo) -> String {
+    unsafe { pg_collation(fcinfo) }.to_uppercase(input)
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_default_collation() {
+        let oid = Spi::get_one::<i64>("SELECT collation_oid('a');").expect("oid was null");
+        assert_eq!(oid as pg_sys::Oid, Collation::DEFAULT.oid());
+    }
+
+    #[pg_test]
+    fn test_explicit_collation() {
+        let oid =
+            Spi::get_one::<i64>(r#"SELECT collation_oid('a' COLLATE "C");"#).expect("oid was null");
+        assert_eq!(oid as pg_sys::Oid, Collation::C.oid());
+        assert_eq!(Collation::C.name(), Some("C".to_string()));
+    }
+
+    #[pg_test]
+    fn test_collation_cmp() {
+        assert_eq!(
+            Spi::get_one::<i32>(r#"SELECT collation_cmp('a', 'B' COLLATE "C");"#),
+            Some(1)
+        );
+        assert_eq!(
+            Spi::get_one::<i32>(r#"SELECT collation_cmp('abc', 'abc' COLLATE "C");"#),
+            Some(0)
+        );
+        assert_eq!(Collation::C.cmp_str("B", "a"), std::cmp::Ordering::Less);
+    }
+
+    #[pg_test]
+    fn test_collation_lower_upper() {
+        assert_eq!(
+            Spi::get_one::<String>(r#"SELECT collation_lower('HeLLo' COLLATE "C");"#),
+            Some("hello".to_string())
+        );
+        assert_eq!(
+            Spi::get_one::<String>(r#"SELECT collation_upper('HeLLo' COLLATE "C");"#),
+            Some("HELLO".to_string())
+        );
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+/// A type with a comment
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct CommentedType {
+    value: i32,
+}
+
+/// An enum with a comment
+#[derive(PostgresEnum)]
+pub enum CommentedEnum {
+    One,
+    Two,
+}
+
+/// An enum without a comment
+#[derive(PostgresEnum)]
+#[no_comment]
+pub enum UncommentedEnum {
+    One,
+    Two,
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    /// Adds one to `value`.
+    ///
+    /// It's commented.
+    #[pg_extern]
+    fn commented_fn(value: i32) -> i32 {
+        value + 1
+    }
+
+    /// Not commented
+    #[pg_extern(no_comment)]
+    fn uncommented_fn(value: i32) -> i32 {
+        value + 1
+    }
+
+    /// A schema with a comment
+    #[pg_schema]
+    mod commented_schema {
+        use pgx::*;
+
+        /// Lives in a commented schema
+        #[pg_extern]
+        fn in_commented_schema() {}
+    }
+
+    #[pg_test]
+    fn test_function_comment() {
+        let comment = Spi::get_one::<String>(
+            "SELECT obj_description('tests.commented_fn(integer)'::regprocedure, 'pg_proc')",
+        );
+        assert_eq!(
+            comment.as_deref(),
+            Some("Adds one to `value`.\n\nIt's commented.")
+        );
+    }
+
+    #[pg_test]
+    fn test_function_no_comment() {
+        let comment = Spi::get_one::<String>(
+            "SELECT obj_description('tests.uncommented_fn(integer)'::regprocedure, 'pg_proc')",
+        );
+        assert_eq!(comment, None);
+    }
+
+    #[pg_test]
+    fn test_type_comment() {
+        let comment =
+            Spi::get_one::<String>("SELECT obj_description('CommentedType'::regtype, 'pg_type')");
+        assert_eq!(comment.as_deref(), Some("A type with a comment"));
+    }
+
+    #[pg_test]
+    fn test_enum_comment() {
+        let comment =
+            Spi::get_one::<String>("SELECT obj_description('CommentedEnum'::regtype, 'pg_type')");
+        assert_eq!(comment.as_deref(), Some("An enum with a comment"));
+    }
+
+    #[pg_test]
+    fn test_enum_no_comment() {
+        let comment =
+            Spi::get_one::<String>("SELECT obj_description('UncommentedEnum'::regtype, 'pg_type')");
+        assert_eq!(comment, None);
+    }
+
+    #[pg_test]
+    fn test_schema_comment() {
+        let comment = Spi::get_one::<String>(
+            "SELECT obj_description(oid, 'pg_namespace') FROM pg_namespace WHERE nspname = 'commented_schema'",
+        );
+        assert_eq!(comment.as_deref(), Some("A schema with a comment"));
+        let comment = Spi::get_one::<String>(
+            "SELECT obj_description('commented_schema.in_commented_schema()'::regprocedure, 'pg_proc')",
+        );
+        assert_eq!(comment.as_deref(), Some("Lives in a commented schema"));
+    }
+}
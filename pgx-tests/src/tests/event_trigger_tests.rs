@@ -0,0 +1,102 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_event_trigger]
+fn event_trigger_log_ddl(trigger: &PgEventTrigger) -> Result<(), String> {
+    let is_create_stmt = trigger
+        .parse_tree()
+        .map(|node| unsafe { is_a(node.as_ptr(), pg_sys::NodeTag_T_CreateStmt) })
+        .unwrap_or(false);
+    for command in trigger.ddl_commands() {
+        Spi::run(&format!(
+            "INSERT INTO event_trigger_log VALUES ('{} {} {} {} {} {}')",
+            trigger.event(),
+            trigger.tag(),
+            is_create_stmt,
+            command.command_tag,
+            command.object_type,
+            command.object_identity.unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
+
+#[pg_event_trigger]
+fn event_trigger_log_drops(trigger: &PgEventTrigger) -> Result<(), String> {
+    for object in trigger
+        .dropped_objects()
+        .into_iter()
+        .filter(|object| object.original)
+    {
+        Spi::run(&format!(
+            "INSERT INTO event_trigger_log VALUES ('{} {} {} {}')",
+            trigger.event(),
+            trigger.tag(),
+            object.object_type,
+            object.object_identity.unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
+
+#[pg_event_trigger(event = "ddl_command_start", tags = ["CREATE CONVERSION"])]
+fn event_trigger_no_conversions(trigger: &PgEventTrigger) -> Result<(), String> {
+    Err(format!("{} is not allowed", trigger.tag()))
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn log() -> Option<Vec<String>> {
+        Spi::get_one::<Vec<String>>("SELECT array_agg(entry) FROM event_trigger_log")
+    }
+
+    #[pg_test]
+    fn test_event_trigger_ddl_commands() {
+        Spi::run("CREATE TABLE event_trigger_log (entry text)");
+        Spi::run(
+            "CREATE EVENT TRIGGER log_ddl ON ddl_command_end WHEN TAG IN ('CREATE TABLE')
+                EXECUTE PROCEDURE event_trigger_log_ddl()",
+        );
+        Spi::run("CREATE TABLE event_trigger_test (id integer)");
+        let schema = Spi::get_one::<String>("SELECT current_schema()::text").unwrap();
+        assert_eq!(
+            log(),
+            Some(vec![format!(
+                "ddl_command_end CREATE TABLE true CREATE TABLE table {}.event_trigger_test",
+                schema
+            )])
+        );
+    }
+
+    #[pg_test]
+    fn test_event_trigger_dropped_objects() {
+        Spi::run("CREATE TABLE event_trigger_log (entry text)");
+        Spi::run("CREATE TABLE event_trigger_test (id integer)");
+        Spi::run(
+            "CREATE EVENT TRIGGER log_drops ON sql_drop
+                EXECUTE PROCEDURE event_trigger_log_drops()",
+        );
+        Spi::run("DROP TABLE event_trigger_test");
+        let schema = Spi::get_one::<String>("SELECT current_schema()::text").unwrap();
+        assert_eq!(
+            log(),
+            Some(vec![format!(
+                "sql_drop DROP TABLE table {}.event_trigger_test",
+                schema
+            )])
+        );
+    }
+
+    #[pg_test(error = "CREATE CONVERSION is not allowed")]
+    fn test_event_trigger_generated() {
+        Spi::run("CREATE CONVERSION event_trigger_conversion FOR 'LATIN1' TO 'UTF8' FROM iso8859_1_to_utf8");
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PostgresType)]
+pub struct Digits {
+    digits: Vec<i32>,
+}
+
+#[pg_extern(immutable)]
+fn digits(value: i64) -> Digits {
+    let mut digits = value
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| d as i32)
+        .collect::<Vec<_>>();
+    digits.sort_unstable();
+    digits.dedup();
+    Digits { digits }
+}
+
+#[pg_operator(immutable)]
+#[opname(@>)]
+fn digits_contains(left: Digits, right: Digits) -> bool {
+    right.digits.iter().all(|d| left.digits.contains(d))
+}
+
+#[pg_gin_opclass]
+impl GinOpClass for Digits {
+    type Key = i32;
+    const OPERATORS: &'static [(u16, &'static str)] = &[(2, "@>")];
+
+    fn extract_value(value: Digits) -> Vec<i32> {
+        value.digits
+    }
+
+    fn extract_query(query: &Digits, _strategy: u16) -> Vec<i32> {
+        query.digits.clone()
+    }
+
+    fn search_mode(query: &Digits, _strategy: u16) -> GinSearchMode {
+        // every value contains the empty set of digits
+        if query.digits.is_empty() {
+            GinSearchMode::All
+        } else {
+            GinSearchMode::Default
+        }
+    }
+
+    fn consistent(check: &[bool], _strategy: u16, _query: &Digits, recheck: &mut bool) -> bool {
+        *recheck = false;
+        check.iter().all(|&present| present)
+    }
+
+    fn tri_consistent(check: &[GinTernary], _strategy: u16, _query: &Digits) -> GinTernary {
+        if check.contains(&GinTernary::False) {
+            GinTernary::False
+        } else if check.contains(&GinTernary::Maybe) {
+            GinTernary::Maybe
+        } else {
+            GinTernary::True
+        }
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn create_indexed_digits() {
+        Spi::run(
+            "CREATE TABLE gin_digits AS SELECT digits(x) AS d FROM generate_series(1, 5000) x",
+        );
+        Spi::run("CREATE INDEX gin_digits_idx ON gin_digits USING gin (d)");
+        Spi::run("SET LOCAL enable_seqscan TO off");
+    }
+
+    #[pg_test]
+    fn test_gin_opclass() {
+        let storage = Spi::get_one::<String>(
+            "SELECT opckeytype::regtype::text FROM pg_opclass JOIN pg_am ON opcmethod = pg_am.oid WHERE opcname = 'digits_gin_ops' AND opcdefault AND amname = 'gin'",
+        );
+        assert_eq!(storage.as_deref(), Some("integer"));
+    }
+
+    #[pg_test]
+    fn test_gin_index_scan() {
+        create_indexed_digits();
+        // 1..=5000 with both a 7 and a 9 in them
+        let count = Spi::get_one::<i64>("SELECT count(*) FROM gin_digits WHERE d @> digits(79)");
+        let expected = (1..=5000)
+            .filter(|x: &i64| {
+                let x = x.to_string();
+                x.contains('7') && x.contains('9')
+            })
+            .count() as i64;
+        assert_eq!(count, Some(expected));
+    }
+
+    #[pg_test]
+    fn test_gin_empty_query() {
+        create_indexed_digits();
+        let count = Spi::get_one::<i64>(
+            r#"SELECT count(*) FROM gin_digits WHERE d @> '{"digits":[]}'::digits"#,
+        );
+        assert_eq!(count, Some(5000));
+    }
+}
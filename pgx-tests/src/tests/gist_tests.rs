@@ -0,0 +1,140 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, PostgresType)]
+pub struct Span {
+    start: i64,
+    end: i64,
+}
+
+impl Span {
+    fn cover(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    fn gap(&self, other: &Span) -> i64 {
+        (other.start - self.end).max(self.start - other.end).max(0)
+    }
+}
+
+#[pg_extern(immutable)]
+fn span(start: i64, end: i64) -> Span {
+    Span { start, end }
+}
+
+#[pg_operator(immutable)]
+#[opname(&&)]
+fn span_overlaps(left: Span, right: Span) -> bool {
+    left.gap(&right) == 0
+}
+
+#[pg_operator(immutable)]
+#[opname(<->)]
+fn span_distance(left: Span, right: Span) -> f64 {
+    left.gap(&right) as f64
+}
+
+#[pg_gist_opclass]
+impl GistOpClass for Span {
+    const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+    const ORDER_BY_OPERATORS: &'static [(u16, &'static str)] = &[(15, "<->")];
+
+    fn consistent(
+        key: &Span,
+        query: &Span,
+        _strategy: u16,
+        _is_leaf: bool,
+        recheck: &mut bool,
+    ) -> bool {
+        *recheck = false;
+        key.gap(query) == 0
+    }
+
+    fn union(keys: Vec<Span>) -> Span {
+        let first = keys[0];
+        keys.into_iter().fold(first, Span::cover)
+    }
+
+    // Postgres 10 requires `compress` and `decompress`, even when the key is the value
+    fn compress(value: Span) -> Span {
+        value
+    }
+
+    fn decompress(key: Span) -> Span {
+        key
+    }
+
+    fn penalty(original: &Span, new: &Span) -> f32 {
+        let covered = original.cover(*new);
+        ((covered.end - covered.start) - (original.end - original.start)) as f32
+    }
+
+    fn picksplit(keys: &[Span]) -> (Vec<usize>, Vec<usize>) {
+        let mut order = (0..keys.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&idx| keys[idx].start);
+        let right = order.split_off(order.len() / 2);
+        (order, right)
+    }
+
+    fn same(a: &Span, b: &Span) -> bool {
+        a == b
+    }
+
+    fn distance(
+        key: &Span,
+        query: &Span,
+        _strategy: u16,
+        _is_leaf: bool,
+        _recheck: &mut bool,
+    ) -> f64 {
+        key.gap(query) as f64
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn create_indexed_spans() {
+        Spi::run(
+            "CREATE TABLE gist_spans AS SELECT span(x, x + 10) AS s FROM generate_series(1, 5000) x",
+        );
+        Spi::run("CREATE INDEX gist_spans_idx ON gist_spans USING gist (s)");
+        Spi::run("SET LOCAL enable_seqscan TO off");
+    }
+
+    #[pg_test]
+    fn test_gist_opclass() {
+        let method = Spi::get_one::<String>(
+            "SELECT amname::text FROM pg_opclass JOIN pg_am ON opcmethod = pg_am.oid WHERE opcname = 'span_gist_ops' AND opcdefault",
+        );
+        assert_eq!(method.as_deref(), Some("gist"));
+    }
+
+    #[pg_test]
+    fn test_gist_index_scan() {
+        create_indexed_spans();
+        let count =
+            Spi::get_one::<i64>("SELECT count(*) FROM gist_spans WHERE s && span(100, 105)");
+        assert_eq!(count, Some(16));
+    }
+
+    #[pg_test]
+    fn test_gist_ordered_scan() {
+        create_indexed_spans();
+        let distance = Spi::get_one::<f64>(
+            "SELECT s <-> span(6000, 6000) FROM gist_spans ORDER BY s <-> span(6000, 6000) LIMIT 1",
+        );
+        assert_eq!(distance, Some(990.0));
+    }
+}
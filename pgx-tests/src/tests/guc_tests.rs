@@ -1,6 +1,26 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+use pgx::*;
+
+/// A declared integer GUC
+///
+/// Which has a long description.
+#[pg_guc(name = "pgx_tests.declared_int", min = -1, max = 100, units = "ms")]
+static DECLARED_INT: GucSetting<i32> = GucSetting::new(42);
+
+/// A declared float GUC
+#[pg_guc(name = "pgx_tests.declared_float", context = Suset, min = 0.0, max = 1.0)]
+static DECLARED_FLOAT: GucSetting<f64> = GucSetting::new(0.5);
+
+/// A declared string GUC
+#[pg_guc(name = "pgx_tests.declared_string")]
+static DECLARED_STRING: GucSetting<Option<&'static str>> = GucSetting::new(Some("declared"));
+
+/// Defines the `#[pg_guc]`s
+#[pg_guard]
+pub extern "C" fn _PG_init() {}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -136,4 +156,131 @@ mod tests {
         Spi::run("SET test.enum = 'three'");
         assert_eq!(GUC.get(), TestEnum::Three);
     }
+
+    #[pg_test]
+    fn test_list_guc() {
+        static GUC: GucSetting<&'static [&'static str]> = GucSetting::new(&["a", "b"]);
+        GucRegistry::define_list_guc(
+            "test.list",
+            "test list guc",
+            "test list guc",
+            &GUC,
+            GucContext::Userset,
+        );
+        assert_eq!(GUC.get(), vec!["a", "b"]);
+
+        Spi::run("SET test.list = 'a, \"b, c\"'");
+        assert_eq!(GUC.get(), vec!["a", "b, c"]);
+
+        Spi::run("SET test.list = ''");
+        assert!(GUC.get().is_empty());
+    }
+
+    #[pg_test(error = "5 is odd")]
+    fn test_guc_check_hook() {
+        static GUC: GucSetting<i32> = GucSetting::new(2);
+        GucRegistry::define_int_guc_with_options(
+            "test.check",
+            "test check hook",
+            "test check hook",
+            &GUC,
+            GucContext::Userset,
+            GucOptions::new().min(0).max(10).check_hook(|value: i32| {
+                if value % 2 == 0 {
+                    Ok(())
+                } else {
+                    Err(format!("{} is odd", value))
+                }
+            }),
+        );
+
+        Spi::run("SET test.check = 4");
+        assert_eq!(GUC.get(), 4);
+
+        Spi::run("SET test.check = 5");
+    }
+
+    #[pg_test]
+    fn test_guc_assign_and_show_hooks() {
+        static GUC: GucSetting<bool> = GucSetting::new(false);
+        static ASSIGNED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+        GucRegistry::define_bool_guc_with_options(
+            "test.hooks",
+            "test assign and show hooks",
+            "test assign and show hooks",
+            &GUC,
+            GucContext::Userset,
+            GucOptions::new()
+                .assign_hook(|value: bool| {
+                    ASSIGNED.store(value, std::sync::atomic::Ordering::SeqCst)
+                })
+                .show_hook(|| String::from("shown")),
+        );
+
+        Spi::run("SET test.hooks = true");
+        assert!(GUC.get());
+        assert!(ASSIGNED.load(std::sync::atomic::Ordering::SeqCst));
+
+        let shown = Spi::get_one::<&str>("SELECT current_setting('test.hooks')")
+            .expect("failed to get SPI result");
+        assert_eq!(shown, "shown");
+    }
+
+    #[pg_test]
+    fn test_guc_flags() {
+        static GUC: GucSetting<i32> = GucSetting::new(10);
+        GucRegistry::define_int_guc_with_options(
+            "test.flags",
+            "test guc flags",
+            "test guc flags",
+            &GUC,
+            GucContext::Userset,
+            GucOptions::new()
+                .min(0)
+                .max(1000)
+                .flags(GucFlags::UNIT_MS | GucFlags::NO_SHOW_ALL),
+        );
+
+        let unit = Spi::get_one::<&str>("SELECT unit FROM pg_settings WHERE name = 'test.flags'");
+        assert!(unit.is_none());
+
+        let shown = Spi::get_one::<&str>("SELECT current_setting('test.flags')")
+            .expect("failed to get SPI result");
+        assert_eq!(shown, "10ms");
+    }
+
+    #[pg_test]
+    fn test_declared_gucs() {
+        assert_eq!(super::DECLARED_INT.get(), 42);
+        assert_eq!(super::DECLARED_FLOAT.get(), 0.5);
+        assert_eq!(super::DECLARED_STRING.get().unwrap(), "declared");
+
+        Spi::run("SET pgx_tests.declared_int = 12");
+        assert_eq!(super::DECLARED_INT.get(), 12);
+
+        Spi::run("SET pgx_tests.declared_float = 0.25");
+        assert_eq!(super::DECLARED_FLOAT.get(), 0.25);
+
+        Spi::run("SET pgx_tests.declared_string = 'foo'");
+        assert_eq!(super::DECLARED_STRING.get().unwrap(), "foo");
+    }
+
+    #[pg_test]
+    fn test_declared_guc_settings() {
+        let setting = Spi::get_one::<bool>(
+            "SELECT vartype = 'integer' AND context = 'user' AND unit = 'ms' \
+                AND min_val = '-1' AND max_val = '100' \
+                AND short_desc = 'A declared integer GUC' \
+                AND extra_desc = 'Which has a long description.' \
+            FROM pg_settings WHERE name = 'pgx_tests.declared_int'",
+        )
+        .expect("failed to get SPI result");
+        assert!(setting);
+
+        let context = Spi::get_one::<&str>(
+            "SELECT context FROM pg_settings WHERE name = 'pgx_tests.declared_float'",
+        )
+        .expect("failed to get SPI result");
+        assert_eq!(context, "superuser");
+    }
 }
@@ -1,15 +1,23 @@
 // Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
 // governed by the MIT license that can be found in the LICENSE file.
 
+mod aggregate_tests;
 mod anyarray_tests;
 mod array_tests;
+mod brin_tests;
 mod bytea_tests;
+mod cast_tests;
 mod cfg_tests;
+mod collation_tests;
+mod comment_tests;
 mod datetime_tests;
 mod default_arg_value_tests;
 mod derive_pgtype_lifetimes;
 mod enum_type_tests;
+mod event_trigger_tests;
 mod fcinfo_tests;
+mod gin_tests;
+mod gist_tests;
 mod guc_tests;
 mod hooks_tests;
 mod inet_tests;
@@ -19,15 +27,27 @@ mod log_tests;
 mod memcxt_tests;
 mod name_tests;
 mod numeric_tests;
+mod pg_string_tests;
 mod pg_extern_args_tests;
 mod pg_try_tests;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+mod planner_support_tests;
 mod postgres_type_tests;
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+mod procedure_tests;
+mod record_tests;
 mod schema_tests;
+mod spgist_tests;
 mod spi_tests;
+mod sql_function_tests;
+mod sql_translatable_tests;
 mod srf_tests;
 mod struct_type_tests;
+mod trigger_tests;
+mod typcache_tests;
 mod uuid_tests;
 mod variadic_tests;
+mod window_tests;
 mod xact_callback_tests;
 mod xid64_tests;
 
@@ -20,4 +20,86 @@ mod tests {
         .expect("failed to get SPI result");
         assert!(result)
     }
+
+    #[pg_extern(cost = 1000, leakproof)]
+    fn is_costly_and_leakproof() {}
+
+    #[pg_test]
+    fn test_cost_and_leakproof() {
+        let result = Spi::get_one::<bool>(
+            "SELECT procost = 1000 AND proleakproof FROM pg_proc WHERE proname = 'is_costly_and_leakproof'",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_extern(rows = 10)]
+    fn has_rows() -> impl std::iter::Iterator<Item = i32> {
+        0..10
+    }
+
+    #[pg_test]
+    fn test_rows() {
+        let result =
+            Spi::get_one::<bool>("SELECT prorows = 10 FROM pg_proc WHERE proname = 'has_rows'")
+                .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_extern(
+        security_definer,
+        set(search_path = "pg_catalog, public", work_mem = "64MB")
+    )]
+    fn is_security_definer() -> String {
+        Spi::get_one("SELECT current_setting('search_path') || ' ' || current_setting('work_mem')")
+            .expect("failed to get SPI result")
+    }
+
+    #[pg_test]
+    fn test_security_definer_and_set() {
+        let result = Spi::get_one::<bool>(
+            "SELECT prosecdef FROM pg_proc WHERE proname = 'is_security_definer'",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+        let result = Spi::get_one::<String>("SELECT is_security_definer()");
+        assert_eq!(result, Some("pg_catalog, public 64MB".to_string()));
+    }
+
+    #[pg_extern(called_on_null_input)]
+    fn is_called_on_null_input(value: i32) -> i32 {
+        value
+    }
+
+    #[pg_test]
+    fn test_called_on_null_input() {
+        let result = Spi::get_one::<bool>(
+            "SELECT NOT proisstrict FROM pg_proc WHERE proname = 'is_called_on_null_input'",
+        )
+        .expect("failed to get SPI result");
+        assert!(result)
+    }
+
+    #[pg_extern(immutable)]
+    fn args_support_fn(_request: Internal) -> Internal {
+        // a NULL pointer tells the planner there's nothing to simplify
+        Internal::from(Some(0))
+    }
+
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    #[pg_extern(support = args_support_fn)]
+    fn has_support(value: i32) -> i32 {
+        value
+    }
+
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    #[pg_test]
+    fn test_support() {
+        let result = Spi::get_one::<bool>(
+            "SELECT prosupport = 'args_support_fn'::regproc FROM pg_proc WHERE proname = 'has_support'",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+        assert_eq!(Spi::get_one::<i32>("SELECT has_support(42)"), Some(42));
+    }
 }
@@ -0,0 +1,67 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn pg_string_echo(input: PgString) -> PgString {
+    input
+}
+
+#[pg_extern]
+fn pg_string_is_borrowed(input: PgString) -> bool {
+    input.is_borrowed()
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+    use std::convert::TryFrom;
+
+    #[pg_test]
+    fn test_server_encoding() {
+        let name = Spi::get_one::<String>("SELECT getdatabaseencoding()::text;")
+            .expect("encoding was null");
+        assert_eq!(server_encoding_name(), name);
+        assert_eq!(is_server_utf8(), name == "UTF8");
+    }
+
+    #[pg_test]
+    fn test_pg_string_roundtrip() {
+        assert_eq!(
+            Spi::get_one::<String>("SELECT pg_string_echo('héllo wörld');"),
+            Some("héllo wörld".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_pg_string_borrows() {
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT pg_string_is_borrowed('plain ascii');"),
+            Some(true)
+        );
+    }
+
+    #[pg_test]
+    fn test_pg_string_try_from() {
+        // `PgString`s are in the server's encoding, which isn't necessarily UTF-8
+        let server_bytes = utf8_to_server("héllo");
+        let valid = PgString::try_from(&*server_bytes).expect("conversion failed");
+        assert_eq!(&*valid, "héllo");
+
+        // only a UTF-8 server hands invalid bytes back as an error, others raise an ERROR
+        if is_server_utf8() {
+            assert!(PgString::try_from(&[0x68, 0xff, 0x6f][..]).is_err());
+        }
+    }
+
+    #[pg_test]
+    fn test_utf8_to_server() {
+        let converted = utf8_to_server("héllo");
+        assert_eq!(server_to_utf8(&converted), Ok("héllo".into()));
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern(immutable)]
+fn support_numbers_rows(request: Internal) -> Internal {
+    PgSupportRequest::call(request, |request| {
+        if let PgSupportRequest::Rows(rows) = request {
+            if let Some(Some(n)) = rows.const_arg::<i32>(0) {
+                rows.set_rows(n as f64);
+            }
+        }
+    })
+}
+
+#[pg_extern(support = support_numbers_rows)]
+fn support_numbers(n: i32) -> impl std::iter::Iterator<Item = i32> {
+    0..n
+}
+
+#[pg_extern(immutable)]
+fn support_selectivity(request: Internal) -> Internal {
+    PgSupportRequest::call(request, |request| {
+        if let PgSupportRequest::Selectivity(selectivity) = request {
+            selectivity.set_selectivity(0.1);
+        }
+    })
+}
+
+#[pg_extern(support = support_selectivity)]
+fn support_is_even(value: i32) -> bool {
+    value % 2 == 0
+}
+
+#[pg_extern(immutable)]
+fn support_simplify(request: Internal) -> Internal {
+    PgSupportRequest::call(request, |request| {
+        if let PgSupportRequest::Simplify(simplify) = request {
+            if simplify.const_arg::<i32>(0).is_some() {
+                // calls with a constant argument become the constant 42
+                let constant = unsafe {
+                    pg_sys::makeConst(
+                        pg_sys::INT4OID,
+                        -1,
+                        pg_sys::InvalidOid,
+                        4,
+                        42.into_datum().unwrap(),
+                        false,
+                        true,
+                    )
+                };
+                simplify.replace_with(unsafe { PgBox::from_pg(constant as *mut pg_sys::Node) });
+            }
+        }
+    })
+}
+
+#[pg_extern(support = support_simplify)]
+fn support_simplified(value: i32) -> i32 {
+    value
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_support_rows() {
+        let plan = Spi::get_one::<String>("EXPLAIN SELECT * FROM support_numbers(1234)")
+            .expect("EXPLAIN returned no plan");
+        assert!(plan.contains("rows=1234"), "{}", plan);
+    }
+
+    #[pg_test]
+    fn test_support_selectivity() {
+        let plan = Spi::get_one::<String>(
+            "EXPLAIN SELECT * FROM generate_series(1, 1000) g WHERE support_is_even(g)",
+        )
+        .expect("EXPLAIN returned no plan");
+        assert!(plan.contains("rows=100 "), "{}", plan);
+    }
+
+    #[pg_test]
+    fn test_support_simplify() {
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT support_simplified(1)"),
+            Some(42)
+        );
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT support_simplified(x) FROM generate_series(1, 1) x"),
+            Some(1)
+        );
+    }
+}
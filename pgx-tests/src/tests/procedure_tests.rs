@@ -0,0 +1,139 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_procedure]
+fn procedure_insert(value: i32) {
+    Spi::run(&format!(
+        "INSERT INTO procedure_test_values VALUES ({})",
+        value
+    ));
+}
+
+#[pg_procedure]
+fn procedure_double(value: inout!(i32)) -> i32 {
+    value * 2
+}
+
+#[pg_procedure]
+fn procedure_coalesce(value: inout!(Option<i32>)) -> Option<i32> {
+    value.or(Some(0))
+}
+
+#[pg_procedure]
+fn procedure_swap(a: inout!(i32), b: inout!(i32)) -> (name!(a, i32), name!(b, i32)) {
+    (b, a)
+}
+
+#[pg_procedure]
+fn procedure_commit() {
+    Spi::commit();
+}
+
+#[pg_procedure]
+fn procedure_rollback() {
+    Spi::rollback();
+}
+
+#[pg_procedure]
+fn procedure_commit_then_rollback(value: i32) {
+    Spi::run(&format!(
+        "INSERT INTO procedure_commit_values VALUES ({})",
+        value
+    ));
+    Spi::commit();
+    Spi::run(&format!(
+        "INSERT INTO procedure_commit_values VALUES ({})",
+        value + 1
+    ));
+    Spi::rollback();
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn call<T: FromDatum + IntoDatum>(query: &str) -> Option<T> {
+        Spi::connect(|mut client| Ok(client.update(query, None, None).first().get_one::<T>()))
+    }
+
+    #[pg_test]
+    fn test_procedure_call() {
+        Spi::run("CREATE TABLE procedure_test_values (value integer)");
+        Spi::run("CALL procedure_insert(42)");
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT value FROM procedure_test_values"),
+            Some(42)
+        );
+    }
+
+    #[pg_test]
+    fn test_procedure_inout() {
+        assert_eq!(call::<i32>("CALL procedure_double(21)"), Some(42));
+    }
+
+    #[pg_test]
+    fn test_procedure_inout_tuple() {
+        let result = Spi::connect(|mut client| {
+            Ok(Some(
+                client
+                    .update("CALL procedure_swap(1, 2)", None, None)
+                    .first()
+                    .get_two::<i32, i32>(),
+            ))
+        });
+        assert_eq!(result, Some((Some(2), Some(1))));
+    }
+
+    #[pg_test]
+    fn test_procedure_inout_null() {
+        assert_eq!(call::<i32>("CALL procedure_coalesce(NULL)"), Some(0));
+        assert_eq!(call::<i32>("CALL procedure_coalesce(7)"), Some(7));
+    }
+
+    #[test]
+    fn test_procedure_commit_survives_rollback() {
+        // a `#[pg_test]` runs within a transaction block, so `CALL` the procedure from a client
+        let mut client = pgx_tests::connect_test_client(crate::pg_test::postgresql_conf_options());
+        client
+            .batch_execute(
+                "CREATE TABLE procedure_commit_values (value integer);
+                 CALL procedure_commit_then_rollback(1);",
+            )
+            .expect("failed to CALL procedure_commit_then_rollback");
+
+        let values = client
+            .query(
+                "SELECT value FROM procedure_commit_values ORDER BY value",
+                &[],
+            )
+            .expect("failed to SELECT from procedure_commit_values")
+            .iter()
+            .map(|row| row.get::<_, i32>(0))
+            .collect::<Vec<_>>();
+        client
+            .batch_execute("DROP TABLE procedure_commit_values")
+            .expect("failed to DROP procedure_commit_values");
+
+        assert_eq!(values, vec![1]);
+    }
+
+    #[pg_test(
+        error = "Spi::commit() can only be used by a procedure that was CALLed outside of a transaction block"
+    )]
+    fn test_procedure_commit_in_transaction_block() {
+        Spi::run("CALL procedure_commit()");
+    }
+
+    #[pg_test(
+        error = "Spi::rollback() can only be used by a procedure that was CALLed outside of a transaction block"
+    )]
+    fn test_procedure_rollback_in_transaction_block() {
+        Spi::run("CALL procedure_rollback()");
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern]
+fn record_out_params(id: i32) -> (name!(id, i32), name!(title, String)) {
+    (id, format!("title {}", id))
+}
+
+#[pg_extern]
+fn record_optional(present: bool) -> Option<(name!(a, i32), name!(b, Option<&'static str>))> {
+    if present {
+        Some((1, None))
+    } else {
+        None
+    }
+}
+
+#[pg_extern]
+fn record_inout_params(
+    counter: inout!(i32),
+    step: i32,
+) -> (name!(counter, i32), name!(previous, i32)) {
+    (counter + step, counter)
+}
+
+#[pg_extern]
+fn record_single_inout(value: inout!(i32)) -> i32 {
+    value * 2
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_record_out_params() {
+        let result = Spi::get_two::<i32, String>("SELECT (record_out_params(7)).*");
+        assert_eq!(result, (Some(7), Some("title 7".to_string())));
+
+        let result = Spi::get_one::<bool>(
+            "SELECT prorettype = 'record'::regtype AND proargmodes = '{i,o,o}'
+               FROM pg_proc WHERE proname = 'record_out_params'",
+        );
+        assert_eq!(result, Some(true));
+    }
+
+    #[pg_test]
+    fn test_record_optional() {
+        let result = Spi::get_two::<i32, String>("SELECT * FROM record_optional(true)");
+        assert_eq!(result, (Some(1), None));
+        let result = Spi::get_one::<bool>("SELECT record_optional(false) IS NULL");
+        assert_eq!(result, Some(true));
+    }
+
+    #[pg_test]
+    fn test_record_inout_params() {
+        let result = Spi::get_two::<i32, i32>("SELECT * FROM record_inout_params(1, 2)");
+        assert_eq!(result, (Some(3), Some(1)));
+
+        let result = Spi::get_one::<bool>(
+            "SELECT proargmodes = '{b,i,o}' FROM pg_proc WHERE proname = 'record_inout_params'",
+        );
+        assert_eq!(result, Some(true));
+    }
+
+    #[pg_test]
+    fn test_record_single_inout() {
+        let result = Spi::get_one::<i32>("SELECT record_single_inout(21)");
+        assert_eq!(result, Some(42));
+    }
+}
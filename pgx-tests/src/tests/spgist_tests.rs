@@ -0,0 +1,140 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PostgresType)]
+pub struct Spot {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PostgresType)]
+pub struct Area {
+    low: Spot,
+    high: Spot,
+}
+
+impl Area {
+    fn contains(&self, spot: &Spot) -> bool {
+        self.low.x <= spot.x
+            && spot.x <= self.high.x
+            && self.low.y <= spot.y
+            && spot.y <= self.high.y
+    }
+
+    /// Can any spot in `quadrant` of `centroid` be inside the area?
+    fn overlaps_quadrant(&self, centroid: &Spot, quadrant: usize) -> bool {
+        let x = if quadrant & 2 != 0 {
+            self.high.x >= centroid.x
+        } else {
+            self.low.x < centroid.x
+        };
+        let y = if quadrant & 1 != 0 {
+            self.high.y >= centroid.y
+        } else {
+            self.low.y < centroid.y
+        };
+        x && y
+    }
+}
+
+fn quadrant(centroid: &Spot, spot: &Spot) -> usize {
+    (spot.x >= centroid.x) as usize * 2 + (spot.y >= centroid.y) as usize
+}
+
+#[pg_extern(immutable)]
+fn spot(x: f64, y: f64) -> Spot {
+    Spot { x, y }
+}
+
+#[pg_extern(immutable)]
+fn area(low: Spot, high: Spot) -> Area {
+    Area { low, high }
+}
+
+#[pg_operator(immutable)]
+#[opname(<@)]
+fn spot_within(left: Spot, right: Area) -> bool {
+    right.contains(&left)
+}
+
+#[pg_spgist_opclass]
+impl SpGistOpClass for Spot {
+    type Prefix = Spot;
+    type Query = Area;
+    const OPERATORS: &'static [(u16, &'static str)] = &[(8, "<@")];
+
+    fn choose(value: &Spot, inner: &SpGistInner<Spot, ()>) -> SpGistChoice<Spot, ()> {
+        let node = match (&inner.prefix, inner.all_the_same) {
+            (Some(centroid), false) => quadrant(centroid, value),
+            _ => 0,
+        };
+        SpGistChoice::MatchNode { node }
+    }
+
+    fn picksplit(values: &[Spot], _level: u32) -> SpGistSplit<Spot, ()> {
+        let n = values.len() as f64;
+        let centroid = Spot {
+            x: values.iter().map(|spot| spot.x).sum::<f64>() / n,
+            y: values.iter().map(|spot| spot.y).sum::<f64>() / n,
+        };
+        SpGistSplit {
+            nodes: values
+                .iter()
+                .map(|spot| quadrant(&centroid, spot))
+                .collect(),
+            prefix: Some(centroid),
+            labels: vec![],
+            n_nodes: 4,
+        }
+    }
+
+    fn inner_consistent(queries: &[(u16, Area)], inner: &SpGistInner<Spot, ()>) -> Vec<usize> {
+        match (&inner.prefix, inner.all_the_same) {
+            (Some(centroid), false) => (0..inner.n_nodes)
+                .filter(|&quadrant| {
+                    queries
+                        .iter()
+                        .all(|(_, area)| area.overlaps_quadrant(centroid, quadrant))
+                })
+                .collect(),
+            _ => (0..inner.n_nodes).collect(),
+        }
+    }
+
+    fn leaf_consistent(value: &Spot, queries: &[(u16, Area)], _recheck: &mut bool) -> bool {
+        queries.iter().all(|(_, area)| area.contains(value))
+    }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_spgist_opclass() {
+        let method = Spi::get_one::<String>(
+            "SELECT amname::text FROM pg_opclass JOIN pg_am ON opcmethod = pg_am.oid WHERE opcname = 'spot_spgist_ops' AND opcdefault",
+        );
+        assert_eq!(method.as_deref(), Some("spgist"));
+    }
+
+    #[pg_test]
+    fn test_spgist_index_scan() {
+        Spi::run(
+            "CREATE TABLE spgist_spots AS SELECT spot(x, y) AS s FROM generate_series(1, 50) x, generate_series(1, 50) y",
+        );
+        Spi::run("CREATE INDEX spgist_spots_idx ON spgist_spots USING spgist (s)");
+        Spi::run("SET LOCAL enable_seqscan TO off");
+        let count = Spi::get_one::<i64>(
+            "SELECT count(*) FROM spgist_spots WHERE s <@ area(spot(10, 10), spot(19, 19))",
+        );
+        assert_eq!(count, Some(100));
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PostgresType)]
+pub struct Counter {
+    count: i32,
+}
+
+#[pg_extern(immutable)]
+fn counter_value(counter: Counter) -> i32 {
+    counter.count
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use crate::tests::sql_function_tests::Counter;
+    use pgx::*;
+
+    #[pg_sql_function(immutable, language = "sql", body = "SELECT $1::bigint * 2")]
+    fn sql_double(value: i32) -> i64;
+
+    #[pg_sql_function(
+        language = "plpgsql",
+        body = "BEGIN RETURN coalesce(value, 'nothing'); END;"
+    )]
+    fn plpgsql_or_nothing(value: Option<&str>) -> String;
+
+    #[pg_sql_function(language = "sql", body = "SELECT generate_series(1, $1)")]
+    fn sql_series(upto: i32) -> impl std::iter::Iterator<Item = i32>;
+
+    #[pg_sql_function(
+        language = "sql",
+        body = "SELECT counter_value($1) + 1",
+        requires = [sql_function_tests::counter_value]
+    )]
+    fn sql_counter_next(counter: Counter) -> i32;
+
+    #[pg_test]
+    fn test_sql_function() {
+        let result = Spi::get_one::<i64>("SELECT tests.sql_double(21)");
+        assert_eq!(result, Some(42));
+        let language = Spi::get_one::<String>(
+            "SELECT lanname::text FROM pg_proc JOIN pg_language ON prolang = pg_language.oid WHERE proname = 'sql_double'",
+        );
+        assert_eq!(language.as_deref(), Some("sql"));
+    }
+
+    #[pg_test]
+    fn test_plpgsql_function() {
+        let result = Spi::get_one::<String>("SELECT tests.plpgsql_or_nothing(NULL)");
+        assert_eq!(result.as_deref(), Some("nothing"));
+        let result = Spi::get_one::<String>("SELECT tests.plpgsql_or_nothing('something')");
+        assert_eq!(result.as_deref(), Some("something"));
+    }
+
+    #[pg_test]
+    fn test_sql_set_returning_function() {
+        let result = Spi::get_one::<i64>("SELECT sum(x) FROM tests.sql_series(4) x");
+        assert_eq!(result, Some(10));
+    }
+
+    #[pg_test]
+    fn test_sql_function_with_custom_type() {
+        let result = Spi::get_one::<i32>(r#"SELECT tests.sql_counter_next('{"count": 41}')"#);
+        assert_eq!(result, Some(42));
+    }
+}
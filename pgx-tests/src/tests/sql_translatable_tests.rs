@@ -0,0 +1,92 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+type Meters = i64;
+
+/// A type pgx knows nothing about, which provides its own SQL type
+pub struct Kilometers(i64);
+
+impl FromDatum for Kilometers {
+    const NEEDS_TYPID: bool = false;
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, typoid: pg_sys::Oid) -> Option<Self> {
+        i64::from_datum(datum, is_null, typoid).map(Kilometers)
+    }
+}
+
+impl IntoDatum for Kilometers {
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        self.0.into_datum()
+    }
+
+    fn type_oid() -> pg_sys::Oid {
+        i64::type_oid()
+    }
+}
+
+impl SqlTranslatable for Kilometers {
+    fn sql_type() -> String {
+        String::from("bigint")
+    }
+}
+
+#[pg_extern]
+fn meters_to_kilometers(meters: Meters) -> Kilometers {
+    Kilometers(meters / 1000)
+}
+
+#[pg_extern]
+fn kilometers_to_meters(kilometers: Option<Kilometers>) -> Option<Meters> {
+    kilometers.map(|kilometers| kilometers.0 * 1000)
+}
+
+#[pg_extern]
+fn oid_of_qualified(oid: pgx::PgOid) -> ::pgx::pg_sys::PgOid {
+    oid
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+    use pgx::*;
+
+    #[test]
+    fn test_sql_types() {
+        assert_eq!(<super::Meters as SqlTranslatable>::sql_type(), "bigint");
+        assert_eq!(
+            <Vec<super::Kilometers> as SqlTranslatable>::sql_type(),
+            "bigint[]"
+        );
+        assert_eq!(<Option<&[u8]> as SqlTranslatable>::sql_type(), "bytea");
+        assert_eq!(<Array<&str> as SqlTranslatable>::sql_type(), "text[]");
+        assert_eq!(<Option<PgOid> as SqlTranslatable>::sql_type(), "oid");
+    }
+
+    #[test]
+    fn test_collatable() {
+        assert!(<Option<Vec<String>> as SqlTranslatable>::collatable());
+        assert!(<&str as SqlTranslatable>::collatable());
+        assert!(!<&[u8] as SqlTranslatable>::collatable());
+        assert!(!<super::Kilometers as SqlTranslatable>::collatable());
+    }
+
+    #[pg_test]
+    fn test_type_alias_and_custom_mapping() {
+        let result =
+            Spi::get_one::<i64>("SELECT kilometers_to_meters(meters_to_kilometers(4200));")
+                .expect("failed to get SPI result");
+        assert_eq!(result, 4000);
+    }
+
+    #[pg_test]
+    fn test_qualified_oid() {
+        let result = Spi::get_one::<bool>(
+            "SELECT pg_get_function_result('oid_of_qualified'::regproc) = 'oid';",
+        )
+        .expect("failed to get SPI result");
+        assert!(result);
+    }
+}
@@ -61,6 +61,46 @@ fn return_none_setof_iterator() -> Option<impl std::iter::Iterator<Item = i32>>
     }
 }
 
+#[pg_extern(materialize)]
+fn example_materialized_series(start: i32, end: i32) -> impl std::iter::Iterator<Item = i32> {
+    start..=end
+}
+
+#[pg_extern(materialize)]
+fn example_materialized_composite_set(
+) -> impl std::iter::Iterator<Item = (name!(idx, i32), name!(value, Option<&'static str>))> {
+    vec![Some("a"), None, Some("c")]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, value)| ((idx + 1) as i32, value))
+}
+
+#[pg_extern(materialize)]
+fn example_materialized_spi_rows() -> impl std::iter::Iterator<Item = String> {
+    // SPI results only live as long as the connection, which is closed before this call returns
+    let mut rows = Vec::new();
+    Spi::connect(|client| {
+        for row in client.select(
+            "SELECT v::text FROM generate_series(1, 3) v ORDER BY v DESC",
+            None,
+            None,
+        ) {
+            rows.push(row[1].value::<&str>().unwrap().to_string());
+        }
+        Ok(Some(()))
+    });
+    rows.into_iter()
+}
+
+#[pg_extern(materialize)]
+fn return_none_materialized_iterator() -> Option<impl std::iter::Iterator<Item = i32>> {
+    if true {
+        None
+    } else {
+        Some(vec![1, 2, 3].into_iter())
+    }
+}
+
 #[cfg(any(test, feature = "pg_test"))]
 #[pgx::pg_schema]
 mod tests {
@@ -159,4 +199,53 @@ mod tests {
 
         assert_eq!(cnt, Some(0))
     }
+
+    #[pg_test]
+    fn test_materialized_series() {
+        assert_eq!(
+            Spi::get_one::<Vec<i32>>(
+                "SELECT array_agg(v) FROM example_materialized_series(1, 5) v"
+            ),
+            Some(vec![1, 2, 3, 4, 5])
+        );
+    }
+
+    #[pg_test]
+    fn test_materialized_series_in_target_list() {
+        assert_eq!(
+            Spi::get_one::<i64>(
+                "SELECT count(*) FROM (SELECT example_materialized_series(1, 4)) x"
+            ),
+            Some(4)
+        );
+    }
+
+    #[pg_test]
+    fn test_materialized_composite_set() {
+        assert_eq!(
+            Spi::get_one::<String>(
+                "SELECT string_agg(idx || ':' || coalesce(value, 'NULL'), ',' ORDER BY idx)
+                   FROM example_materialized_composite_set()"
+            ),
+            Some("1:a,2:NULL,3:c".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_materialized_spi_rows() {
+        assert_eq!(
+            Spi::get_one::<Vec<String>>(
+                "SELECT array_agg(v) FROM example_materialized_spi_rows() v"
+            ),
+            Some(vec!["3".to_string(), "2".to_string(), "1".to_string()])
+        );
+    }
+
+    #[pg_test]
+    fn test_return_none_materialized_iterator() {
+        assert_eq!(
+            Spi::get_one::<i64>("SELECT count(*) FROM return_none_materialized_iterator()"),
+            Some(0)
+        );
+    }
 }
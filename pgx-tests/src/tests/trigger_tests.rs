@@ -0,0 +1,209 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_trigger]
+fn trigger_uppercase_name(
+    trigger: &PgTrigger,
+) -> Result<Option<PgHeapTuple<'_>>, PgHeapTupleError> {
+    let mut new = trigger
+        .new()
+        .expect("not fired by a row-level INSERT or UPDATE");
+    let name = new
+        .get_by_name::<&str>("name")?
+        .map(|name| name.to_uppercase());
+    new.set_by_name("name", name)?;
+    Ok(Some(new))
+}
+
+#[pg_trigger]
+fn trigger_skip_negative(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, PgHeapTupleError> {
+    let new = trigger
+        .new()
+        .expect("not fired by a row-level INSERT or UPDATE");
+    match new.get_by_name::<i32>("id")? {
+        Some(id) if id < 0 => Ok(None),
+        _ => Ok(Some(new)),
+    }
+}
+
+#[pg_trigger]
+fn trigger_keep_old_id(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, PgHeapTupleError> {
+    let old = trigger.old().expect("not fired by a row-level UPDATE");
+    let mut new = trigger.new().expect("not fired by a row-level UPDATE");
+    new.set_by_index(1, old.get_by_index::<i32>(1)?)?;
+    Ok(Some(new))
+}
+
+#[pg_trigger]
+fn trigger_log_event(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, &'static str> {
+    Spi::run(&format!(
+        "INSERT INTO trigger_test_log VALUES ('{} {:?} {:?} {:?} {}.{} {}')",
+        trigger.name(),
+        trigger.when(),
+        trigger.op(),
+        trigger.level(),
+        trigger.table_schema(),
+        trigger.table_name(),
+        trigger.args().join(",")
+    ));
+    Ok(None)
+}
+
+#[pg_trigger]
+fn trigger_count_inserted(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, &'static str> {
+    let table = trigger
+        .new_transition_table_name()
+        .ok_or("no transition table")?;
+    let count = Spi::connect(|mut client| {
+        client.register_trigger_data(trigger);
+        Ok(client
+            .select(&format!("SELECT count(*) FROM {}", table), None, None)
+            .first()
+            .get_one::<i64>())
+    })
+    .ok_or("no count")?;
+    Spi::run(&format!(
+        "INSERT INTO trigger_test_log VALUES ('{}')",
+        count
+    ));
+    Ok(None)
+}
+
+#[pg_trigger]
+fn trigger_reject(_trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, &'static str> {
+    Err("rejected by trigger")
+}
+
+#[pg_trigger]
+fn trigger_wrong_type(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, PgHeapTupleError> {
+    let mut new = trigger
+        .new()
+        .expect("not fired by a row-level INSERT or UPDATE");
+    new.set_by_name("id", Some("not an integer"))?;
+    Ok(Some(new))
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    fn create_tables() {
+        Spi::run("CREATE TABLE trigger_test (id integer, name text)");
+        Spi::run("CREATE TABLE trigger_test_log (entry text)");
+    }
+
+    #[pg_test]
+    fn test_trigger_modifies_new() {
+        create_tables();
+        Spi::run(
+            "CREATE TRIGGER uppercase BEFORE INSERT ON trigger_test
+                FOR EACH ROW EXECUTE PROCEDURE trigger_uppercase_name()",
+        );
+        Spi::run("INSERT INTO trigger_test VALUES (1, 'pgx'), (2, NULL)");
+        let result = Spi::get_one::<Vec<Option<String>>>(
+            "SELECT array_agg(name ORDER BY id) FROM trigger_test",
+        );
+        assert_eq!(result, Some(vec![Some("PGX".to_string()), None]));
+    }
+
+    #[pg_test]
+    fn test_trigger_skips_row() {
+        create_tables();
+        Spi::run(
+            "CREATE TRIGGER skip BEFORE INSERT ON trigger_test
+                FOR EACH ROW EXECUTE PROCEDURE trigger_skip_negative()",
+        );
+        Spi::run("INSERT INTO trigger_test VALUES (-1, 'a'), (1, 'b')");
+        let result = Spi::get_one::<Vec<i32>>("SELECT array_agg(id) FROM trigger_test");
+        assert_eq!(result, Some(vec![1]));
+    }
+
+    #[pg_test]
+    fn test_trigger_old_and_new() {
+        create_tables();
+        Spi::run(
+            "CREATE TRIGGER keep_id BEFORE UPDATE ON trigger_test
+                FOR EACH ROW EXECUTE PROCEDURE trigger_keep_old_id()",
+        );
+        Spi::run("INSERT INTO trigger_test VALUES (1, 'a')");
+        Spi::run("UPDATE trigger_test SET id = 2, name = 'b'");
+        assert_eq!(
+            Spi::get_one::<String>("SELECT id || name FROM trigger_test"),
+            Some("1b".to_string())
+        );
+    }
+
+    #[pg_test]
+    fn test_trigger_metadata() {
+        create_tables();
+        Spi::run(
+            "CREATE TRIGGER log_row AFTER DELETE ON trigger_test
+                FOR EACH ROW EXECUTE PROCEDURE trigger_log_event('a', 'b')",
+        );
+        Spi::run(
+            "CREATE TRIGGER log_statement BEFORE TRUNCATE ON trigger_test
+                FOR EACH STATEMENT EXECUTE PROCEDURE trigger_log_event()",
+        );
+        Spi::run("INSERT INTO trigger_test VALUES (1, 'a')");
+        Spi::run("DELETE FROM trigger_test");
+        Spi::run("TRUNCATE trigger_test");
+        let schema = Spi::get_one::<String>(
+            "SELECT relnamespace::regnamespace::text FROM pg_class WHERE oid = 'trigger_test'::regclass",
+        )
+        .unwrap();
+        let result = Spi::get_one::<Vec<String>>(
+            "SELECT array_agg(entry ORDER BY entry) FROM trigger_test_log",
+        );
+        assert_eq!(
+            result,
+            Some(vec![
+                format!("log_row After Delete Row {}.trigger_test a,b", schema),
+                format!(
+                    "log_statement Before Truncate Statement {}.trigger_test ",
+                    schema
+                ),
+            ])
+        );
+    }
+
+    #[pg_test]
+    fn test_trigger_transition_table() {
+        create_tables();
+        Spi::run(
+            "CREATE TRIGGER count_inserted AFTER INSERT ON trigger_test
+                REFERENCING NEW TABLE AS inserted
+                FOR EACH STATEMENT EXECUTE PROCEDURE trigger_count_inserted()",
+        );
+        Spi::run("INSERT INTO trigger_test SELECT g, 'x' FROM generate_series(1, 3) g");
+        assert_eq!(
+            Spi::get_one::<String>("SELECT entry FROM trigger_test_log"),
+            Some("3".to_string())
+        );
+    }
+
+    #[pg_test(error = "rejected by trigger")]
+    fn test_trigger_error() {
+        create_tables();
+        Spi::run(
+            "CREATE TRIGGER reject BEFORE INSERT ON trigger_test
+                FOR EACH ROW EXECUTE PROCEDURE trigger_reject()",
+        );
+        Spi::run("INSERT INTO trigger_test VALUES (1, 'a')");
+    }
+
+    #[pg_test(error = "type oid 25 is not compatible with attribute 1 of type oid 23")]
+    fn test_trigger_incompatible_type() {
+        create_tables();
+        Spi::run(
+            "CREATE TRIGGER wrong_type BEFORE INSERT ON trigger_test
+                FOR EACH ROW EXECUTE PROCEDURE trigger_wrong_type()",
+        );
+        Spi::run("INSERT INTO trigger_test VALUES (1, 'a')");
+    }
+}
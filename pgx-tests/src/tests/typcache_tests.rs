@@ -0,0 +1,117 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Serialize,
+    Deserialize,
+    PostgresType,
+    PostgresEq,
+    PostgresOrd,
+    PostgresHash,
+)]
+pub struct TypcacheThing(String);
+
+#[pg_extern]
+fn typcache_cmp(a: AnyElement, b: AnyElement) -> i32 {
+    unsafe { datum_cmp(a.datum(), b.datum(), a.oid(), pg_sys::InvalidOid) as i32 }
+}
+
+#[pg_extern]
+fn typcache_eq(a: AnyElement, b: AnyElement) -> bool {
+    unsafe { datum_eq(a.datum(), b.datum(), a.oid(), pg_sys::InvalidOid) }
+}
+
+#[pg_extern]
+fn typcache_hash(a: AnyElement) -> i32 {
+    unsafe { datum_hash(a.datum(), a.oid(), pg_sys::InvalidOid) as i32 }
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_type_cache_entry() {
+        let entry = PgTypeCacheEntry::lookup(pg_sys::INT4OID);
+        assert_eq!(entry.oid(), pg_sys::INT4OID);
+        assert_eq!(entry.typlen(), 4);
+        assert!(entry.typbyval());
+        assert_eq!(entry.typalign(), 'i');
+        assert!(entry.eq_opr().is_some());
+        assert!(entry.cmp_proc().is_some());
+        assert!(entry.hash_proc().is_some());
+    }
+
+    #[pg_test]
+    fn test_datum_cmp_builtin() {
+        assert_eq!(Spi::get_one::<i32>("SELECT typcache_cmp(1, 2);"), Some(-1));
+        assert_eq!(Spi::get_one::<i32>("SELECT typcache_cmp(2, 2);"), Some(0));
+        assert_eq!(Spi::get_one::<i32>("SELECT typcache_cmp(3, 2);"), Some(1));
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT typcache_eq(42::bigint, 42::bigint);"),
+            Some(true)
+        );
+    }
+
+    #[pg_test]
+    fn test_datum_cmp_collation() {
+        let a = "a".into_datum().unwrap();
+        let b = "B".into_datum().unwrap();
+
+        // bytewise, uppercase letters sort before lowercase ones
+        let c = unsafe { datum_cmp(a, b, pg_sys::TEXTOID, pg_sys::C_COLLATION_OID) };
+        assert_eq!(c, std::cmp::Ordering::Greater);
+
+        // whatever the database's locale is, it must agree with Postgres' own `<`
+        let default = unsafe { datum_cmp(a, b, pg_sys::TEXTOID, pg_sys::DEFAULT_COLLATION_OID) };
+        let expected = Spi::get_one::<bool>(r#"SELECT 'a' COLLATE "default" < 'B';"#)
+            .expect("comparison was NULL");
+        assert_eq!(default == std::cmp::Ordering::Less, expected);
+    }
+
+    #[pg_test]
+    fn test_datum_hash_builtin() {
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT typcache_hash(42) = hashint4(42);"),
+            Some(true)
+        );
+    }
+
+    #[pg_test]
+    fn test_datum_cmp_postgres_ord() {
+        assert_eq!(
+            Spi::get_one::<i32>(
+                r#"SELECT typcache_cmp('"a"'::TypcacheThing, '"b"'::TypcacheThing);"#
+            ),
+            Some(-1)
+        );
+        assert_eq!(
+            Spi::get_one::<bool>(
+                r#"SELECT typcache_eq('"a"'::TypcacheThing, '"a"'::TypcacheThing);"#
+            ),
+            Some(true)
+        );
+    }
+
+    #[pg_test]
+    fn test_datum_hash_postgres_hash() {
+        assert_eq!(
+            Spi::get_one::<bool>(
+                r#"SELECT typcache_hash('"a"'::TypcacheThing) = typcachething_hash('"a"'::TypcacheThing);"#
+            ),
+            Some(true)
+        );
+    }
+}
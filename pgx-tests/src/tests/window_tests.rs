@@ -0,0 +1,132 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use pgx::*;
+
+#[pg_extern(window)]
+fn window_row_number(fcinfo: pg_sys::FunctionCallInfo) -> i64 {
+    unsafe { WindowObject::from_fcinfo(fcinfo) }.current_position() + 1
+}
+
+#[pg_extern(window)]
+fn window_row_count(fcinfo: pg_sys::FunctionCallInfo) -> i64 {
+    unsafe { WindowObject::from_fcinfo(fcinfo) }.partition_row_count()
+}
+
+#[pg_extern(window)]
+fn window_lag_ignore_nulls(value: Option<i32>, fcinfo: pg_sys::FunctionCallInfo) -> Option<i32> {
+    let _ = value;
+    let window = unsafe { WindowObject::from_fcinfo(fcinfo) };
+    (1..=window.current_position())
+        .filter_map(|offset| {
+            window.arg_in_partition::<i32>(0, -(offset as i32), WindowSeek::Current, false)
+        })
+        .next()
+}
+
+#[pg_extern(window)]
+fn window_frame_first(value: Option<&str>, fcinfo: pg_sys::FunctionCallInfo) -> Option<String> {
+    let _ = value;
+    unsafe { WindowObject::from_fcinfo(fcinfo) }
+        .arg_in_frame::<&str>(0, 0, WindowSeek::Head, false)
+        .map(|s| s.to_string())
+}
+
+#[pg_extern(window)]
+fn window_current_doubled(value: i32, fcinfo: pg_sys::FunctionCallInfo) -> i32 {
+    let window = unsafe { WindowObject::from_fcinfo(fcinfo) };
+    assert_eq!(window.arg_current::<i32>(0), Some(value));
+    value * 2
+}
+
+#[pg_extern(window)]
+fn window_peer_groups(fcinfo: pg_sys::FunctionCallInfo) -> i64 {
+    let mut window = unsafe { WindowObject::from_fcinfo(fcinfo) };
+    let current = window.current_position();
+    let is_new_group = current == 0 || !window.rows_are_peers(current - 1, current);
+    let groups = window.partition_local_state::<i64>();
+    if is_new_group {
+        *groups += 1;
+    }
+    *groups
+}
+
+#[cfg(any(test, feature = "pg_test"))]
+#[pgx::pg_schema]
+mod tests {
+    #[allow(unused_imports)]
+    use crate as pgx_tests;
+
+    use pgx::*;
+
+    #[pg_test]
+    fn test_window_row_number() {
+        let result = Spi::get_one::<Vec<i64>>(
+            "SELECT array_agg(n ORDER BY v) FROM (
+                SELECT v, window_row_number() OVER (ORDER BY v) AS n
+                  FROM generate_series(10, 14) v
+            ) x;",
+        );
+        assert_eq!(result, Some(vec![1, 2, 3, 4, 5]));
+    }
+
+    #[pg_test]
+    fn test_window_row_count() {
+        let result = Spi::get_one::<Vec<i64>>(
+            "SELECT array_agg(n ORDER BY v) FROM (
+                SELECT v, window_row_count() OVER (PARTITION BY v % 2) AS n
+                  FROM generate_series(1, 5) v
+            ) x;",
+        );
+        assert_eq!(result, Some(vec![3, 2, 3, 2, 3]));
+    }
+
+    #[pg_test]
+    fn test_window_lag_ignore_nulls() {
+        let result = Spi::get_one::<Vec<Option<i32>>>(
+            "SELECT array_agg(lagged ORDER BY id) FROM (
+                SELECT id, window_lag_ignore_nulls(value) OVER (ORDER BY id) AS lagged
+                  FROM (VALUES (1, 10), (2, NULL), (3, NULL), (4, 40)) t(id, value)
+            ) x;",
+        );
+        assert_eq!(result, Some(vec![None, Some(10), Some(10), Some(10)]));
+    }
+
+    #[pg_test]
+    fn test_window_frame_first() {
+        let result = Spi::get_one::<Vec<String>>(
+            "SELECT array_agg(first ORDER BY id) FROM (
+                SELECT id, window_frame_first(value) OVER (
+                    ORDER BY id ROWS BETWEEN 1 PRECEDING AND CURRENT ROW
+                ) AS first
+                  FROM (VALUES (1, 'a'), (2, 'b'), (3, 'c')) t(id, value)
+            ) x;",
+        );
+        assert_eq!(
+            result,
+            Some(vec!["a".to_string(), "a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[pg_test]
+    fn test_window_arg_current() {
+        let result = Spi::get_one::<Vec<i32>>(
+            "SELECT array_agg(d ORDER BY v) FROM (
+                SELECT v, window_current_doubled(v) OVER (ORDER BY v) AS d
+                  FROM generate_series(1, 3) v
+            ) x;",
+        );
+        assert_eq!(result, Some(vec![2, 4, 6]));
+    }
+
+    #[pg_test]
+    fn test_window_partition_local_state() {
+        let result = Spi::get_one::<Vec<i64>>(
+            "SELECT array_agg(g ORDER BY v, id) FROM (
+                SELECT id, v, window_peer_groups() OVER (ORDER BY v) AS g
+                  FROM (VALUES (1, 1), (2, 1), (3, 2), (4, 3), (5, 3)) t(id, v)
+            ) x;",
+        );
+        assert_eq!(result, Some(vec![1, 1, 2, 3, 3]));
+    }
+}
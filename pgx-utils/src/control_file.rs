@@ -0,0 +1,300 @@
+//! Generating an extension's `.control` file from the `[package.metadata.pgx]` table of its
+//! `Cargo.toml`
+use serde_derive::Deserialize;
+
+/// The first line of a generated `.control` file, which tells it apart from one written by hand
+pub const CONTROL_FILE_HEADER: &str =
+    "# Generated by pgx from [package.metadata.pgx] in Cargo.toml\n";
+
+/// The contents of an extension's `.control` file, as declared in `Cargo.toml`.
+///
+/// Every field of `[package.metadata.pgx]` is optional.  `comment` defaults to the package's
+/// `description`, `default_version` to its `version`, and `module_pathname` to
+/// `$libdir/$EXTNAME`.  `relocatable` is always `false`, as pgx requires, and declaring it `true`
+/// is an error.
+///
+/// ```rust
+/// use pgx_utils::control_file::ControlMetadata;
+///
+/// let metadata = ControlMetadata::from_cargo_toml(
+///     r#"
+///     [package]
+///     name = "my-extension"
+///     version = "1.2.0"
+///     description = "An example"
+///
+///     [package.metadata.pgx]
+///     requires = ["hstore", "pg_trgm"]
+///     trusted = true
+///     "#,
+/// )
+/// .unwrap()
+/// .expect("no [package.metadata.pgx]");
+///
+/// assert_eq!(metadata.extname, "my_extension");
+/// assert_eq!(
+///     metadata.to_control_file(),
+///     "\
+///     ## Generated by pgx from [package.metadata.pgx] in Cargo.toml\n\
+///     comment = 'An example'\n\
+///     default_version = '1.2.0'\n\
+///     module_pathname = '$libdir/my_extension'\n\
+///     relocatable = false\n\
+///     superuser = false\n\
+///     trusted = true\n\
+///     requires = 'hstore, pg_trgm'\n\
+///     "
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlMetadata {
+    /// The name of the extension, and of its `$EXTNAME.control` file
+    pub extname: String,
+    pub comment: String,
+    pub default_version: String,
+    pub module_pathname: String,
+    pub superuser: bool,
+    pub trusted: bool,
+    pub schema: Option<String>,
+    pub requires: Vec<String>,
+    pub encoding: Option<String>,
+    pub no_relocate: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoToml {
+    package: Option<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    version: String,
+    description: Option<String>,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+#[derive(Deserialize, Default)]
+struct Metadata {
+    pgx: Option<PgxMetadata>,
+}
+
+#[derive(Deserialize)]
+struct PgxMetadata {
+    comment: Option<String>,
+    default_version: Option<String>,
+    module_pathname: Option<String>,
+    #[serde(default)]
+    relocatable: bool,
+    #[serde(default)]
+    superuser: bool,
+    #[serde(default)]
+    trusted: bool,
+    schema: Option<String>,
+    #[serde(default)]
+    requires: Vec<String>,
+    encoding: Option<String>,
+    #[serde(default)]
+    no_relocate: Vec<String>,
+}
+
+impl ControlMetadata {
+    /// Read the control file of the extension from the contents of its `Cargo.toml`, or `None`
+    /// if it has no `[package.metadata.pgx]` table.  Declaring `relocatable = true` is an error.
+    pub fn from_cargo_toml(cargo_toml: &str) -> Result<Option<Self>, toml::de::Error> {
+        let package = match toml::from_str::<CargoToml>(cargo_toml)?.package {
+            Some(package) => package,
+            // A workspace's `Cargo.toml`
+            None => return Ok(None),
+        };
+        let pgx = match package.metadata.pgx {
+            Some(pgx) => pgx,
+            None => return Ok(None),
+        };
+        if pgx.relocatable {
+            return Err(serde::de::Error::custom(
+                "`relocatable` in `[package.metadata.pgx]` must be `false`, as pgx extensions aren't relocatable",
+            ));
+        }
+        let extname = package.name.replace("-", "_");
+
+        Ok(Some(Self {
+            comment: pgx
+                .comment
+                .or(package.description)
+                .unwrap_or_else(|| format!("{}:  Created by pgx", extname)),
+            default_version: pgx.default_version.unwrap_or(package.version),
+            module_pathname: pgx
+                .module_pathname
+                .unwrap_or_else(|| format!("$libdir/{}", extname)),
+            superuser: pgx.superuser,
+            trusted: pgx.trusted,
+            schema: pgx.schema,
+            requires: pgx.requires,
+            encoding: pgx.encoding,
+            no_relocate: pgx.no_relocate,
+            extname,
+        }))
+    }
+
+    /// The contents of the `.control` file.  Optional properties are only written when they are
+    /// set.
+    pub fn to_control_file(&self) -> String {
+        let quoted = |value: &str| format!("'{}'", value.replace('\'', "''"));
+
+        let mut properties = vec![
+            ("comment", quoted(&self.comment)),
+            ("default_version", quoted(&self.default_version)),
+            ("module_pathname", quoted(&self.module_pathname)),
+            ("relocatable", String::from("false")),
+            ("superuser", self.superuser.to_string()),
+        ];
+        if self.trusted {
+            properties.push(("trusted", self.trusted.to_string()));
+        }
+        if let Some(schema) = &self.schema {
+            properties.push(("schema", schema.clone()));
+        }
+        if !self.requires.is_empty() {
+            properties.push(("requires", quoted(&self.requires.join(", "))));
+        }
+        if let Some(encoding) = &self.encoding {
+            properties.push(("encoding", encoding.clone()));
+        }
+        if !self.no_relocate.is_empty() {
+            properties.push(("no_relocate", quoted(&self.no_relocate.join(", "))));
+        }
+
+        let mut control = String::from(CONTROL_FILE_HEADER);
+        for (key, value) in properties {
+            control.push_str(&format!("{} = {}\n", key, value));
+        }
+        control
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let metadata = ControlMetadata::from_cargo_toml(
+            r#"
+            [package]
+            name = "my-extension"
+            version = "0.1.0"
+
+            [package.metadata.pgx]
+            "#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            metadata,
+            ControlMetadata {
+                extname: String::from("my_extension"),
+                comment: String::from("my_extension:  Created by pgx"),
+                default_version: String::from("0.1.0"),
+                module_pathname: String::from("$libdir/my_extension"),
+                superuser: false,
+                trusted: false,
+                schema: None,
+                requires: vec![],
+                encoding: None,
+                no_relocate: vec![],
+            }
+        );
+        assert_eq!(
+            metadata.to_control_file(),
+            format!(
+                "{}\
+                comment = 'my_extension:  Created by pgx'\n\
+                default_version = '0.1.0'\n\
+                module_pathname = '$libdir/my_extension'\n\
+                relocatable = false\n\
+                superuser = false\n\
+                ",
+                CONTROL_FILE_HEADER
+            )
+        );
+    }
+
+    #[test]
+    fn quoting() {
+        let metadata = ControlMetadata::from_cargo_toml(
+            r#"
+            [package]
+            name = "example"
+            version = "1.0.0"
+            description = "Ignored, as there's a comment"
+
+            [package.metadata.pgx]
+            comment = "It's an example"
+            default_version = "1.0"
+            schema = "example"
+            requires = ["hstore", "o'brien"]
+            no_relocate = ["hstore"]
+            encoding = "UTF8"
+            "#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            metadata.to_control_file(),
+            format!(
+                "{}\
+                comment = 'It''s an example'\n\
+                default_version = '1.0'\n\
+                module_pathname = '$libdir/example'\n\
+                relocatable = false\n\
+                superuser = false\n\
+                schema = example\n\
+                requires = 'hstore, o''brien'\n\
+                encoding = UTF8\n\
+                no_relocate = 'hstore'\n\
+                ",
+                CONTROL_FILE_HEADER
+            )
+        );
+    }
+
+    #[test]
+    fn relocatable() {
+        let relocatable = |value| {
+            ControlMetadata::from_cargo_toml(&format!(
+                r#"
+                [package]
+                name = "example"
+                version = "1.0.0"
+
+                [package.metadata.pgx]
+                relocatable = {}
+                "#,
+                value
+            ))
+        };
+        assert!(relocatable("false").unwrap().is_some());
+        assert!(relocatable("true").is_err());
+    }
+
+    #[test]
+    fn without_metadata() {
+        let package = r#"
+            [package]
+            name = "example"
+            version = "1.0.0"
+            "#;
+        assert_eq!(ControlMetadata::from_cargo_toml(package).unwrap(), None);
+
+        let workspace = r#"
+            [workspace]
+            members = ["example"]
+
+            [workspace.metadata.pgx]
+            trusted = true
+            "#;
+        assert_eq!(ControlMetadata::from_cargo_toml(workspace).unwrap(), None);
+    }
+}
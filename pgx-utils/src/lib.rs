@@ -13,6 +13,7 @@ use std::process::{Command, Stdio};
 use std::str::FromStr;
 use syn::{GenericArgument, ItemFn, PathArguments, ReturnType, Type, TypeParamBound};
 
+pub mod control_file;
 pub mod operator_common;
 pub mod pg_config;
 pub mod sql_entity_graph;
@@ -191,9 +192,20 @@ pub enum ExternArgs {
     Volatile,
     Raw,
     NoGuard,
+    NoComment,
     ParallelSafe,
     ParallelUnsafe,
     ParallelRestricted,
+    Collatable,
+    Window,
+    Materialize,
+    SecurityDefiner,
+    Leakproof,
+    CalledOnNullInput,
+    Cost(String),
+    Rows(String),
+    Set(Vec<(String, String)>),
+    Support(PositioningRef),
     Error(String),
     Schema(String),
     Name(String),
@@ -211,8 +223,35 @@ impl core::fmt::Display for ExternArgs {
             ExternArgs::ParallelSafe => write!(f, "PARALLEL SAFE"),
             ExternArgs::ParallelUnsafe => write!(f, "PARALLEL UNSAFE"),
             ExternArgs::ParallelRestricted => write!(f, "PARALLEL RESTRICTED"),
+            ExternArgs::Collatable => Ok(()),
+            ExternArgs::Window => write!(f, "WINDOW"),
+            ExternArgs::Materialize => Ok(()),
+            ExternArgs::SecurityDefiner => write!(f, "SECURITY DEFINER"),
+            ExternArgs::Leakproof => write!(f, "LEAKPROOF"),
+            ExternArgs::CalledOnNullInput => write!(f, "CALLED ON NULL INPUT"),
+            ExternArgs::Cost(cost) => write!(f, "COST {}", cost),
+            ExternArgs::Rows(rows) => write!(f, "ROWS {}", rows),
+            ExternArgs::Set(settings) => {
+                let settings = settings
+                    .iter()
+                    .map(|(name, value)| {
+                        // `search_path = "a, b"` is a list, and each of its elements is quoted
+                        let values = value
+                            .split(',')
+                            .map(|v| format!("'{}'", v.trim().replace('\'', "''")))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        format!("SET {} TO {}", name, values)
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "{}", settings.join(" "))
+            }
+            // The `SUPPORT` clause needs the SQL name of the support function, which only the
+            // SQL generator can resolve
+            ExternArgs::Support(_) => Ok(()),
             ExternArgs::Error(_) => Ok(()),
             ExternArgs::NoGuard => Ok(()),
+            ExternArgs::NoComment => Ok(()),
             ExternArgs::Schema(_) => Ok(()),
             ExternArgs::Name(_) => Ok(()),
             ExternArgs::Requires(_) => Ok(()),
@@ -229,9 +268,51 @@ impl ToTokens for ExternArgs {
             ExternArgs::Volatile => tokens.append(format_ident!("Volatile")),
             ExternArgs::Raw => tokens.append(format_ident!("Raw")),
             ExternArgs::NoGuard => tokens.append(format_ident!("NoGuard")),
+            ExternArgs::NoComment => tokens.append(format_ident!("NoComment")),
             ExternArgs::ParallelSafe => tokens.append(format_ident!("ParallelSafe")),
             ExternArgs::ParallelUnsafe => tokens.append(format_ident!("ParallelUnsafe")),
             ExternArgs::ParallelRestricted => tokens.append(format_ident!("ParallelRestricted")),
+            ExternArgs::Collatable => tokens.append(format_ident!("Collatable")),
+            ExternArgs::Window => tokens.append(format_ident!("Window")),
+            ExternArgs::Materialize => tokens.append(format_ident!("Materialize")),
+            ExternArgs::SecurityDefiner => tokens.append(format_ident!("SecurityDefiner")),
+            ExternArgs::Leakproof => tokens.append(format_ident!("Leakproof")),
+            ExternArgs::CalledOnNullInput => tokens.append(format_ident!("CalledOnNullInput")),
+            ExternArgs::Cost(cost) => {
+                tokens.append_all(
+                    quote! {
+                        Cost(String::from(#cost))
+                    }
+                    .to_token_stream(),
+                );
+            }
+            ExternArgs::Rows(rows) => {
+                tokens.append_all(
+                    quote! {
+                        Rows(String::from(#rows))
+                    }
+                    .to_token_stream(),
+                );
+            }
+            ExternArgs::Set(settings) => {
+                let settings = settings
+                    .iter()
+                    .map(|(name, value)| quote! { (String::from(#name), String::from(#value)) });
+                tokens.append_all(
+                    quote! {
+                        Set(vec![#(#settings),*])
+                    }
+                    .to_token_stream(),
+                );
+            }
+            ExternArgs::Support(item) => {
+                tokens.append_all(
+                    quote! {
+                        Support(#item)
+                    }
+                    .to_token_stream(),
+                );
+            }
             ExternArgs::Error(_s) => {
                 tokens.append_all(
                     quote! {
@@ -300,9 +381,68 @@ pub fn parse_extern_attributes(attr: TokenStream) -> HashSet<ExternArgs> {
                     "volatile" => args.insert(ExternArgs::Volatile),
                     "raw" => args.insert(ExternArgs::Raw),
                     "no_guard" => args.insert(ExternArgs::NoGuard),
+                    "no_comment" => args.insert(ExternArgs::NoComment),
                     "parallel_safe" => args.insert(ExternArgs::ParallelSafe),
                     "parallel_unsafe" => args.insert(ExternArgs::ParallelUnsafe),
                     "parallel_restricted" => args.insert(ExternArgs::ParallelRestricted),
+                    "collatable" => args.insert(ExternArgs::Collatable),
+                    "window" => args.insert(ExternArgs::Window),
+                    "materialize" => args.insert(ExternArgs::Materialize),
+                    "security_definer" => args.insert(ExternArgs::SecurityDefiner),
+                    "leakproof" => args.insert(ExternArgs::Leakproof),
+                    "called_on_null_input" => args.insert(ExternArgs::CalledOnNullInput),
+                    "cost" => {
+                        let _punc = itr.next().unwrap();
+                        let literal = itr.next().unwrap();
+                        args.insert(ExternArgs::Cost(literal.to_string()))
+                    }
+                    "rows" => {
+                        let _punc = itr.next().unwrap();
+                        let literal = itr.next().unwrap();
+                        args.insert(ExternArgs::Rows(literal.to_string()))
+                    }
+                    "set" => {
+                        let mut settings = Vec::new();
+                        if let Some(TokenTree::Group(g)) = itr.next() {
+                            let mut inner = g.stream().into_iter();
+                            while let Some(name) = inner.next() {
+                                if let TokenTree::Ident(name) = name {
+                                    let _punc = inner.next().unwrap();
+                                    let value = inner.next().unwrap().to_string();
+                                    let value =
+                                        unescape::unescape(&value).expect("failed to unescape");
+
+                                    // trim leading/trailing quotes around the literal
+                                    let value = value[1..value.len() - 1].to_string();
+                                    settings.push((name.to_string(), value));
+                                }
+                            }
+                        }
+                        args.insert(ExternArgs::Set(settings))
+                    }
+                    "support" => {
+                        let _punc = itr.next().unwrap();
+                        let mut path = String::new();
+                        for t in itr.by_ref() {
+                            match t {
+                                TokenTree::Punct(p) if p.as_char() == ',' => break,
+                                TokenTree::Literal(literal) => {
+                                    let name = literal.to_string();
+                                    let name =
+                                        unescape::unescape(&name).expect("failed to unescape");
+                                    path.push_str(&name);
+                                }
+                                t => path.push_str(&t.to_string()),
+                            }
+                        }
+                        let support = if path.starts_with('"') {
+                            // trim leading/trailing quotes around the literal
+                            PositioningRef::Name(path[1..path.len() - 1].to_string())
+                        } else {
+                            PositioningRef::FullPath(path)
+                        };
+                        args.insert(ExternArgs::Support(support))
+                    }
                     "error" => {
                         let _punc = itr.next().unwrap();
                         let literal = itr.next().unwrap();
@@ -557,7 +697,11 @@ pub fn anonymonize_lifetimes(value: &mut syn::Type) {
 
 #[cfg(test)]
 mod tests {
-    use crate::{parse_extern_attributes, ExternArgs};
+    use crate::{
+        parse_extern_attributes,
+        sql_entity_graph::{doc_comment, PositioningRef},
+        ExternArgs,
+    };
     use std::str::FromStr;
 
     #[test]
@@ -570,4 +714,56 @@ mod tests {
             "syntax error at or near \"THIS\"".to_string()
         )));
     }
+
+    #[test]
+    fn parse_function_options() {
+        let s = "cost = 1000, rows = 2.5, set(search_path = \"pg_catalog, public\", work_mem = \"64MB\"), support = my::support_fn, leakproof";
+        let ts = proc_macro2::TokenStream::from_str(s).unwrap();
+
+        let args = parse_extern_attributes(ts);
+        assert!(args.contains(&ExternArgs::Cost("1000".to_string())));
+        assert!(args.contains(&ExternArgs::Rows("2.5".to_string())));
+        let set = ExternArgs::Set(vec![
+            ("search_path".to_string(), "pg_catalog, public".to_string()),
+            ("work_mem".to_string(), "64MB".to_string()),
+        ]);
+        assert_eq!(
+            set.to_string(),
+            "SET search_path TO 'pg_catalog', 'public' SET work_mem TO '64MB'"
+        );
+        assert!(args.contains(&set));
+        assert!(args.contains(&ExternArgs::Support(PositioningRef::FullPath(
+            "my::support_fn".to_string()
+        ))));
+        assert!(args.contains(&ExternArgs::Leakproof));
+    }
+
+    #[test]
+    fn doc_comments() {
+        let item: syn::ItemFn = syn::parse_quote! {
+            /// Adds one.
+            ///
+            /// ```pgxsql
+            /// CREATE FUNCTION add_one(integer) RETURNS integer AS 'MODULE_PATHNAME', 'add_one_wrapper';
+            /// ```
+            ///   It's `x + 1`.
+            fn add_one(x: i32) -> i32 { x + 1 }
+        };
+        assert_eq!(
+            doc_comment(&item.attrs).as_deref(),
+            Some("Adds one.\n\n  It's `x + 1`.")
+        );
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            /// Adds one.
+            #[no_comment]
+            fn add_one(x: i32) -> i32 { x + 1 }
+        };
+        assert_eq!(doc_comment(&item.attrs), None);
+
+        let item: syn::ItemFn = syn::parse_quote! {
+            fn add_one(x: i32) -> i32 { x + 1 }
+        };
+        assert_eq!(doc_comment(&item.attrs), None);
+    }
 }
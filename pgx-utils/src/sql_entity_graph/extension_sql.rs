@@ -54,10 +54,12 @@ impl ToTokens for ExtensionSqlFile {
         let mut finalize = false;
         let mut requires = vec![];
         let mut creates = vec![];
+        let mut translatables = vec![];
         for attr in &self.attrs {
             match attr {
                 ExtensionSqlAttribute::Creates(items) => {
                     creates.append(&mut items.iter().map(|x| x.to_token_stream()).collect());
+                    translatables.extend(items.iter().filter_map(SqlDeclared::sql_translatable));
                 }
                 ExtensionSqlAttribute::Requires(items) => {
                     requires.append(&mut items.iter().map(|x| x.to_token_stream()).collect());
@@ -104,6 +106,8 @@ impl ToTokens for ExtensionSqlFile {
                 };
                 pgx::datum::sql_entity_graph::SqlGraphEntity::CustomSql(submission)
             }
+
+            #(#translatables)*
         };
         tokens.append_all(inv);
     }
@@ -165,6 +169,7 @@ impl ToTokens for ExtensionSql {
         let mut bootstrap = false;
         let mut finalize = false;
         let mut creates = vec![];
+        let mut translatables = vec![];
         let mut requires = vec![];
         for attr in &self.attrs {
             match attr {
@@ -173,6 +178,7 @@ impl ToTokens for ExtensionSql {
                 }
                 ExtensionSqlAttribute::Creates(items) => {
                     creates.append(&mut items.iter().map(|x| x.to_token_stream()).collect());
+                    translatables.extend(items.iter().filter_map(SqlDeclared::sql_translatable));
                 }
                 ExtensionSqlAttribute::Bootstrap => {
                     bootstrap = true;
@@ -208,6 +214,8 @@ impl ToTokens for ExtensionSql {
                 };
                 pgx::datum::sql_entity_graph::SqlGraphEntity::CustomSql(submission)
             }
+
+            #(#translatables)*
         };
         tokens.append_all(inv);
     }
@@ -288,6 +296,27 @@ impl Parse for SqlDeclared {
     }
 }
 
+impl SqlDeclared {
+    /// The `SqlTranslatable` implementation of the Rust type of a `Type(..)` or `Enum(..)`, which
+    /// has the SQL type of that name.
+    fn sql_translatable(&self) -> Option<TokenStream2> {
+        let identifier = match self {
+            SqlDeclared::Type(identifier) | SqlDeclared::Enum(identifier) => identifier,
+            SqlDeclared::Function(_) => return None,
+        };
+        let path: syn::Path =
+            syn::parse_str(identifier).expect("SQL declared entities are parsed from paths");
+        let sql_type = identifier.split("::").last();
+        Some(quote! {
+            impl pgx::datum::SqlTranslatable for #path {
+                fn sql_type() -> String {
+                    String::from(#sql_type)
+                }
+            }
+        })
+    }
+}
+
 impl ToTokens for SqlDeclared {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         let (variant, identifier) = match &self {
@@ -1,6 +1,16 @@
 mod extension_sql;
+mod opclass;
+mod pg_aggregate;
+mod pg_brin_opclass;
+mod pg_event_trigger;
 mod pg_extern;
+mod pg_gin_opclass;
+mod pg_gist_opclass;
+mod pg_guc;
 mod pg_schema;
+mod pg_spgist_opclass;
+mod pg_sql_function;
+mod pg_trigger;
 mod positioning_ref;
 mod postgres_enum;
 mod postgres_hash;
@@ -9,14 +19,73 @@ mod postgres_type;
 
 pub use super::ExternArgs;
 pub use extension_sql::{ExtensionSql, ExtensionSqlFile, SqlDeclared};
-pub use pg_extern::{Argument, PgExtern, PgOperator};
+pub use pg_aggregate::PgAggregate;
+pub use pg_brin_opclass::PgBrinOpClass;
+pub use pg_event_trigger::PgEventTrigger;
+pub use pg_extern::{Argument, PgCast, PgExtern, PgOperator};
+pub use pg_gin_opclass::PgGinOpClass;
+pub use pg_gist_opclass::PgGistOpClass;
+pub use pg_guc::PgGuc;
 pub use pg_schema::Schema;
+pub use pg_spgist_opclass::PgSpGistOpClass;
+pub use pg_sql_function::PgSqlFunction;
+pub use pg_trigger::PgTrigger;
 pub use positioning_ref::PositioningRef;
 pub use postgres_enum::PostgresEnum;
 pub use postgres_hash::PostgresHash;
 pub use postgres_ord::PostgresOrd;
 pub use postgres_type::PostgresType;
 
+/// The Rust doc comment in `attrs`, which becomes the `COMMENT ON` of the SQL object it documents.
+///
+/// ```pgxsql` blocks are left out, as they are SQL overriding the generated SQL rather than
+/// documentation.  Items opting out with a `#[no_comment]` helper attribute have none.
+pub(crate) fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    if attrs.iter().any(|attr| attr.path.is_ident("no_comment")) {
+        return None;
+    }
+    let mut lines = Vec::new();
+    let mut in_commented_sql_block = false;
+    for attr in attrs {
+        let line = match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(line),
+                ..
+            })) if path.is_ident("doc") => line.value(),
+            _ => continue,
+        };
+        if !in_commented_sql_block && line.trim() == "```pgxsql" {
+            in_commented_sql_block = true;
+        } else if in_commented_sql_block && line.trim() == "```" {
+            in_commented_sql_block = false;
+        } else if !in_commented_sql_block {
+            // `/// text` is `#[doc = " text"]`
+            lines.push(line.strip_prefix(' ').unwrap_or(&line).to_string());
+        }
+    }
+    let doc = lines.join("\n").trim().to_string();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+/// Parse the arguments of an attribute macro whose only option is `no_comment`, returning whether
+/// it was given.
+pub(crate) fn parse_no_comment(attr: proc_macro2::TokenStream) -> Result<bool, syn::Error> {
+    if attr.is_empty() {
+        return Ok(false);
+    }
+    let ident: syn::Ident = syn::parse2(attr)?;
+    if ident == "no_comment" {
+        Ok(true)
+    } else {
+        Err(syn::Error::new(ident.span(), "Invalid option"))
+    }
+}
+
 /// Reexports for the pgx SQL generator binaries.
 #[doc(hidden)]
 pub mod reexports {
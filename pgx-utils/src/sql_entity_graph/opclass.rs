@@ -0,0 +1,254 @@
+use super::pg_aggregate::{split_impl, type_entity, SelfResolver};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use std::collections::HashMap;
+use syn::{fold::Fold, parse_quote, Ident, ImplItem, ItemImpl, Type};
+
+/// The `impl ... for ...` block of an operator class macro, such as `#[pg_gist_opclass]`.
+///
+/// Each index access method has its own trait and support functions, but they all share the
+/// parsing of the `impl` block and the `pgx::datum::sql_entity_graph::PgOpClassEntity` they
+/// declare.
+#[derive(Debug, Clone)]
+pub(crate) struct OpClassImpl {
+    pub(crate) item_impl: ItemImpl,
+    pub(crate) target: Type,
+    pub(crate) target_ident: Ident,
+    trait_ident: Ident,
+    method: &'static str,
+    assoc_types: HashMap<String, Type>,
+    fns: Vec<String>,
+    consts: Vec<String>,
+    /// The implementations of the traits of the optional support functions, such as
+    /// `GistCompress`, which the functions of the `impl` block are moved into
+    function_impls: Vec<ItemImpl>,
+}
+
+impl OpClassImpl {
+    /// Parse an `impl #trait_name for ...` block, for the index access method `method`.
+    pub(crate) fn new(
+        item_impl: ItemImpl,
+        trait_name: &str,
+        method: &'static str,
+    ) -> Result<Self, syn::Error> {
+        let macro_name = format!("#[pg_{}_opclass]", method);
+        let trait_ident = item_impl
+            .trait_
+            .as_ref()
+            .and_then(|(_, path, _)| path.segments.last())
+            .map(|segment| segment.ident.clone())
+            .filter(|ident| ident == trait_name)
+            .ok_or_else(|| {
+                syn::Error::new(
+                    Span::call_site(),
+                    format!(
+                        "`{}` can only be used on an `impl {} for ...` block",
+                        macro_name, trait_name
+                    ),
+                )
+            })?;
+
+        let target = *item_impl.self_ty.clone();
+        let target_ident = match &target {
+            Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.clone())
+                .ok_or_else(|| syn::Error::new(Span::call_site(), "expected a type name"))?,
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("`{}` can only be implemented for a named type", macro_name),
+                ))
+            }
+        };
+
+        let mut assoc_types = HashMap::new();
+        let mut fns = Vec::new();
+        let mut consts = Vec::new();
+        for item in &item_impl.items {
+            match item {
+                ImplItem::Type(item_type) => {
+                    assoc_types.insert(item_type.ident.to_string(), item_type.ty.clone());
+                }
+                ImplItem::Method(item_method) => fns.push(item_method.sig.ident.to_string()),
+                ImplItem::Const(item_const) => consts.push(item_const.ident.to_string()),
+                _ => (),
+            }
+        }
+
+        let mut this = Self {
+            item_impl,
+            target,
+            target_ident,
+            trait_ident,
+            method,
+            assoc_types,
+            fns,
+            consts,
+            function_impls: Vec::new(),
+        };
+
+        // Fill in the associated types and constants every operator class trait defaults.
+        this.default_type("Query", parse_quote! { Self });
+        if !this.has_const("NAME") {
+            let name = format!(
+                "{}_{}_ops",
+                this.target_ident.to_string().to_lowercase(),
+                method
+            );
+            this.item_impl
+                .items
+                .push(parse_quote! { const NAME: &'static str = #name; });
+        }
+        Ok(this)
+    }
+
+    pub(crate) fn error(&self, message: &str) -> syn::Error {
+        syn::Error::new(
+            Span::call_site(),
+            format!("`#[pg_{}_opclass]` {}", self.method, message),
+        )
+    }
+
+    pub(crate) fn has_fn(&self, name: &str) -> bool {
+        self.fns.iter().any(|f| f == name)
+    }
+
+    pub(crate) fn has_const(&self, name: &str) -> bool {
+        self.consts.iter().any(|c| c == name)
+    }
+
+    pub(crate) fn require_fns(&self, names: &[&str]) -> Result<(), syn::Error> {
+        match names.iter().find(|name| !self.has_fn(name)) {
+            Some(missing) => Err(self.error(&format!("requires `fn {}`", missing))),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn require_type(&self, name: &str) -> Result<(), syn::Error> {
+        if self.assoc_types.contains_key(name) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("requires `type {}`", name)))
+        }
+    }
+
+    /// Move the functions `names` of the `impl`, if it provides them, into an implementation of
+    /// the trait `trait_name`.
+    pub(crate) fn split_fns(&mut self, names: &[&str], trait_name: &str) {
+        if let Some(function_impl) = split_impl(&mut self.item_impl, names, trait_name) {
+            self.function_impls.push(function_impl);
+        }
+    }
+
+    /// Add `type #name = #ty;` to the `impl` unless it already has one.
+    pub(crate) fn default_type(&mut self, name: &str, ty: Type) {
+        if !self.assoc_types.contains_key(name) {
+            let ident = format_ident!("{}", name);
+            self.item_impl
+                .items
+                .push(parse_quote! { type #ident = #ty; });
+            self.assoc_types.insert(name.to_string(), ty);
+        }
+    }
+
+    /// Is the associated type `name` given, and something other than `Self`?
+    pub(crate) fn is_not_self(&self, name: &str) -> bool {
+        match self.assoc_types.get(name) {
+            None => false,
+            Some(Type::Path(type_path)) => {
+                !(type_path.path.is_ident("Self") || Type::Path(type_path.clone()) == self.target)
+            }
+            Some(_) => true,
+        }
+    }
+
+    /// The associated type `name`, with `Self` resolved, for use outside of the `impl` block
+    pub(crate) fn resolved_type(&self, name: &str) -> Type {
+        let mut resolver = SelfResolver {
+            target: self.target.clone(),
+            assoc_types: self.assoc_types.clone(),
+        };
+        resolver.fold_type(self.assoc_types[name].clone())
+    }
+
+    pub(crate) fn fn_name(&self, suffix: &str) -> Ident {
+        format_ident!(
+            "{}_{}_{}",
+            self.target_ident.to_string().to_lowercase(),
+            self.method,
+            suffix
+        )
+    }
+
+    /// The `impl` block, the `support_fns` and the declaration of the `PgOpClassEntity` which
+    /// uses them, by support function number.
+    pub(crate) fn to_tokens_with(
+        &self,
+        support_fns: TokenStream2,
+        functions: &[(u16, &str)],
+        storage: Option<&Type>,
+        order_by_operators: bool,
+    ) -> TokenStream2 {
+        let item_impl = &self.item_impl;
+        let function_impls = &self.function_impls;
+        let target = &self.target;
+        let trait_ident = &self.trait_ident;
+        let method = self.method;
+        let sql_graph_entity_fn_name = format_ident!(
+            "__pgx_internals_{}_opclass_{}",
+            method,
+            self.target_ident.to_string().to_lowercase()
+        );
+
+        let functions = functions.iter().map(|(number, suffix)| {
+            let fn_name = self.fn_name(suffix).to_string();
+            quote! { (#number, #fn_name) }
+        });
+        let ty = type_entity(target);
+        let storage = match storage {
+            Some(storage) => {
+                let storage = type_entity(storage);
+                quote! { Some(#storage) }
+            }
+            None => quote! { None },
+        };
+        let right_ty = type_entity(&self.resolved_type("Query"));
+        let order_by_operators = if order_by_operators {
+            quote! { <#target as pgx::#trait_ident>::ORDER_BY_OPERATORS.to_vec() }
+        } else {
+            quote! { Vec::new() }
+        };
+
+        quote! {
+            #item_impl
+
+            #(#function_impls)*
+
+            #support_fns
+
+            #[no_mangle]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                use core::any::TypeId;
+                let submission = pgx::datum::sql_entity_graph::PgOpClassEntity {
+                    full_path: core::any::type_name::<#target>(),
+                    module_path: module_path!(),
+                    file: file!(),
+                    line: line!(),
+                    name: <#target as pgx::#trait_ident>::NAME,
+                    method: #method,
+                    default: <#target as pgx::#trait_ident>::DEFAULT,
+                    ty: #ty,
+                    storage: #storage,
+                    right_ty: #right_ty,
+                    operators: <#target as pgx::#trait_ident>::OPERATORS.to_vec(),
+                    order_by_operators: #order_by_operators,
+                    functions: vec![#(#functions),*],
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::OpClass(submission)
+            }
+        }
+    }
+}
@@ -0,0 +1,641 @@
+use crate::anonymonize_lifetimes;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use std::collections::HashMap;
+use syn::{
+    fold::Fold,
+    parse::{Parse, ParseStream},
+    parse_quote, Ident, ImplItem, ItemImpl, Type,
+};
+
+/// A parsed `#[pg_aggregate]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the `impl` block (with any omitted associated types
+/// filled in, and the optional functions moved into implementations of their own traits, such as
+/// `AggregateFinalize`), a `#[pg_extern]` support function for each provided trait function, and
+/// the declaration for a `pgx::datum::sql_entity_graph::PgAggregateEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgAggregate;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgAggregate = parse_quote! {
+///     impl Aggregate for DemoSum {
+///         type State = i32;
+///         type Args = (i32, Option<i32>);
+///
+///         fn state(current: Self::State, (value, bonus): Self::Args) -> Self::State {
+///             current + value + bonus.unwrap_or_default()
+///         }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgAggregate {
+    item_impl: ItemImpl,
+    target: Type,
+    target_ident: Ident,
+    state: Type,
+    args: Vec<Type>,
+    args_is_tuple: bool,
+    direct_args: Vec<Type>,
+    direct_args_is_tuple: bool,
+    finalize: Type,
+    moving_state: Type,
+    hypothetical: bool,
+    has_finalize: bool,
+    has_combine: bool,
+    has_serialize: bool,
+    has_deserialize: bool,
+    has_moving_state: bool,
+    has_moving_finalize: bool,
+    /// The implementations of the traits of the optional functions, such as `AggregateFinalize`,
+    /// which the functions of the `impl` block are moved into
+    function_impls: Vec<ItemImpl>,
+}
+
+impl PgAggregate {
+    pub fn new(mut item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let trait_is_aggregate = item_impl
+            .trait_
+            .as_ref()
+            .and_then(|(_, path, _)| path.segments.last())
+            .map(|segment| segment.ident == "Aggregate")
+            .unwrap_or(false);
+        if !trait_is_aggregate {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_aggregate]` can only be used on an `impl Aggregate for ...` block",
+            ));
+        }
+
+        let target = *item_impl.self_ty.clone();
+        let target_ident = match &target {
+            Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.clone())
+                .ok_or_else(|| syn::Error::new(Span::call_site(), "expected a type name"))?,
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "`#[pg_aggregate]` can only be implemented for a named type",
+                ))
+            }
+        };
+
+        let mut assoc_types = HashMap::new();
+        let mut fns = Vec::new();
+        let mut consts = HashMap::new();
+        for item in &item_impl.items {
+            match item {
+                ImplItem::Type(item_type) => {
+                    assoc_types.insert(item_type.ident.to_string(), item_type.ty.clone());
+                }
+                ImplItem::Method(item_method) => fns.push(item_method.sig.ident.to_string()),
+                ImplItem::Const(item_const) => {
+                    consts.insert(item_const.ident.to_string(), item_const.expr.clone());
+                }
+                _ => (),
+            }
+        }
+
+        if !assoc_types.contains_key("Args") {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_aggregate]` requires `type Args`",
+            ));
+        }
+        if !fns.iter().any(|f| f == "state") {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_aggregate]` requires `fn state`",
+            ));
+        }
+        let has = |name: &str| fns.iter().any(|f| f == name);
+        if has("moving_state") != has("moving_state_inverse") {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_aggregate]` requires both `fn moving_state` and `fn moving_state_inverse`, or neither",
+            ));
+        }
+        if has("serialize") != has("deserialize") {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_aggregate]` requires both `fn serialize` and `fn deserialize`, or neither",
+            ));
+        }
+        if has("moving_finalize") && !has("moving_state") {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_aggregate]` requires `fn moving_state` and `fn moving_state_inverse` for `fn moving_finalize`",
+            ));
+        }
+
+        // Fill in the associated types and constants the trait can't default on its own.
+        let mut defaults: Vec<ImplItem> = Vec::new();
+        if !assoc_types.contains_key("State") {
+            assoc_types.insert("State".to_string(), parse_quote! { Self });
+            defaults.push(parse_quote! { type State = Self; });
+        }
+        let state = assoc_types["State"].clone();
+        if !assoc_types.contains_key("OrderedSetArgs") {
+            assoc_types.insert("OrderedSetArgs".to_string(), parse_quote! { () });
+            defaults.push(parse_quote! { type OrderedSetArgs = (); });
+        }
+        if !assoc_types.contains_key("Finalize") {
+            assoc_types.insert("Finalize".to_string(), state.clone());
+            defaults.push(parse_quote! { type Finalize = #state; });
+        }
+        if !assoc_types.contains_key("MovingState") {
+            assoc_types.insert("MovingState".to_string(), state.clone());
+            defaults.push(parse_quote! { type MovingState = #state; });
+        }
+        if !consts.contains_key("NAME") {
+            let name = target_ident.to_string().to_lowercase();
+            defaults.push(parse_quote! { const NAME: &'static str = #name; });
+        }
+        item_impl.items.extend(defaults);
+
+        let hypothetical = match consts.get("HYPOTHETICAL") {
+            None => false,
+            Some(syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Bool(value),
+                ..
+            })) => value.value,
+            Some(other) => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "`HYPOTHETICAL` must be a literal `true` or `false`",
+                ))
+            }
+        };
+
+        let mut resolver = SelfResolver {
+            target: target.clone(),
+            assoc_types: assoc_types.clone(),
+        };
+        let (args, args_is_tuple) = flatten_tuple(resolver.fold_type(assoc_types["Args"].clone()));
+        let (direct_args, direct_args_is_tuple) =
+            flatten_tuple(resolver.fold_type(assoc_types["OrderedSetArgs"].clone()));
+        let state = resolver.fold_type(state);
+
+        // Postgres only allows a `SERIALFUNC` and `DESERIALFUNC` for an `internal` state.
+        let state_is_internal = match &state {
+            Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident == "Internal")
+                .unwrap_or(false),
+            _ => false,
+        };
+        if has("serialize") && !state_is_internal {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_aggregate]` only allows `fn serialize` and `fn deserialize` for `type State = Internal`",
+            ));
+        }
+
+        let function_impls = [
+            (&["finalize"][..], "AggregateFinalize"),
+            (&["combine"][..], "AggregateCombine"),
+            (&["serialize", "deserialize"][..], "AggregateSerialize"),
+            (
+                &["moving_state", "moving_state_inverse"][..],
+                "AggregateMovingState",
+            ),
+            (&["moving_finalize"][..], "AggregateMovingFinalize"),
+        ]
+        .iter()
+        .filter_map(|(names, trait_name)| split_impl(&mut item_impl, names, trait_name))
+        .collect();
+
+        Ok(Self {
+            state,
+            finalize: resolver.fold_type(assoc_types["Finalize"].clone()),
+            moving_state: resolver.fold_type(assoc_types["MovingState"].clone()),
+            args,
+            args_is_tuple,
+            direct_args,
+            direct_args_is_tuple,
+            hypothetical,
+            has_finalize: has("finalize"),
+            has_combine: has("combine"),
+            has_serialize: has("serialize"),
+            has_deserialize: has("deserialize"),
+            has_moving_state: has("moving_state"),
+            has_moving_finalize: has("moving_finalize"),
+            function_impls,
+            item_impl,
+            target,
+            target_ident,
+        })
+    }
+
+    fn fn_name(&self, suffix: &str) -> Ident {
+        format_ident!(
+            "{}_{}",
+            self.target_ident.to_string().to_lowercase(),
+            suffix
+        )
+    }
+
+    fn support_fns(&self) -> TokenStream2 {
+        let target = &self.target;
+        let state = &self.state;
+        let finalize = &self.finalize;
+        let moving_state = &self.moving_state;
+        let (arg_names, arg_tys) = arg_names_and_types("arg", &self.args);
+        let args_expr = pack_args(&arg_names, self.args_is_tuple);
+        let (direct_arg_names, direct_arg_tys) =
+            arg_names_and_types("direct_arg", &self.direct_args);
+        let direct_args_expr = pack_args(&direct_arg_names, self.direct_args_is_tuple);
+
+        let mut stream = TokenStream2::new();
+
+        let fn_state = self.fn_name("state");
+        stream.append_all(quote! {
+            #[pgx::pg_extern]
+            fn #fn_state(this: #state, #(#arg_names: #arg_tys),*) -> #state {
+                <#target as pgx::Aggregate>::state(this, #args_expr)
+            }
+        });
+
+        if self.has_finalize {
+            let fn_finalize = self.fn_name("finalize");
+            // Hypothetical-set aggregates always use `FINALFUNC_EXTRA`, which passes a `NULL` for
+            // each aggregated argument after the direct arguments.
+            let (extra_names, extra_tys) = if self.hypothetical {
+                let (names, tys) = arg_names_and_types("_extra", &self.args);
+                let tys = tys.into_iter().map(into_option).collect();
+                (names, tys)
+            } else {
+                (Vec::new(), Vec::new())
+            };
+            stream.append_all(quote! {
+                #[pgx::pg_extern]
+                fn #fn_finalize(this: #state, #(#direct_arg_names: #direct_arg_tys,)* #(#extra_names: #extra_tys),*) -> #finalize {
+                    <#target as pgx::AggregateFinalize>::finalize(this, #direct_args_expr)
+                }
+            });
+        }
+
+        if self.has_combine {
+            let fn_combine = self.fn_name("combine");
+            stream.append_all(quote! {
+                #[pgx::pg_extern]
+                fn #fn_combine(this: #state, other: #state) -> #state {
+                    <#target as pgx::AggregateCombine>::combine(this, other)
+                }
+            });
+        }
+
+        if self.has_serialize {
+            let fn_serial = self.fn_name("serial");
+            stream.append_all(quote! {
+                #[pgx::pg_extern(strict)]
+                fn #fn_serial(this: #state) -> Vec<u8> {
+                    <#target as pgx::AggregateSerialize>::serialize(this)
+                }
+            });
+        }
+
+        if self.has_deserialize {
+            let fn_deserial = self.fn_name("deserial");
+            stream.append_all(quote! {
+                #[pgx::pg_extern(strict)]
+                fn #fn_deserial(buf: &[u8], _internal: pgx::Internal) -> #state {
+                    <#target as pgx::AggregateSerialize>::deserialize(buf)
+                }
+            });
+        }
+
+        if self.has_moving_state {
+            let fn_moving_state = self.fn_name("moving_state");
+            let fn_moving_state_inverse = self.fn_name("moving_state_inverse");
+            stream.append_all(quote! {
+                #[pgx::pg_extern]
+                fn #fn_moving_state(this: #moving_state, #(#arg_names: #arg_tys),*) -> #moving_state {
+                    <#target as pgx::AggregateMovingState>::moving_state(this, #args_expr)
+                }
+
+                #[pgx::pg_extern]
+                fn #fn_moving_state_inverse(this: #moving_state, #(#arg_names: #arg_tys),*) -> #moving_state {
+                    <#target as pgx::AggregateMovingState>::moving_state_inverse(this, #args_expr)
+                }
+            });
+        }
+
+        if self.has_moving_finalize {
+            let fn_moving_finalize = self.fn_name("moving_finalize");
+            stream.append_all(quote! {
+                #[pgx::pg_extern]
+                fn #fn_moving_finalize(this: #moving_state) -> #finalize {
+                    <#target as pgx::AggregateMovingFinalize>::moving_finalize(this)
+                }
+            });
+        }
+
+        stream
+    }
+}
+
+impl Parse for PgAggregate {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgAggregate {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let item_impl = &self.item_impl;
+        let function_impls = &self.function_impls;
+        let target = &self.target;
+        let sql_graph_entity_fn_name = syn::Ident::new(
+            &format!(
+                "__pgx_internals_aggregate_{}",
+                self.target_ident.to_string().to_lowercase()
+            ),
+            Span::call_site(),
+        );
+        let support_fns = self.support_fns();
+
+        let fn_name_string = |enabled: bool, suffix: &str| {
+            let name = self.fn_name(suffix).to_string();
+            if enabled {
+                quote! { Some(#name) }
+            } else {
+                quote! { None }
+            }
+        };
+        let sfunc = self.fn_name("state").to_string();
+        let finalfunc = fn_name_string(self.has_finalize, "finalize");
+        let combinefunc = fn_name_string(self.has_combine, "combine");
+        let serialfunc = fn_name_string(self.has_serialize, "serial");
+        let deserialfunc = fn_name_string(self.has_deserialize, "deserial");
+        let msfunc = fn_name_string(self.has_moving_state, "moving_state");
+        let minvfunc = fn_name_string(self.has_moving_state, "moving_state_inverse");
+        let mfinalfunc = fn_name_string(self.has_moving_finalize, "moving_finalize");
+
+        let args = self.args.iter().map(type_entity);
+        let direct_args = self.direct_args.iter().map(type_entity);
+        let stype = type_entity(&self.state);
+        let mstype = if self.has_moving_state {
+            let mstype = type_entity(&self.moving_state);
+            quote! { Some(#mstype) }
+        } else {
+            quote! { None }
+        };
+
+        let inv = quote! {
+            #item_impl
+
+            #(#function_impls)*
+
+            #support_fns
+
+            #[no_mangle]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                use core::any::TypeId;
+                let submission = pgx::datum::sql_entity_graph::PgAggregateEntity {
+                    full_path: core::any::type_name::<#target>(),
+                    module_path: module_path!(),
+                    file: file!(),
+                    line: line!(),
+                    name: <#target as pgx::Aggregate>::NAME,
+                    args: vec![#(#args),*],
+                    direct_args: vec![#(#direct_args),*],
+                    stype: #stype,
+                    sfunc: #sfunc,
+                    finalfunc: #finalfunc,
+                    finalfunc_modify: <#target as pgx::Aggregate>::FINALIZE_MODIFY,
+                    combinefunc: #combinefunc,
+                    serialfunc: #serialfunc,
+                    deserialfunc: #deserialfunc,
+                    initcond: <#target as pgx::Aggregate>::INITIAL_CONDITION,
+                    msfunc: #msfunc,
+                    minvfunc: #minvfunc,
+                    mstype: #mstype,
+                    mfinalfunc: #mfinalfunc,
+                    mfinalfunc_modify: <#target as pgx::Aggregate>::MOVING_FINALIZE_MODIFY,
+                    minitcond: <#target as pgx::Aggregate>::MOVING_INITIAL_CONDITION,
+                    sortop: <#target as pgx::Aggregate>::SORT_OPERATOR,
+                    parallel: <#target as pgx::Aggregate>::PARALLEL,
+                    ordered_set: <#target as pgx::Aggregate>::ORDERED_SET,
+                    hypothetical: <#target as pgx::Aggregate>::HYPOTHETICAL,
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::Aggregate(submission)
+            }
+        };
+        tokens.append_all(inv);
+    }
+}
+
+/// Replaces `Self` and `Self::Assoc` in the associated types, since the generated support
+/// functions live outside of the `impl` block.
+pub(crate) struct SelfResolver {
+    pub(crate) target: Type,
+    pub(crate) assoc_types: HashMap<String, Type>,
+}
+
+impl Fold for SelfResolver {
+    fn fold_type(&mut self, ty: Type) -> Type {
+        if let Type::Path(type_path) = &ty {
+            let segments = &type_path.path.segments;
+            if type_path.qself.is_none()
+                && segments.first().map(|s| s.ident == "Self").unwrap_or(false)
+            {
+                match segments.len() {
+                    1 => return self.target.clone(),
+                    2 => {
+                        let assoc = segments[1].ident.to_string();
+                        if let Some(resolved) = self.assoc_types.get(&assoc).cloned() {
+                            return self.fold_type(resolved);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        syn::fold::fold_type(self, ty)
+    }
+}
+
+/// Move the functions `names` out of `item_impl`, into an implementation of `pgx::#trait_name`
+/// for the same type, unless `item_impl` has none of them.
+///
+/// The optional functions of a trait such as `Aggregate` live in traits of their own, so using a
+/// function which wasn't provided is a compile error, rather than a panic.
+pub(crate) fn split_impl(
+    item_impl: &mut ItemImpl,
+    names: &[&str],
+    trait_name: &str,
+) -> Option<ItemImpl> {
+    let (moved, kept) = item_impl
+        .items
+        .drain(..)
+        .partition::<Vec<_>, _>(|item| match item {
+            ImplItem::Method(item_method) => names.iter().any(|name| item_method.sig.ident == name),
+            _ => false,
+        });
+    item_impl.items = kept;
+    if moved.is_empty() {
+        return None;
+    }
+
+    let trait_ident = format_ident!("{}", trait_name);
+    let (impl_generics, _, where_clause) = item_impl.generics.split_for_impl();
+    let self_ty = &item_impl.self_ty;
+    Some(parse_quote! {
+        impl #impl_generics pgx::#trait_ident for #self_ty #where_clause {
+            #(#moved)*
+        }
+    })
+}
+
+fn flatten_tuple(ty: Type) -> (Vec<Type>, bool) {
+    match ty {
+        Type::Tuple(tuple) => (tuple.elems.into_iter().collect(), true),
+        Type::Paren(paren) => flatten_tuple(*paren.elem),
+        other => (vec![other], false),
+    }
+}
+
+fn arg_names_and_types(prefix: &str, tys: &[Type]) -> (Vec<Ident>, Vec<Type>) {
+    tys.iter()
+        .enumerate()
+        .map(|(idx, ty)| (format_ident!("{}_{}", prefix, idx), ty.clone()))
+        .unzip()
+}
+
+fn pack_args(names: &[Ident], is_tuple: bool) -> TokenStream2 {
+    if is_tuple {
+        quote! { (#(#names,)*) }
+    } else {
+        quote! { #(#names)* }
+    }
+}
+
+fn into_option(ty: Type) -> Type {
+    let is_option = match &ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "Option")
+            .unwrap_or(false),
+        _ => false,
+    };
+    if is_option {
+        ty
+    } else {
+        parse_quote! { Option<#ty> }
+    }
+}
+
+pub(crate) fn type_entity(ty: &Type) -> TokenStream2 {
+    let mut ty = ty.clone();
+    anonymonize_lifetimes(&mut ty);
+    quote! {
+        pgx::datum::sql_entity_graph::AggregateTypeEntity {
+            sql_type: <#ty as pgx::datum::SqlTranslatable>::sql_type(),
+            ty_id: TypeId::of::<#ty>(),
+            full_path: core::any::type_name::<#ty>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PgAggregate;
+    use syn::{parse_quote, ImplItem, ItemImpl};
+
+    #[test]
+    fn optional_fns_are_split_into_their_traits() {
+        let parsed = PgAggregate::new(parse_quote! {
+            impl Aggregate for DemoSum {
+                type State = i32;
+                type Args = i32;
+
+                fn state(current: Self::State, arg: Self::Args) -> Self::State {
+                    current + arg
+                }
+
+                fn finalize(current: Self::State, _direct_args: Self::OrderedSetArgs) -> Self::Finalize {
+                    current
+                }
+            }
+        })
+        .unwrap();
+
+        let has_fn = |item_impl: &ItemImpl, name: &str| {
+            item_impl.items.iter().any(|item| match item {
+                ImplItem::Method(method) => method.sig.ident == name,
+                _ => false,
+            })
+        };
+        assert!(has_fn(&parsed.item_impl, "state"));
+        assert!(!has_fn(&parsed.item_impl, "finalize"));
+        assert_eq!(parsed.function_impls.len(), 1);
+        let function_impl = &parsed.function_impls[0];
+        let trait_path = &function_impl.trait_.as_ref().unwrap().1;
+        assert_eq!(
+            trait_path.segments.last().unwrap().ident,
+            "AggregateFinalize"
+        );
+        assert!(has_fn(function_impl, "finalize"));
+    }
+
+    #[test]
+    fn serialize_requires_an_internal_state() {
+        let err = PgAggregate::new(parse_quote! {
+            impl Aggregate for DemoSum {
+                type State = i32;
+                type Args = i32;
+
+                fn state(current: Self::State, arg: Self::Args) -> Self::State {
+                    current + arg
+                }
+
+                fn serialize(current: Self::State) -> Vec<u8> {
+                    current.to_le_bytes().to_vec()
+                }
+
+                fn deserialize(buf: &[u8]) -> Self::State {
+                    i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])
+                }
+            }
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("type State = Internal"));
+
+        assert!(PgAggregate::new(parse_quote! {
+            impl Aggregate for DemoSum {
+                type State = pgx::Internal;
+                type Args = i32;
+
+                fn state(current: Self::State, arg: Self::Args) -> Self::State {
+                    current
+                }
+
+                fn serialize(current: Self::State) -> Vec<u8> {
+                    Vec::new()
+                }
+
+                fn deserialize(buf: &[u8]) -> Self::State {
+                    pgx::Internal::default()
+                }
+            }
+        })
+        .is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+use super::opclass::OpClassImpl;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    ItemImpl, Type,
+};
+
+/// A parsed `#[pg_brin_opclass]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the `impl` block (with any omitted associated types
+/// filled in), the `#[pg_extern]` support functions, and the declaration for a
+/// `pgx::datum::sql_entity_graph::PgOpClassEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgBrinOpClass;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgBrinOpClass = parse_quote! {
+///     impl BrinOpClass for Reading {
+///         type Summary = Period;
+///         type Query = Period;
+///         const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+///
+///         fn add_value(summary: Option<&Period>, value: Reading) -> Option<Period> { Period::extend(summary, value.at) }
+///         fn consistent(summary: &Period, query: &Period, _strategy: u16) -> bool { summary.overlaps(query) }
+///         fn union(a: &Period, b: &Period) -> Period { a.cover(b) }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgBrinOpClass {
+    opclass: OpClassImpl,
+    summary: Type,
+    has_storage: bool,
+}
+
+impl PgBrinOpClass {
+    pub fn new(item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let opclass = OpClassImpl::new(item_impl, "BrinOpClass", "brin")?;
+        opclass.require_fns(&["add_value", "consistent", "union"])?;
+        opclass.require_type("Summary")?;
+
+        Ok(Self {
+            summary: opclass.resolved_type("Summary"),
+            has_storage: opclass.is_not_self("Summary"),
+            opclass,
+        })
+    }
+
+    /// The support functions, by BRIN support function number
+    fn support_fns(&self) -> Vec<(u16, &'static str)> {
+        vec![
+            (1, "opcinfo"),
+            (2, "add_value"),
+            (3, "consistent"),
+            (4, "union"),
+        ]
+    }
+
+    fn support_fn_tokens(&self) -> TokenStream2 {
+        let target = &self.opclass.target;
+
+        let mut stream = TokenStream2::new();
+        for (_, suffix) in self.support_fns() {
+            let fn_name = self.opclass.fn_name(suffix);
+            let support_fn = match suffix {
+                "opcinfo" => quote! {
+                    fn #fn_name(typoid: pgx::Internal) -> pgx::Internal {
+                        pgx::brin_opcinfo::<#target>(typoid)
+                    }
+                },
+                "add_value" => quote! {
+                    fn #fn_name(_desc: pgx::Internal, column: pgx::Internal, value: pgx::Internal, is_null: pgx::Internal) -> bool {
+                        pgx::brin_add_value::<#target>(column, value, is_null)
+                    }
+                },
+                "consistent" => quote! {
+                    fn #fn_name(_desc: pgx::Internal, column: pgx::Internal, key: pgx::Internal) -> bool {
+                        pgx::brin_consistent::<#target>(column, key)
+                    }
+                },
+                _ => quote! {
+                    fn #fn_name(_desc: pgx::Internal, a: pgx::Internal, b: pgx::Internal) -> bool {
+                        pgx::brin_union::<#target>(a, b)
+                    }
+                },
+            };
+            stream.append_all(quote! {
+                #[pgx::pg_extern(immutable, parallel_safe)]
+                #support_fn
+            });
+        }
+        stream
+    }
+}
+
+impl Parse for PgBrinOpClass {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgBrinOpClass {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let storage = if self.has_storage {
+            Some(&self.summary)
+        } else {
+            None
+        };
+        tokens.append_all(self.opclass.to_tokens_with(
+            self.support_fn_tokens(),
+            &self.support_fns(),
+            storage,
+            false,
+        ));
+    }
+}
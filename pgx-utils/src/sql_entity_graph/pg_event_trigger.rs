@@ -0,0 +1,186 @@
+use super::doc_comment;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Ident, ItemFn, LitStr, Token,
+};
+
+/// The events Postgres can fire an event trigger for
+const EVENTS: &[&str] = &[
+    "ddl_command_start",
+    "ddl_command_end",
+    "sql_drop",
+    "table_rewrite",
+];
+
+/// A parsed `#[pg_event_trigger]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the original function, an `extern "C"` wrapper which
+/// calls it with a `pgx::PgEventTrigger`, and the declaration for a
+/// `pgx::datum::sql_entity_graph::PgExternEntity` returning `event_trigger`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgEventTrigger;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed = PgEventTrigger::new(
+///     quote! { event = "ddl_command_end", tags = ["CREATE TABLE"] },
+///     quote! {
+///         fn example(trigger: &PgEventTrigger) -> Result<(), String> {
+///             Ok(())
+///         }
+///     },
+/// )?;
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgEventTrigger {
+    func: ItemFn,
+    event: Option<LitStr>,
+    tags: Vec<LitStr>,
+    doc: Option<String>,
+}
+
+impl PgEventTrigger {
+    pub fn new(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let func: ItemFn = syn::parse2(item)?;
+        if func.sig.inputs.len() != 1 {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_event_trigger]` functions must take exactly one argument, a `&PgEventTrigger`",
+            ));
+        }
+
+        let mut event = None;
+        let mut tags = Vec::new();
+        let mut doc = doc_comment(&func.attrs);
+        let attrs = syn::parse::Parser::parse2(
+            Punctuated::<EventTriggerAttribute, Token![,]>::parse_terminated,
+            attr,
+        )?;
+        for attr in attrs {
+            match attr {
+                EventTriggerAttribute::Event(value) => {
+                    if !EVENTS.contains(&value.value().as_str()) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!("`event` must be one of {}", EVENTS.join(", ")),
+                        ));
+                    }
+                    event = Some(value)
+                }
+                EventTriggerAttribute::Tags(values) => tags.extend(values),
+                EventTriggerAttribute::NoComment => doc = None,
+            }
+        }
+        if event.is_none() && !tags.is_empty() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`tags` can only be used along with an `event`",
+            ));
+        }
+
+        Ok(Self {
+            func,
+            event,
+            tags,
+            doc,
+        })
+    }
+}
+
+impl ToTokens for PgEventTrigger {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let func = &self.func;
+        let ident = &func.sig.ident;
+        let wrapper_ident = format_ident!("{}_wrapper", ident);
+        let finfo_ident = format_ident!("pg_finfo_{}_wrapper", ident);
+        let sql_graph_entity_fn_name = format_ident!("__pgx_internals_fn_{}", ident);
+        let event = self.event.iter();
+        let tags = &self.tags;
+        let doc = self.doc.iter();
+
+        tokens.append_all(quote! {
+            #func
+
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #finfo_ident() -> &'static pgx::pg_sys::Pg_finfo_record {
+                const V1_API: pgx::pg_sys::Pg_finfo_record = pgx::pg_sys::Pg_finfo_record { api_version: 1 };
+                &V1_API
+            }
+
+            #[pgx::pg_guard]
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #wrapper_ident(fcinfo: pgx::pg_sys::FunctionCallInfo) -> pgx::pg_sys::Datum {
+                unsafe { pgx::PgEventTrigger::call(fcinfo, #ident) }
+            }
+
+            #[no_mangle]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                let submission = pgx::datum::sql_entity_graph::PgExternEntity {
+                    name: stringify!(#ident),
+                    unaliased_name: stringify!(#ident),
+                    schema: None,
+                    file: file!(),
+                    line: line!(),
+                    module_path: core::module_path!(),
+                    full_path: concat!(core::module_path!(), "::", stringify!(#ident)),
+                    extern_attrs: vec![],
+                    search_path: None,
+                    fn_args: vec![],
+                    fn_return: pgx::datum::sql_entity_graph::PgExternReturnEntity::EventTrigger {
+                        event: None#( .unwrap_or(Some(#event)) )*,
+                        tags: vec![#(#tags),*],
+                    },
+                    operator: None,
+                    cast: None,
+                    sql_body: None,
+                    overridden: None,
+                    procedure: false,
+                    doc: None#( .unwrap_or(Some(#doc)) )*,
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::Function(submission)
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+enum EventTriggerAttribute {
+    Event(LitStr),
+    Tags(Vec<LitStr>),
+    NoComment,
+}
+
+impl Parse for EventTriggerAttribute {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let ident: Ident = input.parse()?;
+        if ident == "no_comment" {
+            return Ok(EventTriggerAttribute::NoComment);
+        }
+        let _eq: Token![=] = input.parse()?;
+        match ident.to_string().as_str() {
+            "event" => Ok(EventTriggerAttribute::Event(input.parse()?)),
+            "tags" => {
+                let content;
+                let _bracket = syn::bracketed!(content in input);
+                let tags = content.parse_terminated::<LitStr, Token![,]>(|input| input.parse())?;
+                Ok(EventTriggerAttribute::Tags(tags.into_iter().collect()))
+            }
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected `event = \"...\"`, `tags = [\"...\"]` or `no_comment`",
+            )),
+        }
+    }
+}
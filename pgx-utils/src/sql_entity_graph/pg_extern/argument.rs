@@ -16,6 +16,7 @@ pub struct Argument {
     pat: syn::Ident,
     ty: syn::Type,
     default: Option<String>,
+    inout: bool,
 }
 
 impl Argument {
@@ -27,7 +28,13 @@ impl Argument {
     }
 
     pub fn build_from_pat_type(value: syn::PatType) -> Result<Option<Self>, syn::Error> {
-        let mut true_ty = *value.ty.clone();
+        let (ty, inout) = match *value.ty {
+            syn::Type::Macro(ref macro_pat) if is_inout_macro(&macro_pat.mac) => {
+                (macro_pat.mac.parse_body::<syn::Type>()?, true)
+            }
+            ref ty => (ty.clone(), false),
+        };
+        let mut true_ty = ty.clone();
         anonymonize_lifetimes(&mut true_ty);
 
         let identifier = match *value.pat {
@@ -38,7 +45,7 @@ impl Argument {
             },
             _ => return Err(syn::Error::new(Span::call_site(), "Unable to parse FnArg")),
         };
-        let default = match value.ty.as_ref() {
+        let default = match &ty {
             syn::Type::Macro(macro_pat) => {
                 let mac = &macro_pat.mac;
                 let archetype = mac.path.segments.last().expect("No last segment");
@@ -150,10 +157,20 @@ impl Argument {
             pat: identifier,
             ty: true_ty,
             default,
+            inout,
         }))
     }
 }
 
+/// Is `mac` the `inout!()` macro, which marks a `#[pg_procedure]` argument as `INOUT`?
+fn is_inout_macro(mac: &syn::Macro) -> bool {
+    mac.path
+        .segments
+        .last()
+        .map(|segment| segment.ident == "inout")
+        .unwrap_or_default()
+}
+
 fn handle_default(
     ty: syn::Type,
     archetype: &syn::PathSegment,
@@ -254,6 +271,7 @@ impl ToTokens for Argument {
         let mut found_variadic = false;
         let pat = &self.pat;
         let default = self.default.iter();
+        let is_inout = self.inout;
         let mut ty = self.ty.clone();
         anonymonize_lifetimes(&mut ty);
 
@@ -282,12 +300,11 @@ impl ToTokens for Argument {
             }
             _ => (),
         };
-        let ty_string = ty.to_token_stream().to_string().replace(" ", "");
-
         let quoted = quote! {
             pgx::datum::sql_entity_graph::PgExternArgumentEntity {
                 pattern: stringify!(#pat),
-                ty_source: #ty_string,
+                sql_type: <#ty as pgx::datum::SqlTranslatable>::sql_type(),
+                collatable: <#ty as pgx::datum::SqlTranslatable>::collatable(),
                 ty_id: TypeId::of::<#ty>(),
                 full_path: core::any::type_name::<#ty>(),
                 module_path: {
@@ -298,6 +315,7 @@ impl ToTokens for Argument {
                 },
                 is_optional: #found_optional,
                 is_variadic: #found_variadic,
+                is_inout: #is_inout,
                 default: None#( .unwrap_or(Some(#default)) )*,
             }
         };
@@ -38,9 +38,20 @@ pub enum Attribute {
     Volatile,
     Raw,
     NoGuard,
+    NoComment,
     ParallelSafe,
     ParallelUnsafe,
     ParallelRestricted,
+    Collatable,
+    Window,
+    Materialize,
+    SecurityDefiner,
+    Leakproof,
+    CalledOnNullInput,
+    Cost(syn::Lit),
+    Rows(syn::Lit),
+    Set(Punctuated<Setting, Token![,]>),
+    Support(PositioningRef),
     Error(syn::LitStr),
     Schema(syn::LitStr),
     Name(syn::LitStr),
@@ -56,6 +67,9 @@ impl ToTokens for Attribute {
             Attribute::Volatile => quote! { pgx::datum::sql_entity_graph::ExternArgs::Volatile },
             Attribute::Raw => quote! { pgx::datum::sql_entity_graph::ExternArgs::Raw },
             Attribute::NoGuard => quote! { pgx::datum::sql_entity_graph::ExternArgs::NoGuard },
+            Attribute::NoComment => {
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::NoComment }
+            }
             Attribute::ParallelSafe => {
                 quote! { pgx::datum::sql_entity_graph::ExternArgs::ParallelSafe }
             }
@@ -65,6 +79,40 @@ impl ToTokens for Attribute {
             Attribute::ParallelRestricted => {
                 quote! { pgx::datum::sql_entity_graph::ExternArgs::ParallelRestricted }
             }
+            Attribute::Collatable => {
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::Collatable }
+            }
+            Attribute::Window => quote! { pgx::datum::sql_entity_graph::ExternArgs::Window },
+            Attribute::Materialize => {
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::Materialize }
+            }
+            Attribute::SecurityDefiner => {
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::SecurityDefiner }
+            }
+            Attribute::Leakproof => {
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::Leakproof }
+            }
+            Attribute::CalledOnNullInput => {
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::CalledOnNullInput }
+            }
+            Attribute::Cost(cost) => {
+                let cost = cost.to_token_stream().to_string();
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::Cost(String::from(#cost)) }
+            }
+            Attribute::Rows(rows) => {
+                let rows = rows.to_token_stream().to_string();
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::Rows(String::from(#rows)) }
+            }
+            Attribute::Set(settings) => {
+                let settings_iter = settings.iter().map(|Setting { name, value }| {
+                    let name = name.to_string();
+                    quote! { (String::from(#name), String::from(#value)) }
+                });
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::Set(vec![#(#settings_iter),*]) }
+            }
+            Attribute::Support(item) => {
+                quote! { pgx::datum::sql_entity_graph::ExternArgs::Support(#item) }
+            }
             Attribute::Error(s) => {
                 quote! { pgx::datum::sql_entity_graph::ExternArgs::Error(String::from(#s)) }
             }
@@ -96,9 +144,33 @@ impl Parse for Attribute {
             "volatile" => Self::Volatile,
             "raw" => Self::Raw,
             "no_guard" => Self::NoGuard,
+            "no_comment" => Self::NoComment,
             "parallel_safe" => Self::ParallelSafe,
             "parallel_unsafe" => Self::ParallelUnsafe,
             "parallel_restricted" => Self::ParallelRestricted,
+            "collatable" => Self::Collatable,
+            "window" => Self::Window,
+            "materialize" => Self::Materialize,
+            "security_definer" => Self::SecurityDefiner,
+            "leakproof" => Self::Leakproof,
+            "called_on_null_input" => Self::CalledOnNullInput,
+            "cost" => {
+                let _eq: Token![=] = input.parse()?;
+                Self::Cost(parse_positive_number(input, "cost")?)
+            }
+            "rows" => {
+                let _eq: Token![=] = input.parse()?;
+                Self::Rows(parse_positive_number(input, "rows")?)
+            }
+            "set" => {
+                let content;
+                let _paren = syn::parenthesized!(content in input);
+                Self::Set(content.parse_terminated(Setting::parse)?)
+            }
+            "support" => {
+                let _eq: Token![=] = input.parse()?;
+                Self::Support(input.parse()?)
+            }
             "error" => {
                 let _eq: Token![=] = input.parse()?;
                 let literal: syn::LitStr = input.parse()?;
@@ -125,3 +197,42 @@ impl Parse for Attribute {
         Ok(found)
     }
 }
+
+/// A `name = "value"` configuration parameter of a `set(...)` attribute
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Setting {
+    pub name: syn::Ident,
+    pub value: syn::LitStr,
+}
+
+impl Parse for Setting {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let name = input.parse()?;
+        let _eq: Token![=] = input.parse()?;
+        let value = input.parse()?;
+        Ok(Self { name, value })
+    }
+}
+
+/// Parse the integer or float literal of a `cost` or `rows` attribute, which Postgres requires
+/// to be positive
+fn parse_positive_number(input: ParseStream, attribute: &str) -> Result<syn::Lit, syn::Error> {
+    let literal: syn::Lit = input.parse()?;
+    let value = match &literal {
+        syn::Lit::Int(int) => int.base10_parse::<f64>()?,
+        syn::Lit::Float(float) => float.base10_parse::<f64>()?,
+        _ => {
+            return Err(syn::Error::new(
+                literal.span(),
+                format!("`{}` must be a number", attribute),
+            ))
+        }
+    };
+    if value <= 0.0 {
+        return Err(syn::Error::new(
+            literal.span(),
+            format!("`{}` must be positive", attribute),
+        ));
+    }
+    Ok(literal)
+}
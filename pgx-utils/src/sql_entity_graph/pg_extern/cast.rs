@@ -0,0 +1,46 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::parse::{Parse, ParseStream};
+
+/// A parsed `#[pg_cast]` cast.
+///
+/// It is created during [`PgExtern`](pgx_utils::sql_entity_graph::PgExtern) parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgCast {
+    /// `#[pg_cast]`: only used when explicitly asked for, as in `value::type`
+    Default,
+    /// `#[pg_cast(assignment)]`
+    Assignment,
+    /// `#[pg_cast(implicit)]`
+    Implicit,
+}
+
+impl Parse for PgCast {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        if input.is_empty() {
+            return Ok(PgCast::Default);
+        }
+        let ident: syn::Ident = input.parse()?;
+        match ident.to_string().as_str() {
+            "implicit" => Ok(PgCast::Implicit),
+            "assignment" => Ok(PgCast::Assignment),
+            _ => Err(syn::Error::new(
+                Span::call_site(),
+                "expected `#[pg_cast]`, `#[pg_cast(implicit)]` or `#[pg_cast(assignment)]`",
+            )),
+        }
+    }
+}
+
+impl ToTokens for PgCast {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let quoted = match self {
+            PgCast::Default => quote! { pgx::datum::sql_entity_graph::PgCastEntity::Default },
+            PgCast::Assignment => {
+                quote! { pgx::datum::sql_entity_graph::PgCastEntity::Assignment }
+            }
+            PgCast::Implicit => quote! { pgx::datum::sql_entity_graph::PgCastEntity::Implicit },
+        };
+        tokens.append_all(quoted);
+    }
+}
@@ -1,16 +1,19 @@
 mod argument;
 mod attribute;
+mod cast;
 mod operator;
 mod returning;
 mod search_path;
 
 pub use argument::Argument;
-use attribute::{Attribute, PgxAttributes};
+pub(crate) use attribute::{Attribute, PgxAttributes};
+pub use cast::PgCast;
 pub use operator::PgOperator;
 use operator::{PgxOperatorAttributeWithIdent, PgxOperatorOpName};
 use returning::Returning;
 use search_path::SearchPathList;
 
+use super::doc_comment;
 use eyre::WrapErr;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
@@ -44,6 +47,8 @@ pub struct PgExtern {
     attrs: Option<PgxAttributes>,
     attr_tokens: proc_macro2::TokenStream,
     func: syn::ItemFn,
+    procedure: bool,
+    sql_body: Option<(syn::LitStr, syn::LitStr)>,
 }
 
 impl PgExtern {
@@ -107,6 +112,19 @@ impl PgExtern {
         retval
     }
 
+    fn doc(&self) -> Option<String> {
+        let no_comment = self
+            .attrs
+            .as_ref()
+            .map(|a| a.attrs.iter().any(|attr| attr == &Attribute::NoComment))
+            .unwrap_or_default();
+        if no_comment {
+            None
+        } else {
+            doc_comment(&self.func.attrs)
+        }
+    }
+
     fn operator(&self) -> Option<PgOperator> {
         let mut skel = Option::<PgOperator>::default();
         for attr in &self.func.attrs {
@@ -159,6 +177,21 @@ impl PgExtern {
         skel
     }
 
+    fn cast(&self) -> Result<Option<PgCast>, syn::Error> {
+        let attr = self.func.attrs.iter().find(|attr| {
+            attr.path
+                .segments
+                .last()
+                .map(|segment| segment.ident == "pg_cast")
+                .unwrap_or_default()
+        });
+        match attr {
+            Some(attr) if attr.tokens.is_empty() => Ok(Some(PgCast::Default)),
+            Some(attr) => attr.parse_args().map(Some),
+            None => Ok(None),
+        }
+    }
+
     fn search_path(&self) -> Option<SearchPathList> {
         self.func
             .attrs
@@ -189,15 +222,46 @@ impl PgExtern {
         Returning::try_from(&self.func.sig.output)
     }
 
+    /// Is this a `#[pg_procedure]`, rather than a `#[pg_extern]`?
+    pub fn procedure(&self) -> bool {
+        self.procedure
+    }
+
     pub fn new(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
-        let attrs = syn::parse2::<PgxAttributes>(attr.clone()).ok();
+        let attrs = Some(syn::parse2::<PgxAttributes>(attr.clone())?);
         let func = syn::parse2::<syn::ItemFn>(item)?;
         Ok(Self {
             attrs: attrs,
             attr_tokens: attr,
             func: func,
+            procedure: false,
+            sql_body: None,
+        })
+    }
+
+    /// Like [`PgExtern::new`], but for a `#[pg_procedure]`.
+    pub fn new_procedure(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        Ok(Self {
+            procedure: true,
+            ..Self::new(attr, item)?
         })
     }
+
+    /// A `#[pg_sql_function]`, whose `language` and `body` take the place of the Rust `func`.
+    pub(crate) fn new_sql_function(
+        attrs: PgxAttributes,
+        func: syn::ItemFn,
+        language: syn::LitStr,
+        body: syn::LitStr,
+    ) -> Self {
+        Self {
+            attr_tokens: attrs.to_token_stream(),
+            attrs: Some(attrs),
+            func,
+            procedure: false,
+            sql_body: Some((language, body)),
+        }
+    }
 }
 
 impl ToTokens for PgExtern {
@@ -220,7 +284,24 @@ impl ToTokens for PgExtern {
             }
         };
         let operator = self.operator().into_iter();
+        let cast = match self.cast() {
+            Ok(cast) => cast.into_iter(),
+            Err(e) => {
+                tokens.append_all(e.to_compile_error());
+                return;
+            }
+        };
         let overridden = self.overridden().into_iter();
+        let procedure = self.procedure;
+        let doc = self.doc().into_iter();
+        let sql_body = self.sql_body.iter().map(|(language, body)| {
+            quote! {
+                pgx::datum::sql_entity_graph::PgSqlBodyEntity {
+                    language: #language,
+                    body: #body,
+                }
+            }
+        });
 
         let sql_graph_entity_fn_name =
             syn::Ident::new(&format!("__pgx_internals_fn_{}", ident), Span::call_site());
@@ -241,7 +322,11 @@ impl ToTokens for PgExtern {
                     fn_args: vec![#(#inputs),*],
                     fn_return: #returns,
                     operator: None#( .unwrap_or(Some(#operator)) )*,
+                    cast: None#( .unwrap_or(Some(#cast)) )*,
+                    sql_body: None#( .unwrap_or(Some(#sql_body)) )*,
                     overridden: None#( .unwrap_or(Some(#overridden)) )*,
+                    procedure: #procedure,
+                    doc: None#( .unwrap_or(Some(#doc)) )*,
                 };
                 pgx::datum::sql_entity_graph::SqlGraphEntity::Function(submission)
             }
@@ -259,6 +344,8 @@ impl Parse for PgExtern {
             attrs,
             attr_tokens,
             func,
+            procedure: false,
+            sql_body: None,
         })
     }
 }
@@ -14,6 +14,8 @@ pub enum Returning {
     Type(syn::Type),
     SetOf(syn::TypePath),
     Iterated(Vec<(syn::Type, Option<String>)>),
+    /// A single row, as a tuple of `name!()`s, which become `OUT` parameters
+    Record(Vec<(syn::Type, Option<String>)>),
     /// `pgx_pg_sys::Datum`
     Trigger,
 }
@@ -46,7 +48,11 @@ impl Returning {
     }
 
     fn parse_type_tuple(type_tuple: &mut syn::TypeTuple) -> Returning {
-        let returns: Vec<(syn::Type, Option<_>)> = type_tuple
+        Returning::Iterated(Self::parse_type_tuple_items(type_tuple))
+    }
+
+    fn parse_type_tuple_items(type_tuple: &mut syn::TypeTuple) -> Vec<(syn::Type, Option<String>)> {
+        type_tuple
             .elems
             .iter_mut()
             .flat_map(|elem| {
@@ -69,8 +75,7 @@ impl Returning {
                     ty => Some((ty.clone(), None)),
                 }
             })
-            .collect();
-        Returning::Iterated(returns)
+            .collect()
     }
 
     fn parse_impl_trait(impl_trait: &mut syn::TypeImplTrait) -> Returning {
@@ -138,6 +143,13 @@ impl TryFrom<&syn::ReturnType> for Returning {
                                                 maybe_inner_impl_trait =
                                                     Some(Returning::parse_dyn_trait(dyn_trait))
                                             }
+                                            Some(syn::GenericArgument::Type(syn::Type::Tuple(
+                                                tup,
+                                            ))) if saw_option_ident && !tup.elems.is_empty() => {
+                                                maybe_inner_impl_trait = Some(Returning::Record(
+                                                    Returning::parse_type_tuple_items(tup),
+                                                ))
+                                            }
                                             _ => (),
                                         }
                                     }
@@ -183,7 +195,7 @@ impl TryFrom<&syn::ReturnType> for Returning {
                         if tup.elems.is_empty() {
                             Returning::Type(ty.clone())
                         } else {
-                            Self::parse_type_tuple(tup)
+                            Returning::Record(Self::parse_type_tuple_items(tup))
                         }
                     }
                     _ => {
@@ -205,11 +217,10 @@ impl ToTokens for Returning {
                 pgx::datum::sql_entity_graph::PgExternReturnEntity::None
             },
             Returning::Type(ty) => {
-                let ty_string = ty.to_token_stream().to_string().replace(" ", "");
                 quote! {
                     pgx::datum::sql_entity_graph::PgExternReturnEntity::Type {
                         id: TypeId::of::<#ty>(),
-                        source: #ty_string,
+                        sql_type: <#ty as pgx::datum::SqlTranslatable>::sql_type(),
                         full_path: core::any::type_name::<#ty>(),
                         module_path: {
                             let type_name = core::any::type_name::<#ty>();
@@ -221,11 +232,10 @@ impl ToTokens for Returning {
                 }
             }
             Returning::SetOf(ty) => {
-                let ty_string = ty.to_token_stream().to_string().replace(" ", "");
                 quote! {
                     pgx::datum::sql_entity_graph::PgExternReturnEntity::SetOf {
                         id: TypeId::of::<#ty>(),
-                        source: #ty_string,
+                        sql_type: <#ty as pgx::datum::SqlTranslatable>::sql_type(),
                         full_path: core::any::type_name::<#ty>(),
                         module_path: {
                             let type_name = core::any::type_name::<#ty>();
@@ -236,16 +246,15 @@ impl ToTokens for Returning {
                     }
                 }
             }
-            Returning::Iterated(items) => {
+            Returning::Iterated(items) | Returning::Record(items) => {
                 let quoted_items = items
                     .iter()
                     .map(|(ty, name)| {
-                        let ty_string = ty.to_token_stream().to_string().replace(" ", "");
                         let name_iter = name.iter();
                         quote! {
                             (
                                 TypeId::of::<#ty>(),
-                                #ty_string,
+                                <#ty as pgx::datum::SqlTranslatable>::sql_type(),
                                 core::any::type_name::<#ty>(),
                                 {
                                     let type_name = core::any::type_name::<#ty>();
@@ -258,10 +267,17 @@ impl ToTokens for Returning {
                         }
                     })
                     .collect::<Vec<_>>();
-                quote! {
-                    pgx::datum::sql_entity_graph::PgExternReturnEntity::Iterated(vec![
-                        #(#quoted_items),*
-                    ])
+                match self {
+                    Returning::Record(_) => quote! {
+                        pgx::datum::sql_entity_graph::PgExternReturnEntity::Record(vec![
+                            #(#quoted_items),*
+                        ])
+                    },
+                    _ => quote! {
+                        pgx::datum::sql_entity_graph::PgExternReturnEntity::Iterated(vec![
+                            #(#quoted_items),*
+                        ])
+                    },
                 }
             }
             Returning::Trigger => quote! {
@@ -0,0 +1,152 @@
+use super::opclass::OpClassImpl;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    ItemImpl, Type,
+};
+
+/// A parsed `#[pg_gin_opclass]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the `impl` block (with any omitted associated types
+/// filled in, and the optional functions moved into implementations of their own traits, such as
+/// `GinTriConsistent`), a `#[pg_extern]` support function for `compare` and each provided trait
+/// function, and the declaration for a `pgx::datum::sql_entity_graph::PgOpClassEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgGinOpClass;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgGinOpClass = parse_quote! {
+///     impl GinOpClass for Tags {
+///         type Key = String;
+///         const OPERATORS: &'static [(u16, &'static str)] = &[(2, "@>")];
+///
+///         fn extract_value(value: Tags) -> Vec<String> { value.tags }
+///         fn extract_query(query: &Tags, _strategy: u16) -> Vec<String> { query.tags.clone() }
+///         fn consistent(check: &[bool], _strategy: u16, _query: &Tags, _recheck: &mut bool) -> bool {
+///             check.iter().all(|&present| present)
+///         }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgGinOpClass {
+    opclass: OpClassImpl,
+    key: Type,
+    query: Type,
+    has_storage: bool,
+}
+
+impl PgGinOpClass {
+    pub fn new(item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let mut opclass = OpClassImpl::new(item_impl, "GinOpClass", "gin")?;
+        opclass.require_fns(&["extract_value", "extract_query", "consistent"])?;
+        opclass.require_type("Key")?;
+        opclass.split_fns(&["tri_consistent"], "GinTriConsistent");
+        opclass.split_fns(&["compare_partial"], "GinComparePartial");
+
+        Ok(Self {
+            key: opclass.resolved_type("Key"),
+            query: opclass.resolved_type("Query"),
+            // GIN indexes store keys, which are only the `STORAGE` of the operator class when
+            // they aren't the indexed values themselves
+            has_storage: opclass.is_not_self("Key"),
+            opclass,
+        })
+    }
+
+    /// The support functions the `impl` provides, by GIN support function number
+    fn support_fns(&self) -> Vec<(u16, &'static str)> {
+        vec![
+            (1, "compare"),
+            (2, "extract_value"),
+            (3, "extract_query"),
+            (4, "consistent"),
+            (5, "compare_partial"),
+            (6, "tri_consistent"),
+        ]
+        .into_iter()
+        .filter(|(_, name)| *name == "compare" || self.opclass.has_fn(name))
+        .collect()
+    }
+
+    fn support_fn_tokens(&self) -> TokenStream2 {
+        let target = &self.opclass.target;
+        let key = &self.key;
+        let query = &self.query;
+
+        let mut stream = TokenStream2::new();
+        for (_, suffix) in self.support_fns() {
+            let fn_name = self.opclass.fn_name(suffix);
+            let support_fn = match suffix {
+                "compare" => quote! {
+                    fn #fn_name(a: #key, b: #key) -> i32 {
+                        pgx::gin_compare::<#target>(a, b)
+                    }
+                },
+                "extract_value" => quote! {
+                    fn #fn_name(value: #target, nkeys: pgx::Internal, null_flags: pgx::Internal) -> pgx::Internal {
+                        pgx::gin_extract_value::<#target>(value, nkeys, null_flags)
+                    }
+                },
+                "extract_query" => quote! {
+                    fn #fn_name(query: #query, nkeys: pgx::Internal, strategy: i16, _extra_data: pgx::Internal, null_flags: pgx::Internal, partial_matches: pgx::Internal, search_mode: pgx::Internal) -> pgx::Internal {
+                        pgx::gin_extract_query::<#target>(query, nkeys, strategy, null_flags, partial_matches, search_mode)
+                    }
+                },
+                // Postgres calls `consistent` with eight arguments, even if pgx only needs five
+                "consistent" => quote! {
+                    #[allow(clippy::too_many_arguments)]
+                    fn #fn_name(check: pgx::Internal, strategy: i16, query: #query, nkeys: i32, _extra_data: pgx::Internal, recheck: pgx::Internal, _query_keys: pgx::Internal, _null_flags: pgx::Internal) -> bool {
+                        pgx::gin_consistent::<#target>(check, strategy, query, nkeys, recheck)
+                    }
+                },
+                "compare_partial" => quote! {
+                    fn #fn_name(partial: #key, key: #key, strategy: i16, _extra_data: pgx::Internal) -> i32 {
+                        pgx::gin_compare_partial::<#target>(partial, key, strategy)
+                    }
+                },
+                _ => quote! {
+                    fn #fn_name(check: pgx::Internal, strategy: i16, query: #query, nkeys: i32, _extra_data: pgx::Internal, _query_keys: pgx::Internal, _null_flags: pgx::Internal) -> i8 {
+                        pgx::gin_tri_consistent::<#target>(check, strategy, query, nkeys)
+                    }
+                },
+            };
+            stream.append_all(quote! {
+                #[pgx::pg_extern(immutable, parallel_safe)]
+                #support_fn
+            });
+        }
+        stream
+    }
+}
+
+impl Parse for PgGinOpClass {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgGinOpClass {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let storage = if self.has_storage {
+            Some(&self.key)
+        } else {
+            None
+        };
+        tokens.append_all(self.opclass.to_tokens_with(
+            self.support_fn_tokens(),
+            &self.support_fns(),
+            storage,
+            false,
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+use super::opclass::OpClassImpl;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, ItemImpl, Type,
+};
+
+/// A parsed `#[pg_gist_opclass]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the `impl` block (with any omitted associated types
+/// filled in, and the optional functions moved into implementations of their own traits, such as
+/// `GistCompress`), a `#[pg_extern]` support function for each provided trait function, and the
+/// declaration for a `pgx::datum::sql_entity_graph::PgOpClassEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgGistOpClass;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgGistOpClass = parse_quote! {
+///     impl GistOpClass for Span {
+///         const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+///
+///         fn consistent(key: &Span, query: &Span, _strategy: u16, _is_leaf: bool, _recheck: &mut bool) -> bool {
+///             key.overlaps(query)
+///         }
+///         fn union(keys: Vec<Span>) -> Span { Span::cover(keys) }
+///         fn penalty(original: &Span, new: &Span) -> f32 { original.growth(new) }
+///         fn picksplit(keys: &[Span]) -> (Vec<usize>, Vec<usize>) { Span::split(keys) }
+///         fn same(a: &Span, b: &Span) -> bool { a == b }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgGistOpClass {
+    opclass: OpClassImpl,
+    key: Type,
+    query: Type,
+    has_storage: bool,
+}
+
+impl PgGistOpClass {
+    pub fn new(item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let mut opclass = OpClassImpl::new(item_impl, "GistOpClass", "gist")?;
+        opclass.require_fns(&["consistent", "union", "penalty", "picksplit", "same"])?;
+        if opclass.has_const("ORDER_BY_OPERATORS") && !opclass.has_fn("distance") {
+            return Err(opclass.error("requires `fn distance` for `ORDER_BY_OPERATORS`"));
+        }
+
+        // A `Key` other than `Self` becomes the `STORAGE` of the operator class, which values
+        // have to be compressed to.
+        let has_storage = opclass.is_not_self("Key");
+        if has_storage && !opclass.has_fn("compress") {
+            return Err(opclass.error("requires `fn compress` when `type Key` is not `Self`"));
+        }
+        opclass.default_type("Key", parse_quote! { Self });
+        opclass.split_fns(&["compress"], "GistCompress");
+        opclass.split_fns(&["decompress"], "GistDecompress");
+        opclass.split_fns(&["distance"], "GistDistance");
+        opclass.split_fns(&["fetch"], "GistFetch");
+
+        Ok(Self {
+            key: opclass.resolved_type("Key"),
+            query: opclass.resolved_type("Query"),
+            has_storage,
+            opclass,
+        })
+    }
+
+    /// The support functions the `impl` provides, by GiST support function number
+    fn support_fns(&self) -> Vec<(u16, &'static str)> {
+        vec![
+            (1, "consistent"),
+            (2, "union"),
+            (3, "compress"),
+            (4, "decompress"),
+            (5, "penalty"),
+            (6, "picksplit"),
+            (7, "same"),
+            (8, "distance"),
+            (9, "fetch"),
+        ]
+        .into_iter()
+        .filter(|(_, name)| self.opclass.has_fn(name))
+        .collect()
+    }
+
+    fn support_fn_tokens(&self) -> TokenStream2 {
+        let target = &self.opclass.target;
+        let key = &self.key;
+        let query = &self.query;
+
+        let mut stream = TokenStream2::new();
+        for (_, suffix) in self.support_fns() {
+            let fn_name = self.opclass.fn_name(suffix);
+            let support_fn = match suffix {
+                "consistent" => quote! {
+                    fn #fn_name(entry: pgx::Internal, query: #query, strategy: i16, _subtype: pgx::PgOid, recheck: pgx::Internal) -> bool {
+                        pgx::gist_consistent::<#target>(entry, query, strategy, recheck)
+                    }
+                },
+                "union" => quote! {
+                    fn #fn_name(entryvec: pgx::Internal, _size: pgx::Internal) -> #key {
+                        pgx::gist_union::<#target>(entryvec)
+                    }
+                },
+                "penalty" => quote! {
+                    fn #fn_name(original: pgx::Internal, new: pgx::Internal, penalty: pgx::Internal) -> pgx::Internal {
+                        pgx::gist_penalty::<#target>(original, new, penalty)
+                    }
+                },
+                "picksplit" => quote! {
+                    fn #fn_name(entryvec: pgx::Internal, splitvec: pgx::Internal) -> pgx::Internal {
+                        pgx::gist_picksplit::<#target>(entryvec, splitvec)
+                    }
+                },
+                "same" => quote! {
+                    fn #fn_name(a: #key, b: #key, result: pgx::Internal) -> pgx::Internal {
+                        pgx::gist_same::<#target>(a, b, result)
+                    }
+                },
+                "distance" => quote! {
+                    fn #fn_name(entry: pgx::Internal, query: #query, strategy: i16, _subtype: pgx::PgOid, recheck: pgx::Internal) -> f64 {
+                        pgx::gist_distance::<#target>(entry, query, strategy, recheck)
+                    }
+                },
+                // `compress`, `decompress` and `fetch` all map one `GISTENTRY` to another
+                _ => {
+                    let glue = format_ident!("gist_{}", suffix);
+                    quote! {
+                        fn #fn_name(entry: pgx::Internal) -> pgx::Internal {
+                            pgx::#glue::<#target>(entry)
+                        }
+                    }
+                }
+            };
+            stream.append_all(quote! {
+                #[pgx::pg_extern(immutable, parallel_safe)]
+                #support_fn
+            });
+        }
+        stream
+    }
+}
+
+impl Parse for PgGistOpClass {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgGistOpClass {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let storage = if self.has_storage {
+            Some(&self.key)
+        } else {
+            None
+        };
+        tokens.append_all(self.opclass.to_tokens_with(
+            self.support_fn_tokens(),
+            &self.support_fns(),
+            storage,
+            true,
+        ));
+    }
+}
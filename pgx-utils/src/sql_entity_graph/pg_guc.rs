@@ -0,0 +1,287 @@
+use super::doc_comment;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Expr, Ident, ItemStatic, LitStr, Token,
+};
+
+/// The `GucContext` variants, and the `context` of each in `pg_settings`
+const CONTEXTS: &[(&str, &str)] = &[
+    ("Internal", "internal"),
+    ("Postmaster", "postmaster"),
+    ("Sighup", "sighup"),
+    ("SuBackend", "superuser-backend"),
+    ("Backend", "backend"),
+    ("Suset", "superuser"),
+    ("Userset", "user"),
+];
+
+/// The units a GUC can have, and the `GucFlags` of each
+const UNITS: &[(&str, &str)] = &[
+    ("B", "UNIT_BYTE"),
+    ("kB", "UNIT_KB"),
+    ("MB", "UNIT_MB"),
+    ("8kB", "UNIT_BLOCKS"),
+    ("ms", "UNIT_MS"),
+    ("s", "UNIT_S"),
+    ("min", "UNIT_MIN"),
+];
+
+/// A parsed `#[pg_guc]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the original `static`, a constructor which records its
+/// `pgx::guc::GucDeclaration` when the extension is loaded, and the declaration for a
+/// `pgx::datum::sql_entity_graph::PgGucEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgGuc;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed = PgGuc::new(
+///     quote! { name = "myext.threshold", context = Suset, min = 0, max = 100, units = "ms" },
+///     quote! {
+///         /// How long to wait
+///         static THRESHOLD: GucSetting<i32> = GucSetting::new(10);
+///     },
+/// )?;
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgGuc {
+    item: ItemStatic,
+    name: LitStr,
+    context: Ident,
+    min: Option<Expr>,
+    max: Option<Expr>,
+    units: Option<LitStr>,
+    short_description: String,
+    long_description: Option<String>,
+}
+
+impl PgGuc {
+    pub fn new(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let item: ItemStatic = syn::parse2(item)?;
+        if item.mutability.is_some() {
+            return Err(syn::Error::new_spanned(
+                item.mutability,
+                "`#[pg_guc]` can't be used on a `static mut`, a `GucSetting` is changed by Postgres",
+            ));
+        }
+
+        let mut name = None;
+        let mut context = Ident::new("Userset", Span::call_site());
+        let mut min = None;
+        let mut max = None;
+        let mut units = None;
+        let attrs = syn::parse::Parser::parse2(
+            Punctuated::<GucAttribute, Token![,]>::parse_terminated,
+            attr,
+        )?;
+        for attr in attrs {
+            match attr {
+                GucAttribute::Name(value) => {
+                    if !value.value().contains('.') {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "`name` must be prefixed by the extension's name, such as `myext.threshold`",
+                        ));
+                    }
+                    name = Some(value)
+                }
+                GucAttribute::Context(value) => {
+                    if !CONTEXTS.iter().any(|(variant, _)| value == variant) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!(
+                                "`context` must be one of {}",
+                                CONTEXTS
+                                    .iter()
+                                    .map(|(variant, _)| *variant)
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ),
+                        ));
+                    }
+                    context = value
+                }
+                GucAttribute::Min(value) => min = Some(value),
+                GucAttribute::Max(value) => max = Some(value),
+                GucAttribute::Units(value) => {
+                    if !UNITS.iter().any(|(unit, _)| value.value() == *unit) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!(
+                                "`units` must be one of {}",
+                                UNITS
+                                    .iter()
+                                    .map(|(unit, _)| *unit)
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ),
+                        ));
+                    }
+                    units = Some(value)
+                }
+            }
+        }
+        let name = name.ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "`#[pg_guc]` requires a `name`, such as `name = \"myext.threshold\"`",
+            )
+        })?;
+
+        // The first paragraph of the doc comment is the short description, the rest the long one.
+        let doc = doc_comment(&item.attrs).unwrap_or_default();
+        let (short_description, long_description) = match doc.split_once("\n\n") {
+            Some((short, long)) => (short.replace('\n', " "), Some(long.trim().to_string())),
+            None => (doc.replace('\n', " "), None),
+        };
+
+        Ok(Self {
+            item,
+            name,
+            context,
+            min,
+            max,
+            units,
+            short_description,
+            long_description,
+        })
+    }
+}
+
+impl ToTokens for PgGuc {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let item = &self.item;
+        let ident = &item.ident;
+        let ty = &item.ty;
+        let name = &self.name;
+        let context = &self.context;
+        let pg_settings_context = CONTEXTS
+            .iter()
+            .find(|(variant, _)| context == variant)
+            .map(|(_, context)| *context)
+            .expect("`context` was not validated");
+        let flags = match &self.units {
+            Some(units) => {
+                let flag = UNITS
+                    .iter()
+                    .find(|(unit, _)| units.value() == *unit)
+                    .map(|(_, flag)| format_ident!("{}", flag))
+                    .expect("`units` was not validated");
+                quote! { pgx::guc::GucFlags::#flag }
+            }
+            None => quote! { pgx::guc::GucFlags::empty() },
+        };
+        let units = self.units.iter();
+        let min = self.min.iter();
+        let max = self.max.iter();
+        let min_string = self.min.iter();
+        let max_string = self.max.iter();
+        let short_description = &self.short_description;
+        let long_description = self.long_description.iter();
+        let long_description_entity = self.long_description.iter();
+        let declaration_ident = format_ident!("__pgx_guc_declaration_{}", ident);
+        let sql_graph_entity_fn_name = format_ident!("__pgx_internals_guc_{}", ident);
+
+        tokens.append_all(quote! {
+            #item
+
+            // Records the declaration as the extension's library is loaded, so `_PG_init()` can
+            // define every `#[pg_guc]` without knowing where they are.
+            #[used]
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            #[cfg_attr(
+                any(
+                    target_os = "linux",
+                    target_os = "android",
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_os = "openbsd",
+                    target_os = "dragonfly",
+                    target_os = "illumos",
+                ),
+                link_section = ".init_array"
+            )]
+            #[cfg_attr(any(target_os = "macos", target_os = "ios"), link_section = "__DATA,__mod_init_func")]
+            static #declaration_ident: extern "C" fn() = {
+                extern "C" fn declare() {
+                    pgx::guc::declare_guc(pgx::guc::GucDeclaration {
+                        name: #name,
+                        short_description: #short_description,
+                        long_description: None#( .unwrap_or(Some(#long_description)) )*,
+                        context: pgx::guc::GucContext::#context,
+                        flags: #flags,
+                        define: |declaration| {
+                            pgx::guc::DeclaredGuc::define(
+                                &#ident,
+                                declaration,
+                                None#( .unwrap_or(Some(#min)) )*,
+                                None#( .unwrap_or(Some(#max)) )*,
+                            )
+                        },
+                    });
+                }
+                declare
+            };
+
+            #[no_mangle]
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                let submission = pgx::datum::sql_entity_graph::PgGucEntity {
+                    name: #name,
+                    short_description: #short_description,
+                    long_description: None#( .unwrap_or(Some(#long_description_entity)) )*,
+                    vartype: <#ty as pgx::guc::DeclaredGuc>::VARTYPE,
+                    context: #pg_settings_context,
+                    unit: None#( .unwrap_or(Some(#units)) )*,
+                    min: None#( .unwrap_or(Some(ToString::to_string(&(#min_string)))) )*,
+                    max: None#( .unwrap_or(Some(ToString::to_string(&(#max_string)))) )*,
+                    file: file!(),
+                    line: line!(),
+                    full_path: concat!(module_path!(), "::", stringify!(#ident)),
+                    module_path: module_path!(),
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::Guc(submission)
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+enum GucAttribute {
+    Name(LitStr),
+    Context(Ident),
+    Min(Expr),
+    Max(Expr),
+    Units(LitStr),
+}
+
+impl Parse for GucAttribute {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let ident: Ident = input.parse()?;
+        let _eq: Token![=] = input.parse()?;
+        match ident.to_string().as_str() {
+            "name" => Ok(GucAttribute::Name(input.parse()?)),
+            "context" => Ok(GucAttribute::Context(input.parse()?)),
+            "min" => Ok(GucAttribute::Min(input.parse()?)),
+            "max" => Ok(GucAttribute::Max(input.parse()?)),
+            "units" => Ok(GucAttribute::Units(input.parse()?)),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected `name = \"...\"`, `context = ...`, `min = ...`, `max = ...` or `units = \"...\"`",
+            )),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::{doc_comment, parse_no_comment};
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
 use std::hash::{Hash, Hasher};
@@ -28,13 +29,27 @@ use syn::{
 #[derive(Debug, Clone)]
 pub struct Schema {
     pub module: ItemMod,
+    doc: Option<String>,
+}
+
+impl Schema {
+    /// Parse a `#[pg_schema]` module, along with the `no_comment` option of the attribute.
+    pub fn new(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let no_comment = parse_no_comment(attr)?;
+        let schema: Self = syn::parse2(item)?;
+        Ok(Self {
+            doc: if no_comment { None } else { schema.doc },
+            ..schema
+        })
+    }
 }
 
 impl Parse for Schema {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let module: ItemMod = input.parse()?;
+        let doc = doc_comment(&module.attrs);
 
-        Ok(Self { module })
+        Ok(Self { module, doc })
     }
 }
 
@@ -44,6 +59,7 @@ impl ToTokens for Schema {
         let vis = &self.module.vis;
         let mod_token = &self.module.mod_token;
         let ident = &self.module.ident;
+        let doc = self.doc.iter();
         let (_content_brace, content_items) = &self
             .module
             .content
@@ -69,6 +85,7 @@ impl ToTokens for Schema {
                         name: stringify!(#ident),
                         file: file!(),
                         line: line!(),
+                        doc: None#( .unwrap_or(Some(#doc)) )*,
                     };
                     pgx::datum::sql_entity_graph::SqlGraphEntity::Schema(submission)
                 }
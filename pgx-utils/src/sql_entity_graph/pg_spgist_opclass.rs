@@ -0,0 +1,109 @@
+use super::opclass::OpClassImpl;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, ItemImpl,
+};
+
+/// A parsed `#[pg_spgist_opclass]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the `impl` block (with any omitted associated types
+/// filled in), the `#[pg_extern]` support functions, and the declaration for a
+/// `pgx::datum::sql_entity_graph::PgOpClassEntity`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgSpGistOpClass;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgSpGistOpClass = parse_quote! {
+///     impl SpGistOpClass for Spot {
+///         type Prefix = Spot;
+///         type Query = Area;
+///         const OPERATORS: &'static [(u16, &'static str)] = &[(8, "<@")];
+///
+///         fn choose(value: &Spot, inner: &SpGistInner<Spot, ()>) -> SpGistChoice<Spot, ()> { Spot::quadrant(value, inner) }
+///         fn picksplit(values: &[Spot], _level: u32) -> SpGistSplit<Spot, ()> { Spot::split(values) }
+///         fn inner_consistent(queries: &[(u16, Area)], inner: &SpGistInner<Spot, ()>) -> Vec<usize> { Area::quadrants(queries, inner) }
+///         fn leaf_consistent(value: &Spot, queries: &[(u16, Area)], _recheck: &mut bool) -> bool { Area::contain(queries, value) }
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgSpGistOpClass {
+    opclass: OpClassImpl,
+}
+
+impl PgSpGistOpClass {
+    pub fn new(item_impl: ItemImpl) -> Result<Self, syn::Error> {
+        let mut opclass = OpClassImpl::new(item_impl, "SpGistOpClass", "spgist")?;
+        opclass.require_fns(&["choose", "picksplit", "inner_consistent", "leaf_consistent"])?;
+        opclass.default_type("Prefix", parse_quote! { () });
+        opclass.default_type("Label", parse_quote! { () });
+        Ok(Self { opclass })
+    }
+
+    /// The support functions, by SP-GiST support function number
+    fn support_fns(&self) -> Vec<(u16, &'static str)> {
+        vec![
+            (1, "config"),
+            (2, "choose"),
+            (3, "picksplit"),
+            (4, "inner_consistent"),
+            (5, "leaf_consistent"),
+        ]
+    }
+
+    fn support_fn_tokens(&self) -> TokenStream2 {
+        let target = &self.opclass.target;
+
+        let mut stream = TokenStream2::new();
+        for (_, suffix) in self.support_fns() {
+            let fn_name = self.opclass.fn_name(suffix);
+            let support_fn = if suffix == "leaf_consistent" {
+                quote! {
+                    fn #fn_name(input: pgx::Internal, output: pgx::Internal) -> bool {
+                        pgx::spgist_leaf_consistent::<#target>(input, output)
+                    }
+                }
+            } else {
+                // every other SP-GiST support function fills in its output struct
+                let glue = format_ident!("spgist_{}", suffix);
+                quote! {
+                    fn #fn_name(input: pgx::Internal, output: pgx::Internal) {
+                        pgx::#glue::<#target>(input, output)
+                    }
+                }
+            };
+            stream.append_all(quote! {
+                #[pgx::pg_extern(immutable, parallel_safe)]
+                #support_fn
+            });
+        }
+        stream
+    }
+}
+
+impl Parse for PgSpGistOpClass {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgSpGistOpClass {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        tokens.append_all(self.opclass.to_tokens_with(
+            self.support_fn_tokens(),
+            &self.support_fns(),
+            None,
+            false,
+        ));
+    }
+}
@@ -0,0 +1,144 @@
+use super::pg_extern::{Attribute, PgExtern, PgxAttributes};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::ToTokens;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    LitStr, Token,
+};
+
+/// A parsed `#[pg_sql_function]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the declaration for a
+/// `pgx::datum::sql_entity_graph::PgExternEntity` whose body is SQL, rather than a Rust function.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgSqlFunction;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed = PgSqlFunction::new(
+///     quote! { immutable, language = "sql", body = "SELECT $1 + 1" },
+///     quote! { fn example(a: i32) -> i64; },
+/// )?;
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgSqlFunction {
+    pg_extern: PgExtern,
+}
+
+impl PgSqlFunction {
+    pub fn new(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let declaration: FnDeclaration = syn::parse2(item)?;
+        let attrs = syn::parse::Parser::parse2(
+            Punctuated::<SqlFunctionAttribute, Token![,]>::parse_terminated,
+            attr,
+        )?;
+
+        let mut language = None;
+        let mut body = None;
+        let mut extern_attrs = Punctuated::new();
+        for attr in attrs {
+            match attr {
+                SqlFunctionAttribute::Language(value) => language = Some(value),
+                SqlFunctionAttribute::Body(value) => body = Some(value),
+                SqlFunctionAttribute::Extern(
+                    Attribute::Raw
+                    | Attribute::NoGuard
+                    | Attribute::Window
+                    | Attribute::Materialize
+                    | Attribute::Error(_),
+                ) => {
+                    return Err(syn::Error::new(
+                        Span::call_site(),
+                        "`raw`, `no_guard`, `window`, `materialize` and `error` only apply to Rust functions",
+                    ))
+                }
+                SqlFunctionAttribute::Extern(attr) => extern_attrs.push(attr),
+            }
+        }
+        let language = language.ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "`#[pg_sql_function]` requires a `language`",
+            )
+        })?;
+        let body = body.ok_or_else(|| {
+            syn::Error::new(Span::call_site(), "`#[pg_sql_function]` requires a `body`")
+        })?;
+
+        // Only the signature is used, to map the arguments and return type to SQL
+        let func = syn::ItemFn {
+            attrs: declaration.attrs,
+            vis: declaration.vis,
+            sig: declaration.sig,
+            block: Box::new(parse_quote! {{ unreachable!() }}),
+        };
+        Ok(Self {
+            pg_extern: PgExtern::new_sql_function(
+                PgxAttributes {
+                    attrs: extern_attrs,
+                },
+                func,
+                language,
+                body,
+            ),
+        })
+    }
+}
+
+impl ToTokens for PgSqlFunction {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.pg_extern.to_tokens(tokens)
+    }
+}
+
+/// A Rust function signature without a body, like `fn example(a: i32) -> i64;`
+struct FnDeclaration {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    sig: syn::Signature,
+}
+
+impl Parse for FnDeclaration {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let sig = input.parse()?;
+        let _semi: Token![;] = input.parse()?;
+        Ok(Self { attrs, vis, sig })
+    }
+}
+
+enum SqlFunctionAttribute {
+    Language(LitStr),
+    Body(LitStr),
+    Extern(Attribute),
+}
+
+impl Parse for SqlFunctionAttribute {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        let fork = input.fork();
+        let ident: syn::Ident = fork.parse()?;
+        match ident.to_string().as_str() {
+            "language" | "body" => {
+                let _ident: syn::Ident = input.parse()?;
+                let _eq: Token![=] = input.parse()?;
+                let value: LitStr = input.parse()?;
+                if ident == "language" {
+                    Ok(SqlFunctionAttribute::Language(value))
+                } else {
+                    Ok(SqlFunctionAttribute::Body(value))
+                }
+            }
+            _ => Ok(SqlFunctionAttribute::Extern(input.parse()?)),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::{doc_comment, parse_no_comment};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse::{Parse, ParseStream},
+    ItemFn,
+};
+
+/// A parsed `#[pg_trigger]` item.
+///
+/// It should be used with [`syn::parse::Parse`] functions.
+///
+/// Using [`quote::ToTokens`] will output the original function, an `extern "C"` wrapper which
+/// calls it with a `pgx::PgTrigger`, and the declaration for a
+/// `pgx::datum::sql_entity_graph::PgExternEntity` returning `trigger`.
+///
+/// ```rust
+/// use syn::{Macro, parse::Parse, parse_quote, parse};
+/// use quote::{quote, ToTokens};
+/// use pgx_utils::sql_entity_graph::PgTrigger;
+///
+/// # fn main() -> eyre::Result<()> {
+/// let parsed: PgTrigger = parse_quote! {
+///     fn example(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, PgHeapTupleError> {
+///         Ok(trigger.new())
+///     }
+/// };
+/// let sql_graph_entity_tokens = parsed.to_token_stream();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PgTrigger {
+    func: ItemFn,
+    doc: Option<String>,
+}
+
+impl PgTrigger {
+    pub fn new(func: ItemFn) -> Result<Self, syn::Error> {
+        if func.sig.inputs.len() != 1 {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[pg_trigger]` functions must take exactly one argument, a `&PgTrigger`",
+            ));
+        }
+        let doc = doc_comment(&func.attrs);
+        Ok(Self { func, doc })
+    }
+
+    /// Parse a `#[pg_trigger]` function, along with the `no_comment` option of the attribute.
+    pub fn with_attr(attr: TokenStream2, item: TokenStream2) -> Result<Self, syn::Error> {
+        let no_comment = parse_no_comment(attr)?;
+        let trigger = Self::new(syn::parse2(item)?)?;
+        Ok(Self {
+            doc: if no_comment { None } else { trigger.doc },
+            ..trigger
+        })
+    }
+}
+
+impl Parse for PgTrigger {
+    fn parse(input: ParseStream) -> Result<Self, syn::Error> {
+        Self::new(input.parse()?)
+    }
+}
+
+impl ToTokens for PgTrigger {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let func = &self.func;
+        let ident = &func.sig.ident;
+        let wrapper_ident = format_ident!("{}_wrapper", ident);
+        let finfo_ident = format_ident!("pg_finfo_{}_wrapper", ident);
+        let sql_graph_entity_fn_name = format_ident!("__pgx_internals_fn_{}", ident);
+        let doc = self.doc.iter();
+
+        tokens.append_all(quote! {
+            #func
+
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #finfo_ident() -> &'static pgx::pg_sys::Pg_finfo_record {
+                const V1_API: pgx::pg_sys::Pg_finfo_record = pgx::pg_sys::Pg_finfo_record { api_version: 1 };
+                &V1_API
+            }
+
+            #[pgx::pg_guard]
+            #[no_mangle]
+            #[doc(hidden)]
+            pub extern "C" fn #wrapper_ident(fcinfo: pgx::pg_sys::FunctionCallInfo) -> pgx::pg_sys::Datum {
+                unsafe { pgx::PgTrigger::call(fcinfo, #ident) }
+            }
+
+            #[no_mangle]
+            pub extern "C" fn #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
+                let submission = pgx::datum::sql_entity_graph::PgExternEntity {
+                    name: stringify!(#ident),
+                    unaliased_name: stringify!(#ident),
+                    schema: None,
+                    file: file!(),
+                    line: line!(),
+                    module_path: core::module_path!(),
+                    full_path: concat!(core::module_path!(), "::", stringify!(#ident)),
+                    extern_attrs: vec![],
+                    search_path: None,
+                    fn_args: vec![],
+                    fn_return: pgx::datum::sql_entity_graph::PgExternReturnEntity::Trigger,
+                    operator: None,
+                    cast: None,
+                    sql_body: None,
+                    overridden: None,
+                    procedure: false,
+                    doc: None#( .unwrap_or(Some(#doc)) )*,
+                };
+                pgx::datum::sql_entity_graph::SqlGraphEntity::Function(submission)
+            }
+        });
+    }
+}
@@ -1,3 +1,4 @@
+use super::doc_comment;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
 use syn::{
@@ -33,6 +34,7 @@ pub struct PostgresEnum {
     name: Ident,
     generics: Generics,
     variants: Punctuated<syn::Variant, Token![,]>,
+    doc: Option<String>,
 }
 
 impl PostgresEnum {
@@ -45,6 +47,7 @@ impl PostgresEnum {
             name,
             generics,
             variants,
+            doc: None,
         }
     }
 
@@ -55,18 +58,24 @@ impl PostgresEnum {
                 return Err(syn::Error::new(derive_input.ident.span(), "expected enum"))
             }
         };
-        Ok(Self::new(
-            derive_input.ident,
-            derive_input.generics,
-            data_enum.variants,
-        ))
+        Ok(Self {
+            doc: doc_comment(&derive_input.attrs),
+            ..Self::new(
+                derive_input.ident,
+                derive_input.generics,
+                data_enum.variants,
+            )
+        })
     }
 }
 
 impl Parse for PostgresEnum {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let parsed: ItemEnum = input.parse()?;
-        Ok(Self::new(parsed.ident, parsed.generics, parsed.variants))
+        Ok(Self {
+            doc: doc_comment(&parsed.attrs),
+            ..Self::new(parsed.ident, parsed.generics, parsed.variants)
+        })
     }
 }
 
@@ -81,6 +90,7 @@ impl ToTokens for PostgresEnum {
         let (_impl_generics, ty_generics, _where_clauses) = static_generics.split_for_impl();
 
         let variants = self.variants.iter();
+        let doc = self.doc.iter();
         let sql_graph_entity_fn_name =
             syn::Ident::new(&format!("__pgx_internals_enum_{}", name), Span::call_site());
 
@@ -88,10 +98,10 @@ impl ToTokens for PostgresEnum {
             #[no_mangle]
             pub extern "C" fn  #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
                 let mut mappings = Default::default();
-                <#name #ty_generics as pgx::datum::WithTypeIds>::register_with_refs(&mut mappings, stringify!(#name).to_string());
-                pgx::datum::WithSizedTypeIds::<#name #ty_generics>::register_sized_with_refs(&mut mappings, stringify!(#name).to_string());
-                pgx::datum::WithArrayTypeIds::<#name #ty_generics>::register_array_with_refs(&mut mappings, stringify!(#name).to_string());
-                pgx::datum::WithVarlenaTypeIds::<#name #ty_generics>::register_varlena_with_refs(&mut mappings, stringify!(#name).to_string());
+                <#name #ty_generics as pgx::datum::WithTypeIds>::register_with_refs(&mut mappings);
+                pgx::datum::WithSizedTypeIds::<#name #ty_generics>::register_sized_with_refs(&mut mappings);
+                pgx::datum::WithArrayTypeIds::<#name #ty_generics>::register_array_with_refs(&mut mappings);
+                pgx::datum::WithVarlenaTypeIds::<#name #ty_generics>::register_varlena_with_refs(&mut mappings);
 
                 let submission = pgx::datum::sql_entity_graph::PostgresEnumEntity {
                     name: stringify!(#name),
@@ -101,6 +111,7 @@ impl ToTokens for PostgresEnum {
                     full_path: core::any::type_name::<#name #ty_generics>(),
                     mappings,
                     variants: vec![ #(  stringify!(#variants)  ),* ],
+                    doc: None#( .unwrap_or(Some(#doc)) )*,
                 };
                 pgx::datum::sql_entity_graph::SqlGraphEntity::Enum(submission)
             }
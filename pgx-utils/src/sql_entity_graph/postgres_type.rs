@@ -1,3 +1,4 @@
+use super::doc_comment;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::{quote, ToTokens, TokenStreamExt};
 use std::{
@@ -37,6 +38,7 @@ pub struct PostgresType {
     generics: Generics,
     in_fn: Ident,
     out_fn: Ident,
+    doc: Option<String>,
 }
 
 impl PostgresType {
@@ -46,6 +48,7 @@ impl PostgresType {
             name,
             in_fn,
             out_fn,
+            doc: None,
         }
     }
 
@@ -67,12 +70,15 @@ impl PostgresType {
             &format!("{}_out", derive_input.ident).to_lowercase(),
             derive_input.ident.span(),
         );
-        Ok(Self::new(
-            derive_input.ident,
-            derive_input.generics,
-            funcname_in,
-            funcname_out,
-        ))
+        Ok(Self {
+            doc: doc_comment(&derive_input.attrs),
+            ..Self::new(
+                derive_input.ident,
+                derive_input.generics,
+                funcname_in,
+                funcname_out,
+            )
+        })
     }
 
     pub fn inventory_fn_name(&self) -> String {
@@ -101,12 +107,10 @@ impl Parse for PostgresType {
             &format!("{}_out", parsed.ident).to_lowercase(),
             parsed.ident.span(),
         );
-        Ok(Self::new(
-            parsed.ident,
-            parsed.generics,
-            funcname_in,
-            funcname_out,
-        ))
+        Ok(Self {
+            doc: doc_comment(&parsed.attrs),
+            ..Self::new(parsed.ident, parsed.generics, funcname_in, funcname_out)
+        })
     }
 }
 
@@ -121,6 +125,7 @@ impl ToTokens for PostgresType {
 
         let in_fn = &self.in_fn;
         let out_fn = &self.out_fn;
+        let doc = self.doc.iter();
 
         let sql_graph_entity_fn_name = syn::Ident::new(
             &format!("__pgx_internals_type_{}", self.name),
@@ -131,22 +136,10 @@ impl ToTokens for PostgresType {
             #[no_mangle]
             pub extern "C" fn  #sql_graph_entity_fn_name() -> pgx::datum::sql_entity_graph::SqlGraphEntity {
                 let mut mappings = Default::default();
-                <#name #ty_generics as pgx::datum::WithTypeIds>::register_with_refs(
-                    &mut mappings,
-                    stringify!(#name).to_string()
-                );
-                pgx::datum::WithSizedTypeIds::<#name #ty_generics>::register_sized_with_refs(
-                    &mut mappings,
-                    stringify!(#name).to_string()
-                );
-                pgx::datum::WithArrayTypeIds::<#name #ty_generics>::register_array_with_refs(
-                    &mut mappings,
-                    stringify!(#name).to_string()
-                );
-                pgx::datum::WithVarlenaTypeIds::<#name #ty_generics>::register_varlena_with_refs(
-                    &mut mappings,
-                    stringify!(#name).to_string()
-                );
+                <#name #ty_generics as pgx::datum::WithTypeIds>::register_with_refs(&mut mappings);
+                pgx::datum::WithSizedTypeIds::<#name #ty_generics>::register_sized_with_refs(&mut mappings);
+                pgx::datum::WithArrayTypeIds::<#name #ty_generics>::register_array_with_refs(&mut mappings);
+                pgx::datum::WithVarlenaTypeIds::<#name #ty_generics>::register_varlena_with_refs(&mut mappings);
                 let submission = pgx::datum::sql_entity_graph::PostgresTypeEntity {
                     name: stringify!(#name),
                     file: file!(),
@@ -167,7 +160,8 @@ impl ToTokens for PostgresType {
                         let mut path_items: Vec<_> = out_fn.split("::").collect();
                         let _ = path_items.pop(); // Drop the one we don't want.
                         path_items.join("::")
-                    }
+                    },
+                    doc: None#( .unwrap_or(Some(#doc)) )*,
                 };
                 pgx::datum::sql_entity_graph::SqlGraphEntity::Type(submission)
             }
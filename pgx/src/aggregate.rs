@@ -0,0 +1,205 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for declaring Postgres aggregates with a Rust trait
+//!
+//! Implement [`Aggregate`] for a type and annotate the `impl` block with `#[pg_aggregate]`.  The
+//! macro generates the support functions Postgres needs (`SFUNC`, `FINALFUNC`, `COMBINEFUNC`, etc)
+//! for whichever trait functions the `impl` provides, and `cargo pgx schema` generates the
+//! matching `CREATE AGGREGATE` statement.
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Copy, Clone, Default, PostgresType, Serialize, Deserialize)]
+//! pub struct IntegerAvgState {
+//!     sum: i32,
+//!     n: i32,
+//! }
+//!
+//! #[pg_aggregate]
+//! impl Aggregate for IntegerAvgState {
+//!     type Args = i32;
+//!     type Finalize = i32;
+//!     const NAME: &'static str = "demo_avg";
+//!     const INITIAL_CONDITION: Option<&'static str> = Some(r#"{ "sum": 0, "n": 0 }"#);
+//!
+//!     fn state(mut current: Self::State, arg: Self::Args) -> Self::State {
+//!         current.sum += arg;
+//!         current.n += 1;
+//!         current
+//!     }
+//!
+//!     fn finalize(current: Self::State, _direct_args: Self::OrderedSetArgs) -> Self::Finalize {
+//!         current.sum / current.n
+//!     }
+//! }
+//! ```
+//!
+//! Within the `impl`, the following may be omitted and are filled in by `#[pg_aggregate]`:
+//!
+//! * `type State` defaults to `Self`
+//! * `type OrderedSetArgs` defaults to `()`
+//! * `type Finalize` and `type MovingState` default to the `State` type
+//! * `const NAME` defaults to the lowercased name of the implementing type
+//!
+//! The `impl` may also provide `fn finalize`, `fn combine`, `fn serialize` and `fn deserialize`
+//! (for an [`Internal`](crate::Internal) state only), `fn moving_state` and
+//! `fn moving_state_inverse`, and `fn moving_finalize`.  `#[pg_aggregate]` moves these into
+//! implementations of their own traits, such as [`AggregateFinalize`], so using one which the
+//! `impl` doesn't provide is a compile error.
+//!
+//! `Args` (and `OrderedSetArgs`) may be a single type or a tuple of types, in which case the
+//! aggregate takes one SQL argument per tuple element.  Like `#[pg_extern]`, wrapping a type in
+//! `Option<T>` allows it to be `NULL`.  A state of `Option<T>` with no `INITIAL_CONDITION` starts
+//! out as `NULL`.
+
+/// The `PARALLEL` marking of an aggregate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ParallelOption {
+    Safe,
+    Restricted,
+    Unsafe,
+}
+
+impl std::fmt::Display for ParallelOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParallelOption::Safe => write!(f, "SAFE"),
+            ParallelOption::Restricted => write!(f, "RESTRICTED"),
+            ParallelOption::Unsafe => write!(f, "UNSAFE"),
+        }
+    }
+}
+
+/// The `FINALFUNC_MODIFY` (or `MFINALFUNC_MODIFY`) marking of an aggregate.  Requires Postgres 11
+/// or later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FinalizeModify {
+    ReadOnly,
+    Shareable,
+    ReadWrite,
+}
+
+impl std::fmt::Display for FinalizeModify {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FinalizeModify::ReadOnly => write!(f, "READ_ONLY"),
+            FinalizeModify::Shareable => write!(f, "SHAREABLE"),
+            FinalizeModify::ReadWrite => write!(f, "READ_WRITE"),
+        }
+    }
+}
+
+/// A Postgres aggregate.  See the [module documentation](crate::aggregate) for details.
+///
+/// Only [`Aggregate::state`] is required.  The `impl` block may also provide the functions of
+/// [`AggregateFinalize`], [`AggregateCombine`], [`AggregateSerialize`], [`AggregateMovingState`]
+/// and [`AggregateMovingFinalize`], which `#[pg_aggregate]` moves into implementations of those
+/// traits and turns into SQL support functions.
+pub trait Aggregate
+where
+    Self: Sized,
+{
+    /// The type of the aggregate's transition state (`STYPE`)
+    type State;
+
+    /// The aggregated argument(s)
+    type Args;
+
+    /// The direct argument(s) of an ordered-set aggregate, `()` otherwise
+    type OrderedSetArgs;
+
+    /// The return type of the aggregate
+    type Finalize;
+
+    /// The transition state type of the moving-aggregate mode (`MSTYPE`)
+    type MovingState;
+
+    /// The SQL name of the aggregate
+    const NAME: &'static str;
+
+    /// The `INITCOND` of the aggregate, in the text input format of `State`
+    const INITIAL_CONDITION: Option<&'static str> = None;
+
+    /// The `MINITCOND` of the aggregate, in the text input format of `MovingState`
+    const MOVING_INITIAL_CONDITION: Option<&'static str> = None;
+
+    /// The `PARALLEL` marking of the aggregate.  Postgres assumes `UNSAFE` if this is `None`
+    const PARALLEL: Option<ParallelOption> = None;
+
+    /// The `FINALFUNC_MODIFY` marking of the aggregate, describing whether
+    /// [`AggregateFinalize::finalize`] modifies its state.  Requires Postgres 11 or later
+    const FINALIZE_MODIFY: Option<FinalizeModify> = None;
+
+    /// The `MFINALFUNC_MODIFY` marking of the aggregate, describing whether
+    /// [`AggregateMovingFinalize::moving_finalize`] modifies its state.  Requires Postgres 11 or later
+    const MOVING_FINALIZE_MODIFY: Option<FinalizeModify> = None;
+
+    /// The `SORTOP` of the aggregate, such as `"<"` for a `min()`-like aggregate
+    const SORT_OPERATOR: Option<&'static str> = None;
+
+    /// Is this an ordered-set aggregate, with `OrderedSetArgs` as its direct arguments and `Args`
+    /// given in its `WITHIN GROUP (ORDER BY ...)` clause?  Note that Postgres does not sort the
+    /// input of ordered-set aggregates; that is left to the aggregate itself.
+    const ORDERED_SET: bool = false;
+
+    /// Is this a hypothetical-set aggregate?  Implies `ORDERED_SET`.  The finalize function is
+    /// additionally called with (always `NULL`) values for each of `Args` (`FINALFUNC_EXTRA`).
+    const HYPOTHETICAL: bool = false;
+
+    /// Advance `current` by one input row (`SFUNC`)
+    fn state(current: Self::State, args: Self::Args) -> Self::State;
+}
+
+/// The `FINALFUNC` of an [`Aggregate`].
+///
+/// `#[pg_aggregate]` implements this from the `fn finalize` of the `impl Aggregate` block.
+pub trait AggregateFinalize: Aggregate {
+    /// Compute the aggregate's result (`FINALFUNC`)
+    fn finalize(current: Self::State, direct_args: Self::OrderedSetArgs) -> Self::Finalize;
+}
+
+/// The `COMBINEFUNC` of an [`Aggregate`], for parallel aggregation.
+///
+/// `#[pg_aggregate]` implements this from the `fn combine` of the `impl Aggregate` block.
+pub trait AggregateCombine: Aggregate {
+    /// Combine two partial states for parallel aggregation (`COMBINEFUNC`)
+    fn combine(current: Self::State, other: Self::State) -> Self::State;
+}
+
+/// The `SERIALFUNC` and `DESERIALFUNC` of an [`Aggregate`] whose state is [`Internal`], for
+/// parallel aggregation.
+///
+/// `#[pg_aggregate]` implements this from the `fn serialize` and `fn deserialize` of the
+/// `impl Aggregate` block.
+///
+/// [`Internal`]: crate::Internal
+pub trait AggregateSerialize: Aggregate {
+    /// Serialize an `internal` state for parallel aggregation (`SERIALFUNC`)
+    fn serialize(current: Self::State) -> Vec<u8>;
+
+    /// Deserialize a state serialized by [`AggregateSerialize::serialize`] (`DESERIALFUNC`)
+    fn deserialize(buf: &[u8]) -> Self::State;
+}
+
+/// The `MSFUNC` and `MINVFUNC` of an [`Aggregate`], which make up its moving-aggregate mode.
+///
+/// `#[pg_aggregate]` implements this from the `fn moving_state` and `fn moving_state_inverse` of
+/// the `impl Aggregate` block.
+pub trait AggregateMovingState: Aggregate {
+    /// Advance `current` by one input row in moving-aggregate mode (`MSFUNC`)
+    fn moving_state(current: Self::MovingState, args: Self::Args) -> Self::MovingState;
+
+    /// Remove one input row from `current` in moving-aggregate mode (`MINVFUNC`)
+    fn moving_state_inverse(current: Self::MovingState, args: Self::Args) -> Self::MovingState;
+}
+
+/// The `MFINALFUNC` of an [`Aggregate`] with a moving-aggregate mode.
+///
+/// `#[pg_aggregate]` implements this from the `fn moving_finalize` of the `impl Aggregate` block.
+pub trait AggregateMovingFinalize: AggregateMovingState {
+    /// Compute the aggregate's result in moving-aggregate mode (`MFINALFUNC`)
+    fn moving_finalize(current: Self::MovingState) -> Self::Finalize;
+}
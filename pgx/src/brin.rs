@@ -0,0 +1,214 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for declaring BRIN operator classes with a Rust trait
+//!
+//! Implement [`BrinOpClass`] for a type and annotate the `impl` block with `#[pg_brin_opclass]`.
+//! The macro generates the `opcInfo`, `addValue`, `consistent` and `union` support functions,
+//! and `cargo pgx schema` generates the matching `CREATE OPERATOR CLASS ... USING brin`
+//! statement.
+//!
+//! Each block range of a BRIN index is summarized by a single [`BrinOpClass::Summary`] value,
+//! such as the smallest and largest value in the range ("minmax"), or a value which includes all
+//! of them ("inclusion").  `opcInfo` is generated from the type of the summary, and `NULL`s are
+//! tracked by the support functions, so the trait only ever sees actual values.
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(PostgresType, Serialize, Deserialize)]
+//! pub struct Reading {
+//!     at: i64,
+//!     value: f64,
+//! }
+//!
+//! #[derive(PostgresType, Serialize, Deserialize)]
+//! pub struct Period {
+//!     start: i64,
+//!     end: i64,
+//! }
+//!
+//! #[pg_operator(immutable)]
+//! #[opname(&&)]
+//! fn reading_during(left: Reading, right: Period) -> bool {
+//!     right.start <= left.at && left.at <= right.end
+//! }
+//!
+//! #[pg_brin_opclass]
+//! impl BrinOpClass for Reading {
+//!     type Summary = Period;
+//!     type Query = Period;
+//!     const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+//!
+//!     fn add_value(summary: Option<&Period>, value: Reading) -> Option<Period> {
+//!         match summary {
+//!             Some(period) if period.start <= value.at && value.at <= period.end => None,
+//!             Some(period) => Some(Period {
+//!                 start: period.start.min(value.at),
+//!                 end: period.end.max(value.at),
+//!             }),
+//!             None => Some(Period { start: value.at, end: value.at }),
+//!         }
+//!     }
+//!
+//!     fn consistent(summary: &Period, query: &Period, _strategy: u16) -> bool {
+//!         summary.start <= query.end && query.start <= summary.end
+//!     }
+//!
+//!     fn union(a: &Period, b: &Period) -> Period {
+//!         Period { start: a.start.min(b.start), end: a.end.max(b.end) }
+//!     }
+//! }
+//! ```
+//!
+//! Within the `impl`, the following may be omitted and are filled in by `#[pg_brin_opclass]`:
+//!
+//! * `type Query` defaults to `Self`
+//! * `const NAME` defaults to the lowercased name of the implementing type, suffixed with
+//!   `_brin_ops`
+use crate::{pg_sys, FromDatum, Internal, IntoDatum};
+
+/// A BRIN operator class.  See the [module documentation](crate::brin) for details.
+pub trait BrinOpClass
+where
+    Self: FromDatum + IntoDatum + Sized,
+{
+    /// The summary of the values of a block range, stored in the index (`STORAGE`)
+    type Summary: FromDatum + IntoDatum;
+
+    /// The right-hand argument of the operator class' operators
+    type Query: FromDatum;
+
+    /// The SQL name of the operator class
+    const NAME: &'static str;
+
+    /// Is this the default BRIN operator class of the type?
+    const DEFAULT: bool = true;
+
+    /// The operators of the operator class, by strategy number, such as `&[(3, "&&")]`
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    /// Add `value` to the `summary` of its block range, which is `None` until the range has a
+    /// value (`addValue`).  Return the new summary, or `None` if `summary` already covers `value`.
+    fn add_value(summary: Option<&Self::Summary>, value: Self) -> Option<Self::Summary>;
+
+    /// Can a block range with `summary` contain values matching `query` with the operator of
+    /// `strategy` (`consistent`)?  Matching rows are always rechecked.
+    fn consistent(summary: &Self::Summary, query: &Self::Query, strategy: u16) -> bool;
+
+    /// The summary of two block ranges together (`union`)
+    fn union(a: &Self::Summary, b: &Self::Summary) -> Self::Summary;
+}
+
+unsafe fn brin_values(column: &mut Internal) -> &mut pg_sys::BrinValues {
+    column
+        .get_mut::<pg_sys::BrinValues>()
+        .expect("BRIN support function called with a NULL BrinValues")
+}
+
+unsafe fn summary<T: BrinOpClass>(column: &pg_sys::BrinValues) -> Option<T::Summary> {
+    if column.bv_allnulls {
+        None
+    } else {
+        T::Summary::from_datum(*column.bv_values, false, pg_sys::InvalidOid)
+    }
+}
+
+unsafe fn set_summary<T: BrinOpClass>(column: &mut pg_sys::BrinValues, summary: T::Summary) {
+    *column.bv_values = summary.into_datum().expect("BRIN summaries cannot be NULL");
+    column.bv_allnulls = false;
+}
+
+/// The body of the generated `opcInfo` support function of `T`
+pub fn brin_opcinfo<T: BrinOpClass>(_typoid: Internal) -> Internal {
+    unsafe {
+        let info = pg_sys::palloc0(
+            std::mem::size_of::<pg_sys::BrinOpcInfo>()
+                + std::mem::size_of::<*mut pg_sys::TypeCacheEntry>(),
+        ) as *mut pg_sys::BrinOpcInfo;
+        (*info).oi_nstored = 1;
+        #[cfg(feature = "pg14")]
+        {
+            (*info).oi_regular_nulls = true;
+        }
+        (*info).oi_typcache.as_mut_slice(1)[0] =
+            pg_sys::lookup_type_cache(T::Summary::type_oid(), 0);
+        Internal::from(Some(info as pg_sys::Datum))
+    }
+}
+
+/// The body of the generated `addValue` support function of `T`
+pub fn brin_add_value<T: BrinOpClass>(
+    mut column: Internal,
+    value: Internal,
+    is_null: Internal,
+) -> bool {
+    unsafe {
+        let column = brin_values(&mut column);
+        if is_null.unwrap().unwrap_or(0) != 0 {
+            let modified = !column.bv_hasnulls;
+            column.bv_hasnulls = true;
+            return modified;
+        }
+
+        let value = T::from_datum(value.unwrap().unwrap_or(0), false, pg_sys::InvalidOid)
+            .expect("BRIN addValue function called with a NULL value");
+        let current = summary::<T>(column);
+        match T::add_value(current.as_ref(), value) {
+            Some(summary) => {
+                set_summary::<T>(column, summary);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The body of the generated `consistent` support function of `T`
+pub fn brin_consistent<T: BrinOpClass>(mut column: Internal, key: Internal) -> bool {
+    unsafe {
+        let column = brin_values(&mut column);
+        let key = key
+            .get::<pg_sys::ScanKeyData>()
+            .expect("BRIN consistent function called with a NULL ScanKey");
+
+        // `IS NULL` and `IS NOT NULL` searches
+        if key.sk_flags & pg_sys::SK_ISNULL as i32 != 0 {
+            if key.sk_flags & pg_sys::SK_SEARCHNULL as i32 != 0 {
+                return column.bv_allnulls || column.bv_hasnulls;
+            }
+            if key.sk_flags & pg_sys::SK_SEARCHNOTNULL as i32 != 0 {
+                return !column.bv_allnulls;
+            }
+            return false;
+        }
+
+        match summary::<T>(column) {
+            Some(summary) => {
+                let query = T::Query::from_datum(key.sk_argument, false, pg_sys::InvalidOid)
+                    .expect("BRIN consistent function called with a NULL query");
+                T::consistent(&summary, &query, key.sk_strategy)
+            }
+            // only NULLs in the range
+            None => false,
+        }
+    }
+}
+
+/// The body of the generated `union` support function of `T`
+pub fn brin_union<T: BrinOpClass>(mut a: Internal, mut b: Internal) -> bool {
+    unsafe {
+        let a = brin_values(&mut a);
+        let b = brin_values(&mut b);
+        a.bv_hasnulls |= b.bv_hasnulls;
+
+        let union = match (summary::<T>(a), summary::<T>(b)) {
+            (_, None) => return true,
+            (None, Some(b)) => b,
+            (Some(a), Some(b)) => T::union(&a, &b),
+        };
+        set_summary::<T>(a, union);
+        true
+    }
+}
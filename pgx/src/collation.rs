@@ -0,0 +1,158 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helper types and functions for working with Postgres collations
+//!
+//! Postgres passes the input collation of a function call in `fcinfo->fncollation`.  A
+//! `#[pg_extern]` function can get at it by declaring a trailing `fcinfo: pg_sys::FunctionCallInfo`
+//! argument and calling [`pg_collation`]:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! #[pg_extern(collatable)]
+//! fn my_lower(input: &str, fcinfo: pg_sys::FunctionCallInfo) -> String {
+//!     unsafe { pg_collation(fcinfo) }.to_lowercase(input)
+//! }
+//! ```
+
+use crate::{
+    ereport, pg_sys, server_to_utf8, utf8_to_server, void_mut_ptr, PgLogLevel, PgSqlErrorCode,
+};
+use std::cmp::Ordering;
+use std::ffi::CStr;
+
+/// A Postgres collation, identified by its `pg_collation` oid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Collation(pg_sys::Oid);
+
+impl Collation {
+    /// The database's default collation (`DEFAULT_COLLATION_OID`)
+    pub const DEFAULT: Collation = Collation(pg_sys::DEFAULT_COLLATION_OID);
+
+    /// The "C" collation (`C_COLLATION_OID`)
+    pub const C: Collation = Collation(pg_sys::C_COLLATION_OID);
+
+    /// The "POSIX" collation (`POSIX_COLLATION_OID`)
+    pub const POSIX: Collation = Collation(pg_sys::POSIX_COLLATION_OID);
+
+    /// Wrap the collation with the specified oid.  Returns `None` if `oid` is `InvalidOid`
+    pub fn from_oid(oid: pg_sys::Oid) -> Option<Self> {
+        if oid == pg_sys::InvalidOid {
+            None
+        } else {
+            Some(Collation(oid))
+        }
+    }
+
+    /// The input collation of the function call described by `fcinfo`, if Postgres was able
+    /// to determine one
+    ///
+    /// # Safety
+    ///
+    /// `fcinfo` must be the valid `FunctionCallInfo` Postgres called the function with.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Option<Self> {
+        Collation::from_oid(crate::pg_get_collation(fcinfo))
+    }
+
+    /// The `pg_collation` oid of this collation
+    pub fn oid(&self) -> pg_sys::Oid {
+        self.0
+    }
+
+    /// The name of this collation, as found in `pg_collation.collname`
+    pub fn name(&self) -> Option<String> {
+        let name = unsafe { pg_sys::get_collation_name(self.0) };
+        if name.is_null() {
+            None
+        } else {
+            Some(unsafe { palloc_cstr_to_string(name) })
+        }
+    }
+
+    /// Is this a deterministic collation?  Non-deterministic collations were introduced in
+    /// Postgres 12, so this is always `true` on earlier versions
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    pub fn is_deterministic(&self) -> bool {
+        unsafe { pg_sys::get_collation_isdeterministic(self.0) }
+    }
+
+    /// Is this a deterministic collation?  Non-deterministic collations were introduced in
+    /// Postgres 12, so this is always `true` on earlier versions
+    #[cfg(any(feature = "pg10", feature = "pg11"))]
+    pub fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    /// Compare two strings using this collation, as Postgres' `text` comparison operators do
+    pub fn cmp_str(&self, a: &str, b: &str) -> Ordering {
+        let a = utf8_to_server(a);
+        let b = utf8_to_server(b);
+        let result = unsafe {
+            pg_sys::varstr_cmp(
+                a.as_ptr() as _,
+                a.len() as i32,
+                b.as_ptr() as _,
+                b.len() as i32,
+                self.0,
+            )
+        };
+        result.cmp(&0)
+    }
+
+    /// Lowercase a string according to the rules of this collation, as Postgres' `lower()` does
+    pub fn to_lowercase(&self, s: &str) -> String {
+        let s = utf8_to_server(s);
+        unsafe { palloc_cstr_to_string(pg_sys::str_tolower(s.as_ptr() as _, s.len(), self.0)) }
+    }
+
+    /// Uppercase a string according to the rules of this collation, as Postgres' `upper()` does
+    pub fn to_uppercase(&self, s: &str) -> String {
+        let s = utf8_to_server(s);
+        unsafe { palloc_cstr_to_string(pg_sys::str_toupper(s.as_ptr() as _, s.len(), self.0)) }
+    }
+}
+
+impl Default for Collation {
+    fn default() -> Self {
+        Collation::DEFAULT
+    }
+}
+
+/// The input collation of the function call described by `fcinfo`.
+///
+/// This raises the same ERROR Postgres does for its own string functions if the collation
+/// could not be determined (ie, none of the function's arguments are of a collatable type or
+/// their collations conflict).
+///
+/// # Safety
+///
+/// `fcinfo` must be the valid `FunctionCallInfo` Postgres called the function with.
+pub unsafe fn pg_collation(fcinfo: pg_sys::FunctionCallInfo) -> Collation {
+    match Collation::from_fcinfo(fcinfo) {
+        Some(collation) => collation,
+        None => {
+            ereport(
+                PgLogLevel::ERROR,
+                PgSqlErrorCode::ERRCODE_INDETERMINATE_COLLATION,
+                "could not determine which collation to use (use the COLLATE clause to set the collation explicitly)",
+                file!(),
+                line!(),
+                column!(),
+            );
+            unreachable!()
+        }
+    }
+}
+
+/// Take ownership of a palloc'd string in the server's encoding
+#[inline]
+unsafe fn palloc_cstr_to_string(cstr: *mut std::os::raw::c_char) -> String {
+    let bytes = CStr::from_ptr(cstr).to_bytes();
+    let result = match server_to_utf8(bytes) {
+        Ok(s) => s.into_owned(),
+        Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+    };
+    pg_sys::pfree(cstr as void_mut_ptr);
+    result
+}
@@ -4,8 +4,8 @@
 //! for converting a pg_sys::Datum and a corresponding "is_null" bool into a typed Option
 
 use crate::{
-    pg_sys, text_to_rust_str_unchecked, varlena_to_byte_slice, AllocatedByPostgres, PgBox,
-    PgMemoryContexts,
+    pg_sys, text_to_rust_str, varlena_to_byte_slice, AllocatedByPostgres, PgBox, PgMemoryContexts,
+    PgOid,
 };
 use std::ffi::CStr;
 
@@ -140,6 +140,19 @@ impl FromDatum for u32 {
     }
 }
 
+/// for oid
+impl FromDatum for PgOid {
+    const NEEDS_TYPID: bool = false;
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<PgOid> {
+        if is_null {
+            None
+        } else {
+            Some(PgOid::from(datum as pg_sys::Oid))
+        }
+    }
+}
+
 /// for bigint
 impl FromDatum for i64 {
     const NEEDS_TYPID: bool = false;
@@ -190,7 +203,7 @@ impl<'a> FromDatum for &'a str {
             panic!("a varlena Datum was flagged as non-null but the datum is zero");
         } else {
             let varlena = pg_sys::pg_detoast_datum_packed(datum as *mut pg_sys::varlena);
-            Some(text_to_rust_str_unchecked(varlena))
+            Some(text_to_rust_str(varlena))
         }
     }
 
@@ -216,7 +229,7 @@ impl<'a> FromDatum for &'a str {
                 let varlena = pg_sys::pg_detoast_datum_packed(detoasted);
 
                 // and now we return it as a &str
-                Some(text_to_rust_str_unchecked(varlena))
+                Some(text_to_rust_str(varlena))
             })
         }
     }
@@ -15,7 +15,9 @@ mod into;
 mod item_pointer_data;
 mod json;
 mod numeric;
+mod pg_string;
 pub mod sql_entity_graph;
+mod sql_translatable;
 mod time;
 mod time_stamp;
 mod time_stamp_with_timezone;
@@ -39,7 +41,8 @@ pub use item_pointer_data::*;
 pub use json::*;
 pub use numeric::*;
 use once_cell::sync::Lazy;
-use sql_entity_graph::RustSqlMapping;
+pub use pg_string::*;
+pub use sql_translatable::*;
 use std::any::TypeId;
 pub use time_stamp::*;
 pub use time_stamp_with_timezone::*;
@@ -66,10 +69,9 @@ pub trait PostgresType {}
 /// struct Treat<'a> { best_part: &'a str, };
 ///
 /// let mut mappings = Default::default();
-/// let treat_string = stringify!(Treat).to_string();
-/// <Treat<'static> as WithTypeIds>::register_with_refs(&mut mappings, treat_string.clone());
+/// <Treat<'static> as WithTypeIds>::register_with_refs(&mut mappings);
 ///
-/// assert!(mappings.iter().any(|x| x.id == core::any::TypeId::of::<Treat<'static>>()));
+/// assert!(mappings.contains(&core::any::TypeId::of::<Treat<'static>>()));
 /// ```
 ///
 /// This trait uses the fact that inherent implementations are a higher priority than trait
@@ -85,79 +87,63 @@ pub trait WithTypeIds {
     const OPTION_ARRAY_ID: Lazy<Option<TypeId>>;
     const VARLENA_ID: Lazy<Option<TypeId>>;
 
-    fn register_with_refs(map: &mut std::collections::HashSet<RustSqlMapping>, single_sql: String)
+    fn register_with_refs(map: &mut std::collections::HashSet<TypeId>)
     where
         Self: 'static,
     {
-        Self::register(map, single_sql.clone());
-        <&Self as WithTypeIds>::register(map, single_sql.clone());
-        <&mut Self as WithTypeIds>::register(map, single_sql);
+        Self::register(map);
+        <&Self as WithTypeIds>::register(map);
+        <&mut Self as WithTypeIds>::register(map);
     }
 
-    fn register_sized_with_refs(
-        _map: &mut std::collections::HashSet<RustSqlMapping>,
-        _single_sql: String,
-    ) where
+    fn register_sized_with_refs(_map: &mut std::collections::HashSet<TypeId>)
+    where
         Self: 'static,
     {
         ()
     }
 
-    fn register_sized(_map: &mut std::collections::HashSet<RustSqlMapping>, _single_sql: String)
+    fn register_sized(_map: &mut std::collections::HashSet<TypeId>)
     where
         Self: 'static,
     {
         ()
     }
 
-    fn register_varlena_with_refs(
-        _map: &mut std::collections::HashSet<RustSqlMapping>,
-        _single_sql: String,
-    ) where
+    fn register_varlena_with_refs(_map: &mut std::collections::HashSet<TypeId>)
+    where
         Self: 'static,
     {
         ()
     }
 
-    fn register_varlena(_map: &mut std::collections::HashSet<RustSqlMapping>, _single_sql: String)
+    fn register_varlena(_map: &mut std::collections::HashSet<TypeId>)
     where
         Self: 'static,
     {
         ()
     }
 
-    fn register_array_with_refs(
-        _map: &mut std::collections::HashSet<RustSqlMapping>,
-        _single_sql: String,
-    ) where
+    fn register_array_with_refs(_map: &mut std::collections::HashSet<TypeId>)
+    where
         Self: 'static,
     {
         ()
     }
 
-    fn register_array(_map: &mut std::collections::HashSet<RustSqlMapping>, _single_sql: String)
+    fn register_array(_map: &mut std::collections::HashSet<TypeId>)
     where
         Self: 'static,
     {
         ()
     }
 
-    fn register(set: &mut std::collections::HashSet<RustSqlMapping>, single_sql: String)
+    fn register(set: &mut std::collections::HashSet<TypeId>)
     where
         Self: 'static,
     {
         let rust = core::any::type_name::<Self>();
-        assert_eq!(
-            set.insert(RustSqlMapping {
-                sql: single_sql.clone(),
-                rust: rust.to_string(),
-                id: *Self::ITEM_ID,
-            }),
-            true,
-            "Cannot set mapping of `{}` twice, was already `{}`.",
-            rust,
-            single_sql,
-        );
+        assert!(set.insert(*Self::ITEM_ID), "Cannot map `{}` twice.", rust);
     }
 }
 
@@ -188,14 +174,10 @@ impl<T: 'static + ?Sized> WithTypeIds for T {
 /// pub struct Treat<'a> { best_part: &'a str, };
 ///
 /// let mut mappings = Default::default();
-/// let treat_string = stringify!(Treat).to_string();
 ///
-/// pgx::datum::WithSizedTypeIds::<Treat<'static>>::register_sized_with_refs(
-///     &mut mappings,
-///     treat_string.clone()
-/// );
+/// pgx::datum::WithSizedTypeIds::<Treat<'static>>::register_sized_with_refs(&mut mappings);
 ///
-/// assert!(mappings.iter().any(|x| x.id == core::any::TypeId::of::<Option<Treat<'static>>>()));
+/// assert!(mappings.contains(&core::any::TypeId::of::<Option<Treat<'static>>>()));
 /// ```
 ///
 /// This trait uses the fact that inherent implementations are a higher priority than trait
@@ -213,99 +195,41 @@ impl<T: 'static> WithSizedTypeIds<T> {
     pub const OPTION_VEC_OPTION_ID: Lazy<Option<TypeId>> =
         Lazy::new(|| Some(TypeId::of::<Option<Vec<Option<T>>>>()));
 
-    pub fn register_sized_with_refs(
-        map: &mut std::collections::HashSet<RustSqlMapping>,
-        single_sql: String,
-    ) where
+    pub fn register_sized_with_refs(map: &mut std::collections::HashSet<TypeId>)
+    where
         Self: 'static,
     {
-        WithSizedTypeIds::<T>::register_sized(map, single_sql.clone());
-        WithSizedTypeIds::<&T>::register_sized(map, single_sql.clone());
-        WithSizedTypeIds::<&mut T>::register_sized(map, single_sql);
+        WithSizedTypeIds::<T>::register_sized(map);
+        WithSizedTypeIds::<&T>::register_sized(map);
+        WithSizedTypeIds::<&mut T>::register_sized(map);
     }
 
-    pub fn register_sized(map: &mut std::collections::HashSet<RustSqlMapping>, single_sql: String) {
-        let set_sql = format!("{}[]", single_sql);
-
+    pub fn register_sized(map: &mut std::collections::HashSet<TypeId>) {
         if let Some(id) = *WithSizedTypeIds::<T>::PG_BOX_ID {
             let rust = core::any::type_name::<crate::PgBox<T>>().to_string();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: single_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
 
         if let Some(id) = *WithSizedTypeIds::<T>::OPTION_ID {
             let rust = core::any::type_name::<Option<T>>().to_string();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: single_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
 
         if let Some(id) = *WithSizedTypeIds::<T>::VEC_ID {
             let rust = core::any::type_name::<T>().to_string();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: set_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
         if let Some(id) = *WithSizedTypeIds::<T>::VEC_OPTION_ID {
             let rust = core::any::type_name::<Vec<Option<T>>>();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: set_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
         if let Some(id) = *WithSizedTypeIds::<T>::OPTION_VEC_ID {
             let rust = core::any::type_name::<Option<Vec<T>>>();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: set_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
         if let Some(id) = *WithSizedTypeIds::<T>::OPTION_VEC_OPTION_ID {
             let rust = core::any::type_name::<Option<Vec<Option<T>>>>();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: set_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
     }
 }
@@ -325,14 +249,10 @@ impl<T: 'static> WithSizedTypeIds<T> {
 /// pub struct Treat { best_part: String, };
 ///
 /// let mut mappings = Default::default();
-/// let treat_string = stringify!(Treat).to_string();
 ///
-/// pgx::datum::WithArrayTypeIds::<Treat>::register_array_with_refs(
-///     &mut mappings,
-///     treat_string.clone()
-/// );
+/// pgx::datum::WithArrayTypeIds::<Treat>::register_array_with_refs(&mut mappings);
 ///
-/// assert!(mappings.iter().any(|x| x.id == core::any::TypeId::of::<Array<Treat>>()));
+/// assert!(mappings.contains(&core::any::TypeId::of::<Array<Treat>>()));
 /// ```
 ///
 /// This trait uses the fact that inherent implementations are a higher priority than trait
@@ -344,45 +264,23 @@ impl<T: FromDatum + 'static> WithArrayTypeIds<T> {
     pub const OPTION_ARRAY_ID: Lazy<Option<TypeId>> =
         Lazy::new(|| Some(TypeId::of::<Option<Array<T>>>()));
 
-    pub fn register_array_with_refs(
-        map: &mut std::collections::HashSet<RustSqlMapping>,
-        single_sql: String,
-    ) where
+    pub fn register_array_with_refs(map: &mut std::collections::HashSet<TypeId>)
+    where
         Self: 'static,
     {
-        WithArrayTypeIds::<T>::register_array(map, single_sql.clone());
-        WithArrayTypeIds::<&T>::register_array(map, single_sql.clone());
-        WithArrayTypeIds::<&mut T>::register_array(map, single_sql);
+        WithArrayTypeIds::<T>::register_array(map);
+        WithArrayTypeIds::<&T>::register_array(map);
+        WithArrayTypeIds::<&mut T>::register_array(map);
     }
 
-    pub fn register_array(map: &mut std::collections::HashSet<RustSqlMapping>, single_sql: String) {
-        let set_sql = format!("{}[]", single_sql);
-
+    pub fn register_array(map: &mut std::collections::HashSet<TypeId>) {
         if let Some(id) = *WithArrayTypeIds::<T>::ARRAY_ID {
             let rust = core::any::type_name::<Array<T>>().to_string();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: set_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
         if let Some(id) = *WithArrayTypeIds::<T>::OPTION_ARRAY_ID {
             let rust = core::any::type_name::<Option<Array<T>>>().to_string();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: set_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
     }
 }
@@ -402,14 +300,10 @@ impl<T: FromDatum + 'static> WithArrayTypeIds<T> {
 /// pub struct Treat<'a> { best_part: &'a str, };
 ///
 /// let mut mappings = Default::default();
-/// let treat_string = stringify!(Treat).to_string();
 ///
-/// pgx::datum::WithVarlenaTypeIds::<Treat<'static>>::register_varlena_with_refs(
-///     &mut mappings,
-///     treat_string.clone()
-/// );
+/// pgx::datum::WithVarlenaTypeIds::<Treat<'static>>::register_varlena_with_refs(&mut mappings);
 ///
-/// assert!(mappings.iter().any(|x| x.id == core::any::TypeId::of::<PgVarlena<Treat<'static>>>()));
+/// assert!(mappings.contains(&core::any::TypeId::of::<PgVarlena<Treat<'static>>>()));
 /// ```
 ///
 /// This trait uses the fact that inherent implementations are a higher priority than trait
@@ -419,33 +313,19 @@ pub struct WithVarlenaTypeIds<T>(pub core::marker::PhantomData<T>);
 impl<T: Copy + 'static> WithVarlenaTypeIds<T> {
     pub const VARLENA_ID: Lazy<Option<TypeId>> = Lazy::new(|| Some(TypeId::of::<PgVarlena<T>>()));
 
-    pub fn register_varlena_with_refs(
-        map: &mut std::collections::HashSet<RustSqlMapping>,
-        single_sql: String,
-    ) where
+    pub fn register_varlena_with_refs(map: &mut std::collections::HashSet<TypeId>)
+    where
         Self: 'static,
     {
-        WithVarlenaTypeIds::<T>::register_varlena(map, single_sql.clone());
-        WithVarlenaTypeIds::<&T>::register_varlena(map, single_sql.clone());
-        WithVarlenaTypeIds::<&mut T>::register_varlena(map, single_sql);
+        WithVarlenaTypeIds::<T>::register_varlena(map);
+        WithVarlenaTypeIds::<&T>::register_varlena(map);
+        WithVarlenaTypeIds::<&mut T>::register_varlena(map);
     }
 
-    pub fn register_varlena(
-        map: &mut std::collections::HashSet<RustSqlMapping>,
-        single_sql: String,
-    ) {
+    pub fn register_varlena(map: &mut std::collections::HashSet<TypeId>) {
         if let Some(id) = *WithVarlenaTypeIds::<T>::VARLENA_ID {
             let rust = core::any::type_name::<PgVarlena<T>>();
-            assert_eq!(
-                map.insert(RustSqlMapping {
-                    sql: single_sql.clone(),
-                    rust: rust.to_string(),
-                    id: id,
-                }),
-                true,
-                "Cannot map `{}` twice.",
-                rust,
-            );
+            assert!(map.insert(id), "Cannot map `{}` twice.", rust);
         }
     }
 }
@@ -0,0 +1,118 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+use crate::{pg_sys, server_to_utf8, varlena_to_byte_slice, FromDatum, IntoDatum};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::ops::Deref;
+use std::str::Utf8Error;
+
+/// A Postgres `text` (or `varchar`) value as a Rust string, regardless of the server's encoding.
+///
+/// When the server encoding is UTF-8 (or the value is plain ASCII) this borrows the underlying
+/// Datum, just like `&str`.  Otherwise the value is converted to UTF-8 and owned.
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// #[pg_extern]
+/// fn shout(input: PgString) -> String {
+///     input.to_uppercase()
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PgString<'a>(Cow<'a, str>);
+
+impl<'a> PgString<'a> {
+    /// Convert a Postgres `text *` into a [`PgString`], returning an error if the value is not
+    /// valid UTF-8 after conversion from the server's encoding.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe because it blindly assumes the provided varlena pointer is non-null
+    /// and already detoasted
+    pub unsafe fn try_from_text_p(varlena: *const pg_sys::varlena) -> Result<Self, Utf8Error> {
+        PgString::try_from(varlena_to_byte_slice(varlena))
+    }
+
+    /// Does this [`PgString`] borrow its Datum, rather than owning a converted copy?
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_owned(self) -> String {
+        self.0.into_owned()
+    }
+}
+
+/// Checked conversion from bytes in the server's encoding
+impl<'a> TryFrom<&'a [u8]> for PgString<'a> {
+    type Error = Utf8Error;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        server_to_utf8(bytes).map(PgString)
+    }
+}
+
+impl<'a> From<&'a str> for PgString<'a> {
+    fn from(s: &'a str) -> Self {
+        PgString(Cow::Borrowed(s))
+    }
+}
+
+impl From<String> for PgString<'_> {
+    fn from(s: String) -> Self {
+        PgString(Cow::Owned(s))
+    }
+}
+
+impl Deref for PgString<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<str> for PgString<'_> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for PgString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// for text, varchar
+impl<'a> FromDatum for PgString<'a> {
+    const NEEDS_TYPID: bool = false;
+    #[inline]
+    unsafe fn from_datum(datum: pg_sys::Datum, is_null: bool, _: pg_sys::Oid) -> Option<Self> {
+        if is_null {
+            None
+        } else if datum == 0 {
+            panic!("a varlena Datum was flagged as non-null but the datum is zero");
+        } else {
+            let varlena = pg_sys::pg_detoast_datum_packed(datum as *mut pg_sys::varlena);
+            Some(PgString::try_from_text_p(varlena).expect("text is not valid UTF-8"))
+        }
+    }
+}
+
+impl IntoDatum for PgString<'_> {
+    #[inline]
+    fn into_datum(self) -> Option<pg_sys::Datum> {
+        self.as_str().into_datum()
+    }
+
+    fn type_oid() -> u32 {
+        pg_sys::TEXTOID
+    }
+}
@@ -0,0 +1,340 @@
+use super::upgrade::{statements, Statement};
+
+/// A line of a diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// A unified diff from the SQL `committed`, the contents of the file at `path`, to the SQL
+/// `generated`, or `None` if they only differ in comments, whitespace or the order of statements.
+///
+/// The statements of both are sorted, and the comments before each are left out, so the line
+/// numbers of the diff are those of the sorted statements rather than of the file.
+pub(super) fn sql_diff(committed: &str, generated: &str, path: &str) -> Option<String> {
+    let mut committed = statements(committed);
+    let mut generated = statements(generated);
+    committed.sort_by(|left, right| left.normalized.cmp(&right.normalized));
+    generated.sort_by(|left, right| left.normalized.cmp(&right.normalized));
+    if committed
+        .iter()
+        .map(|statement| &statement.normalized)
+        .eq(generated.iter().map(|statement| &statement.normalized))
+    {
+        return None;
+    }
+
+    // Each statement is followed by a blank line
+    let mut lines = Vec::new();
+    let mut old = committed.iter().peekable();
+    let mut new = generated.iter().peekable();
+    loop {
+        match (old.peek(), new.peek()) {
+            (Some(old_statement), Some(new_statement))
+                if old_statement.normalized == new_statement.normalized =>
+            {
+                lines.extend(canonical(old_statement).into_iter().map(DiffLine::Same));
+                lines.push(DiffLine::Same(String::new()));
+                old.next();
+                new.next();
+            }
+            // Likely the same object, changed: show which of its lines changed
+            (Some(old_statement), Some(new_statement))
+                if head(old_statement) == head(new_statement) =>
+            {
+                lines.extend(line_diff(
+                    &canonical(old_statement),
+                    &canonical(new_statement),
+                ));
+                lines.push(DiffLine::Same(String::new()));
+                old.next();
+                new.next();
+            }
+            (Some(old_statement), Some(new_statement))
+                if old_statement.normalized < new_statement.normalized =>
+            {
+                lines.extend(canonical(old_statement).into_iter().map(DiffLine::Removed));
+                lines.push(DiffLine::Removed(String::new()));
+                old.next();
+            }
+            (Some(old_statement), None) => {
+                lines.extend(canonical(old_statement).into_iter().map(DiffLine::Removed));
+                lines.push(DiffLine::Removed(String::new()));
+                old.next();
+            }
+            (_, Some(new_statement)) => {
+                lines.extend(canonical(new_statement).into_iter().map(DiffLine::Added));
+                lines.push(DiffLine::Added(String::new()));
+                new.next();
+            }
+            (None, None) => break,
+        }
+    }
+
+    Some(unified(&lines, path))
+}
+
+/// The start of `statement` up to its arguments or options, such as `CREATE FUNCTION "add"`,
+/// which statements creating the same object share
+fn head(statement: &Statement) -> &str {
+    let normalized = statement.normalized.as_str();
+    &normalized[..normalized.find('(').unwrap_or(normalized.len())]
+}
+
+/// The lines of `statement`, without the comments before it or trailing whitespace
+fn canonical(statement: &Statement) -> Vec<String> {
+    let mut text = statement.text.as_str();
+    loop {
+        text = text.trim_start();
+        if text.starts_with("--") {
+            text = &text[text.find('\n').unwrap_or(text.len())..];
+        } else if text.starts_with("/*") {
+            text = &text[text.find("*/").map(|end| end + 2).unwrap_or(text.len())..];
+        } else {
+            break;
+        }
+    }
+    text.lines()
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+/// The diff of the lines `old` and `new`, by their longest common subsequence
+fn line_diff(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    // `common[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// `lines` as a unified diff, with three lines of context around each change
+fn unified(lines: &[DiffLine], path: &str) -> String {
+    const CONTEXT: usize = 3;
+
+    // The ranges of `lines` in each hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if let DiffLine::Same(_) = line {
+            continue;
+        }
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = format!("--- {}\n+++ {} (generated)\n", path, path);
+    for (start, end) in hunks {
+        let before = &lines[..start];
+        let hunk = &lines[start..end];
+        let old_start = before
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Added(_)))
+            .count();
+        let new_start = before
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Removed(_)))
+            .count();
+        let old_len = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Added(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Removed(_)))
+            .count();
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_len,
+            new_start + 1,
+            new_len
+        ));
+        for line in hunk {
+            let (prefix, line) = match line {
+                DiffLine::Same(line) => (' ', line),
+                DiffLine::Removed(line) => ('-', line),
+                DiffLine::Added(line) => ('+', line),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMITTED: &str = "\
+-- src/lib.rs:10
+-- hello::hello
+CREATE OR REPLACE FUNCTION \"hello\"() RETURNS text
+STRICT
+LANGUAGE c /* Rust */
+AS 'MODULE_PATHNAME', 'hello_wrapper';
+
+-- src/lib.rs:20
+CREATE TYPE color AS ENUM (
+\t'red',
+\t'blue'
+);
+";
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn equivalent_sql() {
+        assert_eq!(sql_diff(COMMITTED, COMMITTED, "hello.sql"), None);
+
+        let reordered = "\
+-- src/lib.rs:2
+CREATE TYPE color AS ENUM ('red', 'blue');
+/* moved */
+CREATE OR REPLACE FUNCTION \"hello\"()
+\tRETURNS text STRICT
+\tLANGUAGE c AS 'MODULE_PATHNAME', 'hello_wrapper';
+";
+        assert_eq!(sql_diff(COMMITTED, reordered, "hello.sql"), None);
+    }
+
+    #[test]
+    fn changed_function() {
+        let generated = COMMITTED.replace("STRICT", "IMMUTABLE STRICT");
+        assert_eq!(
+            sql_diff(COMMITTED, &generated, "hello.sql").unwrap(),
+            "\
+--- hello.sql
++++ hello.sql (generated)
+@@ -1,5 +1,5 @@
+ CREATE OR REPLACE FUNCTION \"hello\"() RETURNS text
+-STRICT
++IMMUTABLE STRICT
+ LANGUAGE c /* Rust */
+ AS 'MODULE_PATHNAME', 'hello_wrapper';
+ 
+"
+        );
+    }
+
+    #[test]
+    fn added_and_removed_statements() {
+        // Sorted, the schema goes between the function and the type
+        let added = format!("{}\nCREATE SCHEMA extra;\n", COMMITTED);
+        assert_eq!(
+            sql_diff(COMMITTED, &added, "hello.sql").unwrap(),
+            "\
+--- hello.sql
++++ hello.sql (generated)
+@@ -3,6 +3,8 @@
+ LANGUAGE c /* Rust */
+ AS 'MODULE_PATHNAME', 'hello_wrapper';
+ 
++CREATE SCHEMA extra;
++
+ CREATE TYPE color AS ENUM (
+ \t'red',
+ \t'blue'
+"
+        );
+        assert_eq!(
+            sql_diff(&added, COMMITTED, "hello.sql").unwrap(),
+            "\
+--- hello.sql
++++ hello.sql (generated)
+@@ -3,8 +3,6 @@
+ LANGUAGE c /* Rust */
+ AS 'MODULE_PATHNAME', 'hello_wrapper';
+ 
+-CREATE SCHEMA extra;
+-
+ CREATE TYPE color AS ENUM (
+ \t'red',
+ \t'blue'
+"
+        );
+    }
+
+    #[test]
+    fn line_diffs() {
+        assert_eq!(
+            line_diff(&lines(&["a", "b", "c"]), &lines(&["a", "x", "c", "d"])),
+            vec![
+                DiffLine::Same(String::from("a")),
+                DiffLine::Removed(String::from("b")),
+                DiffLine::Added(String::from("x")),
+                DiffLine::Same(String::from("c")),
+                DiffLine::Added(String::from("d")),
+            ]
+        );
+        assert_eq!(
+            line_diff(&lines(&["a", "b"]), &lines(&[])),
+            vec![
+                DiffLine::Removed(String::from("a")),
+                DiffLine::Removed(String::from("b")),
+            ]
+        );
+    }
+
+    #[test]
+    fn unified_hunks() {
+        let mut diff_lines: Vec<DiffLine> = (1..=20)
+            .map(|line| DiffLine::Same(line.to_string()))
+            .collect();
+        diff_lines[1] = DiffLine::Removed(String::from("2"));
+        diff_lines.insert(15, DiffLine::Added(String::from("new")));
+        assert_eq!(
+            unified(&diff_lines, "a.sql"),
+            "\
+--- a.sql
++++ a.sql (generated)
+@@ -1,5 +1,4 @@
+ 1
+-2
+ 3
+ 4
+ 5
+@@ -13,6 +12,7 @@
+ 13
+ 14
+ 15
++new
+ 16
+ 17
+ 18
+"
+        );
+    }
+}
@@ -22,6 +22,13 @@ pub struct ControlFile {
     pub relocatable: bool,
     pub superuser: bool,
     pub schema: Option<String>,
+    /// The extensions this extension depends on.  Postgres creates them before running the
+    /// extension's SQL, so they play no part in ordering the entities of the graph.
+    pub requires: Vec<String>,
+    pub trusted: bool,
+    pub encoding: Option<String>,
+    /// The required extensions whose schema this extension's SQL refers to
+    pub no_relocate: Vec<String>,
 }
 
 impl ControlFile {
@@ -89,10 +96,24 @@ impl ControlFile {
                 })?
                 == &"true",
             schema: temp.get("schema").map(|v| v.to_string()),
+            requires: temp.get("requires").map(|v| list(v)).unwrap_or_default(),
+            trusted: temp.get("trusted") == Some(&"true"),
+            encoding: temp.get("encoding").map(|v| v.to_string()),
+            no_relocate: temp.get("no_relocate").map(|v| list(v)).unwrap_or_default(),
         })
     }
 }
 
+/// The items of a comma separated list property, such as `requires`
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 impl Into<SqlGraphEntity> for ControlFile {
     fn into(self) -> SqlGraphEntity {
         SqlGraphEntity::ExtensionRoot(self)
@@ -163,3 +184,61 @@ impl SqlGraphIdentifier for ControlFile {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optional_properties() {
+        let control = ControlFile::from_str(
+            "\
+            # Generated by pgx from [package.metadata.pgx] in Cargo.toml\n\
+            comment = 'An example'\n\
+            default_version = '1.2.0'\n\
+            module_pathname = '$libdir/example'\n\
+            relocatable = false\n\
+            superuser = false\n\
+            trusted = true\n\
+            requires = 'hstore, pg_trgm'\n\
+            no_relocate = 'hstore'\n\
+            ",
+        )
+        .unwrap();
+        assert_eq!(control.comment, "An example");
+        assert!(control.trusted);
+        assert_eq!(control.requires, vec!["hstore", "pg_trgm"]);
+        assert_eq!(control.no_relocate, vec!["hstore"]);
+        assert_eq!(control.schema, None);
+        assert_eq!(control.encoding, None);
+    }
+
+    #[test]
+    fn defaults() {
+        let control = ControlFile::from_str(
+            "\
+            comment = 'An example'\n\
+            default_version = '1.2.0'\n\
+            module_pathname = '$libdir/example'\n\
+            relocatable = false\n\
+            superuser = true\n\
+            requires = ''\n\
+            ",
+        )
+        .unwrap();
+        assert!(control.superuser);
+        assert!(!control.trusted);
+        assert!(control.requires.is_empty());
+        assert!(control.no_relocate.is_empty());
+    }
+
+    #[test]
+    fn missing_field() {
+        match ControlFile::from_str("comment = 'An example'\n") {
+            Err(ControlFileError::MissingField { field, .. }) => {
+                assert_eq!(field, "default_version")
+            }
+            other => panic!("expected a missing field, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,556 @@
+use eyre::eyre as eyre_err;
+use serde::Serialize;
+
+use super::{pgx_sql::SqlGraphRelationship, PgxSql, SqlGraphEntity, SqlGraphIdentifier};
+
+/// The version of the [`SqlManifest`] format, its `version` field.
+///
+/// It's increased whenever a field is removed, renamed, or changes meaning.  New fields, and new
+/// kinds of entities, may be added without changing it, so readers should ignore what they don't
+/// know.
+pub const SQL_MANIFEST_VERSION: u32 = 1;
+
+/// A machine readable description of the SQL entity graph of an extension, for documentation and
+/// linting tools, as written by `cargo pgx schema --json`.
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "extension": { "comment": "...", "default_version": "1.0", ... },
+///   "entities": [
+///     {
+///       "kind": "function",
+///       "id": "fn my_extension::hello",
+///       "name": "hello",
+///       "rust_path": "my_extension::hello",
+///       "file": "src/lib.rs",
+///       "line": 12,
+///       "schema": null,
+///       "signature": "\"hello\"(text)",
+///       "volatility": "immutable",
+///       "procedure": false
+///     },
+///     ...
+///   ],
+///   "dependencies": [
+///     { "from": "type my_extension::Thing", "to": "fn my_extension::hello", "relationship": "argument" }
+///   ]
+/// }
+/// ```
+///
+/// Entities are listed in the order their SQL is generated in, which creates the dependencies of
+/// each entity before it.  Their `id`s are stable between builds of the extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SqlManifest {
+    /// See [`SQL_MANIFEST_VERSION`]
+    pub version: u32,
+    pub extension: ManifestExtension,
+    pub entities: Vec<ManifestEntity>,
+    pub dependencies: Vec<ManifestDependency>,
+}
+
+/// The extension's control file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestExtension {
+    pub comment: String,
+    pub default_version: String,
+    pub schema: Option<String>,
+    pub relocatable: bool,
+    pub superuser: bool,
+    pub trusted: bool,
+    pub requires: Vec<String>,
+}
+
+/// An entity of the SQL entity graph, tagged by its `kind`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ManifestEntity {
+    Schema {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+    },
+    /// A `#[pg_extern]` (or `#[pg_procedure]`, etc) function
+    Function {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+        /// `None` if the function is created in the extension's schema
+        schema: Option<String>,
+        /// Such as `"hello"(text, integer)`
+        signature: String,
+        /// `immutable`, `stable` or `volatile`
+        volatility: &'static str,
+        procedure: bool,
+    },
+    /// A `#[pg_operator]`, implemented by the function `function`
+    Operator {
+        id: String,
+        name: &'static str,
+        /// The `id` of the function
+        function: String,
+        left: String,
+        right: String,
+        commutator: Option<&'static str>,
+        negator: Option<&'static str>,
+    },
+    /// A `#[derive(PostgresType)]`
+    Type {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+        schema: Option<String>,
+    },
+    /// A type which already exists in Postgres, such as `text`
+    BuiltinType { id: String, name: String },
+    /// A `#[derive(PostgresEnum)]`
+    Enum {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+        schema: Option<String>,
+        variants: Vec<&'static str>,
+    },
+    /// The btree operator family and class of a `#[derive(PostgresOrd)]`
+    Ord {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+    },
+    /// The hash operator family and class of a `#[derive(PostgresHash)]`
+    Hash {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+    },
+    /// A `#[pg_aggregate]`
+    Aggregate {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+    },
+    /// An operator class, such as a `#[pg_gist_opclass]`
+    OperatorClass {
+        id: String,
+        name: &'static str,
+        /// The index access method, such as `gist`
+        method: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+    },
+    /// A `#[pg_guc]`, which has no SQL but is defined when the extension is loaded
+    Guc {
+        id: String,
+        /// Such as `myext.threshold`
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+        /// As in `pg_settings`, such as `integer`
+        vartype: &'static str,
+        /// As in `pg_settings`, such as `superuser`
+        context: &'static str,
+        unit: Option<&'static str>,
+        min: Option<String>,
+        max: Option<String>,
+        short_description: &'static str,
+        long_description: Option<&'static str>,
+    },
+    /// An `extension_sql!()` or `extension_sql_file!()` block
+    ExtensionSql {
+        id: String,
+        name: &'static str,
+        rust_path: &'static str,
+        file: &'static str,
+        line: u32,
+        bootstrap: bool,
+        finalize: bool,
+        sql: &'static str,
+    },
+}
+
+/// The entity `from` must be created before the entity `to`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ManifestDependency {
+    pub from: String,
+    pub to: String,
+    pub relationship: ManifestRelationship,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestRelationship {
+    /// `from` is required by `to`, such as a schema by the functions in it
+    Requires,
+    /// `from` is the type of an argument of `to`
+    Argument,
+    /// `from` is the type `to` returns
+    Return,
+}
+
+impl From<SqlGraphRelationship> for ManifestRelationship {
+    fn from(relationship: SqlGraphRelationship) -> Self {
+        match relationship {
+            SqlGraphRelationship::RequiredBy => ManifestRelationship::Requires,
+            SqlGraphRelationship::RequiredByArg => ManifestRelationship::Argument,
+            SqlGraphRelationship::RequiredByReturn => ManifestRelationship::Return,
+        }
+    }
+}
+
+impl SqlManifest {
+    pub(super) fn build(context: &PgxSql) -> eyre::Result<Self> {
+        let control = &context.control;
+        let mut entities = Vec::new();
+        for index in petgraph::algo::toposort(&context.graph, None).map_err(|e| {
+            eyre_err!(
+                "Failed to toposort SQL entities, node with cycle: {:?}",
+                context.graph[e.node_id()]
+            )
+        })? {
+            let entity = &context.graph[index];
+            let id = entity.dot_identifier();
+            let manifest_entity = match entity {
+                // Described by `extension`
+                SqlGraphEntity::ExtensionRoot(_) => continue,
+                SqlGraphEntity::Schema(item) => ManifestEntity::Schema {
+                    id,
+                    name: item.name,
+                    rust_path: item.module_path,
+                    file: item.file,
+                    line: item.line,
+                },
+                SqlGraphEntity::Function(item) => {
+                    let function = ManifestEntity::Function {
+                        id: id.clone(),
+                        name: item.name,
+                        rust_path: item.full_path,
+                        file: item.file,
+                        line: item.line,
+                        schema: item.sql_schema(context),
+                        signature: item.sql_signature(context)?,
+                        volatility: item.volatility(),
+                        procedure: item.procedure,
+                    };
+                    match &item.operator {
+                        Some(operator) => {
+                            let name = operator.opname.unwrap_or(item.name);
+                            let arguments = item.sql_argument_types(context)?;
+                            let (left, right) = match arguments.as_slice() {
+                                [left, right] => (left.clone(), right.clone()),
+                                _ => {
+                                    return Err(eyre_err!(
+                                        "Operator `{}` does not have two arguments.",
+                                        item.full_path
+                                    ))
+                                }
+                            };
+                            entities.push(function);
+                            ManifestEntity::Operator {
+                                id: format!("operator {}({}, {})", name, left, right),
+                                name,
+                                function: id,
+                                left,
+                                right,
+                                commutator: operator.commutator,
+                                negator: operator.negator,
+                            }
+                        }
+                        None => function,
+                    }
+                }
+                SqlGraphEntity::Type(item) => ManifestEntity::Type {
+                    id,
+                    name: item.name,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                    schema: context.schema_alias_of(&index),
+                },
+                SqlGraphEntity::BuiltinType(name) => ManifestEntity::BuiltinType {
+                    id,
+                    name: name.clone(),
+                },
+                SqlGraphEntity::Enum(item) => ManifestEntity::Enum {
+                    id,
+                    name: item.name,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                    schema: context.schema_alias_of(&index),
+                    variants: item.variants.clone(),
+                },
+                SqlGraphEntity::Ord(item) => ManifestEntity::Ord {
+                    id,
+                    name: item.name,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                },
+                SqlGraphEntity::Hash(item) => ManifestEntity::Hash {
+                    id,
+                    name: item.name,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                },
+                SqlGraphEntity::Aggregate(item) => ManifestEntity::Aggregate {
+                    id,
+                    name: item.name,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                },
+                SqlGraphEntity::OpClass(item) => ManifestEntity::OperatorClass {
+                    id,
+                    name: item.name,
+                    method: item.method,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                },
+                SqlGraphEntity::Guc(item) => ManifestEntity::Guc {
+                    id,
+                    name: item.name,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                    vartype: item.vartype,
+                    context: item.context,
+                    unit: item.unit,
+                    min: item.min.clone(),
+                    max: item.max.clone(),
+                    short_description: item.short_description,
+                    long_description: item.long_description,
+                },
+                SqlGraphEntity::CustomSql(item) => ManifestEntity::ExtensionSql {
+                    id,
+                    name: item.name,
+                    rust_path: item.full_path,
+                    file: item.file,
+                    line: item.line,
+                    bootstrap: item.bootstrap,
+                    finalize: item.finalize,
+                    sql: item.sql,
+                },
+            };
+            entities.push(manifest_entity);
+        }
+
+        let mut dependencies = Vec::new();
+        for edge in context.graph.edge_indices() {
+            let (from, to) = match context.graph.edge_endpoints(edge) {
+                Some(endpoints) => endpoints,
+                None => continue,
+            };
+            if from == context.graph_root || to == context.graph_root {
+                continue;
+            }
+            dependencies.push(ManifestDependency {
+                from: context.graph[from].dot_identifier(),
+                to: context.graph[to].dot_identifier(),
+                relationship: context.graph[edge].into(),
+            });
+        }
+        dependencies.sort();
+        dependencies.dedup();
+
+        Ok(Self {
+            version: SQL_MANIFEST_VERSION,
+            extension: ManifestExtension {
+                comment: control.comment.clone(),
+                default_version: control.default_version.clone(),
+                schema: control.schema.clone(),
+                relocatable: control.relocatable,
+                superuser: control.superuser,
+                trusted: control.trusted,
+                requires: control.requires.clone(),
+            },
+            entities,
+            dependencies,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datum::sql_entity_graph::{
+        ControlFile, PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity,
+        PostgresEnumEntity, SchemaEntity,
+    };
+    use core::any::TypeId;
+    use pgx_utils::ExternArgs;
+
+    struct Color;
+
+    /// An extension with a schema, an enum, and a function taking the enum
+    fn pgx_sql() -> PgxSql {
+        let control = ControlFile::from_str(
+            "\
+            comment = 'An example'\n\
+            default_version = '1.0'\n\
+            module_pathname = '$libdir/example'\n\
+            relocatable = false\n\
+            superuser = false\n\
+            requires = 'hstore'\n\
+            ",
+        )
+        .unwrap();
+        let schema = SchemaEntity {
+            module_path: "example::paints",
+            name: "paints",
+            file: "src/lib.rs",
+            line: 1,
+            doc: None,
+        };
+        let color = PostgresEnumEntity {
+            name: "Color",
+            file: "src/lib.rs",
+            line: 4,
+            full_path: "example::Color",
+            module_path: "example",
+            mappings: vec![TypeId::of::<Color>()].into_iter().collect(),
+            variants: vec!["Red", "Blue"],
+            doc: None,
+        };
+        let paint = PgExternEntity {
+            name: "paint",
+            unaliased_name: "paint",
+            schema: None,
+            file: "src/lib.rs",
+            line: 10,
+            module_path: "example",
+            full_path: "example::paint",
+            extern_attrs: vec![ExternArgs::Immutable],
+            search_path: None,
+            fn_args: vec![PgExternArgumentEntity {
+                pattern: "color",
+                sql_type: String::from("Color"),
+                collatable: false,
+                ty_id: TypeId::of::<Color>(),
+                full_path: "example::Color",
+                module_path: String::from("example"),
+                is_optional: false,
+                is_variadic: false,
+                is_inout: false,
+                default: None,
+            }],
+            fn_return: PgExternReturnEntity::Type {
+                id: TypeId::of::<String>(),
+                sql_type: String::from("text"),
+                full_path: "alloc::string::String",
+                module_path: String::from("alloc::string"),
+            },
+            operator: None,
+            cast: None,
+            sql_body: None,
+            overridden: None,
+            procedure: false,
+            doc: None,
+        };
+        PgxSql::build(
+            vec![
+                SqlGraphEntity::ExtensionRoot(control),
+                SqlGraphEntity::Schema(schema),
+                SqlGraphEntity::Enum(color),
+                SqlGraphEntity::Function(paint),
+            ]
+            .into_iter(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn serialization() {
+        let manifest = serde_json::to_value(&SqlManifest::build(&pgx_sql()).unwrap()).unwrap();
+        assert_eq!(manifest["version"], SQL_MANIFEST_VERSION);
+        assert_eq!(
+            manifest["extension"],
+            serde_json::json!({
+                "comment": "An example",
+                "default_version": "1.0",
+                "schema": null,
+                "relocatable": false,
+                "superuser": false,
+                "trusted": false,
+                "requires": ["hstore"],
+            })
+        );
+
+        let entities = manifest["entities"].as_array().unwrap();
+        let entity = |kind: &str| {
+            entities
+                .iter()
+                .find(|entity| entity["kind"] == kind)
+                .unwrap_or_else(|| panic!("no `{}` in {:#?}", kind, entities))
+        };
+        assert_eq!(
+            entity("schema"),
+            &serde_json::json!({
+                "kind": "schema",
+                "id": "schema example::paints",
+                "name": "paints",
+                "rust_path": "example::paints",
+                "file": "src/lib.rs",
+                "line": 1,
+            })
+        );
+        assert_eq!(
+            entity("enum"),
+            &serde_json::json!({
+                "kind": "enum",
+                "id": "enum example::Color",
+                "name": "Color",
+                "rust_path": "example::Color",
+                "file": "src/lib.rs",
+                "line": 4,
+                "schema": null,
+                "variants": ["Red", "Blue"],
+            })
+        );
+        assert_eq!(
+            entity("function"),
+            &serde_json::json!({
+                "kind": "function",
+                "id": "fn example::paint",
+                "name": "paint",
+                "rust_path": "example::paint",
+                "file": "src/lib.rs",
+                "line": 10,
+                "schema": null,
+                "signature": "\"paint\"(Color)",
+                "volatility": "immutable",
+                "procedure": false,
+            })
+        );
+        assert_eq!(entity("builtin_type")["name"], "alloc::string::String");
+
+        // The enum is created before the function which takes it
+        let position = |id: &str| entities.iter().position(|entity| entity["id"] == id);
+        assert!(position("enum example::Color") < position("fn example::paint"));
+        assert!(manifest["dependencies"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({
+                "from": "enum example::Color",
+                "to": "fn example::paint",
+                "relationship": "argument",
+            })));
+    }
+}
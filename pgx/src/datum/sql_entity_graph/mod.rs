@@ -7,9 +7,19 @@ pub use control_file::ControlFile;
 mod schema;
 pub use schema::SchemaEntity;
 
+mod pg_aggregate;
+pub use pg_aggregate::{AggregateTypeEntity, PgAggregateEntity};
+
+mod pg_opclass;
+pub use pg_opclass::PgOpClassEntity;
+
+mod pg_guc;
+pub use pg_guc::PgGucEntity;
+
 mod pg_extern;
 pub use pg_extern::{
-    PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity, PgOperatorEntity,
+    PgCastEntity, PgExternArgumentEntity, PgExternEntity, PgExternReturnEntity, PgOperatorEntity,
+    PgSqlBodyEntity,
 };
 
 mod extension_sql;
@@ -30,7 +40,20 @@ pub use postgres_hash::PostgresHashEntity;
 mod sql_graph_entity;
 pub use sql_graph_entity::SqlGraphEntity;
 
-use core::any::TypeId;
+mod upgrade;
+pub use upgrade::{
+    SnapshotObject, SqlArgument, SqlArgumentMode, SqlObject, SqlSnapshot, SqlUpgrade,
+    SQL_SNAPSHOT_VERSION,
+};
+
+mod manifest;
+pub use manifest::{
+    ManifestDependency, ManifestEntity, ManifestExtension, ManifestRelationship, SqlManifest,
+    SQL_MANIFEST_VERSION,
+};
+
+mod check;
+
 pub use pgx_utils::sql_entity_graph::*;
 
 /// Able to produce a GraphViz DOT format identifier.
@@ -62,61 +85,15 @@ pub trait ToSql {
     fn to_sql(&self, context: &PgxSql) -> eyre::Result<String>;
 }
 
-/// A mapping from a Rust type to a SQL type, with a `TypeId`.
-///
-/// ```rust
-/// use pgx::datum::sql_entity_graph::RustSqlMapping;
-///
-/// let constructed = RustSqlMapping::of::<i32>(String::from("int"));
-/// let raw = RustSqlMapping {
-///     rust: core::any::type_name::<i32>().to_string(),
-///     sql: String::from("int"),
-///     id: core::any::TypeId::of::<i32>(),
-/// };
-///
-/// assert_eq!(constructed, raw);
-/// ```
-#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct RustSqlMapping {
-    // This is the **resolved** type, not the raw source. This means a Type Aliase of `type Foo = u32` would appear as `u32`.
-    pub rust: String,
-    pub sql: String,
-    pub id: TypeId,
-}
-
-impl RustSqlMapping {
-    pub fn of<T: 'static>(sql: String) -> Self {
-        Self {
-            rust: core::any::type_name::<T>().to_string(),
-            sql: sql.to_string(),
-            id: core::any::TypeId::of::<T>(),
-        }
-    }
-}
-
-/// A mapping from a Rust source fragment to a SQL type, typically for type aliases.
-///
-/// In general, this can only offer a fuzzy matching, as it does not use [`core::any::TypeId`].
-///
-/// ```rust
-/// use pgx::datum::sql_entity_graph::RustSourceOnlySqlMapping;
-///
-/// let constructed = RustSourceOnlySqlMapping::new(
-///     String::from("Oid"),
-///     String::from("int"),
-/// );
-/// ```
-#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct RustSourceOnlySqlMapping {
-    pub rust: String,
-    pub sql: String,
-}
-
-impl RustSourceOnlySqlMapping {
-    pub fn new(rust: String, sql: String) -> Self {
-        Self {
-            rust: rust.to_string(),
-            sql: sql.to_string(),
-        }
+/// The `COMMENT ON` statement documenting `target` (eg `FUNCTION "example"(integer)`) with the
+/// Rust doc comment it was declared with, if any.
+pub(crate) fn comment_sql(target: &str, doc: Option<&str>) -> String {
+    match doc {
+        Some(doc) => format!(
+            "\nCOMMENT ON {} IS '{}';",
+            target,
+            doc.replace('\'', "''")
+        ),
+        None => String::default(),
     }
 }
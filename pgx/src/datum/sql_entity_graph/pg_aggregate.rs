@@ -0,0 +1,295 @@
+use eyre::eyre as eyre_err;
+
+use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql};
+use crate::aggregate::{FinalizeModify, ParallelOption};
+use std::cmp::Ordering;
+
+/// A type used by a [`PgAggregateEntity`] (as an argument or as a state) or a
+/// [`PgOpClassEntity`](super::PgOpClassEntity).
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AggregateTypeEntity {
+    /// From [`SqlTranslatable`](crate::datum::SqlTranslatable)
+    pub sql_type: String,
+    pub ty_id: core::any::TypeId,
+    pub full_path: &'static str,
+}
+
+impl AggregateTypeEntity {
+    pub(crate) fn to_sql_type(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let schema_prefix = context
+            .types
+            .iter()
+            .find(|(ty, _)| ty.id_matches(&self.ty_id))
+            .map(|(_, index)| index)
+            .or_else(|| {
+                context
+                    .enums
+                    .iter()
+                    .find(|(en, _)| en.id_matches(&self.ty_id))
+                    .map(|(_, index)| index)
+            })
+            .map(|index| context.schema_prefix_for(index))
+            .unwrap_or_default();
+        Ok(format!("{}{}", schema_prefix, self.sql_type))
+    }
+}
+
+/// The output of a [`PgAggregate`](crate::datum::sql_entity_graph::PgAggregate) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PgAggregateEntity {
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub name: &'static str,
+    pub args: Vec<AggregateTypeEntity>,
+    pub direct_args: Vec<AggregateTypeEntity>,
+    pub stype: AggregateTypeEntity,
+    pub sfunc: &'static str,
+    pub finalfunc: Option<&'static str>,
+    pub finalfunc_modify: Option<FinalizeModify>,
+    pub combinefunc: Option<&'static str>,
+    pub serialfunc: Option<&'static str>,
+    pub deserialfunc: Option<&'static str>,
+    pub initcond: Option<&'static str>,
+    pub msfunc: Option<&'static str>,
+    pub minvfunc: Option<&'static str>,
+    pub mstype: Option<AggregateTypeEntity>,
+    pub mfinalfunc: Option<&'static str>,
+    pub mfinalfunc_modify: Option<FinalizeModify>,
+    pub minitcond: Option<&'static str>,
+    pub sortop: Option<&'static str>,
+    pub parallel: Option<ParallelOption>,
+    pub ordered_set: bool,
+    pub hypothetical: bool,
+}
+
+impl PgAggregateEntity {
+    /// The names of the `#[pg_extern]` support functions this aggregate uses.
+    pub fn support_fns(&self) -> impl Iterator<Item = &'static str> {
+        std::iter::once(self.sfunc).chain(
+            vec![
+                self.finalfunc,
+                self.combinefunc,
+                self.serialfunc,
+                self.deserialfunc,
+                self.msfunc,
+                self.minvfunc,
+                self.mfinalfunc,
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+
+    /// Every type this aggregate uses, as an argument or as a state.
+    pub fn used_types(&self) -> impl Iterator<Item = &AggregateTypeEntity> {
+        self.args
+            .iter()
+            .chain(self.direct_args.iter())
+            .chain(std::iter::once(&self.stype))
+            .chain(self.mstype.iter())
+    }
+
+    fn support_fn_sql(&self, context: &super::PgxSql, fn_name: &str) -> eyre::Result<String> {
+        let (item, index) = context
+            .externs
+            .iter()
+            .find(|(item, _)| {
+                item.module_path == self.module_path && item.unaliased_name == fn_name
+            })
+            .ok_or_else(|| {
+                eyre_err!(
+                    "Could not find support function `{}` of aggregate `{}`.",
+                    fn_name,
+                    self.full_path
+                )
+            })?;
+        Ok(format!(
+            "{schema}\"{name}\"",
+            schema = item
+                .schema
+                .map(|schema| format!("{}.", schema))
+                .unwrap_or_else(|| context.schema_prefix_for(index)),
+            name = item.name,
+        ))
+    }
+}
+
+impl Ord for PgAggregateEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PgAggregateEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<PgAggregateEntity> for SqlGraphEntity {
+    fn from(aggregate: PgAggregateEntity) -> Self {
+        SqlGraphEntity::Aggregate(aggregate)
+    }
+}
+
+impl SqlGraphIdentifier for PgAggregateEntity {
+    fn dot_identifier(&self) -> String {
+        format!("aggregate {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgAggregateEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let self_index = context.aggregates[self];
+
+        let type_list = |types: &[AggregateTypeEntity]| -> eyre::Result<String> {
+            let mut items = Vec::new();
+            for (idx, ty) in types.iter().enumerate() {
+                let needs_comma = idx < (types.len() - 1);
+                items.push(format!(
+                    "\t{sql_type}{maybe_comma}/* {full_path} */",
+                    sql_type = ty.to_sql_type(context)?,
+                    maybe_comma = if needs_comma { ", " } else { " " },
+                    full_path = ty.full_path,
+                ));
+            }
+            Ok(items.join("\n"))
+        };
+        let arguments = if self.ordered_set || self.hypothetical {
+            format!(
+                "\n{direct_args}{maybe_newline}\tORDER BY\n{args}\n",
+                direct_args = type_list(&self.direct_args)?,
+                maybe_newline = if self.direct_args.is_empty() {
+                    ""
+                } else {
+                    "\n"
+                },
+                args = type_list(&self.args)?,
+            )
+        } else if self.args.is_empty() {
+            String::from("*")
+        } else {
+            format!("\n{}\n", type_list(&self.args)?)
+        };
+
+        let quoted = |value: &str| format!("'{}'", value.replace('\'', "''"));
+
+        let mut options = vec![
+            format!("\tSFUNC = {}", self.support_fn_sql(context, self.sfunc)?),
+            format!(
+                "\tSTYPE = {} /* {} */",
+                self.stype.to_sql_type(context)?,
+                self.stype.full_path
+            ),
+        ];
+        if let Some(value) = self.finalfunc {
+            options.push(format!(
+                "\tFINALFUNC = {}",
+                self.support_fn_sql(context, value)?
+            ));
+            if self.hypothetical {
+                options.push(String::from("\tFINALFUNC_EXTRA"));
+            }
+        }
+        if let Some(value) = self.finalfunc_modify {
+            options.push(format!("\tFINALFUNC_MODIFY = {}", value));
+        }
+        if let Some(value) = self.combinefunc {
+            options.push(format!(
+                "\tCOMBINEFUNC = {}",
+                self.support_fn_sql(context, value)?
+            ));
+        }
+        if let Some(value) = self.serialfunc {
+            options.push(format!(
+                "\tSERIALFUNC = {}",
+                self.support_fn_sql(context, value)?
+            ));
+        }
+        if let Some(value) = self.deserialfunc {
+            options.push(format!(
+                "\tDESERIALFUNC = {}",
+                self.support_fn_sql(context, value)?
+            ));
+        }
+        if let Some(value) = self.initcond {
+            options.push(format!("\tINITCOND = {}", quoted(value)));
+        }
+        if let Some(value) = self.msfunc {
+            options.push(format!(
+                "\tMSFUNC = {}",
+                self.support_fn_sql(context, value)?
+            ));
+        }
+        if let Some(value) = self.minvfunc {
+            options.push(format!(
+                "\tMINVFUNC = {}",
+                self.support_fn_sql(context, value)?
+            ));
+        }
+        if let Some(value) = &self.mstype {
+            options.push(format!(
+                "\tMSTYPE = {} /* {} */",
+                value.to_sql_type(context)?,
+                value.full_path
+            ));
+        }
+        if let Some(value) = self.mfinalfunc {
+            options.push(format!(
+                "\tMFINALFUNC = {}",
+                self.support_fn_sql(context, value)?
+            ));
+        }
+        if let Some(value) = self.mfinalfunc_modify {
+            options.push(format!("\tMFINALFUNC_MODIFY = {}", value));
+        }
+        if let Some(value) = self.minitcond {
+            options.push(format!("\tMINITCOND = {}", quoted(value)));
+        }
+        if let Some(value) = self.sortop {
+            options.push(format!("\tSORTOP = {}", value));
+        }
+        if let Some(value) = self.parallel {
+            options.push(format!("\tPARALLEL = {}", value));
+        }
+        if self.hypothetical {
+            options.push(String::from("\tHYPOTHETICAL"));
+        }
+
+        let sql = format!(
+            "\n\
+                -- {file}:{line}\n\
+                -- {full_path}\n\
+                CREATE AGGREGATE {schema}{name} ({arguments})\n\
+                (\n\
+                    {options}\n\
+                );\
+            ",
+            schema = context.schema_prefix_for(&self_index),
+            name = self.name,
+            full_path = self.full_path,
+            file = self.file,
+            line = self.line,
+            arguments = arguments,
+            options = options.join(",\n"),
+        );
+        tracing::debug!(%sql);
+        Ok(sql)
+    }
+}
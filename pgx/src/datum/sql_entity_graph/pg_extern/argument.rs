@@ -4,12 +4,16 @@ use crate::sql_entity_graph::SqlGraphIdentifier;
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct PgExternArgumentEntity {
     pub pattern: &'static str,
-    pub ty_source: &'static str,
+    /// From [`SqlTranslatable`](crate::datum::SqlTranslatable)
+    pub sql_type: String,
+    /// From [`SqlTranslatable`](crate::datum::SqlTranslatable)
+    pub collatable: bool,
     pub ty_id: core::any::TypeId,
     pub full_path: &'static str,
     pub module_path: String,
     pub is_optional: bool,
     pub is_variadic: bool,
+    pub is_inout: bool,
     pub default: Option<&'static str>,
 }
 
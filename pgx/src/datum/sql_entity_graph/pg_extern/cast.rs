@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// The output of a [`PgCast`](crate::datum::sql_entity_graph::PgCast) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PgCastEntity {
+    Default,
+    Assignment,
+    Implicit,
+}
+
+impl core::fmt::Display for PgCastEntity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PgCastEntity::Default => Ok(()),
+            PgCastEntity::Assignment => write!(f, " AS ASSIGNMENT"),
+            PgCastEntity::Implicit => write!(f, " AS IMPLICIT"),
+        }
+    }
+}
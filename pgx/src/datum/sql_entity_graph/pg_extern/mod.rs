@@ -1,17 +1,25 @@
 mod argument;
+mod cast;
 mod operator;
 mod returning;
+mod sql_body;
 
 use eyre::eyre as eyre_err;
 
 pub use argument::PgExternArgumentEntity;
+pub use cast::PgCastEntity;
 pub use operator::PgOperatorEntity;
 pub use returning::PgExternReturnEntity;
+pub use sql_body::PgSqlBodyEntity;
 
 use pgx_utils::ExternArgs;
 
-use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql};
-use pgx_utils::sql_entity_graph::SqlDeclared;
+use super::{
+    comment_sql, pgx_sql::find_positioning_ref_target, PostgresTypeEntity, SqlGraphEntity,
+    SqlGraphIdentifier, ToSql,
+};
+use core::any::TypeId;
+use pgx_utils::sql_entity_graph::PositioningRef;
 use std::cmp::Ordering;
 
 /// The output of a [`Schema`](crate::datum::sql_entity_graph::Schema) from `quote::ToTokens::to_tokens`.
@@ -29,7 +37,11 @@ pub struct PgExternEntity {
     pub fn_args: Vec<PgExternArgumentEntity>,
     pub fn_return: PgExternReturnEntity,
     pub operator: Option<PgOperatorEntity>,
+    pub cast: Option<PgCastEntity>,
+    pub sql_body: Option<PgSqlBodyEntity>,
     pub overridden: Option<&'static str>,
+    pub procedure: bool,
+    pub doc: Option<&'static str>,
 }
 
 impl Ord for PgExternEntity {
@@ -78,10 +90,84 @@ impl ToSql for PgExternEntity {
         fields(identifier = %self.rust_identifier()),
     )]
     fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let PgExternSql {
+            header,
+            function,
+            event_trigger,
+            comment,
+            operator,
+            cast,
+            ..
+        } = self.sql_parts(context)?;
+        let fn_sql = function + &event_trigger.unwrap_or_default();
+
+        let ext_sql = format!(
+            "{header}{fn_sql}{comment}{overridden}",
+            header = header,
+            fn_sql = if self.overridden.is_some() {
+                let mut inner = fn_sql
+                    .lines()
+                    .map(|f| format!("-- {}", f))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                inner.push_str(
+                    "\n--\n-- Overridden as (due to a `///` comment with a `pgxsql` code block):",
+                );
+                inner
+            } else {
+                fn_sql
+            },
+            comment = comment,
+            overridden = self
+                .overridden
+                .map(|f| String::from("\n") + f + "\n")
+                .unwrap_or_default(),
+        );
+        tracing::debug!(sql = %ext_sql);
+
+        Ok(ext_sql + &operator.unwrap_or_default() + &cast.unwrap_or_default())
+    }
+}
+
+/// The SQL of a [`PgExternEntity`], by the objects it creates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PgExternSql {
+    /// The comments before the function, naming its Rust source and what it requires
+    pub(crate) header: String,
+    /// The `CREATE FUNCTION` (or `CREATE PROCEDURE`) statement
+    pub(crate) function: String,
+    /// The `CREATE EVENT TRIGGER` statement of a `#[pg_event_trigger]` with an `event`
+    pub(crate) event_trigger: Option<String>,
+    /// The `COMMENT ON` statement from the function's doc comment, if any
+    pub(crate) comment: String,
+    /// The `CREATE OPERATOR` statement of an operator
+    pub(crate) operator: Option<String>,
+    /// The `CREATE CAST` statement of a `#[pg_cast]`
+    pub(crate) cast: Option<String>,
+    /// The schema prefix of the function, such as `@extname@.`
+    pub(crate) schema: String,
+    /// The input argument types of the function, which identify it in SQL
+    pub(crate) signature: Vec<String>,
+}
+
+impl PgExternEntity {
+    /// The SQL of the function, by the objects it creates.  The SQL of an `overridden` function
+    /// has no comment, operator or cast.
+    pub(crate) fn sql_parts(&self, context: &super::PgxSql) -> eyre::Result<PgExternSql> {
         let self_index = context.externs[self];
         let mut extern_attrs = self.extern_attrs.clone();
-        // if we already have a STRICT marker we do not need to add it
-        let mut strict_upgrade = !extern_attrs.iter().any(|i| i == &ExternArgs::Strict);
+        if extern_attrs.contains(&ExternArgs::Strict)
+            && extern_attrs.contains(&ExternArgs::CalledOnNullInput)
+        {
+            return Err(eyre_err!(
+                "Function `{}` can not be both `strict` and `called_on_null_input`.",
+                self.name
+            ));
+        }
+        // if we already have a STRICT (or CALLED ON NULL INPUT) marker we do not need to add it
+        let mut strict_upgrade = !extern_attrs
+            .iter()
+            .any(|i| i == &ExternArgs::Strict || i == &ExternArgs::CalledOnNullInput);
         if strict_upgrade {
             for arg in &self.fn_args {
                 if arg.is_optional {
@@ -94,62 +180,109 @@ impl ToSql for PgExternEntity {
             extern_attrs.push(ExternArgs::Strict);
         }
 
-        let fn_sql = format!("\
+        // Postgres only hands a function an input collation if one of its arguments is of a
+        // collatable type, so a `collatable` function without one would never see it.
+        if extern_attrs.contains(&ExternArgs::Collatable) {
+            let any_collatable = self.fn_args.iter().any(|arg| arg.collatable);
+            if !any_collatable {
+                return Err(eyre_err!(
+                    "Function `{}` is marked `collatable` but has no arguments of a collatable type.",
+                    self.name
+                ));
+            }
+        }
+
+        if extern_attrs
+            .iter()
+            .any(|attr| matches!(attr, ExternArgs::Rows(_)))
+        {
+            match self.fn_return {
+                PgExternReturnEntity::SetOf { .. } | PgExternReturnEntity::Iterated(_) => (),
+                _ => {
+                    return Err(eyre_err!(
+                        "Function `{}` uses `rows`, but is not a set-returning function.",
+                        self.name
+                    ))
+                }
+            }
+        }
+
+        let out_params = self.out_params()?;
+        // The input argument types, which identify the function in a `COMMENT ON`
+        let mut signature = Vec::new();
+        let arguments = if !self.fn_args.is_empty() || !out_params.is_empty() {
+            let mut args = Vec::new();
+            for (idx, arg) in self.fn_args.iter().enumerate() {
+                let needs_comma = idx < (self.fn_args.len() - 1) || !out_params.is_empty();
+                let (schema_prefix, sql_type) = self.arg_sql_type(context, arg)?;
+                let variadic = if arg.is_variadic { "VARIADIC " } else { "" };
+                signature.push(format!("{}{}{}", variadic, schema_prefix, sql_type));
+                let buf = format!("\
+                       \t\"{pattern}\" {inout}{variadic}{schema_prefix}{sql_type}{default}{maybe_comma}/* {full_path} */\
+                   ",
+                       pattern = arg.pattern,
+                       schema_prefix = schema_prefix,
+                       sql_type = sql_type,
+                       default = if let Some(def) = arg.default { format!(" DEFAULT {}", def) } else { String::from("") },
+                       variadic = variadic,
+                       inout = if arg.is_inout { "INOUT " } else { "" },
+                       maybe_comma = if needs_comma { ", " } else { " " },
+                       full_path = arg.full_path,
+                );
+                args.push(buf);
+            }
+            for (idx, (id, sql_type, ty_name, _module_path, col_name)) in
+                out_params.iter().enumerate()
+            {
+                let graph_index = context
+                    .graph
+                    .neighbors_undirected(self_index)
+                    .find(|neighbor| match &context.graph[*neighbor] {
+                        SqlGraphEntity::Type(ty) => ty.id_matches(&id),
+                        SqlGraphEntity::Enum(en) => en.id_matches(&id),
+                        SqlGraphEntity::BuiltinType(defined) => defined == ty_name,
+                        _ => false,
+                    });
+                let needs_comma = idx < (out_params.len() - 1);
+                let buf = format!(
+                    "\t\"{col_name}\" OUT {schema_prefix}{ty_resolved}{maybe_comma}/* {ty_name} */",
+                    col_name = col_name.expect("`OUT` parameters are named"),
+                    schema_prefix = if let Some(graph_index) = graph_index {
+                        context.schema_prefix_for(&graph_index)
+                    } else {
+                        "".into()
+                    },
+                    ty_resolved = sql_type,
+                    maybe_comma = if needs_comma { ", " } else { " " },
+                    ty_name = ty_name
+                );
+                args.push(buf);
+            }
+            String::from("\n") + &args.join("\n") + "\n"
+        } else {
+            Default::default()
+        };
+
+        let schema = self
+            .schema
+            .map(|schema| format!("{}.", schema))
+            .unwrap_or_else(|| context.schema_prefix_for(&self_index));
+        let fn_sql = if self.procedure {
+            self.procedure_sql(&schema, &arguments)?
+        } else {
+            format!("\
                                 CREATE OR REPLACE FUNCTION {schema}\"{name}\"({arguments}) {returns}\n\
                                 {extern_attrs}\
                                 {search_path}\
-                                LANGUAGE c /* Rust */\n\
-                                AS 'MODULE_PATHNAME', '{unaliased_name}_wrapper';\
+                                {body}\
                             ",
-                             schema = self.schema.map(|schema| format!("{}.", schema)).unwrap_or_else(|| context.schema_prefix_for(&self_index)),
+                             schema = schema,
                              name = self.name,
-                             unaliased_name = self.unaliased_name,
-                             arguments = if !self.fn_args.is_empty() {
-                                 let mut args = Vec::new();
-                                 for (idx, arg) in self.fn_args.iter().enumerate() {
-                                     let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
-                                         SqlGraphEntity::Type(ty) => ty.id_matches(&arg.ty_id),
-                                         SqlGraphEntity::Enum(en) => en.id_matches(&arg.ty_id),
-                                         SqlGraphEntity::BuiltinType(defined) => defined == &arg.full_path,
-                                         _ => false,
-                                     }).ok_or_else(|| eyre_err!("Could not find arg type in graph. Got: {:?}", arg))?;
-                                     let needs_comma = idx < (self.fn_args.len() - 1);
-                                     let buf = format!("\
-                                            \t\"{pattern}\" {variadic}{schema_prefix}{sql_type}{default}{maybe_comma}/* {full_path} */\
-                                        ",
-                                            pattern = arg.pattern,
-                                            schema_prefix = context.schema_prefix_for(&graph_index),
-                                            // First try to match on [`TypeId`] since it's most reliable.
-                                            sql_type = context.source_only_to_sql_type(arg.ty_source).or_else(|| {
-                                                context.type_id_to_sql_type(arg.ty_id)
-                                            }).or_else(|| {
-                                                // Fall back to fuzzy matching.
-                                                let path = arg.full_path.to_string();
-                                                if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Type(path.clone())) {
-                                                    Some(found.sql())
-                                                }  else if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Enum(path.clone())) {
-                                                    Some(found.sql())
-                                                } else {
-                                                    None
-                                                }
-                                            }).ok_or_else(|| eyre_err!(
-                                                "Failed to map argument `{}` type `{}` to SQL type while building function `{}`.",
-                                                arg.pattern,
-                                                arg.full_path,
-                                                self.name
-                                            ))?,
-                                            default = if let Some(def) = arg.default { format!(" DEFAULT {}", def) } else { String::from("") },
-                                            variadic = if arg.is_variadic { "VARIADIC " } else { "" },
-                                            maybe_comma = if needs_comma { ", " } else { " " },
-                                            full_path = arg.full_path,
-                                     );
-                                     args.push(buf);
-                                 };
-                                 String::from("\n") + &args.join("\n") + "\n"
-                             } else { Default::default() },
+                             body = self.body_sql()?,
+                             arguments = arguments,
                              returns = match &self.fn_return {
                                  PgExternReturnEntity::None => String::from("RETURNS void"),
-                                 PgExternReturnEntity::Type { id, source, full_path, .. } => {
+                                 PgExternReturnEntity::Type { id, sql_type, full_path, .. } => {
                                      let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
                                          SqlGraphEntity::Type(ty) => ty.id_matches(&id),
                                          SqlGraphEntity::Enum(en) => en.id_matches(&id),
@@ -157,23 +290,12 @@ impl ToSql for PgExternEntity {
                                          _ => false,
                                      }).ok_or_else(|| eyre_err!("Could not find return type in graph."))?;
                                      format!("RETURNS {schema_prefix}{sql_type} /* {full_path} */",
-                                             sql_type = context.source_only_to_sql_type(source).or_else(|| {
-                                                 context.type_id_to_sql_type(*id)
-                                             }).or_else(|| {
-                                                    let pat = full_path.to_string();
-                                                    if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Type(pat.clone())) {
-                                                        Some(found.sql())
-                                                    }  else if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Enum(pat.clone())) {
-                                                        Some(found.sql())
-                                                    } else {
-                                                        None
-                                                    }
-                                                }).ok_or_else(|| eyre_err!("Failed to map return type `{}` to SQL type while building function `{}`.", full_path, self.full_path))?,
+                                             sql_type = sql_type,
                                              schema_prefix = context.schema_prefix_for(&graph_index),
                                              full_path = full_path
                                      )
                                  },
-                                 PgExternReturnEntity::SetOf { id, source, full_path, .. } => {
+                                 PgExternReturnEntity::SetOf { id, sql_type, full_path, .. } => {
                                      let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
                                          SqlGraphEntity::Type(ty) => ty.id_matches(&id),
                                          SqlGraphEntity::Enum(en) => en.id_matches(&id),
@@ -181,25 +303,14 @@ impl ToSql for PgExternEntity {
                                          _ => false,
                                      }).ok_or_else(|| eyre_err!("Could not find return type in graph."))?;
                                      format!("RETURNS SETOF {schema_prefix}{sql_type} /* {full_path} */",
-                                             sql_type = context.source_only_to_sql_type(source).or_else(|| {
-                                                 context.type_id_to_sql_type(*id)
-                                             }).or_else(|| {
-                                                    let pat = full_path.to_string();
-                                                    if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Type(pat.clone())) {
-                                                        Some(found.sql())
-                                                    }  else if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Enum(pat.clone())) {
-                                                        Some(found.sql())
-                                                    } else {
-                                                        None
-                                                    }
-                                                }).ok_or_else(|| eyre_err!("Failed to map return type `{}` to SQL type while building function `{}`.", full_path, self.full_path))?,
+                                             sql_type = sql_type,
                                              schema_prefix = context.schema_prefix_for(&graph_index),
                                              full_path = full_path
                                      )
                                  },
                                  PgExternReturnEntity::Iterated(table_items) => {
                                      let mut items = String::new();
-                                     for (idx, (id, source, ty_name, _module_path, col_name)) in table_items.iter().enumerate() {
+                                     for (idx, (id, sql_type, ty_name, _module_path, col_name)) in table_items.iter().enumerate() {
                                          let graph_index = context.graph.neighbors_undirected(self_index).find(|neighbor| match &context.graph[*neighbor] {
                                              SqlGraphEntity::Type(ty) => ty.id_matches(&id),
                                              SqlGraphEntity::Enum(en) => en.id_matches(&id),
@@ -212,18 +323,7 @@ impl ToSql for PgExternEntity {
                                                             schema_prefix = if let Some(graph_index) = graph_index {
                                                                 context.schema_prefix_for(&graph_index)
                                                             } else { "".into() },
-                                                            ty_resolved = context.source_only_to_sql_type(source).or_else(|| {
-                                                                context.type_id_to_sql_type(*id)
-                                                            }).or_else(|| {
-                                                                let pat = ty_name.to_string();
-                                                                if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Type(pat.clone())) {
-                                                                    Some(found.sql())
-                                                                }  else if let Some(found) = context.has_sql_declared_entity(&SqlDeclared::Enum(pat.clone())) {
-                                                                    Some(found.sql())
-                                                                } else {
-                                                                    None
-                                                                }
-                                                            }).ok_or_else(|| eyre_err!("Failed to map return type `{}` to SQL type while building function `{}`.", ty_name, self.name))?,
+                                                            ty_resolved = sql_type,
                                                             needs_comma = if needs_comma { ", " } else { " " },
                                                             ty_name = ty_name
                                          );
@@ -231,7 +331,9 @@ impl ToSql for PgExternEntity {
                                      }
                                      format!("RETURNS TABLE ({}\n)", items)
                                  },
+                                 PgExternReturnEntity::Record(_) => String::from("RETURNS record"),
                                  PgExternReturnEntity::Trigger => String::from("RETURNS trigger"),
+                                 PgExternReturnEntity::EventTrigger { .. } => String::from("RETURNS event_trigger"),
                              },
                              search_path = if let Some(search_path) = &self.search_path {
                                  let retval = format!("SET search_path TO {}", search_path.join(", "));
@@ -240,37 +342,27 @@ impl ToSql for PgExternEntity {
                              extern_attrs = if extern_attrs.is_empty() {
                                  String::default()
                              } else {
-                                 let mut retval = extern_attrs.iter().map(|attr| format!("{}", attr).to_uppercase()).collect::<Vec<_>>().join(" ");
+                                 let mut retval = extern_attrs.iter().map(|attr| match attr {
+                                     ExternArgs::Support(support) => self.support_sql(context, support),
+                                     attr => Ok(format!("{}", attr)),
+                                 }).collect::<eyre::Result<Vec<_>>>()?.join(" ");
                                  retval.push('\n');
                                  retval
                              },
-        );
+            )
+        };
+        let event_trigger = self.event_trigger_sql(&schema);
 
-        let ext_sql = format!(
+        let header = format!(
             "\n\
                                 -- {file}:{line}\n\
                                 -- {module_path}::{name}\n\
                                 {requires}\
-                                {fn_sql}\
-                                {overridden}\
                             ",
             name = self.name,
             module_path = self.module_path,
             file = self.file,
             line = self.line,
-            fn_sql = if self.overridden.is_some() {
-                let mut inner = fn_sql
-                    .lines()
-                    .map(|f| format!("-- {}", f))
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                inner.push_str(
-                    "\n--\n-- Overridden as (due to a `///` comment with a `pgxsql` code block):",
-                );
-                inner
-            } else {
-                fn_sql
-            },
             requires = {
                 let requires_attrs = self
                     .extern_attrs
@@ -297,14 +389,28 @@ impl ToSql for PgExternEntity {
                     "".to_string()
                 }
             },
-            overridden = self
-                .overridden
-                .map(|f| String::from("\n") + f + "\n")
-                .unwrap_or_default(),
         );
-        tracing::debug!(sql = %ext_sql);
 
-        let rendered = match (self.overridden, &self.operator) {
+        let comment = if self.overridden.is_some() {
+            String::default()
+        } else {
+            comment_sql(
+                &format!(
+                    "{kind} {schema}\"{name}\"({signature})",
+                    kind = if self.procedure {
+                        "PROCEDURE"
+                    } else {
+                        "FUNCTION"
+                    },
+                    schema = schema,
+                    name = self.name,
+                    signature = signature.join(", "),
+                ),
+                self.doc,
+            )
+        };
+
+        let operator = match (self.overridden, &self.operator) {
             (None, Some(op)) => {
                 let mut optionals = vec![];
                 if let Some(it) = op.commutator {
@@ -368,17 +474,543 @@ impl ToSql for PgExternEntity {
                                            left_name = left_arg.full_path,
                                            right_name = right_arg.full_path,
                                            schema_prefix_left = context.schema_prefix_for(&left_arg_graph_index),
-                                           left_arg = context.type_id_to_sql_type(left_arg.ty_id).ok_or_else(|| eyre_err!("Failed to map argument `{}` type `{}` to SQL type while building operator `{}`.", left_arg.pattern, left_arg.full_path, self.name))?,
+                                           left_arg = left_arg.sql_type,
                                            schema_prefix_right = context.schema_prefix_for(&right_arg_graph_index),
-                                           right_arg = context.type_id_to_sql_type(right_arg.ty_id).ok_or_else(|| eyre_err!("Failed to map argument `{}` type `{}` to SQL type while building operator `{}`.", right_arg.pattern, right_arg.full_path, self.name))?,
+                                           right_arg = right_arg.sql_type,
                                            maybe_comma = if optionals.len() >= 1 { "," } else { "" },
                                            optionals = if !optionals.is_empty() { optionals.join(",\n") + "\n" } else { "".to_string() },
                 );
                 tracing::debug!(sql = %operator_sql);
-                ext_sql + &operator_sql
+                Some(operator_sql)
+            }
+            (None, None) | (Some(_), Some(_)) | (Some(_), None) => None,
+        };
+        let cast = match (self.overridden, self.cast) {
+            (None, Some(cast)) => {
+                let cast_sql = self.cast_sql(context, cast, &schema, &signature)?;
+                tracing::debug!(sql = %cast_sql);
+                Some(cast_sql)
             }
-            (None, None) | (Some(_), Some(_)) | (Some(_), None) => ext_sql,
+            (None, None) | (Some(_), Some(_)) | (Some(_), None) => None,
         };
-        Ok(rendered)
+
+        Ok(PgExternSql {
+            header,
+            function: fn_sql,
+            event_trigger,
+            comment,
+            operator,
+            cast,
+            schema,
+            signature,
+        })
+    }
+
+    /// The schema prefix and SQL type of the argument `arg`
+    pub(crate) fn arg_sql_type(
+        &self,
+        context: &super::PgxSql,
+        arg: &PgExternArgumentEntity,
+    ) -> eyre::Result<(String, String)> {
+        let graph_index = context
+            .graph
+            .neighbors_undirected(context.externs[self])
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(&arg.ty_id),
+                SqlGraphEntity::Enum(en) => en.id_matches(&arg.ty_id),
+                SqlGraphEntity::BuiltinType(defined) => defined == &arg.full_path,
+                _ => false,
+            })
+            .ok_or_else(|| eyre_err!("Could not find arg type in graph. Got: {:?}", arg))?;
+        let schema_prefix = context.schema_prefix_for(&graph_index);
+        Ok((schema_prefix, arg.sql_type.clone()))
+    }
+
+    /// The schema the function is created in, if it's not the extension's default
+    pub fn sql_schema(&self, context: &super::PgxSql) -> Option<String> {
+        self.schema
+            .map(String::from)
+            .or_else(|| context.schema_alias_of(&context.externs[self]))
+    }
+
+    /// The input argument types of the function, which identify it in SQL, such as
+    /// `text, integer`
+    pub fn sql_argument_types(&self, context: &super::PgxSql) -> eyre::Result<Vec<String>> {
+        self.fn_args
+            .iter()
+            .map(|arg| {
+                let (schema_prefix, sql_type) = self.arg_sql_type(context, arg)?;
+                let variadic = if arg.is_variadic { "VARIADIC " } else { "" };
+                Ok(format!("{}{}{}", variadic, schema_prefix, sql_type))
+            })
+            .collect()
+    }
+
+    /// The SQL signature of the function, as used by `COMMENT ON FUNCTION` or `DROP FUNCTION`,
+    /// such as `"hello"(text, integer)`
+    pub fn sql_signature(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        Ok(format!(
+            "{schema}\"{name}\"({arguments})",
+            schema = self
+                .sql_schema(context)
+                .map(|schema| schema + ".")
+                .unwrap_or_default(),
+            name = self.name,
+            arguments = self.sql_argument_types(context)?.join(", "),
+        ))
+    }
+
+    /// Is the function the input or output function of a type, which is created along with it?
+    pub(crate) fn is_type_io_fn(&self, context: &super::PgxSql) -> bool {
+        context
+            .graph
+            .neighbors_undirected(context.externs[self])
+            .any(|neighbor| {
+                let neighbor_item = &context.graph[neighbor];
+                match neighbor_item {
+                    SqlGraphEntity::Type(PostgresTypeEntity {
+                        in_fn,
+                        in_fn_module_path,
+                        out_fn,
+                        out_fn_module_path,
+                        ..
+                    }) => {
+                        let is_in_fn = self.full_path.starts_with(in_fn_module_path)
+                            && self.full_path.ends_with(in_fn);
+                        if is_in_fn {
+                            tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an in_fn.");
+                        }
+                        let is_out_fn = self.full_path.starts_with(out_fn_module_path)
+                            && self.full_path.ends_with(out_fn);
+                        if is_out_fn {
+                            tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an out_fn.");
+                        }
+                        is_in_fn || is_out_fn
+                    }
+                    _ => false,
+                }
+            })
+    }
+
+    /// The schema prefix of the type `id`, or the builtin type `name`, which the function uses
+    fn type_schema_prefix(
+        &self,
+        context: &super::PgxSql,
+        id: &TypeId,
+        name: &str,
+    ) -> Option<String> {
+        context
+            .graph
+            .neighbors_undirected(context.externs[self])
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(id),
+                SqlGraphEntity::Enum(en) => en.id_matches(id),
+                SqlGraphEntity::BuiltinType(defined) => defined == name,
+                _ => false,
+            })
+            .map(|index| context.schema_prefix_for(&index))
+    }
+
+    /// What the function returns, as in its `RETURNS` clause, such as `SETOF text` or
+    /// `TABLE ("a" integer, "b" text)`
+    pub(crate) fn return_type(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        Ok(match &self.fn_return {
+            PgExternReturnEntity::None => String::from("void"),
+            PgExternReturnEntity::Type {
+                id,
+                sql_type,
+                full_path,
+                ..
+            } => format!(
+                "{}{}",
+                self.type_schema_prefix(context, id, full_path)
+                    .ok_or_else(|| eyre_err!("Could not find return type in graph."))?,
+                sql_type
+            ),
+            PgExternReturnEntity::SetOf {
+                id,
+                sql_type,
+                full_path,
+                ..
+            } => format!(
+                "SETOF {}{}",
+                self.type_schema_prefix(context, id, full_path)
+                    .ok_or_else(|| eyre_err!("Could not find return type in graph."))?,
+                sql_type
+            ),
+            PgExternReturnEntity::Iterated(table_items) => format!(
+                "TABLE ({})",
+                table_items
+                    .iter()
+                    .map(|(id, sql_type, ty_name, _module_path, col_name)| format!(
+                        "\"{}\" {}{}",
+                        col_name.unwrap_or_default(),
+                        self.type_schema_prefix(context, id, ty_name)
+                            .unwrap_or_default(),
+                        sql_type
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            PgExternReturnEntity::Record(_) => String::from("record"),
+            PgExternReturnEntity::Trigger => String::from("trigger"),
+            PgExternReturnEntity::EventTrigger { .. } => String::from("event_trigger"),
+        })
+    }
+
+    /// The names and types of the `OUT` parameters of the function
+    pub(crate) fn out_param_types(
+        &self,
+        context: &super::PgxSql,
+    ) -> eyre::Result<Vec<(&'static str, String)>> {
+        Ok(self
+            .out_params()?
+            .iter()
+            .map(|(id, sql_type, ty_name, _module_path, col_name)| {
+                (
+                    col_name.expect("`OUT` parameters are named"),
+                    format!(
+                        "{}{}",
+                        self.type_schema_prefix(context, id, ty_name)
+                            .unwrap_or_default(),
+                        sql_type
+                    ),
+                )
+            })
+            .collect())
+    }
+
+    /// The volatility of the function: `immutable`, `stable`, or `volatile`, which is Postgres'
+    /// default
+    pub fn volatility(&self) -> &'static str {
+        if self.extern_attrs.contains(&ExternArgs::Immutable) {
+            "immutable"
+        } else if self.extern_attrs.contains(&ExternArgs::Stable) {
+            "stable"
+        } else {
+            "volatile"
+        }
+    }
+
+    /// The `CREATE EVENT TRIGGER` statement for a `#[pg_event_trigger]` with an `event`
+    fn event_trigger_sql(&self, schema: &str) -> Option<String> {
+        match &self.fn_return {
+            PgExternReturnEntity::EventTrigger {
+                event: Some(event),
+                tags,
+            } => Some(format!(
+                "\n\n\
+                    CREATE EVENT TRIGGER \"{name}\" ON {event}\n\
+                    {when}\
+                    EXECUTE PROCEDURE {schema}\"{name}\"();\
+                ",
+                name = self.name,
+                event = event,
+                when = if tags.is_empty() {
+                    String::default()
+                } else {
+                    format!(
+                        "WHEN TAG IN ({})\n",
+                        tags.iter()
+                            .map(|tag| format!("'{}'", tag.replace('\'', "''")))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                },
+                schema = schema,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The `LANGUAGE` and `AS` clauses of the function: its `_wrapper` in the extension's shared
+    /// library, or the body of a `#[pg_sql_function]`.
+    fn body_sql(&self) -> eyre::Result<String> {
+        match &self.sql_body {
+            None => Ok(format!(
+                "\
+                    LANGUAGE c /* Rust */\n\
+                    AS 'MODULE_PATHNAME', '{unaliased_name}_wrapper';\
+                ",
+                unaliased_name = self.unaliased_name,
+            )),
+            Some(PgSqlBodyEntity { body, .. }) if body.contains("$pgx$") => Err(eyre_err!(
+                "The body of function `{}` can not contain `$pgx$`, which quotes it.",
+                self.name
+            )),
+            Some(PgSqlBodyEntity { language, body }) => Ok(format!(
+                "\
+                    LANGUAGE {language}\n\
+                    AS $pgx${body}$pgx$;\
+                ",
+                language = language,
+                body = body,
+            )),
+        }
+    }
+
+    /// The `CREATE CAST` statement for a `#[pg_cast]`, from the type of the first argument to the
+    /// return type.
+    fn cast_sql(
+        &self,
+        context: &super::PgxSql,
+        cast: PgCastEntity,
+        schema: &str,
+        signature: &[String],
+    ) -> eyre::Result<String> {
+        let self_index = context.externs[self];
+        if self.procedure {
+            return Err(eyre_err!(
+                "Procedure `{}` can not be a `#[pg_cast]`.",
+                self.name
+            ));
+        }
+        // Besides the value, a cast function may take the type modifier and whether the cast is explicit
+        let extra_args_valid = match self.fn_args.as_slice() {
+            [_] => true,
+            [_, typmod] => typmod.ty_id == TypeId::of::<i32>(),
+            [_, typmod, explicit] => {
+                typmod.ty_id == TypeId::of::<i32>() && explicit.ty_id == TypeId::of::<bool>()
+            }
+            _ => false,
+        };
+        if !extra_args_valid {
+            return Err(eyre_err!(
+                "Cast function `{}` must take the value to cast, optionally followed by an `i32` type modifier and a `bool`.",
+                self.name
+            ));
+        }
+        let (id, target, full_path) = match &self.fn_return {
+            PgExternReturnEntity::Type {
+                id,
+                sql_type,
+                full_path,
+                ..
+            } => (id, sql_type, full_path),
+            _ => {
+                return Err(eyre_err!(
+                    "Cast function `{}` must return a single value.",
+                    self.name
+                ))
+            }
+        };
+        let graph_index = context
+            .graph
+            .neighbors_undirected(self_index)
+            .find(|neighbor| match &context.graph[*neighbor] {
+                SqlGraphEntity::Type(ty) => ty.id_matches(&id),
+                SqlGraphEntity::Enum(en) => en.id_matches(&id),
+                SqlGraphEntity::BuiltinType(defined) => defined == full_path,
+                _ => false,
+            })
+            .ok_or_else(|| eyre_err!("Could not find return type in graph."))?;
+        Ok(format!(
+            "\n\n\
+                -- {file}:{line}\n\
+                -- {module_path}::{unaliased_name}\n\
+                CREATE CAST ({source} AS {schema_prefix}{target})\n\
+                \tWITH FUNCTION {schema}\"{name}\"({signature}){cast_context};\
+            ",
+            file = self.file,
+            line = self.line,
+            module_path = self.module_path,
+            unaliased_name = self.unaliased_name,
+            source = signature[0],
+            schema_prefix = context.schema_prefix_for(&graph_index),
+            target = target,
+            schema = schema,
+            name = self.name,
+            signature = signature.join(", "),
+            cast_context = cast,
+        ))
+    }
+
+    /// The `SUPPORT` clause naming the planner support function `support`.
+    ///
+    /// A path refers to a `#[pg_extern]`, while a string is used as the SQL name of the function.
+    fn support_sql(
+        &self,
+        context: &super::PgxSql,
+        support: &PositioningRef,
+    ) -> eyre::Result<String> {
+        if cfg!(any(feature = "pg10", feature = "pg11")) {
+            return Err(eyre_err!(
+                "Function `{}` uses `support`, which requires Postgres 12 or later.",
+                self.name
+            ));
+        }
+        match support {
+            PositioningRef::FullPath(_) => {
+                let index = find_positioning_ref_target(
+                    support,
+                    &context.types,
+                    &context.enums,
+                    &context.externs,
+                    &context.schemas,
+                    &context.extension_sqls,
+                )
+                .ok_or_else(|| eyre_err!("Could not find `support` target: {:?}", support))?;
+                match &context.graph[*index] {
+                    SqlGraphEntity::Function(func) => Ok(format!(
+                        "SUPPORT {schema}\"{name}\"",
+                        schema = func
+                            .schema
+                            .map(|schema| format!("{}.", schema))
+                            .unwrap_or_else(|| context.schema_prefix_for(index)),
+                        name = func.name,
+                    )),
+                    _ => Err(eyre_err!(
+                        "The `support` target of function `{}` is not a function: {:?}",
+                        self.name,
+                        support
+                    )),
+                }
+            }
+            PositioningRef::Name(name) => Ok(format!("SUPPORT {}", name)),
+        }
+    }
+
+    /// Check that the `INOUT` arguments line up with what the function returns, and find the
+    /// `name!()`d columns of its returned tuple which are `OUT` parameters.
+    ///
+    /// The new values of `INOUT` arguments are returned either directly, or as the leading
+    /// columns of a tuple, in argument order.
+    fn out_params(
+        &self,
+    ) -> eyre::Result<&[(TypeId, String, &'static str, String, Option<&'static str>)]> {
+        let kind = if self.procedure {
+            "Procedure"
+        } else {
+            "Function"
+        };
+        let inout_args = self
+            .fn_args
+            .iter()
+            .filter(|arg| arg.is_inout)
+            .collect::<Vec<_>>();
+        match (&self.fn_return, inout_args.as_slice()) {
+            (PgExternReturnEntity::Record(columns), _) => {
+                if let Some((idx, _)) = columns
+                    .iter()
+                    .enumerate()
+                    .find(|(_, column)| column.4.is_none())
+                {
+                    return Err(eyre_err!(
+                        "{} `{}` returns a tuple whose column {} is not `name!()`d.",
+                        kind,
+                        self.name,
+                        idx + 1
+                    ));
+                }
+                if columns.len() < 2 {
+                    return Err(eyre_err!(
+                        "{} `{}` returns a tuple of a single column, return its value instead.",
+                        kind,
+                        self.name
+                    ));
+                }
+                for (idx, arg) in inout_args.iter().enumerate() {
+                    if columns.get(idx).and_then(|column| column.4) != Some(arg.pattern) {
+                        return Err(eyre_err!(
+                            "{} `{}` must return its `INOUT` arguments as the leading columns of its tuple, in order, but column {} is not `{}`.",
+                            kind,
+                            self.name,
+                            idx + 1,
+                            arg.pattern
+                        ));
+                    }
+                }
+                let out_params = &columns[inout_args.len()..];
+                if self.procedure
+                    && !out_params.is_empty()
+                    && !cfg!(feature = "pg14")
+                {
+                    return Err(eyre_err!(
+                        "Procedure `{}` returns columns which are not `INOUT` arguments, but `OUT` parameters of procedures require Postgres 14 or later.",
+                        self.name
+                    ));
+                }
+                Ok(out_params)
+            }
+            (PgExternReturnEntity::None, []) => Ok(&[]),
+            (_, []) if !self.procedure => Ok(&[]),
+            (PgExternReturnEntity::Type { sql_type, .. }, [arg]) => {
+                if sql_type != &arg.sql_type {
+                    return Err(eyre_err!(
+                        "{} `{}` returns `{}`, but its `INOUT` argument `{}` is `{}`.",
+                        kind,
+                        self.name,
+                        sql_type,
+                        arg.pattern,
+                        arg.sql_type
+                    ));
+                }
+                Ok(&[])
+            }
+            (_, []) => Err(eyre_err!(
+                "Procedure `{}` has no `INOUT` argument, so it can not return a value.",
+                self.name
+            )),
+            (_, [arg]) => Err(eyre_err!(
+                "{} `{}` must return the type of its `INOUT` argument `{}`, or a tuple with a `name!({}, ...)` column.",
+                kind,
+                self.name,
+                arg.pattern,
+                arg.pattern
+            )),
+            _ => Err(eyre_err!(
+                "{} `{}` has more than one `INOUT` argument, so it must return a tuple of them.",
+                kind,
+                self.name
+            )),
+        }
+    }
+
+    /// The `CREATE PROCEDURE` statement of a `#[pg_procedure]`.
+    fn procedure_sql(&self, schema: &str, arguments: &str) -> eyre::Result<String> {
+        // `CREATE PROCEDURE` rejects the volatility, strictness, etc. markings of a function
+        if let Some(attr) = self.extern_attrs.iter().find(|attr| match attr {
+            ExternArgs::Raw
+            | ExternArgs::NoGuard
+            | ExternArgs::NoComment
+            | ExternArgs::Error(_)
+            | ExternArgs::Schema(_)
+            | ExternArgs::Name(_)
+            | ExternArgs::Requires(_)
+            | ExternArgs::SecurityDefiner
+            | ExternArgs::Set(_) => false,
+            _ => true,
+        }) {
+            return Err(eyre_err!(
+                "Procedure `{}` does not support the `{:?}` attribute.",
+                self.name,
+                attr
+            ));
+        }
+
+        Ok(format!(
+            "\
+                CREATE OR REPLACE PROCEDURE {schema}\"{name}\"({arguments})\n\
+                {extern_attrs}\
+                {search_path}\
+                LANGUAGE c /* Rust */\n\
+                AS 'MODULE_PATHNAME', '{unaliased_name}_wrapper';\
+            ",
+            schema = schema,
+            name = self.name,
+            arguments = arguments,
+            extern_attrs = self
+                .extern_attrs
+                .iter()
+                .filter(|attr| matches!(attr, ExternArgs::SecurityDefiner | ExternArgs::Set(_)))
+                .map(|attr| format!("{}\n", attr))
+                .collect::<String>(),
+            search_path = if let Some(search_path) = &self.search_path {
+                format!("SET search_path TO {}\n", search_path.join(", "))
+            } else {
+                Default::default()
+            },
+            unaliased_name = self.unaliased_name,
+        ))
     }
 }
@@ -5,24 +5,38 @@ pub enum PgExternReturnEntity {
     None,
     Type {
         id: TypeId,
-        source: &'static str,
+        sql_type: String,
         full_path: &'static str,
         module_path: String,
     },
     SetOf {
         id: TypeId,
-        source: &'static str,
+        sql_type: String,
         full_path: &'static str,
         module_path: String,
     },
     Iterated(
         Vec<(
             TypeId,
-            &'static str,         // Source
+            String,               // SQL type
+            &'static str,         // Full path
+            String,               // Module path
+            Option<&'static str>, // Name
+        )>,
+    ),
+    /// A single row, whose columns are `OUT` (or `INOUT`) parameters
+    Record(
+        Vec<(
+            TypeId,
+            String,               // SQL type
             &'static str,         // Full path
             String,               // Module path
             Option<&'static str>, // Name
         )>,
     ),
     Trigger,
+    EventTrigger {
+        event: Option<&'static str>,
+        tags: Vec<&'static str>,
+    },
 }
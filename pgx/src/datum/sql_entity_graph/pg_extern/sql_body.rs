@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// The output of a [`PgSqlFunction`](crate::datum::sql_entity_graph::PgSqlFunction) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PgSqlBodyEntity {
+    pub language: &'static str,
+    pub body: &'static str,
+}
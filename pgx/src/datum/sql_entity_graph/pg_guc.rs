@@ -0,0 +1,53 @@
+use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql};
+
+/// The output of a [`PgGuc`](crate::datum::sql_entity_graph::PgGuc) from `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PgGucEntity {
+    /// The name of the GUC, such as `myext.threshold`
+    pub name: &'static str,
+    pub short_description: &'static str,
+    pub long_description: Option<&'static str>,
+    /// As in the `vartype` column of `pg_settings`, such as `integer`
+    pub vartype: &'static str,
+    /// As in the `context` column of `pg_settings`, such as `superuser`
+    pub context: &'static str,
+    /// Such as `ms` or `kB`
+    pub unit: Option<&'static str>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub file: &'static str,
+    pub line: u32,
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+}
+
+impl From<PgGucEntity> for SqlGraphEntity {
+    fn from(guc: PgGucEntity) -> Self {
+        SqlGraphEntity::Guc(guc)
+    }
+}
+
+impl SqlGraphIdentifier for PgGucEntity {
+    fn dot_identifier(&self) -> String {
+        format!("guc {}", self.name)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgGucEntity {
+    /// GUCs are defined when the extension is loaded rather than by SQL, they're only part of the
+    /// graph to be documented.
+    fn to_sql(&self, _context: &super::PgxSql) -> eyre::Result<String> {
+        Ok(String::default())
+    }
+}
@@ -0,0 +1,171 @@
+use eyre::eyre as eyre_err;
+
+use super::{AggregateTypeEntity, SqlGraphEntity, SqlGraphIdentifier, ToSql};
+use std::cmp::Ordering;
+
+/// The output of a [`PgGistOpClass`](crate::datum::sql_entity_graph::PgGistOpClass) from
+/// `quote::ToTokens::to_tokens`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PgOpClassEntity {
+    pub full_path: &'static str,
+    pub module_path: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub name: &'static str,
+    /// The index access method, such as `gist`
+    pub method: &'static str,
+    pub default: bool,
+    /// The indexed type (`FOR TYPE`)
+    pub ty: AggregateTypeEntity,
+    /// The type stored in the index (`STORAGE`), if it's not `ty`
+    pub storage: Option<AggregateTypeEntity>,
+    /// The right-hand argument of the operators
+    pub right_ty: AggregateTypeEntity,
+    /// The search operators, by strategy number
+    pub operators: Vec<(u16, &'static str)>,
+    /// The ordering operators, by strategy number
+    pub order_by_operators: Vec<(u16, &'static str)>,
+    /// The names of the `#[pg_extern]` support functions, by support function number
+    pub functions: Vec<(u16, &'static str)>,
+}
+
+impl PgOpClassEntity {
+    /// The names of the `#[pg_extern]` support functions this operator class uses.
+    pub fn support_fns(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.functions.iter().map(|(_, name)| *name)
+    }
+
+    /// The operators this operator class uses, with or without an ordering purpose.
+    pub fn used_operators(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.operators
+            .iter()
+            .chain(self.order_by_operators.iter())
+            .map(|(_, opname)| *opname)
+    }
+
+    /// Every type this operator class uses.
+    pub fn used_types(&self) -> impl Iterator<Item = &AggregateTypeEntity> {
+        std::iter::once(&self.ty)
+            .chain(self.storage.iter())
+            .chain(std::iter::once(&self.right_ty))
+    }
+
+    fn support_fn_sql(&self, context: &super::PgxSql, fn_name: &str) -> eyre::Result<String> {
+        let (item, index) = context
+            .externs
+            .iter()
+            .find(|(item, _)| {
+                item.module_path == self.module_path && item.unaliased_name == fn_name
+            })
+            .ok_or_else(|| {
+                eyre_err!(
+                    "Could not find support function `{}` of operator class `{}`.",
+                    fn_name,
+                    self.full_path
+                )
+            })?;
+        Ok(format!(
+            "{schema}\"{name}\"",
+            schema = item
+                .schema
+                .map(|schema| format!("{}.", schema))
+                .unwrap_or_else(|| context.schema_prefix_for(index)),
+            name = item.name,
+        ))
+    }
+}
+
+impl Ord for PgOpClassEntity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.file
+            .cmp(other.file)
+            .then_with(|| self.line.cmp(&other.line))
+    }
+}
+
+impl PartialOrd for PgOpClassEntity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<PgOpClassEntity> for SqlGraphEntity {
+    fn from(opclass: PgOpClassEntity) -> Self {
+        SqlGraphEntity::OpClass(opclass)
+    }
+}
+
+impl SqlGraphIdentifier for PgOpClassEntity {
+    fn dot_identifier(&self) -> String {
+        format!("opclass {}", self.full_path)
+    }
+    fn rust_identifier(&self) -> String {
+        self.full_path.to_string()
+    }
+
+    fn file(&self) -> Option<&'static str> {
+        Some(self.file)
+    }
+
+    fn line(&self) -> Option<u32> {
+        Some(self.line)
+    }
+}
+
+impl ToSql for PgOpClassEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let self_index = context.opclasses[self];
+
+        let ty = self.ty.to_sql_type(context)?;
+        let right_ty = self.right_ty.to_sql_type(context)?;
+
+        let mut items = Vec::new();
+        for (strategy, opname) in &self.operators {
+            items.push(format!(
+                "\tOPERATOR {} {} ({}, {})",
+                strategy, opname, ty, right_ty
+            ));
+        }
+        for (strategy, opname) in &self.order_by_operators {
+            items.push(format!(
+                "\tOPERATOR {} {} ({}, {}) FOR ORDER BY pg_catalog.float_ops",
+                strategy, opname, ty, right_ty
+            ));
+        }
+        for (number, fn_name) in &self.functions {
+            items.push(format!(
+                "\tFUNCTION {} {}",
+                number,
+                self.support_fn_sql(context, fn_name)?
+            ));
+        }
+        if let Some(storage) = &self.storage {
+            items.push(format!(
+                "\tSTORAGE {} /* {} */",
+                storage.to_sql_type(context)?,
+                storage.full_path
+            ));
+        }
+
+        let sql = format!(
+            "\n\
+                -- {file}:{line}\n\
+                -- {full_path}\n\
+                CREATE OPERATOR CLASS {schema}{name}{default} FOR TYPE {ty} USING {method} AS\n\
+                    {items};\
+            ",
+            schema = context.schema_prefix_for(&self_index),
+            name = self.name,
+            default = if self.default { " DEFAULT" } else { "" },
+            ty = ty,
+            method = self.method,
+            full_path = self.full_path,
+            file = self.file,
+            line = self.line,
+            items = items.join(",\n"),
+        );
+        tracing::debug!(%sql);
+        Ok(sql)
+    }
+}
@@ -1,21 +1,21 @@
 use eyre::eyre as eyre_err;
-use std::{any::TypeId, collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug};
 
 use petgraph::{dot::Dot, graph::NodeIndex, stable_graph::StableGraph};
 use tracing::instrument;
 
 use super::{
-    ControlFile, ExtensionSqlEntity, PgExternEntity, PgExternReturnEntity, PositioningRef,
-    PostgresEnumEntity, PostgresHashEntity, PostgresOrdEntity, PostgresTypeEntity,
-    RustSourceOnlySqlMapping, RustSqlMapping, SchemaEntity, SqlDeclaredEntity, SqlGraphEntity,
-    SqlGraphIdentifier, ToSql,
+    check, ControlFile, ExtensionSqlEntity, PgAggregateEntity, PgExternEntity,
+    PgExternReturnEntity, PgGucEntity, PgOpClassEntity, PositioningRef, PostgresEnumEntity,
+    PostgresHashEntity, PostgresOrdEntity, PostgresTypeEntity, SchemaEntity, SqlDeclaredEntity,
+    SqlGraphEntity, SqlGraphIdentifier, SqlManifest, SqlSnapshot, SqlUpgrade, ToSql,
+    SQL_SNAPSHOT_VERSION,
 };
 use pgx_utils::sql_entity_graph::SqlDeclared;
 
 /// A generator for SQL.
 ///
-/// Consumes a base mapping of types (typically `pgx::DEFAULT_TYPEID_SQL_MAPPING`), a
-/// [`ControlFile`], and collections of each SQL entity.
+/// Consumes a [`ControlFile`] and collections of each SQL entity.
 ///
 /// During construction, a Directed Acyclic Graph is formed out the dependencies. For example,
 /// an item `detect_dog(x: &[u8]) -> animals::Dog` would have have a relationship with
@@ -25,11 +25,6 @@ use pgx_utils::sql_entity_graph::SqlDeclared;
 /// out of entities collected during a `pgx::pg_module_magic!()` call in a library.
 #[derive(Debug, Clone)]
 pub struct PgxSql {
-    // This is actually the Debug format of a TypeId!
-    //
-    // This is not a good idea, but without a stable way to create or serialize TypeIds, we have to.
-    pub type_mappings: HashMap<TypeId, RustSqlMapping>,
-    pub source_mappings: HashMap<String, RustSourceOnlySqlMapping>,
     pub control: ControlFile,
     pub graph: StableGraph<SqlGraphEntity, SqlGraphRelationship>,
     pub graph_root: NodeIndex,
@@ -43,6 +38,9 @@ pub struct PgxSql {
     pub enums: HashMap<PostgresEnumEntity, NodeIndex>,
     pub ords: HashMap<PostgresOrdEntity, NodeIndex>,
     pub hashes: HashMap<PostgresHashEntity, NodeIndex>,
+    pub aggregates: HashMap<PgAggregateEntity, NodeIndex>,
+    pub opclasses: HashMap<PgOpClassEntity, NodeIndex>,
+    pub gucs: HashMap<PgGucEntity, NodeIndex>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -53,12 +51,8 @@ pub enum SqlGraphRelationship {
 }
 
 impl PgxSql {
-    #[instrument(level = "error", skip(type_mappings, source_mappings, entities,))]
-    pub fn build(
-        type_mappings: impl Iterator<Item = RustSqlMapping>,
-        source_mappings: impl Iterator<Item = RustSourceOnlySqlMapping>,
-        entities: impl Iterator<Item = SqlGraphEntity>,
-    ) -> eyre::Result<Self> {
+    #[instrument(level = "error", skip(entities,))]
+    pub fn build(entities: impl Iterator<Item = SqlGraphEntity>) -> eyre::Result<Self> {
         let mut graph = StableGraph::new();
 
         let mut entities = entities.collect::<Vec<_>>();
@@ -72,6 +66,9 @@ impl PgxSql {
         let mut enums: Vec<PostgresEnumEntity> = Vec::default();
         let mut ords: Vec<PostgresOrdEntity> = Vec::default();
         let mut hashes: Vec<PostgresHashEntity> = Vec::default();
+        let mut aggregates: Vec<PgAggregateEntity> = Vec::default();
+        let mut opclasses: Vec<PgOpClassEntity> = Vec::default();
+        let mut gucs: Vec<PgGucEntity> = Vec::default();
         for entity in entities {
             match entity {
                 SqlGraphEntity::ExtensionRoot(input_control) => {
@@ -99,6 +96,15 @@ impl PgxSql {
                 SqlGraphEntity::Hash(input_hash) => {
                     hashes.push(input_hash);
                 }
+                SqlGraphEntity::Aggregate(input_aggregate) => {
+                    aggregates.push(input_aggregate);
+                }
+                SqlGraphEntity::OpClass(input_opclass) => {
+                    opclasses.push(input_opclass);
+                }
+                SqlGraphEntity::Guc(input_guc) => {
+                    gucs.push(input_guc);
+                }
             }
         }
 
@@ -129,6 +135,11 @@ impl PgxSql {
         )?;
         let mapped_ords = initialize_ords(&mut graph, root, bootstrap, finalize, ords)?;
         let mapped_hashes = initialize_hashes(&mut graph, root, bootstrap, finalize, hashes)?;
+        let mapped_aggregates =
+            initialize_aggregates(&mut graph, root, bootstrap, finalize, aggregates)?;
+        let mapped_opclasses =
+            initialize_opclasses(&mut graph, root, bootstrap, finalize, opclasses)?;
+        let mapped_gucs = initialize_gucs(&mut graph, root, bootstrap, finalize, gucs)?;
 
         // Now we can circle back and build up the edge sets.
         connect_schemas(&mut graph, &mapped_schemas, root);
@@ -165,10 +176,24 @@ impl PgxSql {
             &mapped_types,
             &mapped_enums,
         );
+        connect_aggregates(
+            &mut graph,
+            &mapped_aggregates,
+            &mapped_schemas,
+            &mapped_types,
+            &mapped_enums,
+            &mapped_externs,
+        )?;
+        connect_opclasses(
+            &mut graph,
+            &mapped_opclasses,
+            &mapped_schemas,
+            &mapped_types,
+            &mapped_enums,
+            &mapped_externs,
+        )?;
 
-        let mut this = Self {
-            type_mappings: type_mappings.map(|x| (x.id.clone(), x)).collect(),
-            source_mappings: source_mappings.map(|x| (x.rust.clone(), x)).collect(),
+        let this = Self {
             control: control,
             schemas: mapped_schemas,
             extension_sqls: mapped_extension_sqls,
@@ -178,12 +203,14 @@ impl PgxSql {
             enums: mapped_enums,
             ords: mapped_ords,
             hashes: mapped_hashes,
+            aggregates: mapped_aggregates,
+            opclasses: mapped_opclasses,
+            gucs: mapped_gucs,
             graph: graph,
             graph_root: root,
             graph_bootstrap: bootstrap,
             graph_finalize: finalize,
         };
-        this.register_types();
         Ok(this)
     }
 
@@ -206,6 +233,111 @@ impl PgxSql {
         Ok(())
     }
 
+    /// Compare the SQL generated for the extension to `file`, the committed SQL file, ignoring
+    /// comments, whitespace and the order of statements.  Returns a unified diff of them if they
+    /// differ.
+    #[instrument(level = "error", skip(self))]
+    pub fn diff_file(&self, file: impl AsRef<str> + Debug) -> eyre::Result<Option<String>> {
+        use std::fs::read_to_string;
+        let committed = read_to_string(file.as_ref())
+            .map_err(|e| eyre_err!("Couldn't read `{}`: {}", file.as_ref(), e))?;
+        let generated = self.to_sql()?;
+        Ok(check::sql_diff(&committed, &generated, file.as_ref()))
+    }
+
+    /// Write the upgrade script to this version of the extension from `from`, the [`SqlSnapshot`]
+    /// file of an earlier version, to `file`.  Returns the changes which need to be migrated by
+    /// hand.
+    ///
+    /// See [`SqlUpgrade`].
+    #[instrument(level = "error", skip(self))]
+    pub fn to_upgrade_file(
+        &self,
+        file: impl AsRef<str> + Debug,
+        from: impl AsRef<str> + Debug,
+    ) -> eyre::Result<Vec<String>> {
+        use std::{
+            fs::{create_dir_all, read_to_string, File},
+            io::Write,
+            path::Path,
+        };
+        let snapshot = read_to_string(from.as_ref())
+            .map_err(|e| eyre_err!("Couldn't read `{}`: {}", from.as_ref(), e))?;
+        let snapshot: SqlSnapshot = serde_json::from_str(&snapshot)?;
+        if snapshot.version != SQL_SNAPSHOT_VERSION {
+            return Err(eyre_err!(
+                "`{}` is a version {} snapshot, but version {} is expected.",
+                from.as_ref(),
+                snapshot.version,
+                SQL_SNAPSHOT_VERSION
+            ));
+        }
+        let upgrade = self.to_upgrade_sql(&snapshot)?;
+        let path = Path::new(file.as_ref());
+
+        let parent = path.parent();
+        if let Some(parent) = parent {
+            create_dir_all(parent)?;
+        }
+        let mut out = File::create(path)?;
+        write!(out, "{}", upgrade.sql)?;
+        Ok(upgrade.warnings)
+    }
+
+    /// Write the [`SqlSnapshot`] of the extension, as JSON, to `file`, to generate upgrade scripts
+    /// from this version of it later.
+    #[instrument(level = "error", err, skip(self))]
+    pub fn to_snapshot_file(&self, file: impl AsRef<str> + Debug) -> eyre::Result<()> {
+        use std::{
+            fs::{create_dir_all, File},
+            io::Write,
+            path::Path,
+        };
+        let generated = serde_json::to_string_pretty(&self.to_snapshot()?)?;
+        let path = Path::new(file.as_ref());
+
+        let parent = path.parent();
+        if let Some(parent) = parent {
+            create_dir_all(parent)?;
+        }
+        let mut out = File::create(path)?;
+        writeln!(out, "{}", generated)?;
+        Ok(())
+    }
+
+    /// The [`SqlSnapshot`] of the extension's SQL objects.
+    pub fn to_snapshot(&self) -> eyre::Result<SqlSnapshot> {
+        SqlSnapshot::build(self)
+    }
+
+    /// Write the [`SqlManifest`] of the extension, as JSON, to `file`.
+    #[instrument(level = "error", err, skip(self))]
+    pub fn to_json(&self, file: impl AsRef<str> + Debug) -> eyre::Result<()> {
+        use std::{
+            fs::{create_dir_all, File},
+            io::Write,
+            path::Path,
+        };
+        let generated = serde_json::to_string_pretty(&self.to_manifest()?)?;
+        let path = Path::new(file.as_ref());
+
+        let parent = path.parent();
+        if let Some(parent) = parent {
+            create_dir_all(parent)?;
+        }
+        let mut out = File::create(path)?;
+        writeln!(out, "{}", generated)?;
+        Ok(())
+    }
+
+    /// A machine readable description of the entities of the extension and their dependencies.
+    ///
+    /// See [`SqlManifest`].
+    #[instrument(level = "error", skip(self))]
+    pub fn to_manifest(&self) -> eyre::Result<SqlManifest> {
+        SqlManifest::build(self)
+    }
+
     #[instrument(level = "error", err, skip(self))]
     pub fn to_dot(&self, file: impl AsRef<str> + Debug) -> eyre::Result<()> {
         use std::{
@@ -257,6 +389,18 @@ impl PgxSql {
                         "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#FFE4E0\", weight = 5, shape = \"diamond\"",
                         node.dot_identifier()
                     ),
+                    SqlGraphEntity::Aggregate(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#D3C4BE\", weight = 5, shape = \"hexagon\"",
+                        node.dot_identifier()
+                    ),
+                    SqlGraphEntity::OpClass(_item) => format!(
+                        "label = \"{}\", penwidth = 0, style = \"filled\", fillcolor = \"#F5E0DC\", weight = 5, shape = \"house\"",
+                        node.dot_identifier()
+                    ),
+                    SqlGraphEntity::Guc(_item) => format!(
+                        "label = \"{}\", weight = 2, shape = \"note\"",
+                        node.dot_identifier()
+                    ),
                     SqlGraphEntity::CustomSql(_item) => format!(
                         "label = \"{}\", weight = 3, shape = \"signature\"",
                         node.dot_identifier()
@@ -320,30 +464,23 @@ impl PgxSql {
         Ok(full_sql)
     }
 
-    #[instrument(level = "error", skip(self))]
-    pub fn register_types(&mut self) {
-        for (item, _index) in self.enums.clone() {
-            for mapping in &item.mappings {
-                assert_eq!(
-                    self.type_mappings
-                        .insert(mapping.id.clone(), mapping.clone()),
-                    None,
-                    "Cannot map `{}` twice.",
-                    item.full_path,
-                );
-            }
-        }
-        for (item, _index) in self.types.clone() {
-            for mapping in &item.mappings {
-                assert_eq!(
-                    self.type_mappings
-                        .insert(mapping.id.clone(), mapping.clone()),
-                    None,
-                    "Cannot map `{}` twice.",
-                    item.full_path,
-                );
-            }
-        }
+    /// The upgrade script to this version of the extension from `from`, the snapshot of an
+    /// earlier version.
+    #[instrument(level = "error", skip(self, from))]
+    pub fn to_upgrade_sql(&self, from: &SqlSnapshot) -> eyre::Result<SqlUpgrade> {
+        let mut upgrade = SqlUpgrade::between(from, &self.to_snapshot()?);
+        upgrade.sql = format!(
+            "\
+            /* \n\
+            This file is auto generated by pgx.\n\
+            \n\
+            It upgrades the extension from an earlier version, and should be reviewed before it is released.\n\
+            */\n\
+            {}\
+        ",
+            upgrade.sql
+        );
+        Ok(upgrade)
     }
 
     pub fn has_sql_declared_entity(&self, identifier: &SqlDeclared) -> Option<&SqlDeclaredEntity> {
@@ -358,26 +495,6 @@ impl PgxSql {
             retval
         })
     }
-
-    pub fn type_id_to_sql_type(&self, id: TypeId) -> Option<String> {
-        self.type_mappings.get(&id).map(|f| f.sql.clone())
-    }
-
-    pub fn source_only_to_sql_type(&self, ty_source: &str) -> Option<String> {
-        self.source_mappings.get(ty_source).map(|f| f.sql.clone())
-    }
-
-    pub fn map_type_to_sql_type<T: 'static>(&mut self, sql: impl AsRef<str> + Debug) {
-        let sql = sql.as_ref().to_string();
-        self.type_mappings.insert(
-            TypeId::of::<T>(),
-            RustSqlMapping {
-                rust: core::any::type_name::<T>().to_string(),
-                sql: sql.clone(),
-                id: core::any::TypeId::of::<T>(),
-            },
-        );
-    }
 }
 
 fn build_base_edges(
@@ -690,7 +807,9 @@ fn initialize_externs(
         }
 
         match &item.fn_return {
-            PgExternReturnEntity::None | PgExternReturnEntity::Trigger => (),
+            PgExternReturnEntity::None
+            | PgExternReturnEntity::Trigger
+            | PgExternReturnEntity::EventTrigger { .. } => (),
             PgExternReturnEntity::Type { id, full_path, .. }
             | PgExternReturnEntity::SetOf { id, full_path, .. } => {
                 let mut found = false;
@@ -714,7 +833,8 @@ fn initialize_externs(
                         });
                 }
             }
-            PgExternReturnEntity::Iterated(iterated_returns) => {
+            PgExternReturnEntity::Iterated(iterated_returns)
+            | PgExternReturnEntity::Record(iterated_returns) => {
                 for iterated_return in iterated_returns {
                     let mut found = false;
                     for (ty_item, &_ty_index) in mapped_types {
@@ -731,10 +851,10 @@ fn initialize_externs(
                     }
                     if !found {
                         mapped_builtin_types
-                            .entry(iterated_return.1.to_string())
+                            .entry(iterated_return.2.to_string())
                             .or_insert_with(|| {
                                 graph.add_node(SqlGraphEntity::BuiltinType(
-                                    iterated_return.1.to_string(),
+                                    iterated_return.2.to_string(),
                                 ))
                             });
                     }
@@ -785,6 +905,21 @@ fn connect_externs(
                         }
                     }
                 }
+                pgx_utils::ExternArgs::Support(support @ PositioningRef::FullPath(_)) => {
+                    if let Some(target) = find_positioning_ref_target(
+                        support,
+                        types,
+                        enums,
+                        externs,
+                        schemas,
+                        extension_sqls,
+                    ) {
+                        tracing::debug!(from = %item.rust_identifier(), to = %graph[*target].rust_identifier(), "Adding Extern after support function edge");
+                        graph.add_edge(*target, index, SqlGraphRelationship::RequiredBy);
+                    } else {
+                        return Err(eyre_err!("Could not find `support` target: {:?}", support));
+                    }
+                }
                 _ => (),
             }
         }
@@ -833,7 +968,9 @@ fn connect_externs(
             }
         }
         match &item.fn_return {
-            PgExternReturnEntity::None | PgExternReturnEntity::Trigger => (),
+            PgExternReturnEntity::None
+            | PgExternReturnEntity::Trigger
+            | PgExternReturnEntity::EventTrigger { .. } => (),
             PgExternReturnEntity::Type { id, full_path, .. }
             | PgExternReturnEntity::SetOf { id, full_path, .. } => {
                 let mut found = false;
@@ -882,7 +1019,8 @@ fn connect_externs(
                     }
                 }
             }
-            PgExternReturnEntity::Iterated(iterated_returns) => {
+            PgExternReturnEntity::Iterated(iterated_returns)
+            | PgExternReturnEntity::Record(iterated_returns) => {
                 for iterated_return in iterated_returns {
                     let mut found = false;
                     for (ty_item, &ty_index) in types {
@@ -909,12 +1047,12 @@ fn connect_externs(
                     }
                     if !found {
                         let builtin_index = builtin_types
-                            .get(&iterated_return.1.to_string())
+                            .get(&iterated_return.2.to_string())
                             .expect(&format!(
                                 "Could not fetch Builtin Type {}.",
-                                iterated_return.1
+                                iterated_return.2
                             ));
-                        tracing::debug!(from = %item.rust_identifier(), to = iterated_return.1, "Adding Extern after BuiltIn Type (due to return) edge");
+                        tracing::debug!(from = %item.rust_identifier(), to = iterated_return.2, "Adding Extern after BuiltIn Type (due to return) edge");
                         graph.add_edge(
                             *builtin_index,
                             index,
@@ -924,18 +1062,18 @@ fn connect_externs(
                     if !found {
                         for (ext_item, ext_index) in extension_sqls {
                             if let Some(_) = ext_item.has_sql_declared_entity(&SqlDeclared::Type(
-                                iterated_return.1.to_string(),
+                                iterated_return.2.to_string(),
                             )) {
-                                tracing::debug!(from = %item.rust_identifier(), to = iterated_return.1, "Adding Extern(arg) after Extension SQL (due to argument) edge");
+                                tracing::debug!(from = %item.rust_identifier(), to = iterated_return.2, "Adding Extern(arg) after Extension SQL (due to argument) edge");
                                 graph.add_edge(
                                     *ext_index,
                                     index,
                                     SqlGraphRelationship::RequiredByArg,
                                 );
                             } else if let Some(_) = ext_item.has_sql_declared_entity(
-                                &SqlDeclared::Enum(iterated_return.1.to_string()),
+                                &SqlDeclared::Enum(iterated_return.2.to_string()),
                             ) {
-                                tracing::debug!(from = %item.rust_identifier(), to = iterated_return.1, "Adding Extern(arg) after Extension SQL (due to argument) edge");
+                                tracing::debug!(from = %item.rust_identifier(), to = iterated_return.2, "Adding Extern(arg) after Extension SQL (due to argument) edge");
                                 graph.add_edge(
                                     *ext_index,
                                     index,
@@ -1048,3 +1186,181 @@ fn connect_hashes(
         }
     }
 }
+
+fn initialize_aggregates(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    aggregates: Vec<PgAggregateEntity>,
+) -> eyre::Result<HashMap<PgAggregateEntity, NodeIndex>> {
+    let mut mapped_aggregates = HashMap::default();
+    for item in aggregates {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_aggregates.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_aggregates)
+}
+
+fn connect_aggregates(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    aggregates: &HashMap<PgAggregateEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    types: &HashMap<PostgresTypeEntity, NodeIndex>,
+    enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    externs: &HashMap<PgExternEntity, NodeIndex>,
+) -> eyre::Result<()> {
+    for (item, &index) in aggregates {
+        for (schema_item, &schema_index) in schemas {
+            if item.module_path == schema_item.module_path {
+                tracing::debug!(from = %item.rust_identifier(), to = schema_item.module_path, "Adding Aggregate after Schema edge.");
+                graph.add_edge(schema_index, index, SqlGraphRelationship::RequiredBy);
+                break;
+            }
+        }
+        for support_fn in item.support_fns() {
+            let extern_index = externs
+                .iter()
+                .find(|(extern_item, _)| {
+                    extern_item.module_path == item.module_path
+                        && extern_item.unaliased_name == support_fn
+                })
+                .map(|(_, &extern_index)| extern_index)
+                .ok_or_else(|| {
+                    eyre_err!(
+                        "Could not find support function `{}` of aggregate `{}`.",
+                        support_fn,
+                        item.rust_identifier()
+                    )
+                })?;
+            tracing::debug!(from = %item.rust_identifier(), to = support_fn, "Adding Aggregate after Extern edge.");
+            graph.add_edge(extern_index, index, SqlGraphRelationship::RequiredBy);
+        }
+        for used_type in item.used_types() {
+            for (ty_item, &ty_index) in types {
+                if ty_item.id_matches(&used_type.ty_id) {
+                    tracing::debug!(from = %item.rust_identifier(), to = ty_item.full_path, "Adding Aggregate after Type edge.");
+                    graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+                    break;
+                }
+            }
+            for (ty_item, &ty_index) in enums {
+                if ty_item.id_matches(&used_type.ty_id) {
+                    tracing::debug!(from = %item.rust_identifier(), to = ty_item.full_path, "Adding Aggregate after Enum edge.");
+                    graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn initialize_opclasses(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    opclasses: Vec<PgOpClassEntity>,
+) -> eyre::Result<HashMap<PgOpClassEntity, NodeIndex>> {
+    let mut mapped_opclasses = HashMap::default();
+    for item in opclasses {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_opclasses.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_opclasses)
+}
+
+fn connect_opclasses(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    opclasses: &HashMap<PgOpClassEntity, NodeIndex>,
+    schemas: &HashMap<SchemaEntity, NodeIndex>,
+    types: &HashMap<PostgresTypeEntity, NodeIndex>,
+    enums: &HashMap<PostgresEnumEntity, NodeIndex>,
+    externs: &HashMap<PgExternEntity, NodeIndex>,
+) -> eyre::Result<()> {
+    for (item, &index) in opclasses {
+        for (schema_item, &schema_index) in schemas {
+            if item.module_path == schema_item.module_path {
+                tracing::debug!(from = %item.rust_identifier(), to = schema_item.module_path, "Adding OpClass after Schema edge.");
+                graph.add_edge(schema_index, index, SqlGraphRelationship::RequiredBy);
+                break;
+            }
+        }
+        for support_fn in item.support_fns() {
+            let extern_index = externs
+                .iter()
+                .find(|(extern_item, _)| {
+                    extern_item.module_path == item.module_path
+                        && extern_item.unaliased_name == support_fn
+                })
+                .map(|(_, &extern_index)| extern_index)
+                .ok_or_else(|| {
+                    eyre_err!(
+                        "Could not find support function `{}` of operator class `{}`.",
+                        support_fn,
+                        item.rust_identifier()
+                    )
+                })?;
+            tracing::debug!(from = %item.rust_identifier(), to = support_fn, "Adding OpClass after Extern edge.");
+            graph.add_edge(extern_index, index, SqlGraphRelationship::RequiredBy);
+        }
+        // The operators themselves are `#[pg_operator]`s taking the indexed type on the left.
+        for opname in item.used_operators() {
+            for (extern_item, &extern_index) in externs {
+                let is_operator = extern_item
+                    .operator
+                    .as_ref()
+                    .map(|operator| operator.opname == Some(opname))
+                    .unwrap_or(false);
+                let takes_ty = extern_item
+                    .fn_args
+                    .get(0)
+                    .map(|arg| arg.ty_id == item.ty.ty_id)
+                    .unwrap_or(false);
+                if is_operator && takes_ty {
+                    tracing::debug!(from = %item.rust_identifier(), to = %extern_item.rust_identifier(), "Adding OpClass after Operator edge.");
+                    graph.add_edge(extern_index, index, SqlGraphRelationship::RequiredBy);
+                }
+            }
+        }
+        for used_type in item.used_types() {
+            for (ty_item, &ty_index) in types {
+                if ty_item.id_matches(&used_type.ty_id) {
+                    tracing::debug!(from = %item.rust_identifier(), to = ty_item.full_path, "Adding OpClass after Type edge.");
+                    graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+                    break;
+                }
+            }
+            for (ty_item, &ty_index) in enums {
+                if ty_item.id_matches(&used_type.ty_id) {
+                    tracing::debug!(from = %item.rust_identifier(), to = ty_item.full_path, "Adding OpClass after Enum edge.");
+                    graph.add_edge(ty_index, index, SqlGraphRelationship::RequiredBy);
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn initialize_gucs(
+    graph: &mut StableGraph<SqlGraphEntity, SqlGraphRelationship>,
+    root: NodeIndex,
+    bootstrap: Option<NodeIndex>,
+    finalize: Option<NodeIndex>,
+    gucs: Vec<PgGucEntity>,
+) -> eyre::Result<HashMap<PgGucEntity, NodeIndex>> {
+    let mut mapped_gucs = HashMap::default();
+    for item in gucs {
+        let entity: SqlGraphEntity = item.clone().into();
+        let index = graph.add_node(entity);
+        mapped_gucs.insert(item, index);
+        build_base_edges(graph, index, root, bootstrap, finalize);
+    }
+    Ok(mapped_gucs)
+}
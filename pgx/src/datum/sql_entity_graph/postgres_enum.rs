@@ -3,7 +3,7 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql};
+use super::{comment_sql, SqlGraphEntity, SqlGraphIdentifier, ToSql};
 
 /// The output of a [`PostgresEnum`](crate::datum::sql_entity_graph::PostgresEnum) from `quote::ToTokens::to_tokens`.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,8 +13,9 @@ pub struct PostgresEnumEntity {
     pub line: u32,
     pub full_path: &'static str,
     pub module_path: &'static str,
-    pub mappings: std::collections::HashSet<super::RustSqlMapping>,
+    pub mappings: std::collections::HashSet<core::any::TypeId>,
     pub variants: Vec<&'static str>,
+    pub doc: Option<&'static str>,
 }
 
 impl crate::PostgresType for PostgresEnumEntity {}
@@ -41,7 +42,7 @@ impl PartialOrd for PostgresEnumEntity {
 
 impl PostgresEnumEntity {
     pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
-        self.mappings.iter().any(|tester| *candidate == tester.id)
+        self.mappings.contains(candidate)
     }
 }
 
@@ -68,11 +69,11 @@ impl SqlGraphIdentifier for PostgresEnumEntity {
     }
 }
 
-impl ToSql for PostgresEnumEntity {
-    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
-    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+impl PostgresEnumEntity {
+    /// The `CREATE TYPE` statement, without the comment on the enum
+    pub(crate) fn create_sql(&self, context: &super::PgxSql) -> String {
         let self_index = context.enums[self];
-        let sql = format!(
+        format!(
             "\n\
                     -- {file}:{line}\n\
                     -- {full_path}\n\
@@ -92,7 +93,23 @@ impl ToSql for PostgresEnumEntity {
                 .collect::<Vec<_>>()
                 .join(",\n")
                 + "\n",
-        );
+        )
+    }
+}
+
+impl ToSql for PostgresEnumEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let self_index = context.enums[self];
+        let sql = self.create_sql(context)
+            + &comment_sql(
+                &format!(
+                    "TYPE {}{}",
+                    context.schema_prefix_for(&self_index),
+                    self.name
+                ),
+                self.doc,
+            );
         tracing::debug!(%sql);
         Ok(sql)
     }
@@ -1,11 +1,10 @@
-use super::RustSqlMapping;
 use eyre::eyre as eyre_err;
 use std::{
     cmp::Ordering,
     hash::{Hash, Hasher},
 };
 
-use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql};
+use super::{comment_sql, SqlGraphEntity, SqlGraphIdentifier, ToSql};
 
 /// The output of a [`PostgresType`](crate::datum::sql_entity_graph::PostgresType) from `quote::ToTokens::to_tokens`.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,11 +14,12 @@ pub struct PostgresTypeEntity {
     pub line: u32,
     pub full_path: &'static str,
     pub module_path: &'static str,
-    pub mappings: std::collections::HashSet<RustSqlMapping>,
+    pub mappings: std::collections::HashSet<core::any::TypeId>,
     pub in_fn: &'static str,
     pub in_fn_module_path: String,
     pub out_fn: &'static str,
     pub out_fn_module_path: String,
+    pub doc: Option<&'static str>,
 }
 
 impl crate::PostgresType for PostgresTypeEntity {}
@@ -46,7 +46,7 @@ impl PartialOrd for PostgresTypeEntity {
 
 impl PostgresTypeEntity {
     pub fn id_matches(&self, candidate: &core::any::TypeId) -> bool {
-        self.mappings.iter().any(|tester| *candidate == tester.id)
+        self.mappings.contains(candidate)
     }
 }
 
@@ -73,9 +73,10 @@ impl SqlGraphIdentifier for PostgresTypeEntity {
     }
 }
 
-impl ToSql for PostgresTypeEntity {
-    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
-    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+impl PostgresTypeEntity {
+    /// The statements creating the type and its input and output functions, without the comment
+    /// on the type
+    pub(crate) fn create_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
         let self_index = context.types[self];
         let item_node = &context.graph[self_index];
         let item = match item_node {
@@ -201,3 +202,19 @@ impl ToSql for PostgresTypeEntity {
         Ok(shell_type + "\n" + &in_fn_sql + "\n" + &out_fn_sql + "\n" + &materialized_type)
     }
 }
+
+impl ToSql for PostgresTypeEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, context: &super::PgxSql) -> eyre::Result<String> {
+        let self_index = context.types[self];
+        Ok(self.create_sql(context)?
+            + &comment_sql(
+                &format!(
+                    "TYPE {}{}",
+                    context.schema_prefix_for(&self_index),
+                    self.name
+                ),
+                self.doc,
+            ))
+    }
+}
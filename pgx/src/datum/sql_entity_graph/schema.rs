@@ -1,4 +1,4 @@
-use super::{SqlGraphEntity, SqlGraphIdentifier, ToSql};
+use super::{comment_sql, SqlGraphEntity, SqlGraphIdentifier, ToSql};
 use std::cmp::Ordering;
 
 /// The output of a [`Schema`](crate::datum::sql_entity_graph::Schema) from `quote::ToTokens::to_tokens`.
@@ -8,6 +8,7 @@ pub struct SchemaEntity {
     pub name: &'static str,
     pub file: &'static str,
     pub line: u32,
+    pub doc: Option<&'static str>,
 }
 
 impl Ord for SchemaEntity {
@@ -47,10 +48,10 @@ impl SqlGraphIdentifier for SchemaEntity {
     }
 }
 
-impl ToSql for SchemaEntity {
-    #[tracing::instrument(level = "debug", err, skip(self, _context), fields(identifier = %self.rust_identifier()))]
-    fn to_sql(&self, _context: &super::PgxSql) -> eyre::Result<String> {
-        let sql = format!(
+impl SchemaEntity {
+    /// The `CREATE SCHEMA` statement, without the comment on the schema
+    pub(crate) fn create_sql(&self) -> String {
+        format!(
             "\n\
                     -- {file}:{line}\n\
                     CREATE SCHEMA IF NOT EXISTS {name}; /* {module_path} */\
@@ -59,7 +60,14 @@ impl ToSql for SchemaEntity {
             file = self.file,
             line = self.line,
             module_path = self.module_path,
-        );
+        )
+    }
+}
+
+impl ToSql for SchemaEntity {
+    #[tracing::instrument(level = "debug", err, skip(self, _context), fields(identifier = %self.rust_identifier()))]
+    fn to_sql(&self, _context: &super::PgxSql) -> eyre::Result<String> {
+        let sql = self.create_sql() + &comment_sql(&format!("SCHEMA {}", self.name), self.doc);
         tracing::debug!(%sql);
         Ok(sql)
     }
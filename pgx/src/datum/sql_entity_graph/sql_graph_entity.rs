@@ -1,6 +1,7 @@
 use super::{
-    ControlFile, ExtensionSqlEntity, PgExternEntity, PostgresEnumEntity, PostgresHashEntity,
-    PostgresOrdEntity, PostgresTypeEntity, SchemaEntity, SqlGraphIdentifier, ToSql,
+    ControlFile, ExtensionSqlEntity, PgAggregateEntity, PgExternEntity, PgGucEntity,
+    PgOpClassEntity, PostgresEnumEntity, PostgresHashEntity, PostgresOrdEntity, PostgresTypeEntity,
+    SchemaEntity, SqlGraphIdentifier, ToSql,
 };
 
 /// An entity corresponding to some SQL required by the extension.
@@ -15,6 +16,9 @@ pub enum SqlGraphEntity {
     Enum(PostgresEnumEntity),
     Ord(PostgresOrdEntity),
     Hash(PostgresHashEntity),
+    Aggregate(PgAggregateEntity),
+    OpClass(PgOpClassEntity),
+    Guc(PgGucEntity),
 }
 
 impl SqlGraphEntity {}
@@ -30,6 +34,9 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.dot_identifier(),
             SqlGraphEntity::Ord(item) => item.dot_identifier(),
             SqlGraphEntity::Hash(item) => item.dot_identifier(),
+            SqlGraphEntity::Aggregate(item) => item.dot_identifier(),
+            SqlGraphEntity::OpClass(item) => item.dot_identifier(),
+            SqlGraphEntity::Guc(item) => item.dot_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.dot_identifier(),
         }
     }
@@ -43,6 +50,9 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.rust_identifier(),
             SqlGraphEntity::Ord(item) => item.rust_identifier(),
             SqlGraphEntity::Hash(item) => item.rust_identifier(),
+            SqlGraphEntity::Aggregate(item) => item.rust_identifier(),
+            SqlGraphEntity::OpClass(item) => item.rust_identifier(),
+            SqlGraphEntity::Guc(item) => item.rust_identifier(),
             SqlGraphEntity::ExtensionRoot(item) => item.rust_identifier(),
         }
     }
@@ -57,6 +67,9 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.file(),
             SqlGraphEntity::Ord(item) => item.file(),
             SqlGraphEntity::Hash(item) => item.file(),
+            SqlGraphEntity::Aggregate(item) => item.file(),
+            SqlGraphEntity::OpClass(item) => item.file(),
+            SqlGraphEntity::Guc(item) => item.file(),
             SqlGraphEntity::ExtensionRoot(item) => item.file(),
         }
     }
@@ -71,6 +84,9 @@ impl SqlGraphIdentifier for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.line(),
             SqlGraphEntity::Ord(item) => item.line(),
             SqlGraphEntity::Hash(item) => item.line(),
+            SqlGraphEntity::Aggregate(item) => item.line(),
+            SqlGraphEntity::OpClass(item) => item.line(),
+            SqlGraphEntity::Guc(item) => item.line(),
             SqlGraphEntity::ExtensionRoot(item) => item.line(),
         }
     }
@@ -86,23 +102,7 @@ impl ToSql for SqlGraphEntity {
             SqlGraphEntity::CustomSql(item) => {
                 item.to_sql(context)
             },
-            SqlGraphEntity::Function(item) => if context.graph.neighbors_undirected(context.externs.get(item).unwrap().clone()).any(|neighbor| {
-                let neighbor_item = &context.graph[neighbor];
-                match neighbor_item {
-                    SqlGraphEntity::Type(PostgresTypeEntity { in_fn, in_fn_module_path, out_fn, out_fn_module_path, .. }) => {
-                        let is_in_fn = item.full_path.starts_with(in_fn_module_path) && item.full_path.ends_with(in_fn);
-                        if is_in_fn {
-                            tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an in_fn.");
-                        }
-                        let is_out_fn = item.full_path.starts_with(out_fn_module_path) && item.full_path.ends_with(out_fn);
-                        if is_out_fn {
-                            tracing::trace!(r#type = %neighbor_item.dot_identifier(), "Skipping, is an out_fn.");
-                        }
-                        is_in_fn || is_out_fn
-                    },
-                    _ => false,
-                }
-            }) {
+            SqlGraphEntity::Function(item) => if item.is_type_io_fn(context) {
                 Ok(String::default())
             } else { item.to_sql(context) },
             SqlGraphEntity::Type(item) => item.to_sql(context),
@@ -110,6 +110,9 @@ impl ToSql for SqlGraphEntity {
             SqlGraphEntity::Enum(item) => item.to_sql(context),
             SqlGraphEntity::Ord(item) => item.to_sql(context),
             SqlGraphEntity::Hash(item) => item.to_sql(context),
+            SqlGraphEntity::Aggregate(item) => item.to_sql(context),
+            SqlGraphEntity::OpClass(item) => item.to_sql(context),
+            SqlGraphEntity::Guc(item) => item.to_sql(context),
             SqlGraphEntity::ExtensionRoot(item) => item.to_sql(context),
         }
     }
@@ -0,0 +1,1317 @@
+use eyre::eyre as eyre_err;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{comment_sql, AggregateTypeEntity, PgExternEntity, PgxSql, SqlGraphEntity, ToSql};
+
+/// The version of the [`SqlSnapshot`] format, which changes when it does incompatibly.
+pub const SQL_SNAPSHOT_VERSION: u32 = 1;
+
+/// An upgrade script from one version of an extension to another, such as a
+/// `sql/$EXTNAME--1.0--1.1.sql` file.
+///
+/// The [`SqlSnapshot`]s of the two versions are compared object by object, by what Postgres
+/// identifies each by (eg a function by its name and argument types):
+///
+/// * New objects are created.
+/// * Changed functions are replaced with `CREATE OR REPLACE`, and new enum values are added with
+///   `ALTER TYPE ... ADD VALUE`.
+/// * Removed objects are dropped, in the reverse of the order they were created in.
+/// * Changed and removed comments are set again, or removed.
+/// * Changes Postgres can't make in place, like a changed return type, are left commented out in
+///   the script with a warning, to be migrated by hand.  So is new or changed custom SQL, from
+///   `extension_sql!()`, which may not be safe to run twice.
+///
+/// ```rust
+/// use pgx::datum::sql_entity_graph::{SnapshotObject, SqlArgument, SqlArgumentMode, SqlObject, SqlSnapshot, SqlUpgrade};
+///
+/// let add = |returns: &str| SnapshotObject {
+///     object: SqlObject::Function {
+///         name: String::from("\"add\""),
+///         arguments: vec![
+///             SqlArgument { name: String::from("a"), mode: SqlArgumentMode::In, ty: String::from("integer"), default: None },
+///             SqlArgument { name: String::from("b"), mode: SqlArgumentMode::In, ty: String::from("integer"), default: None },
+///         ],
+///         returns: String::from(returns),
+///     },
+///     sql: format!("CREATE OR REPLACE FUNCTION \"add\"(\"a\" integer, \"b\" integer) RETURNS {} LANGUAGE c AS 'MODULE_PATHNAME', 'add_wrapper';", returns),
+///     comment: None,
+/// };
+/// let function = |name: &str| SnapshotObject {
+///     object: SqlObject::Function { name: format!("\"{}\"", name), arguments: vec![], returns: String::from("text") },
+///     sql: format!("CREATE OR REPLACE FUNCTION \"{0}\"() RETURNS text LANGUAGE c AS 'MODULE_PATHNAME', '{0}_wrapper';", name),
+///     comment: None,
+/// };
+///
+/// let upgrade = SqlUpgrade::between(
+///     &SqlSnapshot::new(vec![add("integer"), function("old")]),
+///     &SqlSnapshot::new(vec![add("bigint"), function("new")]),
+/// );
+///
+/// assert!(upgrade.sql.contains("DROP FUNCTION IF EXISTS \"old\"();"));
+/// assert!(upgrade.sql.contains("CREATE OR REPLACE FUNCTION \"new\"()"));
+/// assert_eq!(
+///     upgrade.warnings,
+///     vec![String::from("function \"add\"(integer, integer) can't be replaced, because its return type changed from `integer` to `bigint`")],
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SqlUpgrade {
+    /// The upgrade script
+    pub sql: String,
+    /// The changes which need to be migrated by hand
+    pub warnings: Vec<String>,
+}
+
+impl SqlUpgrade {
+    /// The upgrade script from the extension `from` to the extension `to`.
+    pub fn between(from: &SqlSnapshot, to: &SqlSnapshot) -> Self {
+        let old_objects = from
+            .objects
+            .iter()
+            .map(|old| (old.object.describe(), old))
+            .collect::<HashMap<_, _>>();
+        let new_objects = to
+            .objects
+            .iter()
+            .map(|new| (new.object.describe(), new))
+            .collect::<HashMap<_, _>>();
+
+        let mut upgrade = Self::default();
+
+        // Comments are removed from the objects which are kept
+        for old in &from.objects {
+            if let (Some(_), Some(target)) = (&old.comment, old.object.comment_target()) {
+                match new_objects.get(&old.object.describe()) {
+                    Some(new) if new.comment.is_none() => {
+                        upgrade.push(&format!("COMMENT ON {} IS NULL;", target))
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        // Objects are dropped in the reverse of the order they were created in
+        for old in from.objects.iter().rev() {
+            if new_objects.contains_key(&old.object.describe()) {
+                continue;
+            }
+            match &old.object {
+                SqlObject::CustomSql { .. } => upgrade.warn(format!(
+                    "{} was removed, and must be undone by hand",
+                    old.object.describe()
+                )),
+                SqlObject::Type { .. } => upgrade.warn(format!(
+                    "{} was removed, and dropping it also drops everything which uses it",
+                    old.object.describe()
+                )),
+                _ => (),
+            }
+            if let Some(drop) = old.object.drop_sql() {
+                upgrade.push(&drop);
+            }
+        }
+
+        for new in &to.objects {
+            match old_objects.get(&new.object.describe()) {
+                None => match &new.object {
+                    SqlObject::CustomSql { .. } => {
+                        upgrade.warn(format!(
+                            "{} is new, and is left commented out for review",
+                            new.object.describe()
+                        ));
+                        upgrade.push(&commented(new.sql.trim()));
+                    }
+                    _ => {
+                        upgrade.push(new.sql.trim());
+                        if new.comment.is_some() {
+                            upgrade.comment(new);
+                        }
+                    }
+                },
+                Some(old) => {
+                    if old.object != new.object || normalized(&old.sql) != normalized(&new.sql) {
+                        upgrade.change(old, new);
+                    }
+                    if new.comment.is_some() && new.comment != old.comment {
+                        upgrade.comment(new);
+                    }
+                }
+            }
+        }
+
+        upgrade
+    }
+
+    /// Upgrade the object `old`, which changed to `new`.
+    fn change(&mut self, old: &SnapshotObject, new: &SnapshotObject) {
+        let object = &new.object;
+        match (&old.object, object) {
+            (
+                SqlObject::Function {
+                    arguments: old_arguments,
+                    returns: old_returns,
+                    ..
+                },
+                SqlObject::Function {
+                    arguments, returns, ..
+                },
+            ) => {
+                let replaceable = if old_returns != returns {
+                    Err(format!(
+                        "its return type changed from `{}` to `{}`",
+                        old_returns, returns
+                    ))
+                } else {
+                    replaceable(old_arguments, arguments)
+                };
+                self.replace(object, replaceable, new);
+            }
+            (
+                SqlObject::Procedure {
+                    arguments: old_arguments,
+                    ..
+                },
+                SqlObject::Procedure { arguments, .. },
+            ) => self.replace(object, replaceable(old_arguments, arguments), new),
+            (
+                SqlObject::Enum {
+                    values: old_values, ..
+                },
+                SqlObject::Enum { name, values },
+            ) => match added_enum_values(old_values, values) {
+                // Before Postgres 12, `ALTER TYPE ... ADD VALUE` can't run in a transaction, and
+                // extension scripts always do
+                Some(added) if cfg!(any(feature = "pg10", feature = "pg11")) => {
+                    self.warn(format!(
+                        "{} had values added, which Postgres 10 and 11 can't do in an extension script",
+                        object.describe()
+                    ));
+                    for (value, position) in added {
+                        self.push(&commented(&format!(
+                            "ALTER TYPE {} ADD VALUE {}{};",
+                            name, value, position
+                        )));
+                    }
+                }
+                Some(added) => {
+                    for (value, position) in added {
+                        self.push(&format!(
+                            "ALTER TYPE {} ADD VALUE IF NOT EXISTS {}{};",
+                            name, value, position
+                        ));
+                    }
+                }
+                None => {
+                    self.warn(format!(
+                        "{} had values removed or reordered",
+                        object.describe()
+                    ));
+                    self.push(&commented(new.sql.trim()));
+                }
+            },
+            (SqlObject::EventTrigger { .. }, _) => {
+                if let Some(drop) = object.drop_sql() {
+                    self.push(&drop);
+                }
+                self.push(new.sql.trim());
+            }
+            (SqlObject::Schema { .. }, _) => self.push(new.sql.trim()),
+            (SqlObject::CustomSql { .. }, _) => {
+                self.warn(format!(
+                    "{} changed, and is left commented out for review, while what it did before must be undone by hand",
+                    object.describe()
+                ));
+                self.push(&commented(new.sql.trim()));
+            }
+            _ => {
+                self.warn(format!("{} changed", object.describe()));
+                self.push(&commented(new.sql.trim()));
+            }
+        }
+    }
+
+    /// Replace the function (or procedure) `object` with `CREATE OR REPLACE`, if it's `replaceable`.
+    fn replace(
+        &mut self,
+        object: &SqlObject,
+        replaceable: Result<(), String>,
+        new: &SnapshotObject,
+    ) {
+        match replaceable {
+            Ok(()) => self.push(new.sql.trim()),
+            Err(reason) => {
+                self.warn(format!(
+                    "{} can't be replaced, because {}",
+                    object.describe(),
+                    reason
+                ));
+                self.push(&commented(new.sql.trim()));
+            }
+        }
+    }
+
+    /// Set the comment on `object` to its doc comment, or remove it
+    fn comment(&mut self, object: &SnapshotObject) {
+        if let Some(target) = object.object.comment_target() {
+            match &object.comment {
+                Some(comment) => self.push(comment_sql(&target, Some(comment)).trim()),
+                None => self.push(&format!("COMMENT ON {} IS NULL;", target)),
+            }
+        }
+    }
+
+    fn push(&mut self, sql: &str) {
+        self.sql.push('\n');
+        self.sql.push_str(sql);
+        self.sql.push('\n');
+    }
+
+    fn warn(&mut self, warning: String) {
+        self.push(&format!("-- WARNING: {}", warning));
+        self.warnings.push(warning);
+    }
+}
+
+/// The SQL objects of a version of an extension, in the order they are created in, which
+/// [`SqlUpgrade`] compares.
+///
+/// It's built from the extension's [`PgxSql`] entity graph, and written as JSON by the SQL
+/// generator (`--snapshot`), so a later version can be compared to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlSnapshot {
+    /// The [`SQL_SNAPSHOT_VERSION`] it was written with
+    pub version: u32,
+    pub objects: Vec<SnapshotObject>,
+}
+
+/// An object of a [`SqlSnapshot`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotObject {
+    pub object: SqlObject,
+    /// The statements creating the object, without its comment
+    pub sql: String,
+    /// The doc comment of the object, for `COMMENT ON`
+    pub comment: Option<String>,
+}
+
+/// A SQL object of an extension, by what Postgres identifies it by and what decides how it can be
+/// upgraded.  Names are schema qualified (and quoted) as in the extension's SQL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SqlObject {
+    Schema {
+        name: String,
+    },
+    /// A `#[derive(PostgresType)]`, with its input and output functions
+    Type {
+        name: String,
+    },
+    Enum {
+        name: String,
+        /// The values, as SQL strings
+        values: Vec<String>,
+    },
+    Function {
+        name: String,
+        arguments: Vec<SqlArgument>,
+        /// What the function returns, as in its `RETURNS` clause
+        returns: String,
+    },
+    Procedure {
+        name: String,
+        arguments: Vec<SqlArgument>,
+    },
+    Operator {
+        name: String,
+        left: String,
+        right: String,
+    },
+    Cast {
+        source: String,
+        target: String,
+    },
+    EventTrigger {
+        name: String,
+    },
+    Aggregate {
+        name: String,
+        /// The argument types, as in `DROP AGGREGATE`
+        arguments: String,
+    },
+    /// An operator class, with the operator family of the same name it's in
+    OperatorClass {
+        name: String,
+        method: String,
+    },
+    /// An `extension_sql!()` block, or a function whose SQL is overridden by a `pgxsql` code
+    /// block, by its name
+    CustomSql {
+        name: String,
+    },
+}
+
+/// An argument of a [`SqlObject::Function`] or [`SqlObject::Procedure`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlArgument {
+    pub name: String,
+    pub mode: SqlArgumentMode,
+    pub ty: String,
+    pub default: Option<String>,
+}
+
+/// The mode of a [`SqlArgument`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlArgumentMode {
+    In,
+    Out,
+    InOut,
+    Variadic,
+}
+
+impl SqlSnapshot {
+    /// A snapshot of the current [`SQL_SNAPSHOT_VERSION`] of `objects`.
+    pub fn new(objects: Vec<SnapshotObject>) -> Self {
+        Self {
+            version: SQL_SNAPSHOT_VERSION,
+            objects,
+        }
+    }
+
+    /// The snapshot of the extension `context`.
+    pub fn build(context: &PgxSql) -> eyre::Result<Self> {
+        let mut objects = Vec::new();
+        for index in petgraph::algo::toposort(&context.graph, None).map_err(|e| {
+            eyre_err!(
+                "Failed to toposort SQL entities, node with cycle: {:?}",
+                context.graph[e.node_id()]
+            )
+        })? {
+            let schema_prefix = context.schema_prefix_for(&index);
+            match &context.graph[index] {
+                SqlGraphEntity::Schema(item) => {
+                    if item.name != "public" && item.name != "pg_catalog" {
+                        objects.push(SnapshotObject {
+                            object: SqlObject::Schema {
+                                name: item.name.to_string(),
+                            },
+                            sql: item.create_sql(),
+                            comment: item.doc.map(String::from),
+                        });
+                    }
+                }
+                SqlGraphEntity::CustomSql(item) => objects.push(SnapshotObject {
+                    object: SqlObject::CustomSql {
+                        name: item.name.to_string(),
+                    },
+                    sql: item.to_sql(context)?,
+                    comment: None,
+                }),
+                SqlGraphEntity::Function(item) => {
+                    if !item.is_type_io_fn(context) {
+                        objects.extend(function_objects(context, item)?);
+                    }
+                }
+                SqlGraphEntity::Type(item) => objects.push(SnapshotObject {
+                    object: SqlObject::Type {
+                        name: schema_prefix + item.name,
+                    },
+                    sql: item.create_sql(context)?,
+                    comment: item.doc.map(String::from),
+                }),
+                SqlGraphEntity::Enum(item) => objects.push(SnapshotObject {
+                    object: SqlObject::Enum {
+                        name: schema_prefix + item.name,
+                        values: item
+                            .variants
+                            .iter()
+                            .map(|variant| format!("'{}'", variant))
+                            .collect(),
+                    },
+                    sql: item.create_sql(context),
+                    comment: item.doc.map(String::from),
+                }),
+                SqlGraphEntity::Ord(item) => objects.push(SnapshotObject {
+                    object: SqlObject::OperatorClass {
+                        name: format!("{}_btree_ops", item.name),
+                        method: String::from("btree"),
+                    },
+                    sql: item.to_sql(context)?,
+                    comment: None,
+                }),
+                SqlGraphEntity::Hash(item) => objects.push(SnapshotObject {
+                    object: SqlObject::OperatorClass {
+                        name: format!("{}_hash_ops", item.name),
+                        method: String::from("hash"),
+                    },
+                    sql: item.to_sql(context)?,
+                    comment: None,
+                }),
+                SqlGraphEntity::Aggregate(item) => {
+                    let types = |types: &[AggregateTypeEntity]| -> eyre::Result<String> {
+                        Ok(types
+                            .iter()
+                            .map(|ty| ty.to_sql_type(context))
+                            .collect::<eyre::Result<Vec<_>>>()?
+                            .join(", "))
+                    };
+                    let arguments = if item.ordered_set || item.hypothetical {
+                        let direct_args = types(&item.direct_args)?;
+                        format!(
+                            "{}{}ORDER BY {}",
+                            direct_args,
+                            if direct_args.is_empty() { "" } else { " " },
+                            types(&item.args)?
+                        )
+                    } else if item.args.is_empty() {
+                        String::from("*")
+                    } else {
+                        types(&item.args)?
+                    };
+                    objects.push(SnapshotObject {
+                        object: SqlObject::Aggregate {
+                            name: schema_prefix + item.name,
+                            arguments,
+                        },
+                        sql: item.to_sql(context)?,
+                        comment: None,
+                    })
+                }
+                SqlGraphEntity::OpClass(item) => objects.push(SnapshotObject {
+                    object: SqlObject::OperatorClass {
+                        name: schema_prefix + item.name,
+                        method: item.method.to_string(),
+                    },
+                    sql: item.to_sql(context)?,
+                    comment: None,
+                }),
+                SqlGraphEntity::ExtensionRoot(_)
+                | SqlGraphEntity::BuiltinType(_)
+                | SqlGraphEntity::Guc(_) => (),
+            }
+        }
+        Ok(Self::new(objects))
+    }
+}
+
+/// The objects the `#[pg_extern]` `item` creates: the function, and its event trigger, operator
+/// and cast, if any.
+fn function_objects(context: &PgxSql, item: &PgExternEntity) -> eyre::Result<Vec<SnapshotObject>> {
+    if item.overridden.is_some() {
+        return Ok(vec![SnapshotObject {
+            object: SqlObject::CustomSql {
+                name: item.full_path.to_string(),
+            },
+            sql: item.to_sql(context)?,
+            comment: None,
+        }]);
+    }
+
+    let parts = item.sql_parts(context)?;
+    let name = format!("{}\"{}\"", parts.schema, item.name);
+    let mut arguments = Vec::new();
+    for arg in &item.fn_args {
+        let (schema_prefix, sql_type) = item.arg_sql_type(context, arg)?;
+        arguments.push(SqlArgument {
+            name: arg.pattern.to_string(),
+            mode: if arg.is_inout {
+                SqlArgumentMode::InOut
+            } else if arg.is_variadic {
+                SqlArgumentMode::Variadic
+            } else {
+                SqlArgumentMode::In
+            },
+            ty: schema_prefix + &sql_type,
+            default: arg.default.map(String::from),
+        });
+    }
+    for (name, ty) in item.out_param_types(context)? {
+        arguments.push(SqlArgument {
+            name: name.to_string(),
+            mode: SqlArgumentMode::Out,
+            ty,
+            default: None,
+        });
+    }
+
+    let mut objects = vec![SnapshotObject {
+        object: if item.procedure {
+            SqlObject::Procedure { name, arguments }
+        } else {
+            SqlObject::Function {
+                name,
+                arguments,
+                returns: item.return_type(context)?,
+            }
+        },
+        sql: parts.header + &parts.function,
+        comment: item.doc.map(String::from),
+    }];
+    if let Some(sql) = parts.event_trigger {
+        objects.push(SnapshotObject {
+            object: SqlObject::EventTrigger {
+                name: format!("\"{}\"", item.name),
+            },
+            sql,
+            comment: None,
+        });
+    }
+    if let (Some(sql), Some(op)) = (parts.operator, &item.operator) {
+        objects.push(SnapshotObject {
+            object: SqlObject::Operator {
+                name: op.opname.unwrap_or_default().to_string(),
+                left: parts.signature[0].clone(),
+                right: parts.signature[1].clone(),
+            },
+            sql,
+            comment: None,
+        });
+    }
+    if let Some(sql) = parts.cast {
+        objects.push(SnapshotObject {
+            object: SqlObject::Cast {
+                source: parts.signature[0].clone(),
+                target: item.return_type(context)?,
+            },
+            sql,
+            comment: None,
+        });
+    }
+    Ok(objects)
+}
+
+impl SqlObject {
+    /// The object, as in an upgrade warning.  No two objects of an extension have the same
+    /// description.
+    pub fn describe(&self) -> String {
+        match self {
+            SqlObject::Schema { name } => format!("schema {}", name),
+            SqlObject::Type { name } => format!("type {}", name),
+            SqlObject::Enum { name, .. } => format!("enum {}", name),
+            SqlObject::Function {
+                name, arguments, ..
+            } => format!("function {}({})", name, input_types(arguments)),
+            SqlObject::Procedure { name, arguments } => {
+                format!("procedure {}({})", name, input_types(arguments))
+            }
+            SqlObject::Operator { name, left, right } => {
+                format!("operator {}({}, {})", name, left, right)
+            }
+            SqlObject::Cast { source, target } => format!("cast ({} AS {})", source, target),
+            SqlObject::EventTrigger { name } => format!("event trigger {}", name),
+            SqlObject::Aggregate { name, arguments } => {
+                format!("aggregate {}({})", name, arguments)
+            }
+            SqlObject::OperatorClass { name, method } => {
+                format!("operator class {} using {}", name, method)
+            }
+            SqlObject::CustomSql { name } => format!("custom SQL `{}`", name),
+        }
+    }
+
+    /// The object, as in a `COMMENT ON` statement, if it can have a doc comment
+    fn comment_target(&self) -> Option<String> {
+        match self {
+            SqlObject::Schema { name } => Some(format!("SCHEMA {}", name)),
+            SqlObject::Type { name } | SqlObject::Enum { name, .. } => {
+                Some(format!("TYPE {}", name))
+            }
+            SqlObject::Function {
+                name, arguments, ..
+            } => Some(format!("FUNCTION {}({})", name, input_types(arguments))),
+            SqlObject::Procedure { name, arguments } => {
+                Some(format!("PROCEDURE {}({})", name, input_types(arguments)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The statement dropping the object, unless it has to be undone by hand
+    fn drop_sql(&self) -> Option<String> {
+        match self {
+            SqlObject::Schema { name } => Some(format!("DROP SCHEMA IF EXISTS {};", name)),
+            // Base types and their in/out functions depend on each other
+            SqlObject::Type { name } => Some(format!("DROP TYPE IF EXISTS {} CASCADE;", name)),
+            SqlObject::Enum { name, .. } => Some(format!("DROP TYPE IF EXISTS {};", name)),
+            SqlObject::Function {
+                name, arguments, ..
+            } => Some(format!(
+                "DROP FUNCTION IF EXISTS {}({});",
+                name,
+                input_types(arguments)
+            )),
+            SqlObject::Procedure { name, arguments } => Some(format!(
+                "DROP PROCEDURE IF EXISTS {}({});",
+                name,
+                input_types(arguments)
+            )),
+            SqlObject::Operator { name, left, right } => Some(format!(
+                "DROP OPERATOR IF EXISTS {}({}, {});",
+                name, left, right
+            )),
+            SqlObject::Cast { source, target } => {
+                Some(format!("DROP CAST IF EXISTS ({} AS {});", source, target))
+            }
+            SqlObject::EventTrigger { name } => {
+                Some(format!("DROP EVENT TRIGGER IF EXISTS {};", name))
+            }
+            SqlObject::Aggregate { name, arguments } => {
+                Some(format!("DROP AGGREGATE IF EXISTS {}({});", name, arguments))
+            }
+            // Which also drops the operator class
+            SqlObject::OperatorClass { name, method } => Some(format!(
+                "DROP OPERATOR FAMILY IF EXISTS {} USING {};",
+                name, method
+            )),
+            SqlObject::CustomSql { .. } => None,
+        }
+    }
+}
+
+/// The argument types Postgres identifies a function by, such as `text, VARIADIC integer[]`
+fn input_types(arguments: &[SqlArgument]) -> String {
+    arguments
+        .iter()
+        .filter_map(|argument| match argument.mode {
+            SqlArgumentMode::Out => None,
+            SqlArgumentMode::Variadic => Some(format!("VARIADIC {}", argument.ty)),
+            SqlArgumentMode::In | SqlArgumentMode::InOut => Some(argument.ty.clone()),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Can the arguments of a function (or procedure) change from `old` to `new` with
+/// `CREATE OR REPLACE`?  If not, why not.
+fn replaceable(old: &[SqlArgument], new: &[SqlArgument]) -> Result<(), String> {
+    let outputs = |arguments: &[SqlArgument]| {
+        arguments
+            .iter()
+            .filter(|argument| {
+                matches!(argument.mode, SqlArgumentMode::Out | SqlArgumentMode::InOut)
+            })
+            .map(|argument| (argument.name.clone(), argument.ty.clone()))
+            .collect::<Vec<_>>()
+    };
+    if outputs(old) != outputs(new) {
+        return Err(String::from("its OUT arguments changed"));
+    }
+    let input_names = |arguments: &[SqlArgument]| {
+        arguments
+            .iter()
+            .filter(|argument| argument.mode != SqlArgumentMode::Out)
+            .map(|argument| argument.name.clone())
+            .collect::<Vec<_>>()
+    };
+    if input_names(old) != input_names(new) {
+        return Err(String::from("its argument names changed"));
+    }
+    let defaults = |arguments: &[SqlArgument]| {
+        arguments
+            .iter()
+            .filter(|argument| argument.default.is_some())
+            .count()
+    };
+    if defaults(new) < defaults(old) {
+        return Err(String::from("argument defaults were removed"));
+    }
+    Ok(())
+}
+
+/// The values added to the enum values `old` in `new`, with where to add them, if no values were
+/// removed or reordered.
+fn added_enum_values(old: &[String], new: &[String]) -> Option<Vec<(String, String)>> {
+    let kept = new
+        .iter()
+        .filter(|value| old.contains(value))
+        .collect::<Vec<_>>();
+    if kept.len() != old.len() || kept.iter().zip(old).any(|(kept, old)| *kept != old) {
+        return None;
+    }
+
+    let mut added = Vec::new();
+    for (idx, value) in new.iter().enumerate() {
+        if old.contains(value) {
+            continue;
+        }
+        let position = match idx.checked_sub(1).and_then(|previous| new.get(previous)) {
+            Some(previous) => format!(" AFTER {}", previous),
+            None => match old.first() {
+                Some(first) => format!(" BEFORE {}", first),
+                None => String::new(),
+            },
+        };
+        added.push((value.clone(), position));
+    }
+    Some(added)
+}
+
+/// The statements of `sql` without comments and with their whitespace collapsed, to compare it by
+fn normalized(sql: &str) -> Vec<String> {
+    statements(sql)
+        .into_iter()
+        .map(|statement| statement.normalized)
+        .collect()
+}
+
+/// A statement of a SQL file.
+#[derive(Debug, Clone)]
+pub(super) struct Statement {
+    /// The statement as written, with the comments before it
+    pub(super) text: String,
+    /// The statement without comments, its whitespace collapsed, to compare statements by
+    pub(super) normalized: String,
+}
+
+/// Split `sql` into its statements.
+pub(super) fn statements(sql: &str) -> Vec<Statement> {
+    let mut statements = Vec::new();
+    let mut normalized = String::new();
+    let mut start = 0;
+    let mut pos = 0;
+    while let Some(c) = sql[pos..].chars().next() {
+        let rest = &sql[pos..];
+        if rest.starts_with("--") {
+            pos += rest.find('\n').unwrap_or(rest.len());
+            space(&mut normalized);
+        } else if rest.starts_with("/*") {
+            pos += rest.find("*/").map(|end| end + 2).unwrap_or(rest.len());
+            space(&mut normalized);
+        } else if c == '\'' || c == '"' {
+            let len = quoted_len(rest, c);
+            normalized.push_str(&rest[..len]);
+            pos += len;
+        } else if let Some(tag) = dollar_tag(rest) {
+            let len = rest[tag.len()..]
+                .find(tag)
+                .map(|end| end + 2 * tag.len())
+                .unwrap_or(rest.len());
+            normalized.push_str(&rest[..len]);
+            pos += len;
+        } else if c == ';' {
+            pos += 1;
+            // A comment on the rest of the line belongs to the statement
+            let line = &sql[pos..];
+            let eol = line.find('\n').unwrap_or(line.len());
+            let trailing = line[..eol].trim();
+            if trailing.starts_with("--") || trailing.starts_with("/*") && trailing.ends_with("*/")
+            {
+                pos += eol;
+            }
+            if let Some(statement) = Statement::new(&sql[start..pos], &normalized) {
+                statements.push(statement);
+            }
+            normalized.clear();
+            start = pos;
+        } else if c.is_whitespace() {
+            space(&mut normalized);
+            pos += c.len_utf8();
+        } else {
+            if "(),".contains(c) && normalized.ends_with(' ') {
+                normalized.pop();
+            }
+            normalized.push(c);
+            pos += c.len_utf8();
+        }
+    }
+    if let Some(statement) = Statement::new(&sql[start..], &normalized) {
+        statements.push(statement);
+    }
+    statements
+}
+
+impl Statement {
+    fn new(text: &str, normalized: &str) -> Option<Self> {
+        let normalized = normalized.trim();
+        if normalized.is_empty() {
+            return None;
+        }
+        Some(Self {
+            text: text.trim().to_string(),
+            normalized: normalized.to_string(),
+        })
+    }
+}
+
+/// Add a space to `normalized`, unless it would be redundant
+fn space(normalized: &mut String) {
+    match normalized.chars().last() {
+        None | Some(' ') | Some('(') | Some(',') => (),
+        Some(_) => normalized.push(' '),
+    }
+}
+
+/// The length of the string or identifier quoted with `quote` at the start of `sql`
+fn quoted_len(sql: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (idx, c) in sql.char_indices().skip(1) {
+        if c == quote {
+            escaped = !escaped;
+        } else if escaped {
+            return idx;
+        }
+    }
+    sql.len()
+}
+
+/// The `$tag$` of the dollar-quoted string at the start of `sql`, if there is one
+fn dollar_tag(sql: &str) -> Option<&str> {
+    let rest = sql.strip_prefix('$')?;
+    let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_'))?;
+    if rest[end..].starts_with('$') && !rest.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(&sql[..end + 2])
+    } else {
+        None
+    }
+}
+
+/// `sql` as SQL comments
+fn commented(sql: &str) -> String {
+    sql.lines()
+        .map(|line| format!("-- {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argument(name: &str, ty: &str) -> SqlArgument {
+        SqlArgument {
+            name: String::from(name),
+            mode: SqlArgumentMode::In,
+            ty: String::from(ty),
+            default: None,
+        }
+    }
+
+    fn function(name: &str, arguments: Vec<SqlArgument>, returns: &str) -> SnapshotObject {
+        SnapshotObject {
+            object: SqlObject::Function {
+                name: format!("\"{}\"", name),
+                arguments,
+                returns: String::from(returns),
+            },
+            sql: format!(
+                "CREATE OR REPLACE FUNCTION \"{0}\"() RETURNS {1} LANGUAGE c AS 'MODULE_PATHNAME', '{0}_wrapper';",
+                name, returns
+            ),
+            comment: None,
+        }
+    }
+
+    fn object(object: SqlObject, sql: &str) -> SnapshotObject {
+        SnapshotObject {
+            object,
+            sql: String::from(sql),
+            comment: None,
+        }
+    }
+
+    fn enumeration(values: &[&str]) -> SnapshotObject {
+        object(
+            SqlObject::Enum {
+                name: String::from("color"),
+                values: values.iter().map(|value| format!("'{}'", value)).collect(),
+            },
+            &format!(
+                "CREATE TYPE color AS ENUM ({});",
+                values
+                    .iter()
+                    .map(|value| format!("'{}'", value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+    }
+
+    fn operator(function: &str) -> SnapshotObject {
+        object(
+            SqlObject::Operator {
+                name: String::from("=="),
+                left: String::from("point"),
+                right: String::from("point"),
+            },
+            &format!(
+                "CREATE OPERATOR == (PROCEDURE=\"{}\", LEFTARG=point, RIGHTARG=point);",
+                function
+            ),
+        )
+    }
+
+    fn cast(function: &str) -> SnapshotObject {
+        object(
+            SqlObject::Cast {
+                source: String::from("point"),
+                target: String::from("text"),
+            },
+            &format!(
+                "CREATE CAST (point AS text) WITH FUNCTION \"{}\"(point);",
+                function
+            ),
+        )
+    }
+
+    fn custom_sql(sql: &str) -> SnapshotObject {
+        object(
+            SqlObject::CustomSql {
+                name: String::from("bootstrap"),
+            },
+            sql,
+        )
+    }
+
+    fn upgrade(from: Vec<SnapshotObject>, to: Vec<SnapshotObject>) -> SqlUpgrade {
+        SqlUpgrade::between(&SqlSnapshot::new(from), &SqlSnapshot::new(to))
+    }
+
+    #[test]
+    fn unchanged() {
+        let objects = vec![
+            function("hello", vec![], "text"),
+            enumeration(&["red"]),
+            custom_sql("CREATE TABLE things (id integer);"),
+        ];
+        assert_eq!(upgrade(objects.clone(), objects), SqlUpgrade::default());
+    }
+
+    #[test]
+    fn comments_and_whitespace_are_not_changes() {
+        let mut moved = function("hello", vec![], "text");
+        moved.sql = format!(
+            "-- src/lib.rs:42\n{}",
+            moved.sql.replace(" RETURNS", "\n\tRETURNS")
+        );
+        let custom = custom_sql("CREATE TABLE things (id integer);");
+        let reformatted = custom_sql("-- src/lib.rs:7\nCREATE TABLE things (\n\tid integer\n);");
+        assert_eq!(
+            upgrade(
+                vec![function("hello", vec![], "text"), custom],
+                vec![moved, reformatted]
+            ),
+            SqlUpgrade::default()
+        );
+    }
+
+    #[test]
+    fn functions() {
+        let mut changed = function("changed", vec![], "text");
+        changed.sql = changed.sql.replace("LANGUAGE c", "IMMUTABLE LANGUAGE c");
+        let upgrade = upgrade(
+            vec![
+                function("kept", vec![], "text"),
+                function("changed", vec![], "text"),
+                function("removed", vec![argument("a", "integer")], "text"),
+            ],
+            vec![
+                function("kept", vec![], "text"),
+                changed.clone(),
+                function("added", vec![], "text"),
+            ],
+        );
+        assert_eq!(
+            upgrade.sql,
+            format!(
+                "\nDROP FUNCTION IF EXISTS \"removed\"(integer);\n\n{}\n\n{}\n",
+                changed.sql,
+                function("added", vec![], "text").sql
+            )
+        );
+        assert!(upgrade.warnings.is_empty());
+    }
+
+    #[test]
+    fn changed_argument_types_are_a_new_function() {
+        let upgrade = upgrade(
+            vec![function("add", vec![argument("a", "integer")], "integer")],
+            vec![function("add", vec![argument("a", "bigint")], "integer")],
+        );
+        assert!(upgrade
+            .sql
+            .contains("DROP FUNCTION IF EXISTS \"add\"(integer);"));
+        assert!(upgrade.sql.contains("CREATE OR REPLACE FUNCTION \"add\""));
+        assert!(upgrade.warnings.is_empty());
+    }
+
+    #[test]
+    fn changed_return_type() {
+        let new = function("add", vec![argument("a", "integer")], "bigint");
+        let upgrade = upgrade(
+            vec![function("add", vec![argument("a", "integer")], "integer")],
+            vec![new.clone()],
+        );
+        assert_eq!(
+            upgrade.warnings,
+            vec![String::from(
+                "function \"add\"(integer) can't be replaced, because its return type changed from `integer` to `bigint`"
+            )]
+        );
+        assert!(upgrade.sql.contains(&commented(&new.sql)));
+        assert!(!upgrade.sql.contains(&format!("\n{}\n", new.sql)));
+    }
+
+    #[test]
+    fn renamed_arguments() {
+        let upgrade = upgrade(
+            vec![function("add", vec![argument("a", "integer")], "integer")],
+            vec![function("add", vec![argument("b", "integer")], "integer")],
+        );
+        assert_eq!(
+            upgrade.warnings,
+            vec![String::from(
+                "function \"add\"(integer) can't be replaced, because its argument names changed"
+            )]
+        );
+    }
+
+    #[test]
+    fn removed_defaults() {
+        let mut with_default = argument("a", "integer");
+        with_default.default = Some(String::from("1"));
+        let mut changed_default = with_default.clone();
+        changed_default.default = Some(String::from("2"));
+
+        let changed = upgrade(
+            vec![function("add", vec![with_default.clone()], "integer")],
+            vec![function("add", vec![changed_default], "integer")],
+        );
+        assert!(changed.warnings.is_empty());
+
+        let removed = upgrade(
+            vec![function("add", vec![with_default], "integer")],
+            vec![function("add", vec![argument("a", "integer")], "integer")],
+        );
+        assert_eq!(
+            removed.warnings,
+            vec![String::from(
+                "function \"add\"(integer) can't be replaced, because argument defaults were removed"
+            )]
+        );
+    }
+
+    #[test]
+    fn changed_out_arguments() {
+        let mut out = argument("total", "bigint");
+        out.mode = SqlArgumentMode::Out;
+        let mut renamed = out.clone();
+        renamed.name = String::from("sum");
+        let upgrade = upgrade(
+            vec![function(
+                "add",
+                vec![argument("a", "integer"), out],
+                "record",
+            )],
+            vec![function(
+                "add",
+                vec![argument("a", "integer"), renamed],
+                "record",
+            )],
+        );
+        assert_eq!(
+            upgrade.warnings,
+            vec![String::from(
+                "function \"add\"(integer) can't be replaced, because its OUT arguments changed"
+            )]
+        );
+    }
+
+    #[test]
+    fn enums() {
+        let added = upgrade(
+            vec![enumeration(&["red", "blue"])],
+            vec![enumeration(&["green", "red", "purple", "blue", "yellow"])],
+        );
+        let statements = [
+            "ALTER TYPE color ADD VALUE IF NOT EXISTS 'green' BEFORE 'red';",
+            "ALTER TYPE color ADD VALUE IF NOT EXISTS 'purple' AFTER 'red';",
+            "ALTER TYPE color ADD VALUE IF NOT EXISTS 'yellow' AFTER 'blue';",
+        ];
+        if cfg!(any(feature = "pg10", feature = "pg11")) {
+            assert_eq!(
+                added.warnings,
+                vec![String::from("enum color had values added, which Postgres 10 and 11 can't do in an extension script")]
+            );
+            for statement in &statements {
+                let statement = statement.replace(" IF NOT EXISTS", "");
+                assert!(added.sql.contains(&commented(&statement)));
+            }
+        } else {
+            assert!(added.warnings.is_empty());
+            assert_eq!(
+                added.sql,
+                statements
+                    .iter()
+                    .map(|statement| format!("\n{}\n", statement))
+                    .collect::<String>()
+            );
+        }
+
+        let removed = upgrade(
+            vec![enumeration(&["red"]), function("hello", vec![], "text")],
+            vec![function("hello", vec![], "text")],
+        );
+        assert_eq!(removed.sql, "\nDROP TYPE IF EXISTS color;\n");
+
+        let created = upgrade(vec![], vec![enumeration(&["red"])]);
+        assert_eq!(created.sql, "\nCREATE TYPE color AS ENUM ('red');\n");
+    }
+
+    #[test]
+    fn reordered_enum_values() {
+        for values in &[&["blue", "red"][..], &["red"][..]] {
+            let upgrade = upgrade(
+                vec![enumeration(&["red", "blue"])],
+                vec![enumeration(values)],
+            );
+            assert_eq!(
+                upgrade.warnings,
+                vec![String::from("enum color had values removed or reordered")]
+            );
+            assert!(!upgrade.sql.contains("ALTER TYPE"));
+        }
+    }
+
+    #[test]
+    fn operators() {
+        let added = upgrade(vec![], vec![operator("eq")]);
+        assert_eq!(added.sql, format!("\n{}\n", operator("eq").sql));
+
+        let removed = upgrade(vec![operator("eq")], vec![]);
+        assert_eq!(removed.sql, "\nDROP OPERATOR IF EXISTS ==(point, point);\n");
+
+        let changed = upgrade(vec![operator("eq")], vec![operator("equals")]);
+        assert_eq!(
+            changed.warnings,
+            vec![String::from("operator ==(point, point) changed")]
+        );
+        assert!(changed.sql.contains(&commented(&operator("equals").sql)));
+    }
+
+    #[test]
+    fn casts() {
+        let added = upgrade(vec![], vec![cast("to_text")]);
+        assert_eq!(added.sql, format!("\n{}\n", cast("to_text").sql));
+
+        let removed = upgrade(vec![cast("to_text")], vec![]);
+        assert_eq!(removed.sql, "\nDROP CAST IF EXISTS (point AS text);\n");
+
+        let changed = upgrade(vec![cast("to_text")], vec![cast("as_text")]);
+        assert_eq!(
+            changed.warnings,
+            vec![String::from("cast (point AS text) changed")]
+        );
+    }
+
+    #[test]
+    fn objects_are_dropped_in_reverse_order() {
+        let point = object(
+            SqlObject::Type {
+                name: String::from("point"),
+            },
+            "CREATE TYPE point;",
+        );
+        let upgrade = upgrade(
+            vec![
+                point,
+                function(
+                    "eq",
+                    vec![argument("left", "point"), argument("right", "point")],
+                    "bool",
+                ),
+                operator("eq"),
+                cast("eq"),
+            ],
+            vec![],
+        );
+        assert_eq!(
+            upgrade.sql,
+            "\nDROP CAST IF EXISTS (point AS text);\n\
+             \nDROP OPERATOR IF EXISTS ==(point, point);\n\
+             \nDROP FUNCTION IF EXISTS \"eq\"(point, point);\n\
+             \n-- WARNING: type point was removed, and dropping it also drops everything which uses it\n\
+             \nDROP TYPE IF EXISTS point CASCADE;\n"
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let mut commented_function = function("hello", vec![], "text");
+        commented_function.comment = Some(String::from("Say 'hello'"));
+        let mut recommented_function = commented_function.clone();
+        recommented_function.comment = Some(String::from("Say hi"));
+
+        let removed = upgrade(
+            vec![commented_function.clone()],
+            vec![function("hello", vec![], "text")],
+        );
+        assert_eq!(removed.sql, "\nCOMMENT ON FUNCTION \"hello\"() IS NULL;\n");
+
+        let changed = upgrade(vec![commented_function.clone()], vec![recommented_function]);
+        assert_eq!(
+            changed.sql,
+            "\nCOMMENT ON FUNCTION \"hello\"() IS 'Say hi';\n"
+        );
+
+        let added = upgrade(vec![], vec![commented_function.clone()]);
+        assert_eq!(
+            added.sql,
+            format!(
+                "\n{}\n\nCOMMENT ON FUNCTION \"hello\"() IS 'Say ''hello''';\n",
+                commented_function.sql
+            )
+        );
+
+        // Dropping the function drops its comment
+        let dropped = upgrade(vec![commented_function], vec![]);
+        assert_eq!(dropped.sql, "\nDROP FUNCTION IF EXISTS \"hello\"();\n");
+    }
+
+    #[test]
+    fn custom_sql_is_left_for_review() {
+        let old = custom_sql("CREATE TABLE things (id integer);");
+        let new = custom_sql("CREATE TABLE things (id bigint);");
+
+        let changed = upgrade(vec![old.clone()], vec![new.clone()]);
+        assert_eq!(
+            changed.warnings,
+            vec![String::from("custom SQL `bootstrap` changed, and is left commented out for review, while what it did before must be undone by hand")]
+        );
+        assert!(changed.sql.contains(&commented(&new.sql)));
+
+        let added = upgrade(vec![], vec![new.clone()]);
+        assert_eq!(
+            added.warnings,
+            vec![String::from(
+                "custom SQL `bootstrap` is new, and is left commented out for review"
+            )]
+        );
+        assert!(added.sql.contains(&commented(&new.sql)));
+
+        let removed = upgrade(vec![old], vec![]);
+        assert_eq!(
+            removed.warnings,
+            vec![String::from(
+                "custom SQL `bootstrap` was removed, and must be undone by hand"
+            )]
+        );
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_json() {
+        let snapshot = SqlSnapshot::new(vec![
+            function("hello", vec![argument("name", "text")], "text"),
+            enumeration(&["red"]),
+        ]);
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["version"], SQL_SNAPSHOT_VERSION);
+        assert_eq!(json["objects"][0]["object"]["kind"], "function");
+        assert_eq!(json["objects"][0]["object"]["arguments"][0]["mode"], "in");
+        assert_eq!(json["objects"][1]["object"]["kind"], "enum");
+        assert_eq!(
+            serde_json::from_value::<SqlSnapshot>(json).unwrap(),
+            snapshot
+        );
+    }
+}
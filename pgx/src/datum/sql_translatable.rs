@@ -0,0 +1,192 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Mapping of Rust types to the SQL types they're declared as in the generated schema
+use crate::{
+    datum, pg_sys, rel::PgRelation, Array, Date, PgBox, PgOid, PgString, PgVarlena, Time,
+    TimeWithTimeZone, Timestamp, TimestampWithTimeZone, WhoAllocated,
+};
+use std::ffi::CStr;
+
+/// A Rust type which has a SQL type, and so can be used as the argument or return type of a
+/// [`#[pg_extern]`](macro@crate::pg_extern), or in a [`#[pg_aggregate]`](macro@crate::pg_aggregate).
+///
+/// The SQL type is checked for when the extension is compiled, so using a type without a mapping
+/// is a compile error rather than a failure of `cargo pgx schema`.
+///
+/// It's implemented for the types [`pgx`](crate) knows about, and automatically by
+/// [`#[derive(PostgresType)]`](derive@crate::PostgresType) and
+/// [`#[derive(PostgresEnum)]`](derive@crate::PostgresEnum), and for the types an
+/// [`extension_sql!()`](crate::extension_sql) `creates`.  Other types implement it by hand:
+///
+/// ```rust
+/// use pgx::datum::SqlTranslatable;
+///
+/// struct Complex { r: f64, i: f64 }
+///
+/// impl SqlTranslatable for Complex {
+///     fn sql_type() -> String {
+///         String::from("Complex")
+///     }
+/// }
+///
+/// assert_eq!(<Option<Vec<Complex>> as SqlTranslatable>::sql_type(), "Complex[]");
+/// ```
+///
+/// The schema a type is created in is not part of its SQL type, it's prefixed when the SQL is
+/// generated.
+pub trait SqlTranslatable {
+    /// The name of the SQL type, such as `integer` or `text[]`
+    fn sql_type() -> String;
+
+    /// Is the SQL type collatable?  Postgres only hands a function an input collation if one of
+    /// its arguments is, so a [`#[pg_extern(collatable)]`](macro@crate::pg_extern) function needs
+    /// one.
+    fn collatable() -> bool {
+        false
+    }
+}
+
+impl<T: SqlTranslatable + ?Sized> SqlTranslatable for &T {
+    fn sql_type() -> String {
+        T::sql_type()
+    }
+
+    fn collatable() -> bool {
+        T::collatable()
+    }
+}
+
+impl<T: SqlTranslatable + ?Sized> SqlTranslatable for &mut T {
+    fn sql_type() -> String {
+        T::sql_type()
+    }
+
+    fn collatable() -> bool {
+        T::collatable()
+    }
+}
+
+/// `NULL` is handled by the function, so the type is `T`'s
+impl<T: SqlTranslatable> SqlTranslatable for Option<T> {
+    fn sql_type() -> String {
+        T::sql_type()
+    }
+
+    fn collatable() -> bool {
+        T::collatable()
+    }
+}
+
+impl<T: SqlTranslatable> SqlTranslatable for Vec<T> {
+    fn sql_type() -> String {
+        format!("{}[]", T::sql_type())
+    }
+
+    fn collatable() -> bool {
+        T::collatable()
+    }
+}
+
+impl<'a, T: SqlTranslatable + datum::FromDatum> SqlTranslatable for Array<'a, T> {
+    fn sql_type() -> String {
+        format!("{}[]", T::sql_type())
+    }
+
+    fn collatable() -> bool {
+        T::collatable()
+    }
+}
+
+impl<T: SqlTranslatable, AllocatedBy: WhoAllocated<T>> SqlTranslatable for PgBox<T, AllocatedBy> {
+    fn sql_type() -> String {
+        T::sql_type()
+    }
+
+    fn collatable() -> bool {
+        T::collatable()
+    }
+}
+
+impl<T: SqlTranslatable + Copy> SqlTranslatable for PgVarlena<T> {
+    fn sql_type() -> String {
+        T::sql_type()
+    }
+
+    fn collatable() -> bool {
+        T::collatable()
+    }
+}
+
+macro_rules! impl_sql_translatable {
+    (collatable: $($rust:ty => $sql:expr),* $(,)?) => {
+        $(
+            impl SqlTranslatable for $rust {
+                fn sql_type() -> String {
+                    String::from($sql)
+                }
+
+                fn collatable() -> bool {
+                    true
+                }
+            }
+        )*
+    };
+    ($($rust:ty => $sql:expr),* $(,)?) => {
+        $(
+            impl SqlTranslatable for $rust {
+                fn sql_type() -> String {
+                    String::from($sql)
+                }
+            }
+        )*
+    };
+}
+
+// Bytea is a special case: `u8` has no SQL type, so there's no `bytea[]`.
+impl_sql_translatable! {
+    [u8] => "bytea",
+    Vec<u8> => "bytea",
+}
+
+// Polymorphic types are collatable when the actual type is
+impl_sql_translatable! {
+    collatable:
+    str => "text",
+    String => "text",
+    PgString<'_> => "text",
+    char => "varchar",
+    datum::AnyElement => "anyelement",
+    datum::AnyArray => "anyarray",
+}
+
+impl_sql_translatable! {
+    CStr => "cstring",
+    () => "void",
+    i8 => "\"char\"",
+    i16 => "smallint",
+    i32 => "integer",
+    i64 => "bigint",
+    bool => "bool",
+    f32 => "real",
+    f64 => "double precision",
+    datum::JsonB => "jsonb",
+    datum::Json => "json",
+    // `u32` isn't here: `pg_sys::Oid` is an alias of it, and `oid`s are `PgOid`s
+    PgOid => "oid",
+    pg_sys::ItemPointerData => "tid",
+    pg_sys::Point => "point",
+    pg_sys::BOX => "box",
+    Date => "date",
+    Time => "time",
+    TimeWithTimeZone => "time with time zone",
+    Timestamp => "timestamp",
+    TimestampWithTimeZone => "timestamp with time zone",
+    pg_sys::PlannerInfo => "internal",
+    pg_sys::IndexAmRoutine => "internal",
+    datum::Internal => "internal",
+    PgRelation => "regclass",
+    datum::Numeric => "numeric",
+    datum::Inet => "inet",
+    datum::Uuid => "uuid",
+}
@@ -0,0 +1,108 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Helper functions for converting between Rust's UTF-8 strings and the server's character encoding
+//!
+//! Postgres stores `text` in the encoding of the database it lives in, which isn't necessarily
+//! UTF-8.  These functions convert to and from that encoding, only doing work (and only
+//! allocating) when the server's encoding actually requires it.
+use crate::{pg_sys, void_mut_ptr};
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::str::Utf8Error;
+
+/// The character encoding of the current database
+#[inline]
+pub fn server_encoding() -> pg_sys::pg_enc {
+    unsafe { pg_sys::GetDatabaseEncoding() as pg_sys::pg_enc }
+}
+
+/// The name of the character encoding of the current database, such as `UTF8` or `LATIN1`
+pub fn server_encoding_name() -> &'static str {
+    unsafe { CStr::from_ptr(pg_sys::GetDatabaseEncodingName()) }
+        .to_str()
+        .expect("server encoding name is not valid UTF-8")
+}
+
+/// Is the current database encoded in UTF-8?  If so, no conversions are necessary
+#[inline]
+pub fn is_server_utf8() -> bool {
+    server_encoding() == pg_sys::pg_enc_PG_UTF8
+}
+
+/// Convert `bytes`, which are in the server's encoding, into UTF-8.
+///
+/// If no conversion is necessary the input is returned as-is.  Otherwise the result is allocated
+/// by Postgres in `CurrentMemoryContext`.  Raises a Postgres ERROR if `bytes` aren't valid in the
+/// server's encoding or contain characters with no UTF-8 equivalent.
+///
+/// ## Safety
+///
+/// The returned slice may be backed by Postgres-allocated memory and will become invalid the
+/// moment `CurrentMemoryContext` is reset or deleted.
+pub unsafe fn server_to_utf8_bytes(bytes: &[u8]) -> &[u8] {
+    if is_server_utf8() || bytes.is_ascii() {
+        return bytes;
+    }
+
+    let converted = pg_sys::pg_server_to_any(
+        bytes.as_ptr() as *const std::os::raw::c_char,
+        bytes.len() as i32,
+        pg_sys::pg_enc_PG_UTF8 as i32,
+    );
+
+    if converted as *const u8 == bytes.as_ptr() {
+        bytes
+    } else {
+        CStr::from_ptr(converted).to_bytes()
+    }
+}
+
+/// Convert `bytes`, which are in the server's encoding, into a Rust string.
+///
+/// Borrows `bytes` if no conversion is necessary, otherwise returns an owned copy of the
+/// converted string.  Returns an error if the server encoding is UTF-8 and `bytes` aren't valid
+/// UTF-8.  In any other encoding, including `SQL_ASCII`, Postgres validates the conversion and
+/// raises an ERROR instead, as [`server_to_utf8_bytes`] does.
+pub fn server_to_utf8(bytes: &[u8]) -> Result<Cow<'_, str>, Utf8Error> {
+    if is_server_utf8() || bytes.is_ascii() {
+        return std::str::from_utf8(bytes).map(Cow::Borrowed);
+    }
+
+    unsafe {
+        let converted = server_to_utf8_bytes(bytes);
+        if converted.as_ptr() == bytes.as_ptr() {
+            std::str::from_utf8(bytes).map(Cow::Borrowed)
+        } else {
+            let result = std::str::from_utf8(converted).map(|s| Cow::Owned(s.to_owned()));
+            pg_sys::pfree(converted.as_ptr() as void_mut_ptr);
+            result
+        }
+    }
+}
+
+/// Convert a Rust string into the server's encoding.
+///
+/// Borrows `s` if no conversion is necessary.  Raises a Postgres ERROR if `s` contains characters
+/// that have no equivalent in the server's encoding.
+pub fn utf8_to_server(s: &str) -> Cow<'_, [u8]> {
+    if is_server_utf8() || s.is_ascii() {
+        return Cow::Borrowed(s.as_bytes());
+    }
+
+    unsafe {
+        let converted = pg_sys::pg_any_to_server(
+            s.as_ptr() as *const std::os::raw::c_char,
+            s.len() as i32,
+            pg_sys::pg_enc_PG_UTF8 as i32,
+        );
+
+        if converted as *const u8 == s.as_ptr() {
+            Cow::Borrowed(s.as_bytes())
+        } else {
+            let result = CStr::from_ptr(converted).to_bytes().to_vec();
+            pg_sys::pfree(converted as void_mut_ptr);
+            Cow::Owned(result)
+        }
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Safe wrappers around Postgres' event trigger API (`commands/event_trigger.h`)
+//!
+//! A `#[pg_event_trigger]` function is created as `RETURNS event_trigger`, and receives a
+//! [`PgEventTrigger`] describing the command which fired it.  If an `event` is given, the
+//! `CREATE EVENT TRIGGER` statement is generated as well:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! #[pg_event_trigger(event = "ddl_command_end", tags = ["CREATE TABLE"])]
+//! fn require_primary_keys(trigger: &PgEventTrigger) -> Result<(), String> {
+//!     for command in trigger.ddl_commands() {
+//!         let identity = command.object_identity.unwrap_or_default();
+//!         let has_pkey = Spi::get_one::<bool>(&format!(
+//!             "SELECT EXISTS(SELECT 1 FROM pg_index WHERE indrelid = {} AND indisprimary)",
+//!             command.objid
+//!         ));
+//!         if has_pkey != Some(true) {
+//!             return Err(format!("table {} must have a primary key", identity));
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+use crate::{is_a, pg_sys, PgBox, Spi};
+
+/// A row of `pg_event_trigger_ddl_commands()`, available to `ddl_command_end` event triggers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgEventTriggerDdlCommand {
+    pub classid: pg_sys::Oid,
+    pub objid: pg_sys::Oid,
+    pub objsubid: i32,
+    pub command_tag: String,
+    pub object_type: String,
+    pub schema_name: Option<String>,
+    pub object_identity: Option<String>,
+    pub in_extension: bool,
+}
+
+/// A row of `pg_event_trigger_dropped_objects()`, available to `sql_drop` event triggers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgEventTriggerDroppedObject {
+    pub classid: pg_sys::Oid,
+    pub objid: pg_sys::Oid,
+    pub objsubid: i32,
+    pub original: bool,
+    pub normal: bool,
+    pub is_temporary: bool,
+    pub object_type: String,
+    pub schema_name: Option<String>,
+    pub object_name: Option<String>,
+    pub object_identity: Option<String>,
+    pub address_names: Vec<String>,
+    pub address_args: Vec<String>,
+}
+
+/// The `EventTriggerData` Postgres hands to an event trigger function through `fcinfo->context`
+pub struct PgEventTrigger {
+    event_trigger_data: *mut pg_sys::EventTriggerData,
+}
+
+impl PgEventTrigger {
+    /// The `PgEventTrigger` of the event trigger call described by `fcinfo`.
+    ///
+    /// Panics if the function was not called as an event trigger.
+    ///
+    /// # Safety
+    ///
+    /// `fcinfo` must be a valid pointer to the `FunctionCallInfo` Postgres passed to the
+    /// function, and the returned `PgEventTrigger` must not outlive the call.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let context = fcinfo.as_ref().expect("fcinfo is NULL").context;
+        if !is_a(context, pg_sys::NodeTag_T_EventTriggerData) {
+            panic!("function was not called as an event trigger");
+        }
+        PgEventTrigger {
+            event_trigger_data: context as *mut pg_sys::EventTriggerData,
+        }
+    }
+
+    /// Run the `#[pg_event_trigger]` function `f` for the event trigger call described by
+    /// `fcinfo`, raising its error, if any
+    ///
+    /// # Safety
+    ///
+    /// `fcinfo` must be a valid pointer to the `FunctionCallInfo` Postgres passed to the event
+    /// trigger function.
+    #[doc(hidden)]
+    pub unsafe fn call<E: std::fmt::Display>(
+        fcinfo: pg_sys::FunctionCallInfo,
+        f: impl FnOnce(&PgEventTrigger) -> Result<(), E>,
+    ) -> pg_sys::Datum {
+        if let Err(e) = f(&PgEventTrigger::from_fcinfo(fcinfo)) {
+            panic!("{}", e);
+        }
+        0
+    }
+
+    /// The underlying `pg_sys::EventTriggerData`
+    pub fn as_ptr(&self) -> *mut pg_sys::EventTriggerData {
+        self.event_trigger_data
+    }
+
+    /// The event the trigger fired for, such as `ddl_command_end`
+    pub fn event(&self) -> &str {
+        unsafe { std::ffi::CStr::from_ptr((*self.event_trigger_data).event) }
+            .to_str()
+            .expect("event name is not valid UTF8")
+    }
+
+    /// The command tag of the command which fired the trigger, such as `CREATE TABLE`
+    pub fn tag(&self) -> &str {
+        #[cfg(any(feature = "pg10", feature = "pg11", feature = "pg12"))]
+        let tag = unsafe { (*self.event_trigger_data).tag };
+        #[cfg(any(feature = "pg13", feature = "pg14"))]
+        let tag = unsafe { pg_sys::GetCommandTagName((*self.event_trigger_data).tag) };
+
+        unsafe { std::ffi::CStr::from_ptr(tag) }
+            .to_str()
+            .expect("command tag is not valid UTF8")
+    }
+
+    /// The parse tree of the command which fired the trigger.  Use `is_a()` to find out which
+    /// kind of statement it is.
+    ///
+    /// This is `None` for `sql_drop` and `table_rewrite` events fired by internally generated
+    /// commands.
+    pub fn parse_tree(&self) -> Option<PgBox<pg_sys::Node>> {
+        let parsetree = unsafe { (*self.event_trigger_data).parsetree };
+        if parsetree.is_null() {
+            None
+        } else {
+            Some(unsafe { PgBox::from_pg(parsetree) })
+        }
+    }
+
+    /// The commands run by the statement which fired the trigger, as reported by
+    /// `pg_event_trigger_ddl_commands()`.
+    ///
+    /// Postgres raises an `ERROR` unless the trigger fired for `ddl_command_end`.
+    pub fn ddl_commands(&self) -> Vec<PgEventTriggerDdlCommand> {
+        let mut commands = Vec::new();
+        Spi::connect(|client| {
+            let table = client.select(
+                "SELECT classid, objid, objsubid, command_tag, object_type, schema_name, \
+                        object_identity, in_extension \
+                   FROM pg_event_trigger_ddl_commands()",
+                None,
+                None,
+            );
+            for row in table {
+                commands.push(PgEventTriggerDdlCommand {
+                    classid: row["classid"].value().unwrap(),
+                    objid: row["objid"].value().unwrap(),
+                    objsubid: row["objsubid"].value().unwrap(),
+                    command_tag: row["command_tag"].value().unwrap(),
+                    object_type: row["object_type"].value().unwrap(),
+                    schema_name: row["schema_name"].value(),
+                    object_identity: row["object_identity"].value(),
+                    in_extension: row["in_extension"].value().unwrap(),
+                });
+            }
+            Ok(Some(()))
+        });
+        commands
+    }
+
+    /// The objects dropped by the statement which fired the trigger, as reported by
+    /// `pg_event_trigger_dropped_objects()`.
+    ///
+    /// Postgres raises an `ERROR` unless the trigger fired for `sql_drop`.
+    pub fn dropped_objects(&self) -> Vec<PgEventTriggerDroppedObject> {
+        let mut objects = Vec::new();
+        Spi::connect(|client| {
+            let table = client.select(
+                "SELECT classid, objid, objsubid, original, normal, is_temporary, object_type, \
+                        schema_name, object_name, object_identity, address_names, address_args \
+                   FROM pg_event_trigger_dropped_objects()",
+                None,
+                None,
+            );
+            for row in table {
+                objects.push(PgEventTriggerDroppedObject {
+                    classid: row["classid"].value().unwrap(),
+                    objid: row["objid"].value().unwrap(),
+                    objsubid: row["objsubid"].value().unwrap(),
+                    original: row["original"].value().unwrap(),
+                    normal: row["normal"].value().unwrap(),
+                    is_temporary: row["is_temporary"].value().unwrap(),
+                    object_type: row["object_type"].value().unwrap(),
+                    schema_name: row["schema_name"].value(),
+                    object_name: row["object_name"].value(),
+                    object_identity: row["object_identity"].value(),
+                    address_names: row["address_names"].value().unwrap_or_default(),
+                    address_args: row["address_args"].value().unwrap_or_default(),
+                });
+            }
+            Ok(Some(()))
+        });
+        objects
+    }
+}
@@ -80,6 +80,28 @@ macro_rules! variadic {
     };
 }
 
+/// A macro for marking an argument of a `#[pg_procedure]` or `#[pg_extern]` as `INOUT`.
+///
+/// The function returns the argument's new value, either directly or as the `name!()`d column of
+/// the same name in its returned tuple.
+///
+/// ## Examples
+///
+/// ```rust,ignore
+/// use pgx::*;
+///
+/// #[pg_procedure]
+/// fn double_it(value: inout!(i32)) -> i32 {
+///     value * 2
+/// }
+/// ```
+#[macro_export]
+macro_rules! inout {
+    ($ty:ty) => {
+        $ty
+    };
+}
+
 #[cfg(any(feature = "pg10", feature = "pg11"))]
 mod pg_10_11 {
     use crate::{pg_sys, FromDatum};
@@ -206,6 +228,19 @@ pub unsafe fn get_getarg_type(fcinfo: pg_sys::FunctionCallInfo, num: usize) -> p
     pg_sys::get_fn_expr_argtype(fcinfo.as_ref().unwrap().flinfo, num as std::os::raw::c_int)
 }
 
+/// ```c
+/// #define PG_GET_COLLATION() (fcinfo->fncollation)
+/// ```
+///
+/// # Safety
+///
+/// The provided `fcinfo` must be valid otherwise this function results in undefined behavior due
+/// to an out of bounds read.
+#[inline]
+pub unsafe fn pg_get_collation(fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Oid {
+    fcinfo.as_ref().unwrap().fncollation
+}
+
 /// this is intended for Postgres functions that take an actual `cstring` argument, not for getting
 /// a varlena argument type as a CStr.
 #[inline]
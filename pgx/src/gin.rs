@@ -0,0 +1,327 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for declaring GIN operator classes with a Rust trait
+//!
+//! Implement [`GinOpClass`] for a type and annotate the `impl` block with `#[pg_gin_opclass]`.
+//! The macro generates the support functions Postgres needs (`compare`, `extractValue`,
+//! `extractQuery`, `consistent`, etc) for whichever trait functions the `impl` provides, and
+//! `cargo pgx schema` generates the matching `CREATE OPERATOR CLASS ... USING gin` statement.
+//!
+//! The trait functions return the keys of a value or query as a `Vec`, and the support functions
+//! fill in the `Datum *` and `nkeys` out-parameters Postgres passes them.  Keys are compared with
+//! their [`Ord`] implementation.
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(PostgresType, Serialize, Deserialize)]
+//! pub struct Tags {
+//!     tags: Vec<String>,
+//! }
+//!
+//! #[pg_operator(immutable)]
+//! #[opname(@>)]
+//! fn tags_contains(left: Tags, right: Tags) -> bool {
+//!     right.tags.iter().all(|tag| left.tags.contains(tag))
+//! }
+//!
+//! #[pg_gin_opclass]
+//! impl GinOpClass for Tags {
+//!     type Key = String;
+//!     const OPERATORS: &'static [(u16, &'static str)] = &[(2, "@>")];
+//!
+//!     fn extract_value(value: Tags) -> Vec<String> {
+//!         value.tags
+//!     }
+//!
+//!     fn extract_query(query: &Tags, _strategy: u16) -> Vec<String> {
+//!         query.tags.clone()
+//!     }
+//!
+//!     fn consistent(check: &[bool], _strategy: u16, _query: &Tags, recheck: &mut bool) -> bool {
+//!         *recheck = false;
+//!         check.iter().all(|&present| present)
+//!     }
+//! }
+//! ```
+//!
+//! Within the `impl`, the following may be omitted and are filled in by `#[pg_gin_opclass]`:
+//!
+//! * `type Query` defaults to `Self`
+//! * `const NAME` defaults to the lowercased name of the implementing type, suffixed with
+//!   `_gin_ops`
+use crate::{pg_sys, FromDatum, Internal, IntoDatum};
+use std::cmp::Ordering;
+
+/// How a GIN index scan uses the keys [`GinOpClass::extract_query`] returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GinSearchMode {
+    /// Only items containing at least one of the keys can match.  Without any keys, nothing
+    /// matches.
+    Default,
+    /// Like `Default`, but items without any keys can also match
+    IncludeEmpty,
+    /// Every non-empty item can match
+    All,
+    /// Every item can match, even an empty or `NULL` one
+    Everything,
+}
+
+impl GinSearchMode {
+    fn as_i32(&self) -> i32 {
+        (match self {
+            GinSearchMode::Default => pg_sys::GIN_SEARCH_MODE_DEFAULT,
+            GinSearchMode::IncludeEmpty => pg_sys::GIN_SEARCH_MODE_INCLUDE_EMPTY,
+            GinSearchMode::All => pg_sys::GIN_SEARCH_MODE_ALL,
+            GinSearchMode::Everything => pg_sys::GIN_SEARCH_MODE_EVERYTHING,
+        }) as i32
+    }
+}
+
+/// A `GinTernaryValue`, the three-valued logic of [`GinTriConsistent::tri_consistent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GinTernary {
+    False,
+    True,
+    /// Unknown: the key may or may not be present, or the item may or may not match
+    Maybe,
+}
+
+impl GinTernary {
+    fn from_value(value: pg_sys::GinTernaryValue) -> Self {
+        match value as u32 {
+            pg_sys::GIN_FALSE => GinTernary::False,
+            pg_sys::GIN_TRUE => GinTernary::True,
+            _ => GinTernary::Maybe,
+        }
+    }
+
+    fn as_i8(&self) -> i8 {
+        (match self {
+            GinTernary::False => pg_sys::GIN_FALSE,
+            GinTernary::True => pg_sys::GIN_TRUE,
+            GinTernary::Maybe => pg_sys::GIN_MAYBE,
+        }) as i8
+    }
+}
+
+/// The result of [`GinComparePartial::compare_partial`] for one index key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GinPartialMatch {
+    /// The key doesn't match, but later keys might
+    NoMatch,
+    /// The key matches
+    Match,
+    /// The key doesn't match, and neither does any later key
+    Stop,
+}
+
+/// A GIN operator class.  See the [module documentation](crate::gin) for details.
+///
+/// [`GinOpClass::extract_value`], [`GinOpClass::extract_query`] and [`GinOpClass::consistent`]
+/// are required.  The `impl` block may also provide the functions of [`GinTriConsistent`] and
+/// [`GinComparePartial`], which `#[pg_gin_opclass]` moves into implementations of those traits
+/// and turns into SQL support functions.
+pub trait GinOpClass
+where
+    Self: FromDatum + IntoDatum + Sized,
+{
+    /// The type of the keys extracted from values and queries, and stored in the index
+    /// (`STORAGE`).  Its [`Ord`] implementation is the `compare` support function.
+    type Key: FromDatum + IntoDatum + Ord;
+
+    /// The right-hand argument of the operator class' operators
+    type Query: FromDatum;
+
+    /// The SQL name of the operator class
+    const NAME: &'static str;
+
+    /// Is this the default GIN operator class of the type?
+    const DEFAULT: bool = true;
+
+    /// The operators of the operator class, by strategy number, such as `&[(2, "@>")]`
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    /// The keys of an indexed value (`extractValue`)
+    fn extract_value(value: Self) -> Vec<Self::Key>;
+
+    /// The keys to look up in the index for the operator of `strategy` against `query`
+    /// (`extractQuery`)
+    fn extract_query(query: &Self::Query, strategy: u16) -> Vec<Self::Key>;
+
+    /// How the scan uses the keys of `query`.  See [`GinSearchMode`].
+    fn search_mode(_query: &Self::Query, _strategy: u16) -> GinSearchMode {
+        GinSearchMode::Default
+    }
+
+    /// Should `key`, one of the keys of a query, match index keys through
+    /// [`GinComparePartial::compare_partial`] rather than only itself?
+    fn partial_match(_key: &Self::Key, _strategy: u16) -> bool {
+        false
+    }
+
+    /// Does an item match `query`, given which of the keys [`GinOpClass::extract_query`] returned
+    /// for it are present in the item (`check`, in the same order)?
+    ///
+    /// `recheck` starts out as `true`, meaning the operator is re-evaluated against the heap
+    /// tuple.  Set it to `false` when the answer is exact.
+    fn consistent(check: &[bool], strategy: u16, query: &Self::Query, recheck: &mut bool) -> bool;
+}
+
+/// The `triConsistent` support function of a [`GinOpClass`].
+///
+/// `#[pg_gin_opclass]` implements this from the `fn tri_consistent` of the `impl GinOpClass`
+/// block.
+pub trait GinTriConsistent: GinOpClass {
+    /// Like [`GinOpClass::consistent`], where the presence of some keys may be unknown.  Return
+    /// [`GinTernary::Maybe`] where the answer needs to be rechecked (`triConsistent`).
+    fn tri_consistent(check: &[GinTernary], strategy: u16, query: &Self::Query) -> GinTernary;
+}
+
+/// The `comparePartial` support function of a [`GinOpClass`], for partial matches.
+///
+/// `#[pg_gin_opclass]` implements this from the `fn compare_partial` of the `impl GinOpClass`
+/// block.
+pub trait GinComparePartial: GinOpClass {
+    /// Does the index key `key` match the partial query key `partial` (`comparePartial`)?  Index
+    /// keys are visited in order, starting at `partial`.
+    fn compare_partial(partial: &Self::Key, key: &Self::Key, strategy: u16) -> GinPartialMatch;
+}
+
+/// Write `keys` to a new `palloc`'d array, and their number to `nkeys`
+fn keys_to_datums<K: IntoDatum>(keys: Vec<K>, nkeys: &Internal) -> *mut pg_sys::Datum {
+    let nkeys =
+        unsafe { nkeys.get_mut::<i32>() }.expect("GIN support function called with a NULL nkeys");
+    *nkeys = keys.len() as i32;
+    let datums = unsafe {
+        pg_sys::palloc(keys.len().max(1) * std::mem::size_of::<pg_sys::Datum>())
+            as *mut pg_sys::Datum
+    };
+    for (idx, key) in keys.into_iter().enumerate() {
+        unsafe {
+            *datums.add(idx) = key.into_datum().expect("GIN keys cannot be NULL");
+        }
+    }
+    datums
+}
+
+/// Tell Postgres none of the keys are `NULL`
+fn no_null_keys(null_flags: &Internal) {
+    if let Some(null_flags) = unsafe { null_flags.get_mut::<*mut bool>() } {
+        *null_flags = std::ptr::null_mut();
+    }
+}
+
+/// The body of the generated `compare` support function of `T`
+pub fn gin_compare<T: GinOpClass>(a: T::Key, b: T::Key) -> i32 {
+    match a.cmp(&b) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// The body of the generated `extractValue` support function of `T`
+pub fn gin_extract_value<T: GinOpClass>(
+    value: T,
+    nkeys: Internal,
+    null_flags: Internal,
+) -> Internal {
+    let datums = keys_to_datums(T::extract_value(value), &nkeys);
+    no_null_keys(&null_flags);
+    Internal::from(Some(datums as pg_sys::Datum))
+}
+
+/// The body of the generated `extractQuery` support function of `T`
+pub fn gin_extract_query<T: GinOpClass>(
+    query: T::Query,
+    nkeys: Internal,
+    strategy: i16,
+    null_flags: Internal,
+    partial_matches: Internal,
+    search_mode: Internal,
+) -> Internal {
+    let strategy = strategy as u16;
+    let keys = T::extract_query(&query, strategy);
+
+    let partial = keys
+        .iter()
+        .map(|key| T::partial_match(key, strategy))
+        .collect::<Vec<_>>();
+    if partial.iter().any(|&partial| partial) {
+        if let Some(partial_matches) = unsafe { partial_matches.get_mut::<*mut bool>() } {
+            let flags =
+                unsafe { pg_sys::palloc(partial.len() * std::mem::size_of::<bool>()) as *mut bool };
+            for (idx, partial) in partial.into_iter().enumerate() {
+                unsafe { *flags.add(idx) = partial };
+            }
+            *partial_matches = flags;
+        }
+    }
+
+    if let Some(search_mode) = unsafe { search_mode.get_mut::<i32>() } {
+        *search_mode = T::search_mode(&query, strategy).as_i32();
+    }
+    let datums = keys_to_datums(keys, &nkeys);
+    no_null_keys(&null_flags);
+    Internal::from(Some(datums as pg_sys::Datum))
+}
+
+/// The body of the generated `consistent` support function of `T`
+pub fn gin_consistent<T: GinOpClass>(
+    check: Internal,
+    strategy: i16,
+    query: T::Query,
+    nkeys: i32,
+    recheck: Internal,
+) -> bool {
+    let check = unsafe {
+        std::slice::from_raw_parts(
+            check
+                .unwrap()
+                .expect("GIN consistent function called with a NULL check")
+                as *const bool,
+            nkeys as usize,
+        )
+    };
+    let recheck = unsafe { recheck.get_mut::<bool>() }
+        .expect("GIN consistent function called with a NULL recheck");
+    T::consistent(check, strategy as u16, &query, recheck)
+}
+
+/// The body of the generated `triConsistent` support function of `T`
+pub fn gin_tri_consistent<T: GinTriConsistent>(
+    check: Internal,
+    strategy: i16,
+    query: T::Query,
+    nkeys: i32,
+) -> i8 {
+    let check = unsafe {
+        std::slice::from_raw_parts(
+            check
+                .unwrap()
+                .expect("GIN triConsistent function called with a NULL check")
+                as *const pg_sys::GinTernaryValue,
+            nkeys as usize,
+        )
+    }
+    .iter()
+    .map(|&value| GinTernary::from_value(value))
+    .collect::<Vec<_>>();
+    T::tri_consistent(&check, strategy as u16, &query).as_i8()
+}
+
+/// The body of the generated `comparePartial` support function of `T`
+pub fn gin_compare_partial<T: GinComparePartial>(
+    partial: T::Key,
+    key: T::Key,
+    strategy: i16,
+) -> i32 {
+    match T::compare_partial(&partial, &key, strategy as u16) {
+        GinPartialMatch::NoMatch => -1,
+        GinPartialMatch::Match => 0,
+        GinPartialMatch::Stop => 1,
+    }
+}
@@ -0,0 +1,449 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for declaring GiST operator classes with a Rust trait
+//!
+//! Implement [`GistOpClass`] for a type and annotate the `impl` block with `#[pg_gist_opclass]`.
+//! The macro generates the support functions Postgres needs (`consistent`, `union`, `penalty`,
+//! etc) for whichever trait functions the `impl` provides, and `cargo pgx schema` generates the
+//! matching `CREATE OPERATOR CLASS ... USING gist` statement.
+//!
+//! The trait functions work with deserialized keys.  The support functions translate to and from
+//! the `GISTENTRY` and `GistEntryVector` structures Postgres passes them, through [`GistEntry`]
+//! and [`GistEntryVector`].
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Copy, Clone, PostgresType, Serialize, Deserialize)]
+//! pub struct Span {
+//!     start: i64,
+//!     end: i64,
+//! }
+//!
+//! #[pg_operator(immutable)]
+//! #[opname(&&)]
+//! fn span_overlaps(left: Span, right: Span) -> bool {
+//!     left.start <= right.end && right.start <= left.end
+//! }
+//!
+//! #[pg_gist_opclass]
+//! impl GistOpClass for Span {
+//!     const OPERATORS: &'static [(u16, &'static str)] = &[(3, "&&")];
+//!
+//!     fn consistent(key: &Span, query: &Span, _strategy: u16, _is_leaf: bool, recheck: &mut bool) -> bool {
+//!         *recheck = false;
+//!         key.start <= query.end && query.start <= key.end
+//!     }
+//!
+//!     fn union(keys: Vec<Span>) -> Span {
+//!         let first = keys[0];
+//!         keys.into_iter().fold(first, |a, b| Span {
+//!             start: a.start.min(b.start),
+//!             end: a.end.max(b.end),
+//!         })
+//!     }
+//!
+//!     fn penalty(original: &Span, new: &Span) -> f32 {
+//!         let grown = (original.start - new.start).max(0) + (new.end - original.end).max(0);
+//!         grown as f32
+//!     }
+//!
+//!     fn picksplit(keys: &[Span]) -> (Vec<usize>, Vec<usize>) {
+//!         let mut order = (0..keys.len()).collect::<Vec<_>>();
+//!         order.sort_by_key(|&idx| keys[idx].start);
+//!         let right = order.split_off(order.len() / 2);
+//!         (order, right)
+//!     }
+//!
+//!     fn same(a: &Span, b: &Span) -> bool {
+//!         a.start == b.start && a.end == b.end
+//!     }
+//! }
+//! ```
+//!
+//! Within the `impl`, the following may be omitted and are filled in by `#[pg_gist_opclass]`:
+//!
+//! * `type Key` defaults to `Self`
+//! * `type Query` defaults to `Self`
+//! * `const NAME` defaults to the lowercased name of the implementing type, suffixed with
+//!   `_gist_ops`
+//!
+//! When `Key` is not `Self` (as a bounding box would be for a polygon), the `impl` must also
+//! provide [`GistCompress::compress`].  Postgres 10 requires both [`GistCompress::compress`] and
+//! [`GistDecompress::decompress`] from every GiST operator class.
+use crate::{pg_sys, FromDatum, Internal, IntoDatum, PgBox};
+use std::marker::PhantomData;
+
+/// A GiST operator class.  See the [module documentation](crate::gist) for details.
+///
+/// [`GistOpClass::consistent`], [`GistOpClass::union`], [`GistOpClass::penalty`],
+/// [`GistOpClass::picksplit`] and [`GistOpClass::same`] are required.  The `impl` block may
+/// also provide the functions of [`GistCompress`], [`GistDecompress`], [`GistDistance`] and
+/// [`GistFetch`], which `#[pg_gist_opclass]` moves into implementations of those traits and turns
+/// into SQL support functions.
+pub trait GistOpClass
+where
+    Self: FromDatum + IntoDatum + Sized,
+{
+    /// The type of the keys stored in the index (`STORAGE`)
+    type Key: FromDatum + IntoDatum;
+
+    /// The right-hand argument of the operator class' operators
+    type Query: FromDatum;
+
+    /// The SQL name of the operator class
+    const NAME: &'static str;
+
+    /// Is this the default GiST operator class of the type?
+    const DEFAULT: bool = true;
+
+    /// The operators of the operator class, by strategy number, such as `&[(3, "&&")]`
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    /// The ordering operators of the operator class, by strategy number, such as
+    /// `&[(15, "<->")]`.  They require [`GistDistance::distance`].
+    const ORDER_BY_OPERATORS: &'static [(u16, &'static str)] = &[];
+
+    /// Could any value under `key` satisfy the operator of `strategy` against `query`?  For a leaf
+    /// key (`is_leaf`), this is whether the indexed value does.
+    ///
+    /// `recheck` starts out as `true`, meaning the operator is re-evaluated against the heap
+    /// tuple.  Set it to `false` when a leaf answer is exact.
+    fn consistent(
+        key: &Self::Key,
+        query: &Self::Query,
+        strategy: u16,
+        is_leaf: bool,
+        recheck: &mut bool,
+    ) -> bool;
+
+    /// A key covering all of `keys`
+    fn union(keys: Vec<Self::Key>) -> Self::Key;
+
+    /// The cost of widening `original` to also cover `new`
+    fn penalty(original: &Self::Key, new: &Self::Key) -> f32;
+
+    /// Split an overflowing page, returning the indexes of `keys` which go to the left and to
+    /// the right page.  Every index must be returned exactly once.
+    fn picksplit(keys: &[Self::Key]) -> (Vec<usize>, Vec<usize>);
+
+    /// Are the two keys identical?
+    fn same(a: &Self::Key, b: &Self::Key) -> bool;
+}
+
+/// The `compress` support function of a [`GistOpClass`].
+///
+/// `#[pg_gist_opclass]` implements this from the `fn compress` of the `impl GistOpClass` block.
+pub trait GistCompress: GistOpClass {
+    /// Convert an indexed value to the key stored for it
+    fn compress(value: Self) -> Self::Key;
+}
+
+/// The `decompress` support function of a [`GistOpClass`].
+///
+/// `#[pg_gist_opclass]` implements this from the `fn decompress` of the `impl GistOpClass` block.
+pub trait GistDecompress: GistOpClass {
+    /// Convert a stored key to the key the other support functions work with
+    fn decompress(key: Self::Key) -> Self::Key;
+}
+
+/// The `distance` support function of a [`GistOpClass`], for its `ORDER_BY_OPERATORS`.
+///
+/// `#[pg_gist_opclass]` implements this from the `fn distance` of the `impl GistOpClass` block.
+pub trait GistDistance: GistOpClass {
+    /// The distance from `key` to `query`, for the ordering operator of `strategy`.  For an inner
+    /// key it must not exceed the distance of any value under it.
+    ///
+    /// `recheck` starts out as `false`.  Set it to `true` if a leaf distance is only a lower
+    /// bound.
+    fn distance(
+        key: &Self::Key,
+        query: &Self::Query,
+        strategy: u16,
+        is_leaf: bool,
+        recheck: &mut bool,
+    ) -> f64;
+}
+
+/// The `fetch` support function of a [`GistOpClass`], for index-only scans.
+///
+/// `#[pg_gist_opclass]` implements this from the `fn fetch` of the `impl GistOpClass` block.
+pub trait GistFetch: GistOpClass {
+    /// Reconstruct the indexed value from a leaf key, for index-only scans
+    fn fetch(key: Self::Key) -> Self;
+}
+
+/// A `GISTENTRY`, as passed to the GiST support functions, whose key is a `K`
+pub struct GistEntry<K> {
+    entry: *mut pg_sys::GISTENTRY,
+    __marker: PhantomData<K>,
+}
+
+impl<K> GistEntry<K> {
+    /// Wrap the `GISTENTRY` pointed to by `entry`.
+    ///
+    /// ## Safety
+    ///
+    /// `entry` must point to a valid `GISTENTRY` whose key is a `K`.
+    pub unsafe fn from_ptr(entry: *mut pg_sys::GISTENTRY) -> Self {
+        Self {
+            entry,
+            __marker: PhantomData,
+        }
+    }
+
+    /// Wrap the `GISTENTRY` a support function was passed as an `internal` argument.
+    ///
+    /// ## Safety
+    ///
+    /// `internal` must point to a valid `GISTENTRY` whose key is a `K`.
+    pub unsafe fn from_internal(internal: Internal) -> Self {
+        let datum = internal
+            .unwrap()
+            .expect("GiST support function called with a NULL GISTENTRY");
+        Self::from_ptr(datum as *mut pg_sys::GISTENTRY)
+    }
+
+    /// Is the entry on a leaf page of the index?
+    pub fn is_leaf(&self) -> bool {
+        unsafe {
+            let page = (*self.entry).page as *mut pg_sys::PageHeaderData;
+            if page.is_null() {
+                return false;
+            }
+            let opaque = (page as *mut u8).add((*page).pd_special as usize)
+                as *mut pg_sys::GISTPageOpaqueData;
+            (*opaque).flags as u32 & pg_sys::F_LEAF != 0
+        }
+    }
+
+    /// Does the entry hold a new, not yet compressed, leaf value?
+    pub fn is_leaf_key(&self) -> bool {
+        unsafe { (*self.entry).leafkey }
+    }
+
+    /// A new entry at the same position as this one, with `key` as its key
+    pub fn with_key<T: IntoDatum>(&self, key: T) -> GistEntry<T> {
+        let mut entry = PgBox::<pg_sys::GISTENTRY>::alloc0();
+        entry.key = key.into_datum().expect("GiST keys cannot be NULL");
+        unsafe {
+            entry.rel = (*self.entry).rel;
+            entry.page = (*self.entry).page;
+            entry.offset = (*self.entry).offset;
+            GistEntry::from_ptr(entry.into_pg())
+        }
+    }
+
+    /// The pointer to the `GISTENTRY`, for returning from a support function
+    pub fn into_internal(self) -> Internal {
+        Internal::from(Some(self.entry as pg_sys::Datum))
+    }
+}
+
+impl<K: FromDatum> GistEntry<K> {
+    /// The key of the entry
+    pub fn key(&self) -> K {
+        unsafe { K::from_datum((*self.entry).key, false, pg_sys::InvalidOid) }
+            .expect("GiST keys cannot be NULL")
+    }
+}
+
+/// A `GistEntryVector`, as passed to the GiST `union` and `picksplit` support functions, whose
+/// keys are a `K`
+pub struct GistEntryVector<K> {
+    vector: *mut pg_sys::GistEntryVector,
+    __marker: PhantomData<K>,
+}
+
+impl<K> GistEntryVector<K> {
+    /// Wrap the `GistEntryVector` a support function was passed as an `internal` argument.
+    ///
+    /// ## Safety
+    ///
+    /// `internal` must point to a valid `GistEntryVector` whose keys are a `K`.
+    pub unsafe fn from_internal(internal: Internal) -> Self {
+        let datum = internal
+            .unwrap()
+            .expect("GiST support function called with a NULL GistEntryVector");
+        Self {
+            vector: datum as *mut pg_sys::GistEntryVector,
+            __marker: PhantomData,
+        }
+    }
+
+    /// The number of entries in the vector.  For `picksplit`, the first entry is unused.
+    pub fn len(&self) -> usize {
+        unsafe { (*self.vector).n as usize }
+    }
+
+    /// Is the vector empty?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The entry at `index`
+    pub fn get(&self, index: usize) -> Option<GistEntry<K>> {
+        if index < self.len() {
+            unsafe {
+                let entries = (*self.vector).vector.as_ptr() as *mut pg_sys::GISTENTRY;
+                Some(GistEntry::from_ptr(entries.add(index)))
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over all the entries of the vector
+    pub fn iter(&self) -> impl Iterator<Item = GistEntry<K>> + '_ {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+}
+
+/// The body of the generated `consistent` support function of `T`
+pub fn gist_consistent<T: GistOpClass>(
+    entry: Internal,
+    query: T::Query,
+    strategy: i16,
+    recheck: Internal,
+) -> bool {
+    let entry = unsafe { GistEntry::<T::Key>::from_internal(entry) };
+    let recheck = unsafe { recheck.get_mut::<bool>() }
+        .expect("GiST consistent function called with a NULL recheck");
+    T::consistent(
+        &entry.key(),
+        &query,
+        strategy as u16,
+        entry.is_leaf(),
+        recheck,
+    )
+}
+
+/// The body of the generated `union` support function of `T`
+pub fn gist_union<T: GistOpClass>(entryvec: Internal) -> T::Key {
+    let entryvec = unsafe { GistEntryVector::<T::Key>::from_internal(entryvec) };
+    T::union(entryvec.iter().map(|entry| entry.key()).collect())
+}
+
+/// The body of the generated `compress` support function of `T`
+pub fn gist_compress<T: GistCompress>(entry: Internal) -> Internal {
+    let entry = unsafe { GistEntry::<T>::from_internal(entry) };
+    if entry.is_leaf_key() {
+        entry.with_key(T::compress(entry.key())).into_internal()
+    } else {
+        // Inner keys come out of `union`, so are already compressed
+        entry.into_internal()
+    }
+}
+
+/// The body of the generated `decompress` support function of `T`
+pub fn gist_decompress<T: GistDecompress>(entry: Internal) -> Internal {
+    let entry = unsafe { GistEntry::<T::Key>::from_internal(entry) };
+    entry.with_key(T::decompress(entry.key())).into_internal()
+}
+
+/// The body of the generated `penalty` support function of `T`
+pub fn gist_penalty<T: GistOpClass>(
+    original: Internal,
+    new: Internal,
+    penalty: Internal,
+) -> Internal {
+    let original = unsafe { GistEntry::<T::Key>::from_internal(original) };
+    let new = unsafe { GistEntry::<T::Key>::from_internal(new) };
+    let result = unsafe { penalty.get_mut::<f32>() }
+        .expect("GiST penalty function called with a NULL penalty");
+    *result = T::penalty(&original.key(), &new.key());
+    penalty
+}
+
+/// The body of the generated `picksplit` support function of `T`
+pub fn gist_picksplit<T: GistOpClass>(entryvec: Internal, splitvec: Internal) -> Internal {
+    let entryvec = unsafe { GistEntryVector::<T::Key>::from_internal(entryvec) };
+    let split = unsafe { splitvec.get_mut::<pg_sys::GIST_SPLITVEC>() }
+        .expect("GiST picksplit function called with a NULL GIST_SPLITVEC");
+
+    // `picksplit` entries start at `FirstOffsetNumber`, the first entry is unused
+    let first = pg_sys::FirstOffsetNumber as usize;
+    let keys = (first..entryvec.len())
+        .map(|offset| entryvec.get(offset).unwrap().key())
+        .collect::<Vec<_>>();
+    let (left, right) = T::picksplit(&keys);
+
+    let mut assigned = vec![false; keys.len()];
+    for &index in left.iter().chain(right.iter()) {
+        match assigned.get_mut(index) {
+            Some(seen) if !*seen => *seen = true,
+            _ => panic!(
+                "GistOpClass::picksplit returned an out of range or repeated index: {}",
+                index
+            ),
+        }
+    }
+    if assigned.iter().any(|seen| !seen) {
+        panic!("GistOpClass::picksplit must return every index exactly once");
+    }
+
+    let side = |indexes: &[usize]| -> (*mut pg_sys::OffsetNumber, i32, pg_sys::Datum) {
+        let offsets = unsafe {
+            pg_sys::palloc((indexes.len().max(1)) * std::mem::size_of::<pg_sys::OffsetNumber>())
+                as *mut pg_sys::OffsetNumber
+        };
+        for (position, &index) in indexes.iter().enumerate() {
+            unsafe { *offsets.add(position) = (index + first) as pg_sys::OffsetNumber };
+        }
+        let union = T::union(
+            indexes
+                .iter()
+                .map(|&index| entryvec.get(index + first).unwrap().key())
+                .collect(),
+        );
+        (
+            offsets,
+            indexes.len() as i32,
+            union.into_datum().expect("GiST keys cannot be NULL"),
+        )
+    };
+    let (spl_left, spl_nleft, spl_ldatum) = side(&left);
+    let (spl_right, spl_nright, spl_rdatum) = side(&right);
+    split.spl_left = spl_left;
+    split.spl_nleft = spl_nleft;
+    split.spl_ldatum = spl_ldatum;
+    split.spl_right = spl_right;
+    split.spl_nright = spl_nright;
+    split.spl_rdatum = spl_rdatum;
+    splitvec
+}
+
+/// The body of the generated `same` support function of `T`
+pub fn gist_same<T: GistOpClass>(a: T::Key, b: T::Key, result: Internal) -> Internal {
+    let same =
+        unsafe { result.get_mut::<bool>() }.expect("GiST same function called with a NULL result");
+    *same = T::same(&a, &b);
+    result
+}
+
+/// The body of the generated `distance` support function of `T`
+pub fn gist_distance<T: GistDistance>(
+    entry: Internal,
+    query: T::Query,
+    strategy: i16,
+    recheck: Internal,
+) -> f64 {
+    let entry = unsafe { GistEntry::<T::Key>::from_internal(entry) };
+    let recheck = unsafe { recheck.get_mut::<bool>() }
+        .expect("GiST distance function called with a NULL recheck");
+    T::distance(
+        &entry.key(),
+        &query,
+        strategy as u16,
+        entry.is_leaf(),
+        recheck,
+    )
+}
+
+/// The body of the generated `fetch` support function of `T`
+pub fn gist_fetch<T: GistFetch>(entry: Internal) -> Internal {
+    let entry = unsafe { GistEntry::<T::Key>::from_internal(entry) };
+    entry.with_key(T::fetch(entry.key())).into_internal()
+}
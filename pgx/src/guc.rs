@@ -2,10 +2,20 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 //! Provides a safe interface into Postgres' Configuration System (GUC)
+//!
+//! GUCs are either declared with [`#[pg_guc]`](macro@crate::pg_guc), which defines them when the
+//! extension is loaded, or defined by hand in `_PG_init()` with [`GucRegistry`].
 use crate::{pg_sys, PgMemoryContexts};
+use once_cell::sync::Lazy;
+use std::any::{Any, TypeId};
 use std::cell::Cell;
-use std::ffi::CStr;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GucContext {
     /// cannot be set by the user at all, but only through
     /// internal processes ("server_version" is an example).  These are GUC
@@ -137,6 +147,548 @@ where
     }
 }
 
+unsafe impl Sync for GucSetting<&'static [&'static str]> {}
+/// A list-valued GUC, such as `myext.names = 'a, b, "c, d"'`
+impl GucSetting<&'static [&'static str]> {
+    pub fn get(&self) -> Vec<String> {
+        let ptr = unsafe { *self.char_p.as_ptr() };
+        if ptr.is_null() {
+            Vec::new()
+        } else {
+            split_guc_list(&unsafe { CStr::from_ptr(ptr) }.to_string_lossy())
+        }
+    }
+
+    unsafe fn as_ptr(&self) -> *mut *mut std::os::raw::c_char {
+        self.char_p.as_ptr()
+    }
+}
+
+/// Split the value of a list-valued GUC like Postgres does: elements are separated by commas, and
+/// double-quoted to contain commas or surrounding whitespace (with `""` for a quote).
+fn split_guc_list(value: &str) -> Vec<String> {
+    let mut elements = Vec::new();
+    let mut chars = value.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut element = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next() {
+                    Some('"') if chars.next_if_eq(&'"').is_some() => element.push('"'),
+                    Some('"') | None => break,
+                    Some(c) => element.push(c),
+                }
+            }
+            while chars.next_if(|&c| c != ',').is_some() {}
+        } else {
+            while let Some(c) = chars.next_if(|&c| c != ',') {
+                element.push(c);
+            }
+            element.truncate(element.trim_end().len());
+        }
+        chars.next();
+        elements.push(element);
+    }
+    elements
+}
+
+/// The inverse of [`split_guc_list`], for the boot value of a list-valued GUC
+fn join_guc_list(elements: &[&str]) -> String {
+    elements
+        .iter()
+        .map(|element| {
+            if element.is_empty() || element.contains(&[',', '"'][..]) || element.trim() != *element
+            {
+                format!("\"{}\"", element.replace('"', "\"\""))
+            } else {
+                element.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+bitflags! {
+    /// The flags of a GUC, such as its unit
+    pub struct GucFlags: i32 {
+        /// The value is a comma-separated list, set by [`GucRegistry::define_list_guc`]
+        const LIST_INPUT = pg_sys::GUC_LIST_INPUT as i32;
+        /// The elements of the list are double-quoted when needed
+        const LIST_QUOTE = pg_sys::GUC_LIST_QUOTE as i32;
+        /// Not shown by `SHOW ALL` or in `pg_settings`
+        const NO_SHOW_ALL = pg_sys::GUC_NO_SHOW_ALL as i32;
+        /// Not reset by `RESET ALL`
+        const NO_RESET_ALL = pg_sys::GUC_NO_RESET_ALL as i32;
+        /// Reported to the client whenever it changes
+        const REPORT = pg_sys::GUC_REPORT as i32;
+        /// Not included in `postgresql.conf.sample`
+        const NOT_IN_SAMPLE = pg_sys::GUC_NOT_IN_SAMPLE as i32;
+        /// Can't be set in `postgresql.conf`
+        const DISALLOW_IN_FILE = pg_sys::GUC_DISALLOW_IN_FILE as i32;
+        /// Only shown to superusers
+        const SUPERUSER_ONLY = pg_sys::GUC_SUPERUSER_ONLY as i32;
+        /// Limited to `NAMEDATALEN - 1` bytes
+        const IS_NAME = pg_sys::GUC_IS_NAME as i32;
+        /// Can't be set by a security-restricted operation
+        const NOT_WHILE_SEC_REST = pg_sys::GUC_NOT_WHILE_SEC_REST as i32;
+        /// Can't be set by `ALTER SYSTEM`
+        const DISALLOW_IN_AUTO_FILE = pg_sys::GUC_DISALLOW_IN_AUTO_FILE as i32;
+        /// An amount of memory in kilobytes
+        const UNIT_KB = pg_sys::GUC_UNIT_KB as i32;
+        /// An amount of memory in blocks (`BLCKSZ`, usually 8kB)
+        const UNIT_BLOCKS = pg_sys::GUC_UNIT_BLOCKS as i32;
+        /// An amount of memory in WAL blocks (`XLOG_BLCKSZ`)
+        const UNIT_XBLOCKS = pg_sys::GUC_UNIT_XBLOCKS as i32;
+        /// An amount of memory in megabytes
+        const UNIT_MB = pg_sys::GUC_UNIT_MB as i32;
+        /// An amount of memory in bytes
+        #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+        const UNIT_BYTE = pg_sys::GUC_UNIT_BYTE as i32;
+        /// A duration in milliseconds
+        const UNIT_MS = pg_sys::GUC_UNIT_MS as i32;
+        /// A duration in seconds
+        const UNIT_S = pg_sys::GUC_UNIT_S as i32;
+        /// A duration in minutes
+        const UNIT_MIN = pg_sys::GUC_UNIT_MIN as i32;
+        /// Shown by `EXPLAIN (SETTINGS)` when it isn't the default
+        #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+        const EXPLAIN = pg_sys::GUC_EXPLAIN as i32;
+    }
+}
+
+/// A type of [`GucSetting`], which determines what the hooks of its [`GucOptions`] are called with
+pub trait GucType: Sized {
+    /// The value of the GUC, as given to the check and assign hooks
+    type Value;
+
+    #[doc(hidden)]
+    type CheckHook: Copy + Default;
+
+    #[doc(hidden)]
+    type AssignHook: Copy + Default;
+
+    #[doc(hidden)]
+    fn check_hook<F>() -> Self::CheckHook
+    where
+        F: Fn(Self::Value) -> Result<(), String> + Send + Sync + 'static;
+
+    #[doc(hidden)]
+    fn assign_hook<F>() -> Self::AssignHook
+    where
+        F: Fn(Self::Value) + Send + Sync + 'static;
+}
+
+/// A [`GucType`] whose values are limited to a range, by [`GucOptions::min`] and
+/// [`GucOptions::max`]
+pub trait GucRange: GucType {}
+
+impl GucRange for i32 {}
+impl GucRange for f64 {}
+
+impl GucType for bool {
+    type Value = bool;
+    type CheckHook = pg_sys::GucBoolCheckHook;
+    type AssignHook = pg_sys::GucBoolAssignHook;
+
+    fn check_hook<F>() -> Self::CheckHook
+    where
+        F: Fn(bool) -> Result<(), String> + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn check<F>(
+            newval: *mut bool,
+            _extra: *mut *mut c_void,
+            _source: pg_sys::GucSource,
+        ) -> bool
+        where
+            F: Fn(bool) -> Result<(), String> + Send + Sync + 'static,
+        {
+            call_check_hook::<F, _>(*newval)
+        }
+        Some(check::<F>)
+    }
+
+    fn assign_hook<F>() -> Self::AssignHook
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn assign<F>(newval: bool, _extra: *mut c_void)
+        where
+            F: Fn(bool) + Send + Sync + 'static,
+        {
+            call_assign_hook::<F, _>(newval)
+        }
+        Some(assign::<F>)
+    }
+}
+
+impl GucType for i32 {
+    type Value = i32;
+    type CheckHook = pg_sys::GucIntCheckHook;
+    type AssignHook = pg_sys::GucIntAssignHook;
+
+    fn check_hook<F>() -> Self::CheckHook
+    where
+        F: Fn(i32) -> Result<(), String> + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn check<F>(
+            newval: *mut c_int,
+            _extra: *mut *mut c_void,
+            _source: pg_sys::GucSource,
+        ) -> bool
+        where
+            F: Fn(i32) -> Result<(), String> + Send + Sync + 'static,
+        {
+            call_check_hook::<F, _>(*newval)
+        }
+        Some(check::<F>)
+    }
+
+    fn assign_hook<F>() -> Self::AssignHook
+    where
+        F: Fn(i32) + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn assign<F>(newval: c_int, _extra: *mut c_void)
+        where
+            F: Fn(i32) + Send + Sync + 'static,
+        {
+            call_assign_hook::<F, _>(newval)
+        }
+        Some(assign::<F>)
+    }
+}
+
+impl GucType for f64 {
+    type Value = f64;
+    type CheckHook = pg_sys::GucRealCheckHook;
+    type AssignHook = pg_sys::GucRealAssignHook;
+
+    fn check_hook<F>() -> Self::CheckHook
+    where
+        F: Fn(f64) -> Result<(), String> + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn check<F>(
+            newval: *mut f64,
+            _extra: *mut *mut c_void,
+            _source: pg_sys::GucSource,
+        ) -> bool
+        where
+            F: Fn(f64) -> Result<(), String> + Send + Sync + 'static,
+        {
+            call_check_hook::<F, _>(*newval)
+        }
+        Some(check::<F>)
+    }
+
+    fn assign_hook<F>() -> Self::AssignHook
+    where
+        F: Fn(f64) + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn assign<F>(newval: f64, _extra: *mut c_void)
+        where
+            F: Fn(f64) + Send + Sync + 'static,
+        {
+            call_assign_hook::<F, _>(newval)
+        }
+        Some(assign::<F>)
+    }
+}
+
+impl GucType for Option<&'static str> {
+    type Value = Option<String>;
+    type CheckHook = pg_sys::GucStringCheckHook;
+    type AssignHook = pg_sys::GucStringAssignHook;
+
+    fn check_hook<F>() -> Self::CheckHook
+    where
+        F: Fn(Option<String>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn check<F>(
+            newval: *mut *mut c_char,
+            _extra: *mut *mut c_void,
+            _source: pg_sys::GucSource,
+        ) -> bool
+        where
+            F: Fn(Option<String>) -> Result<(), String> + Send + Sync + 'static,
+        {
+            call_check_hook::<F, _>(string_value(*newval))
+        }
+        Some(check::<F>)
+    }
+
+    fn assign_hook<F>() -> Self::AssignHook
+    where
+        F: Fn(Option<String>) + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn assign<F>(newval: *const c_char, _extra: *mut c_void)
+        where
+            F: Fn(Option<String>) + Send + Sync + 'static,
+        {
+            call_assign_hook::<F, _>(string_value(newval))
+        }
+        Some(assign::<F>)
+    }
+}
+
+impl GucType for &'static [&'static str] {
+    type Value = Vec<String>;
+    type CheckHook = pg_sys::GucStringCheckHook;
+    type AssignHook = pg_sys::GucStringAssignHook;
+
+    fn check_hook<F>() -> Self::CheckHook
+    where
+        F: Fn(Vec<String>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn check<F>(
+            newval: *mut *mut c_char,
+            _extra: *mut *mut c_void,
+            _source: pg_sys::GucSource,
+        ) -> bool
+        where
+            F: Fn(Vec<String>) -> Result<(), String> + Send + Sync + 'static,
+        {
+            call_check_hook::<F, _>(list_value(*newval))
+        }
+        Some(check::<F>)
+    }
+
+    fn assign_hook<F>() -> Self::AssignHook
+    where
+        F: Fn(Vec<String>) + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn assign<F>(newval: *const c_char, _extra: *mut c_void)
+        where
+            F: Fn(Vec<String>) + Send + Sync + 'static,
+        {
+            call_assign_hook::<F, _>(list_value(newval))
+        }
+        Some(assign::<F>)
+    }
+}
+
+impl<T> GucType for T
+where
+    T: GucEnum<T> + Copy,
+{
+    type Value = T;
+    type CheckHook = pg_sys::GucEnumCheckHook;
+    type AssignHook = pg_sys::GucEnumAssignHook;
+
+    fn check_hook<F>() -> Self::CheckHook
+    where
+        F: Fn(T) -> Result<(), String> + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn check<T, F>(
+            newval: *mut c_int,
+            _extra: *mut *mut c_void,
+            _source: pg_sys::GucSource,
+        ) -> bool
+        where
+            T: GucEnum<T> + Copy,
+            F: Fn(T) -> Result<(), String> + Send + Sync + 'static,
+        {
+            call_check_hook::<F, _>(T::from_ordinal(*newval))
+        }
+        Some(check::<T, F>)
+    }
+
+    fn assign_hook<F>() -> Self::AssignHook
+    where
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn assign<T, F>(newval: c_int, _extra: *mut c_void)
+        where
+            T: GucEnum<T> + Copy,
+            F: Fn(T) + Send + Sync + 'static,
+        {
+            call_assign_hook::<F, _>(T::from_ordinal(newval))
+        }
+        Some(assign::<T, F>)
+    }
+}
+
+unsafe fn string_value(value: *const c_char) -> Option<String> {
+    if value.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(value).to_string_lossy().into_owned())
+    }
+}
+
+unsafe fn list_value(value: *const c_char) -> Vec<String> {
+    string_value(value)
+        .map(|value| split_guc_list(&value))
+        .unwrap_or_default()
+}
+
+/// The hooks of [`GucOptions`], by the type of their closure.
+///
+/// Postgres calls a hook without saying which GUC it's for, so each hook is a function generic
+/// over the closure's type, which finds the closure here.
+static GUC_HOOKS: Lazy<Mutex<HashMap<TypeId, &'static (dyn Any + Send + Sync)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn register_hook<F: Any + Send + Sync>(hook: F) {
+    let mut hooks = GUC_HOOKS.lock().expect("GUC hooks are poisoned");
+    // Closures which don't capture anything are all the same, but others might not be
+    if std::mem::size_of::<F>() != 0 && hooks.contains_key(&TypeId::of::<F>()) {
+        panic!("a GUC hook closure which captures values can only be used by one GUC");
+    }
+    hooks.insert(TypeId::of::<F>(), Box::leak(Box::new(hook)));
+}
+
+fn registered_hook<F: Any + Send + Sync>() -> &'static F {
+    GUC_HOOKS
+        .lock()
+        .expect("GUC hooks are poisoned")
+        .get(&TypeId::of::<F>())
+        .and_then(|hook| hook.downcast_ref::<F>())
+        .expect("GUC hook is not registered")
+}
+
+/// Call a check hook, an `Err` is reported by Postgres as the message of the failed check.
+///
+/// The value is moved into the hook, so there's nothing left inconsistent if it panics.
+unsafe fn call_check_hook<F, V>(value: V) -> bool
+where
+    F: Fn(V) -> Result<(), String> + Send + Sync + 'static,
+{
+    pg_sys::guard::guard(AssertUnwindSafe(|| match registered_hook::<F>()(value) {
+        Ok(()) => true,
+        Err(message) => {
+            pg_sys::GUC_check_errmsg_string =
+                PgMemoryContexts::CurrentMemoryContext.pstrdup(&message);
+            false
+        }
+    }))
+}
+
+unsafe fn call_assign_hook<F, V>(value: V)
+where
+    F: Fn(V) + Send + Sync + 'static,
+{
+    pg_sys::guard::guard(AssertUnwindSafe(|| registered_hook::<F>()(value)))
+}
+
+/// The range, flags and hooks of a GUC defined by [`GucRegistry`]:
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// static MAX_RETRIES: GucSetting<i32> = GucSetting::new(3);
+///
+/// GucRegistry::define_int_guc_with_options(
+///     "myext.max_retries",
+///     "How often to retry",
+///     "How often to retry a failed request",
+///     &MAX_RETRIES,
+///     GucContext::Userset,
+///     GucOptions::new()
+///         .min(0)
+///         .max(100)
+///         .flags(GucFlags::NOT_IN_SAMPLE)
+///         .check_hook(|retries| match retries % 2 {
+///             0 => Ok(()),
+///             _ => Err(format!("{} is not even", retries)),
+///         })
+///         .assign_hook(|retries| info!("retrying {} times", retries))
+///         .show_hook(|| format!("{} times", MAX_RETRIES.get())),
+/// );
+/// ```
+///
+/// A hook may be called with a value which is never assigned, such as when `SET` fails, so it
+/// shouldn't `ERROR`, and the assign hook shouldn't do anything which can fail.
+pub struct GucOptions<T: GucType> {
+    min: Option<T::Value>,
+    max: Option<T::Value>,
+    flags: GucFlags,
+    check_hook: T::CheckHook,
+    assign_hook: T::AssignHook,
+    show_hook: pg_sys::GucShowHook,
+}
+
+impl<T: GucType> Default for GucOptions<T> {
+    fn default() -> Self {
+        Self {
+            min: None,
+            max: None,
+            flags: GucFlags::empty(),
+            check_hook: Default::default(),
+            assign_hook: Default::default(),
+            show_hook: None,
+        }
+    }
+}
+
+impl<T: GucType> GucOptions<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `flags` to those of the GUC
+    pub fn flags(mut self, flags: GucFlags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Check a new value before it is assigned, rejecting it with an `Err` describing why
+    pub fn check_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(T::Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        register_hook(hook);
+        self.check_hook = T::check_hook::<F>();
+        self
+    }
+
+    /// Called with a new value just before it's assigned
+    pub fn assign_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(T::Value) + Send + Sync + 'static,
+    {
+        register_hook(hook);
+        self.assign_hook = T::assign_hook::<F>();
+        self
+    }
+
+    /// Show the value of the GUC, for `SHOW` and `pg_settings`
+    pub fn show_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        unsafe extern "C" fn show<F>() -> *const c_char
+        where
+            F: Fn() -> String + Send + Sync + 'static,
+        {
+            pg_sys::guard::guard(|| {
+                PgMemoryContexts::CurrentMemoryContext.pstrdup(&registered_hook::<F>()())
+            })
+        }
+
+        register_hook(hook);
+        self.show_hook = Some(show::<F>);
+        self
+    }
+}
+
+impl<T: GucRange> GucOptions<T> {
+    /// The smallest value the GUC may be set to, the smallest value of its type if not given
+    pub fn min(mut self, min: T::Value) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// The largest value the GUC may be set to, the largest value of its type if not given
+    pub fn max(mut self, max: T::Value) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
 pub struct GucRegistry {}
 impl GucRegistry {
     pub fn define_bool_guc(
@@ -146,20 +698,32 @@ impl GucRegistry {
         setting: &GucSetting<bool>,
         context: GucContext,
     ) {
-        unsafe {
-            pg_sys::DefineCustomBoolVariable(
-                PgMemoryContexts::TopMemoryContext.pstrdup(name),
-                PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
-                PgMemoryContexts::TopMemoryContext.pstrdup(long_description),
-                setting.as_ptr(),
-                setting.get(),
-                context as isize as u32,
-                0,
-                None,
-                None,
-                None,
-            )
-        }
+        Self::define_bool_guc_with_options(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            GucOptions::new(),
+        )
+    }
+
+    pub fn define_bool_guc_with_options(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<bool>,
+        context: GucContext,
+        options: GucOptions<bool>,
+    ) {
+        define_bool(
+            name,
+            short_description,
+            Some(long_description),
+            setting,
+            context,
+            options,
+        )
     }
 
     pub fn define_int_guc(
@@ -171,22 +735,32 @@ impl GucRegistry {
         max_value: i32,
         context: GucContext,
     ) {
-        unsafe {
-            pg_sys::DefineCustomIntVariable(
-                PgMemoryContexts::TopMemoryContext.pstrdup(name),
-                PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
-                PgMemoryContexts::TopMemoryContext.pstrdup(long_description),
-                setting.as_ptr(),
-                setting.get(),
-                min_value,
-                max_value,
-                context as isize as u32,
-                0,
-                None,
-                None,
-                None,
-            )
-        }
+        Self::define_int_guc_with_options(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            GucOptions::new().min(min_value).max(max_value),
+        )
+    }
+
+    pub fn define_int_guc_with_options(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<i32>,
+        context: GucContext,
+        options: GucOptions<i32>,
+    ) {
+        define_int(
+            name,
+            short_description,
+            Some(long_description),
+            setting,
+            context,
+            options,
+        )
     }
 
     pub fn define_string_guc(
@@ -196,25 +770,69 @@ impl GucRegistry {
         setting: &GucSetting<Option<&'static str>>,
         context: GucContext,
     ) {
-        unsafe {
-            let boot_value = match setting.value.get() {
-                Some(s) => PgMemoryContexts::TopMemoryContext.pstrdup(s),
-                None => std::ptr::null_mut(),
-            };
-
-            pg_sys::DefineCustomStringVariable(
-                PgMemoryContexts::TopMemoryContext.pstrdup(name),
-                PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
-                PgMemoryContexts::TopMemoryContext.pstrdup(long_description),
-                setting.as_ptr(),
-                boot_value,
-                context as isize as u32,
-                0,
-                None,
-                None,
-                None,
-            )
-        }
+        Self::define_string_guc_with_options(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            GucOptions::new(),
+        )
+    }
+
+    pub fn define_string_guc_with_options(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<Option<&'static str>>,
+        context: GucContext,
+        options: GucOptions<Option<&'static str>>,
+    ) {
+        define_string(
+            name,
+            short_description,
+            Some(long_description),
+            setting,
+            context,
+            options,
+        )
+    }
+
+    /// Define a string GUC whose value is a comma-separated list, such as
+    /// `SET myext.names = 'a, b, "c, d"'`, which the setting gets as a `Vec<String>`
+    pub fn define_list_guc(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<&'static [&'static str]>,
+        context: GucContext,
+    ) {
+        Self::define_list_guc_with_options(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            GucOptions::new(),
+        )
+    }
+
+    pub fn define_list_guc_with_options(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<&'static [&'static str]>,
+        context: GucContext,
+        options: GucOptions<&'static [&'static str]>,
+    ) {
+        define_list(
+            name,
+            short_description,
+            Some(long_description),
+            setting,
+            context,
+            options,
+        )
     }
 
     pub fn define_float_guc(
@@ -226,22 +844,32 @@ impl GucRegistry {
         max_value: f64,
         context: GucContext,
     ) {
-        unsafe {
-            pg_sys::DefineCustomRealVariable(
-                PgMemoryContexts::TopMemoryContext.pstrdup(name),
-                PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
-                PgMemoryContexts::TopMemoryContext.pstrdup(long_description),
-                setting.as_ptr(),
-                setting.get(),
-                min_value,
-                max_value,
-                context as isize as u32,
-                0,
-                None,
-                None,
-                None,
-            )
-        }
+        Self::define_float_guc_with_options(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            GucOptions::new().min(min_value).max(max_value),
+        )
+    }
+
+    pub fn define_float_guc_with_options(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<f64>,
+        context: GucContext,
+        options: GucOptions<f64>,
+    ) {
+        define_float(
+            name,
+            short_description,
+            Some(long_description),
+            setting,
+            context,
+            options,
+        )
     }
 
     pub fn define_enum_guc<T>(
@@ -253,20 +881,373 @@ impl GucRegistry {
     ) where
         T: GucEnum<T> + Copy,
     {
-        unsafe {
-            pg_sys::DefineCustomEnumVariable(
-                PgMemoryContexts::TopMemoryContext.pstrdup(name),
-                PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
-                PgMemoryContexts::TopMemoryContext.pstrdup(long_description),
-                setting.as_ptr(),
-                setting.value.get().to_ordinal(),
-                setting.value.get().config_matrix(),
-                context as isize as u32,
-                0,
-                None,
-                None,
-                None,
-            )
-        }
+        Self::define_enum_guc_with_options(
+            name,
+            short_description,
+            long_description,
+            setting,
+            context,
+            GucOptions::new(),
+        )
+    }
+
+    pub fn define_enum_guc_with_options<T>(
+        name: &str,
+        short_description: &str,
+        long_description: &str,
+        setting: &GucSetting<T>,
+        context: GucContext,
+        options: GucOptions<T>,
+    ) where
+        T: GucEnum<T> + Copy,
+    {
+        define_enum(
+            name,
+            short_description,
+            Some(long_description),
+            setting,
+            context,
+            options,
+        )
+    }
+}
+
+/// A GUC declared with [`#[pg_guc]`](macro@crate::pg_guc), which is defined by
+/// [`define_declared_gucs`]
+#[derive(Clone)]
+pub struct GucDeclaration {
+    pub name: &'static str,
+    pub short_description: &'static str,
+    pub long_description: Option<&'static str>,
+    pub context: GucContext,
+    /// The unit of the GUC, if it has one
+    pub flags: GucFlags,
+    /// Defines the [`GucSetting`] the declaration is for
+    pub define: fn(&GucDeclaration),
+}
+
+/// A [`GucSetting`] which can be declared with [`#[pg_guc]`](macro@crate::pg_guc)
+pub trait DeclaredGuc {
+    /// The type of the `min` and `max` of the GUC, `()` for types without a range
+    type Bound;
+
+    /// The type of the GUC, as in the `vartype` column of `pg_settings`
+    const VARTYPE: &'static str;
+
+    /// Define the GUC, with its range limited to `min` and `max` if they're given
+    fn define(
+        &'static self,
+        declaration: &GucDeclaration,
+        min: Option<Self::Bound>,
+        max: Option<Self::Bound>,
+    );
+}
+
+impl DeclaredGuc for GucSetting<bool> {
+    type Bound = ();
+    const VARTYPE: &'static str = "bool";
+
+    fn define(&'static self, declaration: &GucDeclaration, _min: Option<()>, _max: Option<()>) {
+        define_bool(
+            declaration.name,
+            declaration.short_description,
+            declaration.long_description,
+            self,
+            declaration.context,
+            GucOptions::new().flags(declaration.flags),
+        )
+    }
+}
+
+impl DeclaredGuc for GucSetting<i32> {
+    type Bound = i32;
+    const VARTYPE: &'static str = "integer";
+
+    fn define(&'static self, declaration: &GucDeclaration, min: Option<i32>, max: Option<i32>) {
+        define_int(
+            declaration.name,
+            declaration.short_description,
+            declaration.long_description,
+            self,
+            declaration.context,
+            GucOptions {
+                min,
+                max,
+                ..GucOptions::new().flags(declaration.flags)
+            },
+        )
+    }
+}
+
+impl DeclaredGuc for GucSetting<f64> {
+    type Bound = f64;
+    const VARTYPE: &'static str = "real";
+
+    fn define(&'static self, declaration: &GucDeclaration, min: Option<f64>, max: Option<f64>) {
+        define_float(
+            declaration.name,
+            declaration.short_description,
+            declaration.long_description,
+            self,
+            declaration.context,
+            GucOptions {
+                min,
+                max,
+                ..GucOptions::new().flags(declaration.flags)
+            },
+        )
+    }
+}
+
+impl DeclaredGuc for GucSetting<Option<&'static str>> {
+    type Bound = ();
+    const VARTYPE: &'static str = "string";
+
+    fn define(&'static self, declaration: &GucDeclaration, _min: Option<()>, _max: Option<()>) {
+        define_string(
+            declaration.name,
+            declaration.short_description,
+            declaration.long_description,
+            self,
+            declaration.context,
+            GucOptions::new().flags(declaration.flags),
+        )
+    }
+}
+
+impl DeclaredGuc for GucSetting<&'static [&'static str]> {
+    type Bound = ();
+    const VARTYPE: &'static str = "string";
+
+    fn define(&'static self, declaration: &GucDeclaration, _min: Option<()>, _max: Option<()>) {
+        define_list(
+            declaration.name,
+            declaration.short_description,
+            declaration.long_description,
+            self,
+            declaration.context,
+            GucOptions::new().flags(declaration.flags),
+        )
+    }
+}
+
+impl<T> DeclaredGuc for GucSetting<T>
+where
+    T: GucEnum<T> + Copy,
+{
+    type Bound = ();
+    const VARTYPE: &'static str = "enum";
+
+    fn define(&'static self, declaration: &GucDeclaration, _min: Option<()>, _max: Option<()>) {
+        define_enum(
+            declaration.name,
+            declaration.short_description,
+            declaration.long_description,
+            self,
+            declaration.context,
+            GucOptions::new().flags(declaration.flags),
+        )
+    }
+}
+
+static DECLARED_GUCS: Lazy<Mutex<Vec<GucDeclaration>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Record a GUC declared with [`#[pg_guc]`](macro@crate::pg_guc).  This happens as the
+/// extension's library is loaded, before its `_PG_init()` is called.
+#[doc(hidden)]
+pub fn declare_guc(declaration: GucDeclaration) {
+    DECLARED_GUCS
+        .lock()
+        .expect("declared GUCs are poisoned")
+        .push(declaration);
+}
+
+/// Define the GUCs declared with [`#[pg_guc]`](macro@crate::pg_guc), in order of their names, and
+/// reserve their prefixes (the `myext` of `myext.threshold`) with `EmitWarningsOnPlaceholders`.
+///
+/// A [`#[pg_guard]`](macro@crate::pg_guard) `_PG_init()` calls this before its own body, so it
+/// only needs to be called by hand from a `_PG_init()` which isn't `#[pg_guard]`.
+pub fn define_declared_gucs() {
+    let mut declarations = DECLARED_GUCS
+        .lock()
+        .expect("declared GUCs are poisoned")
+        .clone();
+    declarations.sort_by_key(|declaration| declaration.name);
+
+    for declaration in &declarations {
+        (declaration.define)(declaration);
+    }
+
+    let prefixes = declarations
+        .iter()
+        .filter_map(|declaration| declaration.name.split_once('.'))
+        .map(|(prefix, _)| prefix)
+        .collect::<BTreeSet<_>>();
+    for prefix in prefixes {
+        let prefix = CString::new(prefix).expect("GUC prefix contains a null byte");
+        unsafe { pg_sys::EmitWarningsOnPlaceholders(prefix.as_ptr()) }
+    }
+}
+
+fn pstrdup_description(description: Option<&str>) -> *mut std::os::raw::c_char {
+    match description {
+        Some(description) => PgMemoryContexts::TopMemoryContext.pstrdup(description),
+        None => std::ptr::null_mut(),
+    }
+}
+
+fn define_bool(
+    name: &str,
+    short_description: &str,
+    long_description: Option<&str>,
+    setting: &GucSetting<bool>,
+    context: GucContext,
+    options: GucOptions<bool>,
+) {
+    unsafe {
+        pg_sys::DefineCustomBoolVariable(
+            PgMemoryContexts::TopMemoryContext.pstrdup(name),
+            PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+            pstrdup_description(long_description),
+            setting.as_ptr(),
+            setting.get(),
+            context as isize as u32,
+            options.flags.bits(),
+            options.check_hook,
+            options.assign_hook,
+            options.show_hook,
+        )
+    }
+}
+
+fn define_int(
+    name: &str,
+    short_description: &str,
+    long_description: Option<&str>,
+    setting: &GucSetting<i32>,
+    context: GucContext,
+    options: GucOptions<i32>,
+) {
+    unsafe {
+        pg_sys::DefineCustomIntVariable(
+            PgMemoryContexts::TopMemoryContext.pstrdup(name),
+            PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+            pstrdup_description(long_description),
+            setting.as_ptr(),
+            setting.get(),
+            options.min.unwrap_or(i32::MIN),
+            options.max.unwrap_or(i32::MAX),
+            context as isize as u32,
+            options.flags.bits(),
+            options.check_hook,
+            options.assign_hook,
+            options.show_hook,
+        )
+    }
+}
+
+fn define_string(
+    name: &str,
+    short_description: &str,
+    long_description: Option<&str>,
+    setting: &GucSetting<Option<&'static str>>,
+    context: GucContext,
+    options: GucOptions<Option<&'static str>>,
+) {
+    unsafe {
+        let boot_value = match setting.value.get() {
+            Some(s) => PgMemoryContexts::TopMemoryContext.pstrdup(s),
+            None => std::ptr::null_mut(),
+        };
+
+        pg_sys::DefineCustomStringVariable(
+            PgMemoryContexts::TopMemoryContext.pstrdup(name),
+            PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+            pstrdup_description(long_description),
+            setting.as_ptr(),
+            boot_value,
+            context as isize as u32,
+            options.flags.bits(),
+            options.check_hook,
+            options.assign_hook,
+            options.show_hook,
+        )
+    }
+}
+
+fn define_list(
+    name: &str,
+    short_description: &str,
+    long_description: Option<&str>,
+    setting: &GucSetting<&'static [&'static str]>,
+    context: GucContext,
+    options: GucOptions<&'static [&'static str]>,
+) {
+    unsafe {
+        pg_sys::DefineCustomStringVariable(
+            PgMemoryContexts::TopMemoryContext.pstrdup(name),
+            PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+            pstrdup_description(long_description),
+            setting.as_ptr(),
+            PgMemoryContexts::TopMemoryContext.pstrdup(&join_guc_list(setting.value.get())),
+            context as isize as u32,
+            (options.flags | GucFlags::LIST_INPUT).bits(),
+            options.check_hook,
+            options.assign_hook,
+            options.show_hook,
+        )
+    }
+}
+
+fn define_float(
+    name: &str,
+    short_description: &str,
+    long_description: Option<&str>,
+    setting: &GucSetting<f64>,
+    context: GucContext,
+    options: GucOptions<f64>,
+) {
+    unsafe {
+        pg_sys::DefineCustomRealVariable(
+            PgMemoryContexts::TopMemoryContext.pstrdup(name),
+            PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+            pstrdup_description(long_description),
+            setting.as_ptr(),
+            setting.get(),
+            options.min.unwrap_or(f64::MIN),
+            options.max.unwrap_or(f64::MAX),
+            context as isize as u32,
+            options.flags.bits(),
+            options.check_hook,
+            options.assign_hook,
+            options.show_hook,
+        )
+    }
+}
+
+fn define_enum<T>(
+    name: &str,
+    short_description: &str,
+    long_description: Option<&str>,
+    setting: &GucSetting<T>,
+    context: GucContext,
+    options: GucOptions<T>,
+) where
+    T: GucEnum<T> + Copy,
+{
+    unsafe {
+        pg_sys::DefineCustomEnumVariable(
+            PgMemoryContexts::TopMemoryContext.pstrdup(name),
+            PgMemoryContexts::TopMemoryContext.pstrdup(short_description),
+            pstrdup_description(long_description),
+            setting.as_ptr(),
+            setting.value.get().to_ordinal(),
+            setting.value.get().config_matrix(),
+            context as isize as u32,
+            options.flags.bits(),
+            options.check_hook,
+            options.assign_hook,
+            options.show_hook,
+        )
     }
 }
@@ -0,0 +1,170 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides a typed wrapper around a `pg_sys::HeapTuple` and the `PgTupleDesc` that describes it
+use crate::{heap_getattr_raw, pg_sys, FromDatum, IntoDatum, PgTupleDesc};
+
+/// Errors that can occur when reading or writing the attributes of a [`PgHeapTuple`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgHeapTupleError {
+    /// The tuple has no attribute with this name
+    NoSuchAttributeName(String),
+    /// The tuple has no attribute with this (1-based) number
+    NoSuchAttributeNumber(usize),
+    /// The Rust type's SQL type isn't binary coercible to the attribute's type
+    IncompatibleTypes {
+        attno: usize,
+        attribute_type: pg_sys::Oid,
+        value_type: pg_sys::Oid,
+    },
+}
+
+impl std::fmt::Display for PgHeapTupleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgHeapTupleError::NoSuchAttributeName(name) => {
+                write!(f, "no such attribute: {}", name)
+            }
+            PgHeapTupleError::NoSuchAttributeNumber(attno) => {
+                write!(f, "no such attribute number: {}", attno)
+            }
+            PgHeapTupleError::IncompatibleTypes {
+                attno,
+                attribute_type,
+                value_type,
+            } => write!(
+                f,
+                "type oid {} is not compatible with attribute {} of type oid {}",
+                value_type, attno, attribute_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PgHeapTupleError {}
+
+/// A `pg_sys::HeapTuple` along with the `PgTupleDesc` describing its attributes.
+///
+/// Attribute numbers are 1-based, just like `heap_getattr()`.
+pub struct PgHeapTuple<'a> {
+    tuple: pg_sys::HeapTuple,
+    tupdesc: PgTupleDesc<'a>,
+}
+
+impl<'a> PgHeapTuple<'a> {
+    /// Wrap a Postgres-provided `pg_sys::HeapTuple` that is described by `tupdesc`.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot validate that `tuple` is a valid pointer, nor that
+    /// `tupdesc` actually describes it
+    pub unsafe fn from_heap_tuple(tupdesc: PgTupleDesc<'a>, tuple: pg_sys::HeapTuple) -> Self {
+        PgHeapTuple { tuple, tupdesc }
+    }
+
+    /// The `PgTupleDesc` that describes this tuple
+    pub fn tupdesc(&self) -> &PgTupleDesc<'a> {
+        &self.tupdesc
+    }
+
+    /// The underlying `pg_sys::HeapTuple`
+    pub fn as_ptr(&self) -> pg_sys::HeapTuple {
+        self.tuple
+    }
+
+    /// Give the underlying `pg_sys::HeapTuple` back to Postgres
+    pub fn into_pg(self) -> pg_sys::HeapTuple {
+        self.tuple
+    }
+
+    /// The number of attributes in this tuple, including dropped ones
+    pub fn len(&self) -> usize {
+        self.tupdesc.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tupdesc.is_empty()
+    }
+
+    /// Get the value of the attribute numbered `attno`.  `Ok(None)` means it is `NULL`.
+    pub fn get_by_index<T: FromDatum>(&self, attno: usize) -> Result<Option<T>, PgHeapTupleError> {
+        let attribute = self.attribute(attno)?;
+        unsafe {
+            match heap_getattr_raw(self.tuple, attno, self.tupdesc.as_ptr()) {
+                Some(datum) => Ok(T::from_datum(datum, false, attribute.atttypid)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    /// Get the value of the attribute named `name`.  `Ok(None)` means it is `NULL`.
+    pub fn get_by_name<T: FromDatum>(&self, name: &str) -> Result<Option<T>, PgHeapTupleError> {
+        self.get_by_index(self.attno(name)?)
+    }
+
+    /// Replace the value of the attribute numbered `attno`.  Pass `None` (as an `Option<T>`) to
+    /// set it to `NULL`.
+    ///
+    /// The tuple is replaced with a modified copy, allocated in `CurrentMemoryContext`.
+    pub fn set_by_index<T: IntoDatum>(
+        &mut self,
+        attno: usize,
+        value: T,
+    ) -> Result<(), PgHeapTupleError> {
+        let attribute_type = self.attribute(attno)?.atttypid;
+        let value_type = T::type_oid();
+        if value_type != attribute_type
+            && !unsafe { pg_sys::IsBinaryCoercible(value_type, attribute_type) }
+        {
+            return Err(PgHeapTupleError::IncompatibleTypes {
+                attno,
+                attribute_type,
+                value_type,
+            });
+        }
+
+        let mut column = attno as i32;
+        let (mut datum, mut isnull) = match value.into_datum() {
+            Some(datum) => (datum, false),
+            None => (0, true),
+        };
+        self.tuple = unsafe {
+            pg_sys::heap_modify_tuple_by_cols(
+                self.tuple,
+                self.tupdesc.as_ptr(),
+                1,
+                &mut column,
+                &mut datum,
+                &mut isnull,
+            )
+        };
+        Ok(())
+    }
+
+    /// Replace the value of the attribute named `name`.  Pass `None` (as an `Option<T>`) to set
+    /// it to `NULL`.
+    ///
+    /// The tuple is replaced with a modified copy, allocated in `CurrentMemoryContext`.
+    pub fn set_by_name<T: IntoDatum>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> Result<(), PgHeapTupleError> {
+        self.set_by_index(self.attno(name)?, value)
+    }
+
+    fn attribute(&self, attno: usize) -> Result<&pg_sys::FormData_pg_attribute, PgHeapTupleError> {
+        match attno.checked_sub(1).and_then(|i| self.tupdesc.get(i)) {
+            Some(attribute) if !attribute.is_dropped() => Ok(attribute),
+            _ => Err(PgHeapTupleError::NoSuchAttributeNumber(attno)),
+        }
+    }
+
+    fn attno(&self, name: &str) -> Result<usize, PgHeapTupleError> {
+        self.tupdesc
+            .iter()
+            .position(|attribute| !attribute.is_dropped() && attribute.name() == name)
+            .map(|i| i + 1)
+            .ok_or_else(|| PgHeapTupleError::NoSuchAttributeName(name.to_string()))
+    }
+}
@@ -27,11 +27,19 @@ extern crate bitflags;
 // expose our various derive macros
 pub use pgx_macros::*;
 
+pub mod aggregate;
+pub mod brin;
 pub mod callbacks;
+pub mod collation;
 pub mod datum;
+pub mod encoding;
 pub mod enum_helper;
+pub mod event_trigger;
 pub mod fcinfo;
+pub mod gin;
+pub mod gist;
 pub mod guc;
+pub mod heap_tuple;
 pub mod hooks;
 pub mod htup;
 pub mod inoutfuncs;
@@ -47,26 +55,39 @@ pub mod misc;
 pub mod namespace;
 pub mod nodes;
 pub mod pgbox;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub mod planner_support;
 pub mod rel;
 pub mod shmem;
+pub mod spgist;
 pub mod spi;
 pub mod stringinfo;
 pub mod trigger_support;
 pub mod tupdesc;
+pub mod tuplestore;
+pub mod typcache;
 pub mod varlena;
+pub mod window;
 pub mod wrappers;
 pub mod xid;
 
 #[doc(hidden)]
 pub use once_cell;
 
+pub use aggregate::*;
 pub use atomics::*;
+pub use brin::*;
 pub use callbacks::*;
-use datum::sql_entity_graph::{RustSourceOnlySqlMapping, RustSqlMapping};
+pub use collation::*;
 pub use datum::*;
+pub use encoding::*;
 pub use enum_helper::*;
+pub use event_trigger::*;
 pub use fcinfo::*;
+pub use gin::*;
+pub use gist::*;
 pub use guc::*;
+pub use heap_tuple::*;
 pub use hooks::*;
 pub use htup::*;
 pub use inoutfuncs::*;
@@ -78,13 +99,19 @@ pub use memcxt::*;
 pub use namespace::*;
 pub use nodes::*;
 pub use pgbox::*;
+#[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+pub use planner_support::*;
 pub use rel::*;
 pub use shmem::*;
+pub use spgist::*;
 pub use spi::*;
 pub use stringinfo::*;
 pub use trigger_support::*;
 pub use tupdesc::*;
+pub use tuplestore::*;
+pub use typcache::*;
 pub use varlena::*;
+pub use window::*;
 pub use wrappers::*;
 pub use xid::*;
 
@@ -92,137 +119,6 @@ pub use pgx_pg_sys as pg_sys; // the module only, not its contents
 pub use pgx_pg_sys::submodules::*;
 pub use pgx_pg_sys::PgBuiltInOids; // reexport this so it looks like it comes from here
 
-use core::any::TypeId;
-use once_cell::sync::Lazy;
-use std::collections::HashSet;
-
-macro_rules! map_source_only {
-    ($map:ident, $rust:ty, $sql:expr) => {{
-        let ty = stringify!($rust).to_string().replace(" ", "");
-        assert_eq!(
-            $map.insert(RustSourceOnlySqlMapping::new(ty.clone(), $sql.to_string(),)),
-            true,
-            "Cannot map {} twice",
-            ty
-        );
-
-        let ty = stringify!(Option<$rust>).to_string().replace(" ", "");
-        assert_eq!(
-            $map.insert(RustSourceOnlySqlMapping::new(ty.clone(), $sql.to_string(),)),
-            true,
-            "Cannot map {} twice",
-            ty
-        );
-
-        let ty = stringify!(Vec<$rust>).to_string().replace(" ", "");
-        assert_eq!(
-            $map.insert(RustSourceOnlySqlMapping::new(
-                ty.clone(),
-                format!("{}[]", $sql),
-            )),
-            true,
-            "Cannot map {} twice",
-            ty
-        );
-
-        let ty = stringify!(Array<$rust>).to_string().replace(" ", "");
-        assert_eq!(
-            $map.insert(RustSourceOnlySqlMapping::new(
-                ty.clone(),
-                format!("{}[]", $sql),
-            )),
-            true,
-            "Cannot map {} twice",
-            ty
-        );
-    }};
-}
-
-pub static DEFAULT_SOURCE_ONLY_SQL_MAPPING: Lazy<HashSet<RustSourceOnlySqlMapping>> =
-    Lazy::new(|| {
-        let mut m = HashSet::new();
-
-        map_source_only!(m, pg_sys::Oid, "Oid");
-
-        m
-    });
-
-macro_rules! map_type {
-    ($map:ident, $rust:ty, $sql:expr) => {{
-        <$rust as WithTypeIds>::register_with_refs(&mut $map, $sql.to_string());
-        WithSizedTypeIds::<$rust>::register_sized_with_refs(&mut $map, $sql.to_string());
-        WithArrayTypeIds::<$rust>::register_array_with_refs(&mut $map, $sql.to_string());
-        WithVarlenaTypeIds::<$rust>::register_varlena_with_refs(&mut $map, $sql.to_string());
-    }};
-}
-
-/// The default lookup for [`TypeId`]s to both Rust and SQL types via a [`RustSqlMapping`].
-///
-/// This only contains types known to [`pgx`](crate), so it will not include types defined by things
-/// like [`derive@PostgresType`] in the local extension.
-pub static DEFAULT_TYPEID_SQL_MAPPING: Lazy<HashSet<RustSqlMapping>> = Lazy::new(|| {
-    let mut m = HashSet::new();
-
-    // `str` isn't sized, so we can't lean on the macro.
-    <str as WithTypeIds>::register(&mut m, "text".to_string());
-    map_type!(m, &str, "text");
-
-    // Bytea is a special case, notice how it has no `bytea[]`.
-    m.insert(RustSqlMapping {
-        sql: String::from("bytea"),
-        id: TypeId::of::<&[u8]>(),
-        rust: core::any::type_name::<&[u8]>().to_string(),
-    });
-    m.insert(RustSqlMapping {
-        sql: String::from("bytea"),
-        id: TypeId::of::<Option<&[u8]>>(),
-        rust: core::any::type_name::<Option<&[u8]>>().to_string(),
-    });
-    m.insert(RustSqlMapping {
-        sql: String::from("bytea"),
-        id: TypeId::of::<Vec<u8>>(),
-        rust: core::any::type_name::<Vec<u8>>().to_string(),
-    });
-    m.insert(RustSqlMapping {
-        sql: String::from("bytea"),
-        id: TypeId::of::<Option<Vec<u8>>>(),
-        rust: core::any::type_name::<Option<Vec<u8>>>().to_string(),
-    });
-
-    map_type!(m, String, "text");
-    map_type!(m, &std::ffi::CStr, "cstring");
-    map_type!(m, (), "void");
-    map_type!(m, i8, "\"char\"");
-    map_type!(m, i16, "smallint");
-    map_type!(m, i32, "integer");
-    map_type!(m, i64, "bigint");
-    map_type!(m, bool, "bool");
-    map_type!(m, char, "varchar");
-    map_type!(m, f32, "real");
-    map_type!(m, f64, "double precision");
-    map_type!(m, datum::JsonB, "jsonb");
-    map_type!(m, datum::Json, "json");
-    map_type!(m, pgx_pg_sys::ItemPointerData, "tid");
-    map_type!(m, pgx_pg_sys::Point, "point");
-    map_type!(m, pgx_pg_sys::BOX, "box");
-    map_type!(m, Date, "date");
-    map_type!(m, Time, "time");
-    map_type!(m, TimeWithTimeZone, "time with time zone");
-    map_type!(m, Timestamp, "timestamp");
-    map_type!(m, TimestampWithTimeZone, "timestamp with time zone");
-    map_type!(m, pgx_pg_sys::PlannerInfo, "internal");
-    map_type!(m, datum::Internal, "internal");
-    map_type!(m, pgbox::PgBox<pgx_pg_sys::IndexAmRoutine>, "internal");
-    map_type!(m, rel::PgRelation, "regclass");
-    map_type!(m, datum::Numeric, "numeric");
-    map_type!(m, datum::AnyElement, "anyelement");
-    map_type!(m, datum::AnyArray, "anyarray");
-    map_type!(m, datum::Inet, "inet");
-    map_type!(m, datum::Uuid, "uuid");
-
-    m
-});
-
 /// A macro for marking a library compatible with [`pgx`][crate].
 ///
 /// <div class="example-wrap" style="display:inline-block">
@@ -395,6 +291,12 @@ macro_rules! pg_binary_magic {
             let matches = clap::App::new("sql-generator")
                 .arg(clap::Arg::with_name("sql").long("sql").value_name("FILE").takes_value(true))
                 .arg(clap::Arg::with_name("dot").long("dot").value_name("FILE").takes_value(true))
+                .arg(clap::Arg::with_name("json").long("json").value_name("FILE").takes_value(true))
+                .arg(clap::Arg::with_name("snapshot").long("snapshot").value_name("FILE").takes_value(true))
+                .arg(clap::Arg::with_name("check").long("check").conflicts_with_all(&["dot", "json", "snapshot", "upgrade"]))
+                // The snapshot of the earlier version, written with `--snapshot`
+                .arg(clap::Arg::with_name("upgrade-from").long("upgrade-from").value_name("FILE").takes_value(true).requires("upgrade"))
+                .arg(clap::Arg::with_name("upgrade").long("upgrade").value_name("FILE").takes_value(true).requires("upgrade-from"))
                 // The `cargo-pgx` tool passes via env.
                 .arg(clap::Arg::with_name("symbols").value_name("SYMBOL").env("PGX_SQL_ENTITY_SYMBOLS").use_delimiter(true).multiple(true).takes_value(true))
                 .get_matches();
@@ -443,7 +345,18 @@ macro_rules! pg_binary_magic {
                 }
             };
 
-            let pgx_sql = PgxSql::build(pgx::DEFAULT_TYPEID_SQL_MAPPING.clone().into_iter(), pgx::DEFAULT_SOURCE_ONLY_SQL_MAPPING.clone().into_iter(), entities.into_iter()).unwrap();
+            let pgx_sql = PgxSql::build(entities.into_iter()).unwrap();
+
+            if matches.is_present("check") {
+                tracing::info!(path = %path, "Checking SQL");
+                if let Some(diff) = pgx_sql.diff_file(path)? {
+                    print!("{}", diff);
+                    tracing::error!(path = %path, "Out of date with the extension's SQL");
+                    // Any other failure exits with 1, so `cargo pgx schema --check` can tell them apart
+                    std::process::exit(2);
+                }
+                return Ok(());
+            }
 
             tracing::info!(path = %path, "Writing SQL");
             pgx_sql.to_file(path)?;
@@ -451,6 +364,20 @@ macro_rules! pg_binary_magic {
                 tracing::info!(dot = %dot_path, "Writing Graphviz DOT");
                 pgx_sql.to_dot(dot_path)?;
             }
+            if let Some(json_path) = matches.value_of("json") {
+                tracing::info!(json = %json_path, "Writing JSON manifest");
+                pgx_sql.to_json(json_path)?;
+            }
+            if let Some(snapshot_path) = matches.value_of("snapshot") {
+                tracing::info!(snapshot = %snapshot_path, "Writing SQL snapshot");
+                pgx_sql.to_snapshot_file(snapshot_path)?;
+            }
+            if let (Some(upgrade_path), Some(from_path)) = (matches.value_of("upgrade"), matches.value_of("upgrade-from")) {
+                tracing::info!(upgrade = %upgrade_path, from = %from_path, "Writing upgrade SQL");
+                for warning in pgx_sql.to_upgrade_file(upgrade_path, from_path)? {
+                    tracing::warn!("{}", warning);
+                }
+            }
             Ok(())
         }
     };
@@ -0,0 +1,312 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Safe wrappers around Postgres' planner support function API (`nodes/supportnodes.h`), which
+//! requires Postgres 12 or later
+//!
+//! A support function is a `#[pg_extern]` taking and returning [`Internal`].  It's attached to
+//! the function it helps plan with `#[pg_extern(support = ...)]`, and answers the requests of the
+//! planner through [`PgSupportRequest::call`]:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! #[pg_extern(immutable)]
+//! fn numbers_support(request: Internal) -> Internal {
+//!     PgSupportRequest::call(request, |request| {
+//!         if let PgSupportRequest::Rows(rows) = request {
+//!             // `numbers(n)` returns `n` rows
+//!             if let Some(Some(n)) = rows.const_arg::<i32>(0) {
+//!                 rows.set_rows(n as f64);
+//!             }
+//!         }
+//!     })
+//! }
+//!
+//! #[pg_extern(support = numbers_support)]
+//! fn numbers(n: i32) -> impl std::iter::Iterator<Item = i32> {
+//!     0..n
+//! }
+//! ```
+use crate::{is_a, pg_sys, FromDatum, Internal, PgBox, PgList};
+
+/// A request from the planner to a support function, by the kind of `pg_sys::Node` it is
+pub enum PgSupportRequest<'a> {
+    /// `SupportRequestSimplify`: replace a call to the function with a simpler expression
+    Simplify(PgSupportRequestSimplify<'a>),
+    /// `SupportRequestSelectivity`: estimate the selectivity of a boolean function
+    Selectivity(PgSupportRequestSelectivity<'a>),
+    /// `SupportRequestCost`: estimate the execution cost of the function
+    Cost(PgSupportRequestCost<'a>),
+    /// `SupportRequestRows`: estimate the number of rows a set-returning function returns
+    Rows(PgSupportRequestRows<'a>),
+    /// A request this API doesn't wrap, such as `SupportRequestIndexCondition`.  Leaving it
+    /// unanswered is always correct.
+    Other(&'a pg_sys::Node),
+}
+
+impl<'a> PgSupportRequest<'a> {
+    /// Run the support function body `f` for the planner's `request`, and return what the
+    /// support function should return.
+    ///
+    /// Requests `f` doesn't answer (by calling one of the setters of the request) are left for
+    /// the planner to estimate as it would without a support function.
+    pub fn call(request: Internal, f: impl FnOnce(&mut PgSupportRequest<'_>)) -> Internal {
+        let node = request
+            .unwrap()
+            .expect("support function called with a NULL request")
+            as *mut pg_sys::Node;
+        let mut request = unsafe { PgSupportRequest::from_node(node) };
+        f(&mut request);
+
+        let result = match request {
+            PgSupportRequest::Simplify(simplify) => simplify.replacement,
+            PgSupportRequest::Selectivity(PgSupportRequestSelectivity { handled, .. })
+            | PgSupportRequest::Cost(PgSupportRequestCost { handled, .. })
+            | PgSupportRequest::Rows(PgSupportRequestRows { handled, .. })
+                if handled =>
+            {
+                node
+            }
+            _ => std::ptr::null_mut(),
+        };
+        Internal::from(Some(result as pg_sys::Datum))
+    }
+
+    /// Wrap the request `node` the planner handed to a support function.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot ensure that `node` is a valid `pg_sys::Node` which
+    /// lives as long as `'a`
+    pub unsafe fn from_node(node: *mut pg_sys::Node) -> Self {
+        if is_a(node, pg_sys::NodeTag_T_SupportRequestSimplify) {
+            PgSupportRequest::Simplify(PgSupportRequestSimplify {
+                request: &mut *(node as *mut pg_sys::SupportRequestSimplify),
+                replacement: std::ptr::null_mut(),
+            })
+        } else if is_a(node, pg_sys::NodeTag_T_SupportRequestSelectivity) {
+            PgSupportRequest::Selectivity(PgSupportRequestSelectivity {
+                request: &mut *(node as *mut pg_sys::SupportRequestSelectivity),
+                handled: false,
+            })
+        } else if is_a(node, pg_sys::NodeTag_T_SupportRequestCost) {
+            PgSupportRequest::Cost(PgSupportRequestCost {
+                request: &mut *(node as *mut pg_sys::SupportRequestCost),
+                handled: false,
+            })
+        } else if is_a(node, pg_sys::NodeTag_T_SupportRequestRows) {
+            PgSupportRequest::Rows(PgSupportRequestRows {
+                request: &mut *(node as *mut pg_sys::SupportRequestRows),
+                handled: false,
+            })
+        } else {
+            PgSupportRequest::Other(node.as_ref().expect("support request is NULL"))
+        }
+    }
+
+    /// The Oid of the function the request is about, if the request says
+    pub fn funcid(&self) -> Option<pg_sys::Oid> {
+        match self {
+            PgSupportRequest::Simplify(simplify) => Some(simplify.funcid()),
+            PgSupportRequest::Selectivity(selectivity) => Some(selectivity.funcid()),
+            PgSupportRequest::Cost(cost) => Some(cost.funcid()),
+            PgSupportRequest::Rows(rows) => Some(rows.funcid()),
+            PgSupportRequest::Other(_) => None,
+        }
+    }
+}
+
+/// Asks to replace a call to the function with a simpler expression, typically when some of its
+/// arguments are constants
+pub struct PgSupportRequestSimplify<'a> {
+    request: &'a mut pg_sys::SupportRequestSimplify,
+    replacement: *mut pg_sys::Node,
+}
+
+impl<'a> PgSupportRequestSimplify<'a> {
+    /// The underlying `pg_sys::SupportRequestSimplify`
+    pub fn as_ptr(&self) -> *const pg_sys::SupportRequestSimplify {
+        self.request
+    }
+
+    /// The `pg_sys::PlannerInfo` of the query being planned, which is NULL when simplifying an
+    /// expression outside of planning
+    pub fn root(&self) -> *mut pg_sys::PlannerInfo {
+        self.request.root
+    }
+
+    pub fn funcid(&self) -> pg_sys::Oid {
+        unsafe { (*self.request.fcall).funcid }
+    }
+
+    /// The argument expressions of the call
+    pub fn args(&self) -> PgList<pg_sys::Node> {
+        unsafe { PgList::from_pg((*self.request.fcall).args) }
+    }
+
+    /// The value of argument `index` (0-based), if it's a constant.  `Some(None)` is a constant
+    /// `NULL`.
+    pub fn const_arg<T: FromDatum>(&self, index: usize) -> Option<Option<T>> {
+        const_arg(&self.args(), index)
+    }
+
+    /// Replace the call with `expression`, which must be of the function's result type
+    pub fn replace_with(&mut self, expression: PgBox<pg_sys::Node>) {
+        self.replacement = expression.into_pg();
+    }
+}
+
+/// Asks for the fraction of rows for which a boolean function, used as a `WHERE` clause, returns
+/// true
+pub struct PgSupportRequestSelectivity<'a> {
+    request: &'a mut pg_sys::SupportRequestSelectivity,
+    handled: bool,
+}
+
+impl<'a> PgSupportRequestSelectivity<'a> {
+    /// The underlying `pg_sys::SupportRequestSelectivity`
+    pub fn as_ptr(&self) -> *const pg_sys::SupportRequestSelectivity {
+        self.request
+    }
+
+    /// The `pg_sys::PlannerInfo` of the query being planned
+    pub fn root(&self) -> *mut pg_sys::PlannerInfo {
+        self.request.root
+    }
+
+    pub fn funcid(&self) -> pg_sys::Oid {
+        self.request.funcid
+    }
+
+    /// The argument expressions of the call
+    pub fn args(&self) -> PgList<pg_sys::Node> {
+        unsafe { PgList::from_pg(self.request.args) }
+    }
+
+    /// The value of argument `index` (0-based), if it's a constant.  `Some(None)` is a constant
+    /// `NULL`.
+    pub fn const_arg<T: FromDatum>(&self, index: usize) -> Option<Option<T>> {
+        const_arg(&self.args(), index)
+    }
+
+    /// Is this the selectivity of a join clause, rather than of a restriction clause?
+    pub fn is_join(&self) -> bool {
+        self.request.is_join
+    }
+
+    /// Answer the request with a `selectivity` between 0.0 and 1.0
+    pub fn set_selectivity(&mut self, selectivity: f64) {
+        if !(0.0..=1.0).contains(&selectivity) {
+            panic!(
+                "selectivity must be between 0.0 and 1.0, but was {}",
+                selectivity
+            );
+        }
+        self.request.selectivity = selectivity;
+        self.handled = true;
+    }
+}
+
+/// Asks for the cost of executing the function, in units of `cpu_operator_cost`
+pub struct PgSupportRequestCost<'a> {
+    request: &'a mut pg_sys::SupportRequestCost,
+    handled: bool,
+}
+
+impl<'a> PgSupportRequestCost<'a> {
+    /// The underlying `pg_sys::SupportRequestCost`
+    pub fn as_ptr(&self) -> *const pg_sys::SupportRequestCost {
+        self.request
+    }
+
+    /// The `pg_sys::PlannerInfo` of the query being planned, which may be NULL
+    pub fn root(&self) -> *mut pg_sys::PlannerInfo {
+        self.request.root
+    }
+
+    pub fn funcid(&self) -> pg_sys::Oid {
+        self.request.funcid
+    }
+
+    /// The argument expressions of the call, which are unknown (and empty) when the cost of the
+    /// function itself is asked for
+    pub fn args(&self) -> PgList<pg_sys::Node> {
+        unsafe { PgList::from_pg(call_args(self.request.node)) }
+    }
+
+    /// The value of argument `index` (0-based), if it's a constant.  `Some(None)` is a constant
+    /// `NULL`.
+    pub fn const_arg<T: FromDatum>(&self, index: usize) -> Option<Option<T>> {
+        const_arg(&self.args(), index)
+    }
+
+    /// Answer the request with the one-time `startup` cost and the cost `per_tuple`
+    pub fn set_cost(&mut self, startup: f64, per_tuple: f64) {
+        self.request.startup = startup;
+        self.request.per_tuple = per_tuple;
+        self.handled = true;
+    }
+}
+
+/// Asks for the number of rows a set-returning function returns
+pub struct PgSupportRequestRows<'a> {
+    request: &'a mut pg_sys::SupportRequestRows,
+    handled: bool,
+}
+
+impl<'a> PgSupportRequestRows<'a> {
+    /// The underlying `pg_sys::SupportRequestRows`
+    pub fn as_ptr(&self) -> *const pg_sys::SupportRequestRows {
+        self.request
+    }
+
+    /// The `pg_sys::PlannerInfo` of the query being planned, which may be NULL
+    pub fn root(&self) -> *mut pg_sys::PlannerInfo {
+        self.request.root
+    }
+
+    pub fn funcid(&self) -> pg_sys::Oid {
+        self.request.funcid
+    }
+
+    /// The argument expressions of the call
+    pub fn args(&self) -> PgList<pg_sys::Node> {
+        unsafe { PgList::from_pg(call_args(self.request.node)) }
+    }
+
+    /// The value of argument `index` (0-based), if it's a constant.  `Some(None)` is a constant
+    /// `NULL`.
+    pub fn const_arg<T: FromDatum>(&self, index: usize) -> Option<Option<T>> {
+        const_arg(&self.args(), index)
+    }
+
+    /// Answer the request with the estimated number of `rows`
+    pub fn set_rows(&mut self, rows: f64) {
+        self.request.rows = rows;
+        self.handled = true;
+    }
+}
+
+/// The argument list of `node`, a `FuncExpr` or an `OpExpr`
+fn call_args(node: *mut pg_sys::Node) -> *mut pg_sys::List {
+    unsafe {
+        if is_a(node, pg_sys::NodeTag_T_FuncExpr) {
+            (*(node as *mut pg_sys::FuncExpr)).args
+        } else if is_a(node, pg_sys::NodeTag_T_OpExpr) {
+            (*(node as *mut pg_sys::OpExpr)).args
+        } else {
+            std::ptr::null_mut()
+        }
+    }
+}
+
+fn const_arg<T: FromDatum>(args: &PgList<pg_sys::Node>, index: usize) -> Option<Option<T>> {
+    let arg = args.get_ptr(index)?;
+    if unsafe { is_a(arg, pg_sys::NodeTag_T_Const) } {
+        let arg = unsafe { &*(arg as *mut pg_sys::Const) };
+        Some(unsafe { T::from_datum(arg.constvalue, arg.constisnull, arg.consttype) })
+    } else {
+        None
+    }
+}
@@ -0,0 +1,427 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Support for declaring SP-GiST operator classes with a Rust trait
+//!
+//! Implement [`SpGistOpClass`] for a type and annotate the `impl` block with
+//! `#[pg_spgist_opclass]`.  The macro generates the `config`, `choose`, `picksplit`,
+//! `inner_consistent` and `leaf_consistent` support functions, and `cargo pgx schema` generates
+//! the matching `CREATE OPERATOR CLASS ... USING spgist` statement.
+//!
+//! An SP-GiST index is a tree of inner tuples, each with an optional
+//! [`SpGistOpClass::Prefix`] and a number of nodes, optionally labeled with a
+//! [`SpGistOpClass::Label`].  Values are stored whole in the leaves of the tree, so `config` is
+//! generated from the associated types.  Use `()` for a `Prefix` or `Label` the tree doesn't
+//! have.
+//!
+//! The `level` of an inner tuple is its depth in the tree, starting at `0` for the root.
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Copy, Clone, PostgresType, Serialize, Deserialize)]
+//! pub struct Spot {
+//!     x: f64,
+//!     y: f64,
+//! }
+//!
+//! #[derive(PostgresType, Serialize, Deserialize)]
+//! pub struct Area {
+//!     low: Spot,
+//!     high: Spot,
+//! }
+//!
+//! fn contains(area: &Area, spot: &Spot) -> bool {
+//!     area.low.x <= spot.x && spot.x <= area.high.x && area.low.y <= spot.y && spot.y <= area.high.y
+//! }
+//!
+//! #[pg_operator(immutable)]
+//! #[opname(<@)]
+//! fn spot_within(left: Spot, right: Area) -> bool {
+//!     contains(&right, &left)
+//! }
+//!
+//! fn quadrant(centroid: &Spot, spot: &Spot) -> usize {
+//!     (spot.x >= centroid.x) as usize * 2 + (spot.y >= centroid.y) as usize
+//! }
+//!
+//! // a quad-tree: every inner tuple has a centroid prefix and four nodes
+//! #[pg_spgist_opclass]
+//! impl SpGistOpClass for Spot {
+//!     type Prefix = Spot;
+//!     type Query = Area;
+//!     const OPERATORS: &'static [(u16, &'static str)] = &[(8, "<@")];
+//!
+//!     fn choose(value: &Spot, inner: &SpGistInner<Spot, ()>) -> SpGistChoice<Spot, ()> {
+//!         let node = if inner.all_the_same { 0 } else { quadrant(inner.prefix.as_ref().unwrap(), value) };
+//!         SpGistChoice::MatchNode { node }
+//!     }
+//!
+//!     fn picksplit(values: &[Spot], _level: u32) -> SpGistSplit<Spot, ()> {
+//!         let n = values.len() as f64;
+//!         let centroid = Spot {
+//!             x: values.iter().map(|spot| spot.x).sum::<f64>() / n,
+//!             y: values.iter().map(|spot| spot.y).sum::<f64>() / n,
+//!         };
+//!         SpGistSplit {
+//!             nodes: values.iter().map(|spot| quadrant(&centroid, spot)).collect(),
+//!             prefix: Some(centroid),
+//!             labels: vec![],
+//!             n_nodes: 4,
+//!         }
+//!     }
+//!
+//!     fn inner_consistent(_queries: &[(u16, Area)], inner: &SpGistInner<Spot, ()>) -> Vec<usize> {
+//!         // a real implementation only descends into the quadrants overlapping the queries
+//!         (0..inner.n_nodes).collect()
+//!     }
+//!
+//!     fn leaf_consistent(value: &Spot, queries: &[(u16, Area)], _recheck: &mut bool) -> bool {
+//!         queries.iter().all(|(_, area)| contains(area, value))
+//!     }
+//! }
+//! ```
+//!
+//! Within the `impl`, the following may be omitted and are filled in by `#[pg_spgist_opclass]`:
+//!
+//! * `type Prefix` and `type Label` default to `()`
+//! * `type Query` defaults to `Self`
+//! * `const NAME` defaults to the lowercased name of the implementing type, suffixed with
+//!   `_spgist_ops`
+use crate::{pg_sys, FromDatum, Internal, IntoDatum};
+use std::any::TypeId;
+
+/// An inner tuple of an SP-GiST index, as passed to [`SpGistOpClass::choose`] and
+/// [`SpGistOpClass::inner_consistent`]
+#[derive(Debug)]
+pub struct SpGistInner<P, L> {
+    pub prefix: Option<P>,
+    /// The labels of the nodes, or empty if the nodes aren't labeled
+    pub labels: Vec<L>,
+    pub n_nodes: usize,
+    pub level: u32,
+    /// Do all the nodes lead to equivalent values?  Then any node is as good as the others.
+    pub all_the_same: bool,
+}
+
+/// Where [`SpGistOpClass::choose`] puts a new value
+#[derive(Debug)]
+pub enum SpGistChoice<P, L> {
+    /// Descend into an existing node
+    MatchNode { node: usize },
+    /// Add a node with `label` at position `node` of the inner tuple, and descend into it.  Not
+    /// allowed when the inner tuple is `all_the_same`.
+    AddNode { label: L, node: usize },
+    /// Replace the inner tuple with a new one with `prefix` and `n_nodes` nodes, whose node
+    /// `child_node` leads to the original inner tuple, now with `postfix_prefix`.
+    SplitTuple {
+        prefix: Option<P>,
+        labels: Vec<L>,
+        n_nodes: usize,
+        child_node: usize,
+        postfix_prefix: Option<P>,
+    },
+}
+
+/// How [`SpGistOpClass::picksplit`] splits values into the nodes of a new inner tuple
+#[derive(Debug)]
+pub struct SpGistSplit<P, L> {
+    pub prefix: Option<P>,
+    /// The labels of the nodes, or empty if they aren't labeled
+    pub labels: Vec<L>,
+    pub n_nodes: usize,
+    /// The node of each value, in the same order as the values
+    pub nodes: Vec<usize>,
+}
+
+/// An SP-GiST operator class.  See the [module documentation](crate::spgist) for details.
+pub trait SpGistOpClass
+where
+    Self: FromDatum + IntoDatum + Sized,
+{
+    /// The prefix of inner tuples, or `()`
+    type Prefix: FromDatum + IntoDatum + 'static;
+
+    /// The label of the nodes of inner tuples, or `()`
+    type Label: FromDatum + IntoDatum + 'static;
+
+    /// The right-hand argument of the operator class' operators
+    type Query: FromDatum;
+
+    /// The SQL name of the operator class
+    const NAME: &'static str;
+
+    /// Is this the default SP-GiST operator class of the type?
+    const DEFAULT: bool = true;
+
+    /// The operators of the operator class, by strategy number, such as `&[(8, "<@")]`
+    const OPERATORS: &'static [(u16, &'static str)];
+
+    /// Which node of `inner` `value` belongs in (`choose`)
+    fn choose(
+        value: &Self,
+        inner: &SpGistInner<Self::Prefix, Self::Label>,
+    ) -> SpGistChoice<Self::Prefix, Self::Label>;
+
+    /// Split `values`, which no longer fit on a leaf page, into the nodes of a new inner tuple at
+    /// `level` (`picksplit`)
+    fn picksplit(values: &[Self], level: u32) -> SpGistSplit<Self::Prefix, Self::Label>;
+
+    /// The nodes of `inner` which can lead to values matching all of `queries`, each the
+    /// strategy number of an operator and its right-hand argument (`inner_consistent`)
+    fn inner_consistent(
+        queries: &[(u16, Self::Query)],
+        inner: &SpGistInner<Self::Prefix, Self::Label>,
+    ) -> Vec<usize>;
+
+    /// Does `value` match all of `queries` (`leaf_consistent`)?
+    ///
+    /// `recheck` starts out as `false`.  Set it to `true` when the operators have to be
+    /// re-evaluated against the heap tuple.
+    fn leaf_consistent(value: &Self, queries: &[(u16, Self::Query)], recheck: &mut bool) -> bool;
+}
+
+/// The SQL type of a prefix or label, which is `void` for `()`
+fn type_oid<T: IntoDatum + 'static>() -> pg_sys::Oid {
+    if TypeId::of::<T>() == TypeId::of::<()>() {
+        pg_sys::VOIDOID
+    } else {
+        T::type_oid()
+    }
+}
+
+unsafe fn datums<T: FromDatum>(datums: *const pg_sys::Datum, len: usize) -> Vec<T> {
+    if datums.is_null() {
+        return Vec::new();
+    }
+    std::slice::from_raw_parts(datums, len)
+        .iter()
+        .filter_map(|&datum| T::from_datum(datum, false, pg_sys::InvalidOid))
+        .collect()
+}
+
+/// `(has_prefix, prefix_datum)` of `prefix`
+fn prefix_datum<P: IntoDatum>(prefix: Option<P>) -> (bool, pg_sys::Datum) {
+    match prefix.and_then(|prefix| prefix.into_datum()) {
+        Some(datum) => (true, datum),
+        None => (false, 0),
+    }
+}
+
+/// `labels` as a new `palloc`'d array, or NULL if the nodes aren't labeled
+fn label_datums<L: IntoDatum>(labels: Vec<L>) -> *mut pg_sys::Datum {
+    let labels = labels
+        .into_iter()
+        .filter_map(|label| label.into_datum())
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        return std::ptr::null_mut();
+    }
+    palloc_slice(&labels)
+}
+
+fn palloc_slice<T: Copy>(values: &[T]) -> *mut T {
+    unsafe {
+        let array = pg_sys::palloc(values.len().max(1) * std::mem::size_of::<T>()) as *mut T;
+        std::ptr::copy_nonoverlapping(values.as_ptr(), array, values.len());
+        array
+    }
+}
+
+unsafe fn inner_tuple<T: SpGistOpClass>(
+    has_prefix: bool,
+    prefix: pg_sys::Datum,
+    labels: *const pg_sys::Datum,
+    n_nodes: i32,
+    level: i32,
+    all_the_same: bool,
+) -> SpGistInner<T::Prefix, T::Label> {
+    SpGistInner {
+        prefix: if has_prefix {
+            T::Prefix::from_datum(prefix, false, pg_sys::InvalidOid)
+        } else {
+            None
+        },
+        labels: datums(labels, n_nodes as usize),
+        n_nodes: n_nodes as usize,
+        level: level as u32,
+        all_the_same,
+    }
+}
+
+unsafe fn queries<Q: FromDatum>(scankeys: pg_sys::ScanKey, nkeys: i32) -> Vec<(u16, Q)> {
+    if scankeys.is_null() {
+        return Vec::new();
+    }
+    std::slice::from_raw_parts(scankeys, nkeys as usize)
+        .iter()
+        .map(|key| {
+            let query = Q::from_datum(key.sk_argument, false, pg_sys::InvalidOid)
+                .expect("SP-GiST consistent function called with a NULL query");
+            (key.sk_strategy, query)
+        })
+        .collect()
+}
+
+/// The body of the generated `config` support function of `T`
+pub fn spgist_config<T: SpGistOpClass>(_config_in: Internal, config_out: Internal) {
+    let config = unsafe { config_out.get_mut::<pg_sys::spgConfigOut>() }
+        .expect("SP-GiST config function called with a NULL spgConfigOut");
+    config.prefixType = type_oid::<T::Prefix>();
+    config.labelType = type_oid::<T::Label>();
+    config.canReturnData = true;
+    config.longValuesOK = false;
+}
+
+/// The body of the generated `choose` support function of `T`
+pub fn spgist_choose<T: SpGistOpClass>(choose_in: Internal, choose_out: Internal) {
+    unsafe {
+        let input = choose_in
+            .get::<pg_sys::spgChooseIn>()
+            .expect("SP-GiST choose function called with a NULL spgChooseIn");
+        let out = choose_out
+            .get_mut::<pg_sys::spgChooseOut>()
+            .expect("SP-GiST choose function called with a NULL spgChooseOut");
+
+        let value = T::from_datum(input.datum, false, pg_sys::InvalidOid)
+            .expect("SP-GiST choose function called with a NULL value");
+        let inner = inner_tuple::<T>(
+            input.hasPrefix,
+            input.prefixDatum,
+            input.nodeLabels,
+            input.nNodes,
+            input.level,
+            input.allTheSame,
+        );
+
+        match T::choose(&value, &inner) {
+            SpGistChoice::MatchNode { node } => {
+                out.resultType = pg_sys::spgChooseResultType_spgMatchNode;
+                out.result.matchNode.nodeN = node as i32;
+                out.result.matchNode.levelAdd = 1;
+                out.result.matchNode.restDatum = input.datum;
+            }
+            SpGistChoice::AddNode { label, node } => {
+                out.resultType = pg_sys::spgChooseResultType_spgAddNode;
+                out.result.addNode.nodeLabel = label.into_datum().unwrap_or(0);
+                out.result.addNode.nodeN = node as i32;
+            }
+            SpGistChoice::SplitTuple {
+                prefix,
+                labels,
+                n_nodes,
+                child_node,
+                postfix_prefix,
+            } => {
+                let (has_prefix, prefix) = prefix_datum(prefix);
+                let (postfix_has_prefix, postfix_prefix) = prefix_datum(postfix_prefix);
+                out.resultType = pg_sys::spgChooseResultType_spgSplitTuple;
+                out.result.splitTuple.prefixHasPrefix = has_prefix;
+                out.result.splitTuple.prefixPrefixDatum = prefix;
+                out.result.splitTuple.prefixNNodes = n_nodes as i32;
+                out.result.splitTuple.prefixNodeLabels = label_datums(labels);
+                out.result.splitTuple.childNodeN = child_node as i32;
+                out.result.splitTuple.postfixHasPrefix = postfix_has_prefix;
+                out.result.splitTuple.postfixPrefixDatum = postfix_prefix;
+            }
+        }
+    }
+}
+
+/// The body of the generated `picksplit` support function of `T`
+pub fn spgist_picksplit<T: SpGistOpClass>(picksplit_in: Internal, picksplit_out: Internal) {
+    unsafe {
+        let input = picksplit_in
+            .get::<pg_sys::spgPickSplitIn>()
+            .expect("SP-GiST picksplit function called with a NULL spgPickSplitIn");
+        let out = picksplit_out
+            .get_mut::<pg_sys::spgPickSplitOut>()
+            .expect("SP-GiST picksplit function called with a NULL spgPickSplitOut");
+
+        let leaves = std::slice::from_raw_parts(input.datums, input.nTuples as usize);
+        let values = datums::<T>(input.datums, input.nTuples as usize);
+        let split = T::picksplit(&values, input.level as u32);
+        if split.nodes.len() != values.len() {
+            panic!(
+                "SP-GiST picksplit assigned {} of {} values to a node",
+                split.nodes.len(),
+                values.len()
+            );
+        }
+        if let Some(node) = split.nodes.iter().find(|&&node| node >= split.n_nodes) {
+            panic!(
+                "SP-GiST picksplit assigned a value to node {} of {}",
+                node, split.n_nodes
+            );
+        }
+
+        let (has_prefix, prefix) = prefix_datum(split.prefix);
+        let nodes = split
+            .nodes
+            .iter()
+            .map(|&node| node as i32)
+            .collect::<Vec<_>>();
+        out.hasPrefix = has_prefix;
+        out.prefixDatum = prefix;
+        out.nNodes = split.n_nodes as i32;
+        out.nodeLabels = label_datums(split.labels);
+        out.mapTuplesToNodes = palloc_slice(&nodes);
+        out.leafTupleDatums = palloc_slice(leaves);
+    }
+}
+
+/// The body of the generated `inner_consistent` support function of `T`
+pub fn spgist_inner_consistent<T: SpGistOpClass>(
+    inner_consistent_in: Internal,
+    inner_consistent_out: Internal,
+) {
+    unsafe {
+        let input = inner_consistent_in
+            .get::<pg_sys::spgInnerConsistentIn>()
+            .expect("SP-GiST inner_consistent function called with a NULL spgInnerConsistentIn");
+        let out = inner_consistent_out
+            .get_mut::<pg_sys::spgInnerConsistentOut>()
+            .expect("SP-GiST inner_consistent function called with a NULL spgInnerConsistentOut");
+
+        let queries = queries::<T::Query>(input.scankeys, input.nkeys);
+        let inner = inner_tuple::<T>(
+            input.hasPrefix,
+            input.prefixDatum,
+            input.nodeLabels,
+            input.nNodes,
+            input.level,
+            input.allTheSame,
+        );
+
+        let nodes = T::inner_consistent(&queries, &inner)
+            .into_iter()
+            .map(|node| node as i32)
+            .collect::<Vec<_>>();
+        out.nNodes = nodes.len() as i32;
+        out.levelAdds = palloc_slice(&vec![1; nodes.len()]);
+        out.nodeNumbers = palloc_slice(&nodes);
+    }
+}
+
+/// The body of the generated `leaf_consistent` support function of `T`
+pub fn spgist_leaf_consistent<T: SpGistOpClass>(
+    leaf_consistent_in: Internal,
+    leaf_consistent_out: Internal,
+) -> bool {
+    unsafe {
+        let input = leaf_consistent_in
+            .get::<pg_sys::spgLeafConsistentIn>()
+            .expect("SP-GiST leaf_consistent function called with a NULL spgLeafConsistentIn");
+        let out = leaf_consistent_out
+            .get_mut::<pg_sys::spgLeafConsistentOut>()
+            .expect("SP-GiST leaf_consistent function called with a NULL spgLeafConsistentOut");
+
+        let value = T::from_datum(input.leafDatum, false, pg_sys::InvalidOid)
+            .expect("SP-GiST leaf_consistent function called with a NULL value");
+        let queries = queries::<T::Query>(input.scankeys, input.nkeys);
+        out.leafValue = input.leafDatum;
+        out.recheck = false;
+        T::leaf_consistent(&value, &queries, &mut out.recheck)
+    }
+}
@@ -3,9 +3,10 @@
 
 //! Safe access to Postgres' *Server Programming Interface* (SPI).
 
-use crate::{pg_sys, FromDatum, IntoDatum, Json, PgMemoryContexts, PgOid};
+use crate::{pg_sys, FromDatum, IntoDatum, Json, PgMemoryContexts, PgOid, PgTrigger};
 use enum_primitive_derive::*;
 use num_traits::FromPrimitive;
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::{Index, IndexMut};
@@ -54,6 +55,32 @@ pub enum SpiError {
 
 pub struct Spi;
 
+thread_local! {
+    /// Is the innermost SPI connection the non-atomic one [`Spi::call_procedure`] made for a
+    /// procedure that may control transactions?
+    static NONATOMIC_CONNECTION: Cell<bool> = Cell::new(false);
+}
+
+/// Run the body of a `#[pg_procedure]` with [`Spi::call_procedure`].  Procedures were introduced
+/// in Postgres 11, so this is a compile error on Postgres 10
+#[doc(hidden)]
+#[macro_export]
+#[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+macro_rules! __pgx_call_procedure {
+    ($fcinfo:expr, $body:expr) => {
+        unsafe { $crate::Spi::call_procedure($fcinfo, $body) }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(feature = "pg10")]
+macro_rules! __pgx_call_procedure {
+    ($fcinfo:expr, $body:expr) => {
+        compile_error!("#[pg_procedure] requires Postgres 11 or later")
+    };
+}
+
 pub struct SpiClient;
 
 #[derive(Debug)]
@@ -200,19 +227,26 @@ impl Spi {
             PgMemoryContexts::For(PgMemoryContexts::CurrentMemoryContext.value());
 
         /// a struct to manage our SPI connection lifetime
-        struct SpiConnection;
+        struct SpiConnection {
+            was_nonatomic: bool,
+        }
         impl SpiConnection {
             /// Connect to Postgres' SPI system
             fn connect() -> Self {
                 // connect to SPI
                 Spi::check_status(unsafe { pg_sys::SPI_connect() });
-                SpiConnection
+
+                // this connection is atomic, even within a procedure that may control transactions
+                let was_nonatomic = NONATOMIC_CONNECTION.with(|nonatomic| nonatomic.replace(false));
+                SpiConnection { was_nonatomic }
             }
         }
 
         impl Drop for SpiConnection {
             /// when SpiConnection is dropped, we make sure to disconnect from SPI
             fn drop(&mut self) {
+                NONATOMIC_CONNECTION.with(|nonatomic| nonatomic.set(self.was_nonatomic));
+
                 // disconnect from SPI
                 Spi::check_status(unsafe { pg_sys::SPI_finish() });
             }
@@ -259,6 +293,75 @@ impl Spi {
         }
     }
 
+    /// Run the body `f` of a `#[pg_procedure]`.
+    ///
+    /// If the procedure was `CALL`ed in a non-atomic context (outside of a transaction block and
+    /// not from within a function), `f` runs within a non-atomic SPI connection, which allows it
+    /// to use [`Spi::commit`] and [`Spi::rollback`].
+    ///
+    /// # Safety
+    ///
+    /// `fcinfo` must be a valid pointer to the `FunctionCallInfo` Postgres passed to the
+    /// procedure.
+    #[doc(hidden)]
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    pub unsafe fn call_procedure<R, F: FnOnce() -> R>(fcinfo: pg_sys::FunctionCallInfo, f: F) -> R {
+        let context = fcinfo.as_ref().expect("fcinfo is NULL").context;
+        let nonatomic = crate::is_a(context, pg_sys::NodeTag_T_CallContext)
+            && !(*(context as *mut pg_sys::CallContext)).atomic;
+        if !nonatomic {
+            return f();
+        }
+
+        /// a struct to manage the lifetime of a procedure's non-atomic SPI connection
+        struct NonatomicSpiConnection {
+            was_nonatomic: bool,
+        }
+
+        impl Drop for NonatomicSpiConnection {
+            fn drop(&mut self) {
+                NONATOMIC_CONNECTION.with(|nonatomic| nonatomic.set(self.was_nonatomic));
+                Spi::check_status(unsafe { pg_sys::SPI_finish() });
+            }
+        }
+
+        Spi::check_status(pg_sys::SPI_connect_ext(pg_sys::SPI_OPT_NONATOMIC as i32));
+        let _connection = NonatomicSpiConnection {
+            was_nonatomic: NONATOMIC_CONNECTION.with(|nonatomic| nonatomic.replace(true)),
+        };
+
+        f()
+    }
+
+    /// Commit the current transaction and start a new one.
+    ///
+    /// Only a `#[pg_procedure]` `CALL`ed outside of a transaction block (and not from within a
+    /// function) may control transactions, and not from within [`Spi::connect`].  Panics otherwise.
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    pub fn commit() {
+        Spi::check_nonatomic("commit");
+        unsafe { pg_sys::SPI_commit() }
+    }
+
+    /// Roll back the current transaction and start a new one.
+    ///
+    /// The same restrictions as [`Spi::commit`] apply.
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    pub fn rollback() {
+        Spi::check_nonatomic("rollback");
+        unsafe { pg_sys::SPI_rollback() }
+    }
+
+    #[cfg(any(feature = "pg11", feature = "pg12", feature = "pg13", feature = "pg14"))]
+    fn check_nonatomic(operation: &str) {
+        if !NONATOMIC_CONNECTION.with(Cell::get) {
+            panic!(
+                "Spi::{}() can only be used by a procedure that was CALLed outside of a transaction block",
+                operation
+            );
+        }
+    }
+
     pub fn check_status(status_code: i32) -> SpiOk {
         if status_code > 0 {
             let status_enum = SpiOk::from_i32(status_code);
@@ -308,6 +411,12 @@ impl SpiClient {
         SpiClient::execute(query, false, limit, args)
     }
 
+    /// Make the transition tables of `trigger` queryable through this client, under the names
+    /// given by the trigger's `REFERENCING` clause
+    pub fn register_trigger_data(&mut self, trigger: &PgTrigger) {
+        Spi::check_status(unsafe { pg_sys::SPI_register_trigger_data(trigger.as_ptr()) });
+    }
+
     fn execute(
         query: &str,
         read_only: bool,
@@ -2,8 +2,22 @@
 // governed by the MIT license that can be found in the LICENSE file.
 
 //! Helper functions for working with custom Rust trigger functions
-
-use crate::{is_a, pg_sys};
+//!
+//! A `#[pg_trigger]` function receives a [`PgTrigger`] describing the event that fired it, and
+//! returns the row Postgres should use in its place:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! #[pg_trigger]
+//! fn uppercase_name(trigger: &PgTrigger) -> Result<Option<PgHeapTuple<'_>>, PgHeapTupleError> {
+//!     let mut new = trigger.new().expect("not fired by a row-level INSERT or UPDATE");
+//!     let name = new.get_by_name::<&str>("name")?.map(|name| name.to_uppercase());
+//!     new.set_by_name("name", name)?;
+//!     Ok(Some(new))
+//! }
+//! ```
+use crate::{is_a, pg_sys, PgHeapTuple, PgRelation, PgTupleDesc};
 
 #[inline]
 pub unsafe fn called_as_trigger(fcinfo: pg_sys::FunctionCallInfo) -> bool {
@@ -55,3 +69,224 @@ pub fn trigger_fired_after(event: u32) -> bool {
 pub fn trigger_fired_instead(event: u32) -> bool {
     event & pg_sys::TRIGGER_EVENT_TIMINGMASK == pg_sys::TRIGGER_EVENT_INSTEAD
 }
+
+/// When a trigger fires, relative to the event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerWhen {
+    Before,
+    After,
+    InsteadOf,
+}
+
+/// The operation that fired a trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerOperation {
+    Insert,
+    Update,
+    Delete,
+    Truncate,
+}
+
+/// Whether a trigger fires once per row or once per statement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgTriggerLevel {
+    Row,
+    Statement,
+}
+
+/// The `TriggerData` Postgres hands to a trigger function through `fcinfo->context`
+pub struct PgTrigger {
+    trigger_data: *mut pg_sys::TriggerData,
+    relation: PgRelation,
+}
+
+impl PgTrigger {
+    /// The `PgTrigger` of the trigger call described by `fcinfo`.
+    ///
+    /// Panics if the function was not called as a trigger.
+    ///
+    /// # Safety
+    ///
+    /// `fcinfo` must be a valid pointer to the `FunctionCallInfo` Postgres passed to the
+    /// function, and the returned `PgTrigger` must not outlive the call.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        if !called_as_trigger(fcinfo) {
+            panic!("function was not called as a trigger");
+        }
+        let trigger_data = (*fcinfo).context as *mut pg_sys::TriggerData;
+        PgTrigger {
+            trigger_data,
+            relation: PgRelation::from_pg((*trigger_data).tg_relation),
+        }
+    }
+
+    /// Run the `#[pg_trigger]` function `f` for the trigger call described by `fcinfo`, and
+    /// convert its result into the `Datum` Postgres expects
+    ///
+    /// # Safety
+    ///
+    /// `fcinfo` must be a valid pointer to the `FunctionCallInfo` Postgres passed to the trigger
+    /// function.
+    #[doc(hidden)]
+    pub unsafe fn call<E: std::fmt::Display>(
+        fcinfo: pg_sys::FunctionCallInfo,
+        f: impl for<'a> FnOnce(&'a PgTrigger) -> Result<Option<PgHeapTuple<'a>>, E>,
+    ) -> pg_sys::Datum {
+        let trigger = PgTrigger::from_fcinfo(fcinfo);
+        let datum = match f(&trigger) {
+            Ok(Some(tuple)) => tuple.into_pg() as pg_sys::Datum,
+            Ok(None) => 0,
+            Err(e) => panic!("{}", e),
+        };
+        // The returned tuple borrows `trigger`, so close its relation only once it's converted
+        drop(trigger);
+        datum
+    }
+
+    /// The underlying `pg_sys::TriggerData`
+    pub fn as_ptr(&self) -> *mut pg_sys::TriggerData {
+        self.trigger_data
+    }
+
+    fn event(&self) -> u32 {
+        unsafe { (*self.trigger_data).tg_event }
+    }
+
+    fn trigger(&self) -> &pg_sys::Trigger {
+        unsafe { (*self.trigger_data).tg_trigger.as_ref() }.expect("tg_trigger is NULL")
+    }
+
+    /// When the trigger fired
+    pub fn when(&self) -> PgTriggerWhen {
+        let event = self.event();
+        if trigger_fired_before(event) {
+            PgTriggerWhen::Before
+        } else if trigger_fired_after(event) {
+            PgTriggerWhen::After
+        } else {
+            PgTriggerWhen::InsteadOf
+        }
+    }
+
+    /// The operation that fired the trigger
+    pub fn op(&self) -> PgTriggerOperation {
+        let event = self.event();
+        if trigger_fired_by_insert(event) {
+            PgTriggerOperation::Insert
+        } else if trigger_fired_by_update(event) {
+            PgTriggerOperation::Update
+        } else if trigger_fired_by_delete(event) {
+            PgTriggerOperation::Delete
+        } else {
+            PgTriggerOperation::Truncate
+        }
+    }
+
+    /// Whether the trigger fired for a row or for a statement
+    pub fn level(&self) -> PgTriggerLevel {
+        if trigger_fired_for_row(self.event()) {
+            PgTriggerLevel::Row
+        } else {
+            PgTriggerLevel::Statement
+        }
+    }
+
+    /// The name of the trigger, as given to `CREATE TRIGGER`
+    pub fn name(&self) -> &str {
+        unsafe { std::ffi::CStr::from_ptr(self.trigger().tgname) }
+            .to_str()
+            .expect("trigger name is not valid UTF8")
+    }
+
+    /// The table the trigger fired on
+    pub fn relation(&self) -> &PgRelation {
+        &self.relation
+    }
+
+    /// The name of the table the trigger fired on
+    pub fn table_name(&self) -> &str {
+        self.relation.name()
+    }
+
+    /// The name of the schema of the table the trigger fired on
+    pub fn table_schema(&self) -> &str {
+        self.relation.namespace()
+    }
+
+    /// The arguments given to the function in `CREATE TRIGGER`
+    pub fn args(&self) -> Vec<&str> {
+        let trigger = self.trigger();
+        if trigger.tgnargs == 0 {
+            return Vec::new();
+        }
+        unsafe { std::slice::from_raw_parts(trigger.tgargs, trigger.tgnargs as usize) }
+            .iter()
+            .map(|arg| {
+                unsafe { std::ffi::CStr::from_ptr(*arg) }
+                    .to_str()
+                    .expect("trigger argument is not valid UTF8")
+            })
+            .collect()
+    }
+
+    /// The row before an `UPDATE` or `DELETE`, for row-level triggers
+    pub fn old(&self) -> Option<PgHeapTuple<'_>> {
+        match (self.level(), self.op()) {
+            (PgTriggerLevel::Row, PgTriggerOperation::Update)
+            | (PgTriggerLevel::Row, PgTriggerOperation::Delete) => {
+                self.tuple(unsafe { (*self.trigger_data).tg_trigtuple })
+            }
+            _ => None,
+        }
+    }
+
+    /// The row after an `INSERT` or `UPDATE`, for row-level triggers
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(&self) -> Option<PgHeapTuple<'_>> {
+        match (self.level(), self.op()) {
+            (PgTriggerLevel::Row, PgTriggerOperation::Insert) => {
+                self.tuple(unsafe { (*self.trigger_data).tg_trigtuple })
+            }
+            (PgTriggerLevel::Row, PgTriggerOperation::Update) => {
+                self.tuple(unsafe { (*self.trigger_data).tg_newtuple })
+            }
+            _ => None,
+        }
+    }
+
+    fn tuple(&self, tuple: pg_sys::HeapTuple) -> Option<PgHeapTuple<'_>> {
+        if tuple.is_null() {
+            None
+        } else {
+            Some(unsafe {
+                PgHeapTuple::from_heap_tuple(PgTupleDesc::from_relation(&self.relation), tuple)
+            })
+        }
+    }
+
+    /// The name of the `REFERENCING OLD TABLE` transition table, if the trigger has one.
+    ///
+    /// Use [`crate::SpiClient::register_trigger_data`] to make it queryable.
+    pub fn old_transition_table_name(&self) -> Option<&str> {
+        transition_table_name(self.trigger().tgoldtable)
+    }
+
+    /// The name of the `REFERENCING NEW TABLE` transition table, if the trigger has one.
+    ///
+    /// Use [`crate::SpiClient::register_trigger_data`] to make it queryable.
+    pub fn new_transition_table_name(&self) -> Option<&str> {
+        transition_table_name(self.trigger().tgnewtable)
+    }
+}
+
+fn transition_table_name<'a>(name: *const std::os::raw::c_char) -> Option<&'a str> {
+    if name.is_null() {
+        None
+    } else {
+        Some(
+            unsafe { std::ffi::CStr::from_ptr(name) }
+                .to_str()
+                .expect("transition table name is not valid UTF8"),
+        )
+    }
+}
@@ -0,0 +1,152 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! A safe wrapper around Postgres' `Tuplestorestate`, which collects tuples in memory (spilling to
+//! disk past `work_mem`)
+//!
+//! This is what `#[pg_extern(materialize)]` set-returning functions use to hand all their rows to
+//! Postgres at once (`SFRM_Materialize`), rather than one per call:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! #[pg_extern(materialize)]
+//! fn table_names() -> impl std::iter::Iterator<Item = String> {
+//!     let mut names = Vec::new();
+//!     Spi::connect(|client| {
+//!         for row in client.select("SELECT relname::text FROM pg_class", None, None) {
+//!             names.push(row[1].value::<String>().unwrap());
+//!         }
+//!         Ok(Some(()))
+//!     });
+//!     names.into_iter()
+//! }
+//! ```
+use crate::{is_a, pg_sys, PgHeapTuple, PgMemoryContexts};
+
+/// A `pg_sys::Tuplestorestate` along with the `pg_sys::TupleDesc` of the tuples it holds
+pub struct Tuplestore {
+    state: *mut pg_sys::Tuplestorestate,
+    tupdesc: pg_sys::TupleDesc,
+}
+
+impl Tuplestore {
+    /// Begin an empty tuplestore, in `CurrentMemoryContext`, for tuples described by `tupdesc`.
+    ///
+    /// If `random_access` is false, the tuplestore can only be read once, front to back.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot ensure that `tupdesc` is valid, nor that it lives as
+    /// long as the tuplestore
+    pub unsafe fn new(tupdesc: pg_sys::TupleDesc, random_access: bool) -> Self {
+        Tuplestore {
+            state: pg_sys::tuplestore_begin_heap(random_access, false, pg_sys::work_mem),
+            tupdesc,
+        }
+    }
+
+    /// Begin an empty tuplestore for the results of the set-returning function call described by
+    /// `fcinfo`, to be handed to Postgres with [`Tuplestore::return_materialized`].
+    ///
+    /// The tuplestore lives in the query's memory context and holds tuples of the calling query's
+    /// expected row type.
+    ///
+    /// Panics if the caller can't accept a materialized set.
+    pub fn for_materialize(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let rsinfo = Self::return_set_info(fcinfo);
+        if rsinfo.allowedModes & pg_sys::SetFunctionReturnMode_SFRM_Materialize as i32 == 0 {
+            panic!("materialize mode required, but it is not allowed in this context");
+        }
+        if rsinfo.expectedDesc.is_null() {
+            panic!(
+                "set-valued function called in context that does not provide an expected row type"
+            );
+        }
+
+        let random_access =
+            rsinfo.allowedModes & pg_sys::SetFunctionReturnMode_SFRM_Materialize_Random as i32 != 0;
+        let expected_desc = rsinfo.expectedDesc;
+        let per_query_memory = unsafe { rsinfo.econtext.as_ref() }
+            .expect("ReturnSetInfo has no ExprContext")
+            .ecxt_per_query_memory;
+        PgMemoryContexts::For(per_query_memory).switch_to(|_| unsafe {
+            Tuplestore::new(pg_sys::CreateTupleDescCopy(expected_desc), random_access)
+        })
+    }
+
+    fn return_set_info<'a>(fcinfo: pg_sys::FunctionCallInfo) -> &'a mut pg_sys::ReturnSetInfo {
+        let resultinfo = unsafe { fcinfo.as_ref() }
+            .expect("fcinfo is NULL")
+            .resultinfo;
+        if !unsafe { is_a(resultinfo, pg_sys::NodeTag_T_ReturnSetInfo) } {
+            panic!("set-valued function called in context that cannot accept a set");
+        }
+        unsafe { &mut *(resultinfo as *mut pg_sys::ReturnSetInfo) }
+    }
+
+    /// The underlying `pg_sys::Tuplestorestate`
+    pub fn as_ptr(&self) -> *mut pg_sys::Tuplestorestate {
+        self.state
+    }
+
+    /// The `pg_sys::TupleDesc` describing this tuplestore's tuples
+    pub fn tupdesc(&self) -> pg_sys::TupleDesc {
+        self.tupdesc
+    }
+
+    /// The number of tuples in this tuplestore
+    pub fn len(&self) -> usize {
+        unsafe { pg_sys::tuplestore_tuple_count(self.state) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add a tuple made of `values`, one per attribute.  `None` values are `NULL`.
+    ///
+    /// Panics if there isn't exactly one value for each attribute.
+    pub fn put_values(&mut self, values: &[Option<pg_sys::Datum>]) {
+        let natts = unsafe { (*self.tupdesc).natts } as usize;
+        if values.len() != natts {
+            panic!(
+                "tuplestore expects {} values per tuple, but was given {}",
+                natts,
+                values.len()
+            );
+        }
+
+        let mut datums = values
+            .iter()
+            .map(|value| value.unwrap_or(0))
+            .collect::<Vec<_>>();
+        let mut nulls = values
+            .iter()
+            .map(|value| value.is_none())
+            .collect::<Vec<_>>();
+        unsafe {
+            pg_sys::tuplestore_putvalues(
+                self.state,
+                self.tupdesc,
+                datums.as_mut_ptr(),
+                nulls.as_mut_ptr(),
+            )
+        }
+    }
+
+    /// Add a copy of `tuple`, which must be of this tuplestore's row type
+    pub fn put_tuple(&mut self, tuple: &PgHeapTuple<'_>) {
+        unsafe { pg_sys::tuplestore_puttuple(self.state, tuple.as_ptr()) }
+    }
+
+    /// Hand this tuplestore to Postgres as the result of the set-returning function call described
+    /// by `fcinfo`, and return the `Datum` the function should return
+    pub fn return_materialized(self, fcinfo: pg_sys::FunctionCallInfo) -> pg_sys::Datum {
+        let rsinfo = Self::return_set_info(fcinfo);
+        rsinfo.returnMode = pg_sys::SetFunctionReturnMode_SFRM_Materialize;
+        rsinfo.setResult = self.state;
+        rsinfo.setDesc = self.tupdesc;
+        0
+    }
+}
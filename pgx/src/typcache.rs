@@ -0,0 +1,233 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Provides a safe interface into Postgres' type cache (`utils/typcache.h`), along with
+//! helper functions for comparing and hashing arbitrary Datums using Postgres' own semantics
+use crate::pg_sys;
+use std::cmp::Ordering;
+
+/// A wrapper around a Postgres `TypeCacheEntry *`, as returned by `lookup_type_cache()`.
+///
+/// Entries are owned by Postgres' type cache and live for the lifetime of the backend, so
+/// there's nothing to free here.
+///
+/// ## Examples
+///
+/// ```rust,no_run
+/// use pgx::*;
+///
+/// let entry = PgTypeCacheEntry::lookup(pg_sys::INT4OID);
+/// assert_eq!(entry.typlen(), 4);
+/// assert!(entry.typbyval());
+/// assert!(entry.cmp_proc().is_some());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PgTypeCacheEntry {
+    entry: *mut pg_sys::TypeCacheEntry,
+}
+
+impl PgTypeCacheEntry {
+    /// The set of `TYPECACHE_*` flags we request by default: everything needed to compare,
+    /// test for equality, and hash Datums of the type
+    pub const DEFAULT_FLAGS: u32 = pg_sys::TYPECACHE_EQ_OPR
+        | pg_sys::TYPECACHE_CMP_PROC
+        | pg_sys::TYPECACHE_HASH_PROC
+        | pg_sys::TYPECACHE_EQ_OPR_FINFO
+        | pg_sys::TYPECACHE_CMP_PROC_FINFO
+        | pg_sys::TYPECACHE_HASH_PROC_FINFO;
+
+    /// Look up the type cache entry for the type with the specified `typoid`, populating its
+    /// btree comparator, hash function, and equality operator.
+    ///
+    /// Raises a Postgres ERROR if the type doesn't exist.
+    pub fn lookup(typoid: pg_sys::Oid) -> Self {
+        PgTypeCacheEntry::lookup_with_flags(typoid, PgTypeCacheEntry::DEFAULT_FLAGS)
+    }
+
+    /// Look up the type cache entry for the type with the specified `typoid`, asking Postgres
+    /// to only populate the information described by the `TYPECACHE_*` `flags`
+    pub fn lookup_with_flags(typoid: pg_sys::Oid, flags: u32) -> Self {
+        let entry = unsafe { pg_sys::lookup_type_cache(typoid, flags as i32) };
+        if entry.is_null() {
+            panic!("lookup_type_cache() returned NULL for type oid {}", typoid);
+        }
+        PgTypeCacheEntry { entry }
+    }
+
+    /// The oid of the type this entry describes
+    pub fn oid(&self) -> pg_sys::Oid {
+        self.entry_ref().type_id
+    }
+
+    /// `pg_type.typlen`
+    pub fn typlen(&self) -> i16 {
+        self.entry_ref().typlen
+    }
+
+    /// `pg_type.typbyval`
+    pub fn typbyval(&self) -> bool {
+        self.entry_ref().typbyval
+    }
+
+    /// `pg_type.typalign`
+    pub fn typalign(&self) -> char {
+        self.entry_ref().typalign as u8 as char
+    }
+
+    /// `pg_type.typcollation`, if the type is collatable
+    #[cfg(any(feature = "pg12", feature = "pg13", feature = "pg14"))]
+    pub fn typcollation(&self) -> Option<pg_sys::Oid> {
+        none_if_invalid(self.entry_ref().typcollation)
+    }
+
+    /// The type's default equality operator, if it has one
+    pub fn eq_opr(&self) -> Option<pg_sys::Oid> {
+        none_if_invalid(self.entry_ref().eq_opr)
+    }
+
+    /// The type's default btree comparison function, if it has one
+    pub fn cmp_proc(&self) -> Option<pg_sys::Oid> {
+        none_if_invalid(self.entry_ref().cmp_proc)
+    }
+
+    /// The type's default hash function, if it has one
+    pub fn hash_proc(&self) -> Option<pg_sys::Oid> {
+        none_if_invalid(self.entry_ref().hash_proc)
+    }
+
+    /// Compare two Datums of this type using its default btree comparison function.
+    ///
+    /// Raises a Postgres ERROR if the type has no default btree operator class.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot ensure `a` and `b` are valid, non-null Datums of
+    /// this entry's type
+    pub unsafe fn cmp(
+        &self,
+        a: pg_sys::Datum,
+        b: pg_sys::Datum,
+        collation: pg_sys::Oid,
+    ) -> Ordering {
+        if self.cmp_proc().is_none() {
+            error!(
+                "could not identify a comparison function for type oid {}",
+                self.oid()
+            );
+        }
+
+        let finfo = &mut (*self.entry).cmp_proc_finfo;
+        let result = pg_sys::FunctionCall2Coll(finfo, collation, a, b) as i32;
+        result.cmp(&0)
+    }
+
+    /// Determine if two Datums of this type are equal using its default equality operator.
+    ///
+    /// Raises a Postgres ERROR if the type has no default equality operator.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot ensure `a` and `b` are valid, non-null Datums of
+    /// this entry's type
+    pub unsafe fn eq(&self, a: pg_sys::Datum, b: pg_sys::Datum, collation: pg_sys::Oid) -> bool {
+        if self.eq_opr().is_none() {
+            error!(
+                "could not identify an equality operator for type oid {}",
+                self.oid()
+            );
+        }
+
+        let finfo = &mut (*self.entry).eq_opr_finfo;
+        pg_sys::FunctionCall2Coll(finfo, collation, a, b) != 0
+    }
+
+    /// Hash a Datum of this type using its default hash function.
+    ///
+    /// Raises a Postgres ERROR if the type has no default hash operator class.
+    ///
+    /// ## Safety
+    ///
+    /// This function is unsafe as it cannot ensure `datum` is a valid, non-null Datum of
+    /// this entry's type
+    pub unsafe fn hash(&self, datum: pg_sys::Datum, collation: pg_sys::Oid) -> u32 {
+        if self.hash_proc().is_none() {
+            error!(
+                "could not identify a hash function for type oid {}",
+                self.oid()
+            );
+        }
+
+        let finfo = &mut (*self.entry).hash_proc_finfo;
+        pg_sys::FunctionCall1Coll(finfo, collation, datum) as u32
+    }
+
+    /// Get at the underlying `TypeCacheEntry *`
+    pub fn as_ptr(&self) -> *mut pg_sys::TypeCacheEntry {
+        self.entry
+    }
+
+    #[inline]
+    fn entry_ref(&self) -> &pg_sys::TypeCacheEntry {
+        unsafe { &*self.entry }
+    }
+}
+
+#[inline]
+fn none_if_invalid(oid: pg_sys::Oid) -> Option<pg_sys::Oid> {
+    if oid == pg_sys::InvalidOid {
+        None
+    } else {
+        Some(oid)
+    }
+}
+
+/// Compare two Datums of the type `typoid` using that type's default btree comparison function,
+/// which is what Postgres itself uses for `ORDER BY`.
+///
+/// Works with any type that has a default btree operator class, including types that
+/// `#[derive(PostgresOrd)]`.
+///
+/// ## Safety
+///
+/// This function is unsafe as it cannot ensure `a` and `b` are valid, non-null Datums of the
+/// type `typoid`
+pub unsafe fn datum_cmp(
+    a: pg_sys::Datum,
+    b: pg_sys::Datum,
+    typoid: pg_sys::Oid,
+    collation: pg_sys::Oid,
+) -> Ordering {
+    PgTypeCacheEntry::lookup_with_flags(typoid, pg_sys::TYPECACHE_CMP_PROC_FINFO)
+        .cmp(a, b, collation)
+}
+
+/// Determine if two Datums of the type `typoid` are equal using that type's default equality
+/// operator.
+///
+/// ## Safety
+///
+/// This function is unsafe as it cannot ensure `a` and `b` are valid, non-null Datums of the
+/// type `typoid`
+pub unsafe fn datum_eq(
+    a: pg_sys::Datum,
+    b: pg_sys::Datum,
+    typoid: pg_sys::Oid,
+    collation: pg_sys::Oid,
+) -> bool {
+    PgTypeCacheEntry::lookup_with_flags(typoid, pg_sys::TYPECACHE_EQ_OPR_FINFO).eq(a, b, collation)
+}
+
+/// Hash a Datum of the type `typoid` using that type's default hash function, which is what
+/// Postgres itself uses for hash joins and hash indexes.
+///
+/// Works with any type that has a default hash operator class, including types that
+/// `#[derive(PostgresHash)]`.
+///
+/// ## Safety
+///
+/// This function is unsafe as it cannot ensure `datum` is a valid, non-null Datum of the
+/// type `typoid`
+pub unsafe fn datum_hash(datum: pg_sys::Datum, typoid: pg_sys::Oid, collation: pg_sys::Oid) -> u32 {
+    PgTypeCacheEntry::lookup_with_flags(typoid, pg_sys::TYPECACHE_HASH_PROC_FINFO)
+        .hash(datum, collation)
+}
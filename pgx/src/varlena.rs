@@ -294,6 +294,29 @@ pub unsafe fn text_to_rust_str_unchecked<'a>(varlena: *const pg_sys::varlena) ->
     std::str::from_utf8_unchecked(std::slice::from_raw_parts(data as *mut u8, len))
 }
 
+/// Convert a Postgres `varlena *` (or `text *`) into a Rust `&str`, converting from the server's
+/// encoding to UTF-8 if necessary.
+///
+/// ## Safety
+///
+/// This function is unsafe because it blindly assumes the provided varlena pointer is non-null.
+///
+/// When the server encoding is UTF-8 this function is zero-copy, just like [`text_to_rust_str_unchecked`].
+/// Otherwise the converted string is allocated in `CurrentMemoryContext`.  Either way, the return
+/// value is backed by Postgres-allocated memory and will become invalid the moment Postgres frees it.
+///
+/// Raises a Postgres ERROR if the text isn't valid in the server's encoding, as
+/// [`server_to_utf8_bytes`](crate::server_to_utf8_bytes) does.
+#[inline]
+pub unsafe fn text_to_rust_str<'a>(varlena: *const pg_sys::varlena) -> &'a str {
+    if crate::is_server_utf8() {
+        return text_to_rust_str_unchecked(varlena);
+    }
+
+    let bytes = crate::server_to_utf8_bytes(varlena_to_byte_slice(varlena));
+    std::str::from_utf8(bytes).expect("text is not valid UTF-8")
+}
+
 /// Convert a Postgres `varlena *` (or `byte *`) into a Rust `&[u8]`.
 ///
 /// ## Safety
@@ -310,12 +333,12 @@ pub unsafe fn varlena_to_byte_slice<'a>(varlena: *const pg_sys::varlena) -> &'a
     std::slice::from_raw_parts(data as *const u8, len)
 }
 
-/// Convert a Rust `&str` into a Postgres `text *`.
+/// Convert a Rust `&str` into a Postgres `text *`, converting to the server's encoding if necessary.
 ///
 /// This allocates the returned Postgres `text *` in `CurrentMemoryContext`.
 #[inline]
 pub fn rust_str_to_text_p(s: &str) -> PgBox<pg_sys::varlena> {
-    let bytea = rust_byte_slice_to_bytea(s.as_bytes());
+    let bytea = rust_byte_slice_to_bytea(&crate::utf8_to_server(s));
 
     // a pg_sys::bytea is a type alias for pg_sys::varlena so this cast is fine
     // SAFETY: bytea will be a valid pointer
@@ -0,0 +1,202 @@
+// Copyright 2020 ZomboDB, LLC <zombodb@gmail.com>. All rights reserved. Use of this source code is
+// governed by the MIT license that can be found in the LICENSE file.
+
+//! Safe wrappers around Postgres' window function API (`windowapi.h`)
+//!
+//! A `#[pg_extern(window)]` function is created as a `WINDOW` function.  Its arguments are those of
+//! the current row, and it can inspect the rest of its partition or frame through the
+//! [`WindowObject`] for its trailing `fcinfo` argument:
+//!
+//! ```rust,no_run
+//! use pgx::*;
+//!
+//! /// Like `lag()`, but skips over `NULL`s
+//! #[pg_extern(window)]
+//! fn lag_ignore_nulls(value: Option<i32>, fcinfo: pg_sys::FunctionCallInfo) -> Option<i32> {
+//!     let window = unsafe { WindowObject::from_fcinfo(fcinfo) };
+//!     let current = window.current_position();
+//!     (1..=current)
+//!         .filter_map(|offset| {
+//!             window.arg_in_partition::<i32>(0, -(offset as i32), WindowSeek::Current, false)
+//!         })
+//!         .next()
+//! }
+//! ```
+use crate::{is_a, pg_sys, FromDatum};
+
+/// The position a [`WindowObject`] seek is relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSeek {
+    /// Relative to the current row
+    Current,
+    /// Relative to the first row of the partition or frame
+    Head,
+    /// Relative to the last row of the partition or frame
+    Tail,
+}
+
+impl WindowSeek {
+    fn as_seektype(&self) -> i32 {
+        (match self {
+            WindowSeek::Current => pg_sys::WINDOW_SEEK_CURRENT,
+            WindowSeek::Head => pg_sys::WINDOW_SEEK_HEAD,
+            WindowSeek::Tail => pg_sys::WINDOW_SEEK_TAIL,
+        }) as i32
+    }
+}
+
+/// The `WindowObject` Postgres hands to a window function through `fcinfo->context`
+pub struct WindowObject {
+    winobj: pg_sys::WindowObject,
+    fcinfo: pg_sys::FunctionCallInfo,
+}
+
+impl WindowObject {
+    /// The `WindowObject` of the window function call described by `fcinfo`.
+    ///
+    /// Panics if the function was not called as a window function.
+    ///
+    /// # Safety
+    ///
+    /// `fcinfo` must be a valid pointer to the `FunctionCallInfo` Postgres passed to the
+    /// function, and the returned `WindowObject` must not outlive the call.
+    pub unsafe fn from_fcinfo(fcinfo: pg_sys::FunctionCallInfo) -> Self {
+        let context = fcinfo.as_ref().expect("fcinfo is NULL").context;
+        if context.is_null() || !is_a(context, pg_sys::NodeTag_T_WindowObjectData) {
+            panic!("function was not called as a window function");
+        }
+        WindowObject {
+            winobj: context as pg_sys::WindowObject,
+            fcinfo,
+        }
+    }
+
+    /// The position of the current row within its partition, starting at zero
+    pub fn current_position(&self) -> i64 {
+        unsafe { pg_sys::WinGetCurrentPosition(self.winobj) }
+    }
+
+    /// The total number of rows in the current partition
+    pub fn partition_row_count(&self) -> i64 {
+        unsafe { pg_sys::WinGetPartitionRowCount(self.winobj) }
+    }
+
+    /// Tell Postgres that rows before `position` will no longer be fetched, so it may discard them
+    pub fn set_mark_position(&self, position: i64) {
+        unsafe { pg_sys::WinSetMarkPosition(self.winobj, position) }
+    }
+
+    /// Are the rows at `position1` and `position2` peers according to the window's `ORDER BY`?
+    pub fn rows_are_peers(&self, position1: i64, position2: i64) -> bool {
+        unsafe { pg_sys::WinRowsArePeers(self.winobj, position1, position2) }
+    }
+
+    /// State that lives as long as the current partition.
+    ///
+    /// Postgres allocates the memory the first time it is requested for each partition, in which
+    /// case it is initialized to `T::default()`.  As the memory is freed by Postgres, `T` must not
+    /// need to be dropped.
+    pub fn partition_local_state<T: Copy + Default>(&mut self) -> &mut T {
+        unsafe {
+            // Postgres zeroes the memory when it's first allocated, so `initialized` starts out
+            // as `false`.
+            let memory =
+                pg_sys::WinGetPartitionLocalMemory(self.winobj, std::mem::size_of::<(bool, T)>())
+                    as *mut (bool, T);
+            let (initialized, state) = &mut *memory;
+            if !*initialized {
+                std::ptr::write(state, T::default());
+                *initialized = true;
+            }
+            state
+        }
+    }
+
+    /// The value of argument `argno` for the row `relpos` rows away from `seek` within the
+    /// current partition.  Returns `None` if the value is `NULL` or if that row is outside the
+    /// partition.
+    ///
+    /// If `set_mark` is true, rows before the fetched row may be discarded (see
+    /// [`WindowObject::set_mark_position`]).
+    pub fn arg_in_partition<T: FromDatum>(
+        &self,
+        argno: usize,
+        relpos: i32,
+        seek: WindowSeek,
+        set_mark: bool,
+    ) -> Option<T> {
+        let mut isnull = true;
+        let mut isout = false;
+        let datum = unsafe {
+            pg_sys::WinGetFuncArgInPartition(
+                self.winobj,
+                argno as i32,
+                relpos,
+                seek.as_seektype(),
+                set_mark,
+                &mut isnull,
+                &mut isout,
+            )
+        };
+        if isout {
+            None
+        } else {
+            self.arg_from_datum(argno, datum, isnull)
+        }
+    }
+
+    /// The value of argument `argno` for the row `relpos` rows away from `seek` within the
+    /// current frame.  Returns `None` if the value is `NULL` or if that row is outside the frame.
+    ///
+    /// If `set_mark` is true, rows before the fetched row may be discarded (see
+    /// [`WindowObject::set_mark_position`]).
+    pub fn arg_in_frame<T: FromDatum>(
+        &self,
+        argno: usize,
+        relpos: i32,
+        seek: WindowSeek,
+        set_mark: bool,
+    ) -> Option<T> {
+        let mut isnull = true;
+        let mut isout = false;
+        let datum = unsafe {
+            pg_sys::WinGetFuncArgInFrame(
+                self.winobj,
+                argno as i32,
+                relpos,
+                seek.as_seektype(),
+                set_mark,
+                &mut isnull,
+                &mut isout,
+            )
+        };
+        if isout {
+            None
+        } else {
+            self.arg_from_datum(argno, datum, isnull)
+        }
+    }
+
+    /// The value of argument `argno` for the current row
+    pub fn arg_current<T: FromDatum>(&self, argno: usize) -> Option<T> {
+        let mut isnull = true;
+        let datum = unsafe { pg_sys::WinGetFuncArgCurrent(self.winobj, argno as i32, &mut isnull) };
+        self.arg_from_datum(argno, datum, isnull)
+    }
+
+    fn arg_from_datum<T: FromDatum>(
+        &self,
+        argno: usize,
+        datum: pg_sys::Datum,
+        isnull: bool,
+    ) -> Option<T> {
+        unsafe {
+            let typid = if T::NEEDS_TYPID {
+                crate::get_getarg_type(self.fcinfo, argno)
+            } else {
+                pg_sys::InvalidOid
+            };
+            T::from_datum(datum, isnull, typid)
+        }
+    }
+}